@@ -1,16 +1,12 @@
 use silk_engine::prelude::*;
 
-struct MyApp<'a> {
-    #[allow(unused)]
-    app: &'a mut AppContext<Self>,
+struct MyApp {
     packer: Guillotine,
     rects: Vec<(u16, u16, u16, u16)>,
 }
 
-impl App for MyApp<'_> {
-    fn new(app: *mut AppContext<Self>) -> Self {
-        let app = unsafe { &mut *app };
-
+impl App for MyApp {
+    fn new(_ctx: &mut AppContext<Self>) -> Self {
         let _font = Font::new("segoe-ui", 64);
         let mut rects = vec![];
         let mut packer = Guillotine::new(512, 512);
@@ -55,10 +51,10 @@ impl App for MyApp<'_> {
         println!("Rects: {}", rects.len());
         println!("Free Rects: {}", packer.free_rects.len());
         println!("Perim Sum: {perim}");
-        Self { app, packer, rects }
+        Self { packer, rects }
     }
 
-    fn update(&mut self) {}
+    fn update(&mut self, _ctx: &mut AppContext<Self>) {}
 
     fn render(&mut self, gfx: &mut Renderer) {
         gfx.stroke_width = 0.2;
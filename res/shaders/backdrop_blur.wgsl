@@ -0,0 +1,39 @@
+struct Pass {
+    // blur direction
+    dir: vec2f,
+    // in texels
+    radius: f32,
+    // xy origin, zw size of blurred region in texels
+    region: vec4u,
+}
+
+@group(0) @binding(0) var src: texture_2d<f32>;
+@group(0) @binding(1) var src_sampler: sampler;
+@group(0) @binding(2) var dst: texture_storage_2d<rgba16float, write>;
+// dynamic, offset selects pass
+@group(0) @binding(3) var<uniform> params: Pass;
+
+const TAPS: i32 = 12;
+
+// separable gaussian blur of backdrop region, dispatched over region size
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    if any(id.xy >= params.region.zw) {
+        return;
+    }
+    let p = id.xy + params.region.xy;
+    let size = vec2f(textureDimensions(src));
+    let uv = (vec2f(p) + 0.5) / size;
+    let radius = max(params.radius, 1.0);
+    let sigma = radius * 0.5;
+    let step = params.dir * radius / f32(TAPS) / size;
+    var col = vec4f(0.0);
+    var total = 0.0;
+    for (var i = -TAPS; i <= TAPS; i++) {
+        let x = f32(i) / f32(TAPS) * radius / sigma;
+        let w = exp(-0.5 * x * x);
+        col += textureSampleLevel(src, src_sampler, uv + step * f32(i), 0.0) * w;
+        total += w;
+    }
+    textureStore(dst, p, col / total);
+}
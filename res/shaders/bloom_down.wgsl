@@ -0,0 +1,53 @@
+struct Params {
+    threshold: f32,
+    knee: f32,
+    // only first downsample applies threshold
+    first: u32,
+}
+
+@group(0) @binding(0) var src: texture_2d<f32>;
+@group(0) @binding(1) var src_sampler: sampler;
+@group(0) @binding(2) var dst: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(3) var<uniform> params: Params;
+
+fn sample(uv: vec2f) -> vec3f {
+    return textureSampleLevel(src, src_sampler, uv, 0.0).rgb;
+}
+
+// soft knee threshold, keeps bright parts only
+fn threshold(col: vec3f) -> vec3f {
+    let br = max(col.r, max(col.g, col.b));
+    let knee = params.knee + 1e-5;
+    var soft = clamp(br - params.threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee);
+    return col * max(soft, br - params.threshold) / max(br, 1e-5);
+}
+
+// 13 tap downsample, weighted to reduce aliasing/flickering
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    let size = textureDimensions(dst);
+    if any(id.xy >= size) {
+        return;
+    }
+    let uv = (vec2f(id.xy) + 0.5) / vec2f(size);
+    let t = 1.0 / vec2f(textureDimensions(src));
+    let a = sample(uv + t * vec2f(-2.0, -2.0));
+    let b = sample(uv + t * vec2f( 0.0, -2.0));
+    let c = sample(uv + t * vec2f( 2.0, -2.0));
+    let d = sample(uv + t * vec2f(-2.0,  0.0));
+    let e = sample(uv);
+    let f = sample(uv + t * vec2f( 2.0,  0.0));
+    let g = sample(uv + t * vec2f(-2.0,  2.0));
+    let h = sample(uv + t * vec2f( 0.0,  2.0));
+    let i = sample(uv + t * vec2f( 2.0,  2.0));
+    let j = sample(uv + t * vec2f(-1.0, -1.0));
+    let k = sample(uv + t * vec2f( 1.0, -1.0));
+    let l = sample(uv + t * vec2f(-1.0,  1.0));
+    let m = sample(uv + t * vec2f( 1.0,  1.0));
+    var col = e * 0.125 + (a + c + g + i) * 0.03125 + (b + d + f + h) * 0.0625 + (j + k + l + m) * 0.125;
+    if params.first != 0u {
+        col = threshold(col);
+    }
+    textureStore(dst, id.xy, vec4f(col, 1.0));
+}
@@ -0,0 +1,28 @@
+@group(0) @binding(0) var src: texture_2d<f32>;
+@group(0) @binding(1) var src_sampler: sampler;
+@group(0) @binding(2) var dst: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(3) var<uniform> intensity: f32;
+// same size as dst, upsampled src is added to it
+@group(0) @binding(4) var base: texture_2d<f32>;
+
+fn sample(uv: vec2f) -> vec3f {
+    return textureSampleLevel(src, src_sampler, uv, 0.0).rgb;
+}
+
+// 9 tap tent upsample
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    let size = textureDimensions(dst);
+    if any(id.xy >= size) {
+        return;
+    }
+    let uv = (vec2f(id.xy) + 0.5) / vec2f(size);
+    let t = 1.0 / vec2f(textureDimensions(src));
+    var col = sample(uv) * 4.0;
+    col += (sample(uv + vec2f(-t.x, 0.0)) + sample(uv + vec2f(t.x, 0.0))
+        + sample(uv + vec2f(0.0, -t.y)) + sample(uv + vec2f(0.0, t.y))) * 2.0;
+    col += sample(uv - t) + sample(uv + t)
+        + sample(uv + vec2f(-t.x, t.y)) + sample(uv + vec2f(t.x, -t.y));
+    let b = textureLoad(base, id.xy, 0);
+    textureStore(dst, id.xy, vec4f(b.rgb + col / 16.0 * intensity, b.a));
+}
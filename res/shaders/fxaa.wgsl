@@ -1,23 +1,36 @@
-@vertex
-fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4f {
-    var positions = array<vec2f, 3>(
-        vec2f(-1.0, -1.0),
-        vec2f(3.0, -1.0), 
-        vec2f(-1.0, 3.0)  
-    );
-    let pos = positions[vertex_index];
-    return vec4f(pos, 0.0, 1.0);
+@group(0) @binding(0) var src: texture_2d<f32>;
+@group(0) @binding(1) var src_sampler: sampler;
+@group(0) @binding(2) var dst: texture_storage_2d<rgba16float, write>;
+struct Params {
+    dither: f32,
+    // src is linear, edges are found on perceptual luma
+    linear: u32,
 }
 
-@group(0) @binding(0) var img: texture_2d<f32>;
-@group(0) @binding(1) var img_sampler: sampler;
+@group(0) @binding(3) var<uniform> params: Params;
+
+// interleaved gradient noise, cheap noise with blue-ish spectrum
+fn ign(p: vec2f) -> f32 {
+    return fract(52.9829189 * fract(dot(p, vec2f(0.06711056, 0.00583715))));
+}
+
+// adds +-strength/255 of noise to hide 8 bit gradient banding,
+// scaled by alpha so premultiplied transparent pixels stay transparent
+fn dithered(col: vec4f, p: vec2f) -> vec4f {
+    return vec4f(col.rgb + (ign(p) - 0.5) * params.dither / 255.0 * col.a, col.a);
+}
 
 fn luma(col: vec3f) -> f32 {
-    return dot(col, vec3f(0.299, 0.587, 0.114));
+    let l = dot(col, vec3f(0.299, 0.587, 0.114));
+    if params.linear != 0u {
+        // close enough to srgb encoding for edge detection
+        return sqrt(max(l, 0.0));
+    }
+    return l;
 }
 
 fn sample(coord: vec2i) -> vec4f {
-    return textureLoad(img, coord, 0);
+    return textureLoad(src, coord, 0);
 }
 
 fn sample_luma(coord: vec2i) -> f32 {
@@ -28,13 +41,20 @@ const MIN_THRESHOLD: f32 = 1.0 / 24.0;
 const MAX_THRESHOLD: f32 = 1.0 / 12.0;
 const ITERS: u32 = 8;
 
-@fragment
-fn fs_main(@builtin(position) coord: vec4f) -> @location(0) vec4f {
-    let p = vec2i(coord.xy);
-    
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    if any(id.xy >= textureDimensions(src)) {
+        return;
+    }
+    textureStore(dst, id.xy, fxaa(vec2f(id.xy) + 0.5));
+}
+
+fn fxaa(coord: vec2f) -> vec4f {
+    let p = vec2i(coord);
+
     let col = sample(p);
     if true {
-        return col;
+        return dithered(col, coord);
     }
     let c = luma(col.rgb);
 
@@ -47,7 +67,7 @@ fn fs_main(@builtin(position) coord: vec4f) -> @location(0) vec4f {
     let max = max(c, max(max(n, s), max(e, w)));
     let rng = max - min;
     if rng < max(MIN_THRESHOLD, max * MAX_THRESHOLD) {
-        return col;
+        return dithered(col, coord);
     }
 
     let ne = sample_luma(p + vec2i( 1,  1));
@@ -61,7 +81,7 @@ fn fs_main(@builtin(position) coord: vec4f) -> @location(0) vec4f {
     let luma = vec2f(select(vec2f(w, e), vec2f(s, n), is_horz)); 
     let grad = vec2f(luma - c);
     let grad_scl = 0.25 * max(abs(grad.x), abs(grad.y));
-    let ires = 1.0 / vec2f(textureDimensions(img));
+    let ires = 1.0 / vec2f(textureDimensions(src));
     var step = select(ires.x, ires.y, is_horz);
     var avg = 0.0;
     if abs(grad.x) >= abs(grad.y) {
@@ -70,7 +90,7 @@ fn fs_main(@builtin(position) coord: vec4f) -> @location(0) vec4f {
     } else {
         avg = mix(luma.y, c, 0.5);
     }
-    let uv = coord.xy * ires;
+    let uv = coord * ires;
     var cur_uv = uv;
     if is_horz {
         cur_uv.y += step * 0.5;
@@ -80,19 +100,19 @@ fn fs_main(@builtin(position) coord: vec4f) -> @location(0) vec4f {
     let off = select(vec2f(0, ires.y), vec2f(ires.x, 0), is_horz);
     var uv1 = cur_uv - off;
     var uv2 = cur_uv + off;
-    var end1 = luma(textureSample(img, img_sampler, uv1).rgb) - avg;
-    var end2 = luma(textureSample(img, img_sampler, uv2).rgb) - avg;
+    var end1 = luma(textureSampleLevel(src, src_sampler, uv1, 0.0).rgb) - avg;
+    var end2 = luma(textureSampleLevel(src, src_sampler, uv2, 0.0).rgb) - avg;
     var reached1 = abs(end1) >= grad_scl;
     var reached2 = abs(end2) >= grad_scl;
     for (var i = 0u; i < ITERS && !(reached1 && reached2); i += 1u) {
         if !reached1 {
             uv1 -= off;
-            end1 = luma(textureSample(img, img_sampler, uv1).rgb) - avg;
+            end1 = luma(textureSampleLevel(src, src_sampler, uv1, 0.0).rgb) - avg;
             reached1 = abs(end1) >= grad_scl;
         }
         if !reached2 {
             uv2 += off;
-            end2 = luma(textureSample(img, img_sampler, uv2).rgb) - avg;
+            end2 = luma(textureSampleLevel(src, src_sampler, uv2, 0.0).rgb) - avg;
             reached2 = abs(end2) >= grad_scl;
         }
     }
@@ -112,5 +132,5 @@ fn fs_main(@builtin(position) coord: vec4f) -> @location(0) vec4f {
     } else {
         final_uv.x += final_off * step;
     }
-    return textureSample(img, img_sampler, final_uv);
+    return dithered(textureSampleLevel(src, src_sampler, final_uv, 0.0), coord);
 }
\ No newline at end of file
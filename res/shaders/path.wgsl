@@ -0,0 +1,40 @@
+struct Vertex {
+    @location(0) pos: vec2f,
+    @location(1) color: u32,
+}
+
+struct VSOut {
+    @builtin(position) pos: vec4f,
+    @location(0) color: vec4f,
+}
+
+// same as in render.wgsl
+struct Globals {
+    res: vec2f,
+    dither: f32,
+    jitter: vec2f,
+    linear: u32,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+
+fn srgb_to_linear(c: vec4f) -> vec4f {
+    if globals.linear == 0u {
+        return c;
+    }
+    let rgb = select(pow((c.rgb + 0.055) / 1.055, vec3f(2.4)), c.rgb / 12.92, c.rgb <= vec3f(0.04045));
+    return vec4f(rgb, c.a);
+}
+
+@vertex
+fn vs_main(in: Vertex) -> VSOut {
+    var out: VSOut;
+    out.pos = vec4f(in.pos * 2.0 - 1.0 + globals.jitter, 0, 1);
+    out.color = srgb_to_linear(unpack4x8unorm(in.color));
+    return out;
+}
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4f {
+    return in.color;
+}
@@ -0,0 +1,68 @@
+// renders pick id of each instance, see Renderer::pick
+struct Vertex {
+    @location(0) pos: vec2f,
+    @location(1) scale: vec2f,
+    @location(2) color: u32,
+    @location(3) roundness: f32,
+    @location(4) rotation: f32,
+    @location(5) stroke_width: f32,
+    @location(6) stroke_color: u32,
+    @location(7) tex_coord: vec2u, // packed whxy
+    @location(8) flags: u32,
+    @location(9) mask: vec2f, // progress, mask param
+    @location(10) tiling: vec2f, // img repeats across shape
+    @location(11) custom: vec4f, // read by custom shaders
+}
+
+struct VSOut {
+    @builtin(position) pos: vec4f,
+    @location(0) uv: vec2f,
+    @location(1) roundness: f32,
+    @interpolate(flat) @location(2) scale: vec2f,
+    @interpolate(flat) @location(3) id: u32,
+}
+
+struct Globals {
+    res: vec2f,
+    dither: f32,
+    // subpixel offset in ndc, for taa
+    jitter: vec2f,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vert_idx: u32, in: Vertex, @location(12) id: u32) -> VSOut {
+    var out: VSOut;
+    let uv = vec2f(vec2u(vert_idx % 2u, vert_idx / 2u));
+    out.uv = uv * 2.0 - 1.0;
+    let suv = out.uv * in.scale;
+    let rot_uv = suv * cos(in.rotation) + vec2f(-1, 1) * suv.yx * globals.res.yx / globals.res * sin(in.rotation);
+    out.pos = vec4f((in.pos * 2.0 - 1.0) + rot_uv * 2.0, 0, 1);
+    out.roundness = in.roundness;
+    out.scale = in.scale * globals.res;
+    out.scale /= min(out.scale.x, out.scale.y);
+    out.id = id;
+    return out;
+}
+
+fn elongated_rrect(p: vec2f, r: f32, h: vec2f) -> f32 {
+	let q = abs(p) - h;
+	let a = max(q, vec2f(0)) - 1.0 + r;
+	return length(max(a, vec2f(0))) + min(max(a.x, a.y), 0.0) - r + min(max(q.x, q.y), 0.0);
+}
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) u32 {
+    // masks and textures are ignored, whole shape is pickable
+    var r = 0.0;
+    if in.roundness < 1.0 {
+        r = elongated_rrect(in.uv * in.scale, in.roundness, in.scale - 1);
+    } else {
+        r = length(in.uv) - 1.0;
+    }
+    if in.id == 0u || r > 0.0 {
+        discard;
+    }
+    return in.id;
+}
@@ -7,6 +7,42 @@ struct Vertex {
     @location(5) stroke_width: f32,
     @location(6) stroke_color: u32,
     @location(7) tex_coord: vec2u, // packed whxy
+    @location(8) flags: u32,
+    @location(9) mask: vec2f, // progress, mask param
+    @location(10) tiling: vec2f, // img repeats across shape
+    @location(11) custom: vec4f, // read by custom shaders
+}
+
+// Vertex packed to 48 bytes, vs_main takes it instead with VertexFormat::Compact
+struct CompactVertex {
+    @location(0) pos: u32, // snorm16 of (pos - 0.5) / 1.5
+    @location(1) scale: u32, // snorm16 of scale / 2
+    @location(2) color: u32,
+    @location(3) stroke_color: u32,
+    @location(4) rotation_stroke: u32, // f16 rotation, stroke width
+    @location(5) flags: u32, // top byte is roundness unorm8 of roundness / 2
+    @location(6) tex_coord: vec2u,
+    @location(7) mask: u32, // f16
+    @location(8) tiling: u32, // f16
+    @location(9) custom: vec2u, // f16
+}
+
+fn unpack_vertex(c: CompactVertex) -> Vertex {
+    var v: Vertex;
+    v.pos = unpack2x16snorm(c.pos) * 1.5 + 0.5;
+    v.scale = unpack2x16snorm(c.scale) * 2.0;
+    v.color = c.color;
+    let rotation_stroke = unpack2x16float(c.rotation_stroke);
+    v.rotation = rotation_stroke.x;
+    v.stroke_width = rotation_stroke.y;
+    v.stroke_color = c.stroke_color;
+    v.roundness = f32(c.flags >> 24u) / 127.5;
+    v.flags = c.flags & 0xffffffu;
+    v.tex_coord = c.tex_coord;
+    v.mask = unpack2x16float(c.mask);
+    v.tiling = unpack2x16float(c.tiling);
+    v.custom = vec4f(unpack2x16float(c.custom.x), unpack2x16float(c.custom.y));
+    return v;
 }
 
 struct VSOut {
@@ -18,24 +54,100 @@ struct VSOut {
     @location(4) stroke_width: f32,
     @interpolate(flat) @location(5) scale: vec2f,
     @interpolate(flat) @location(6) tex_coord: vec4u,
+    @interpolate(flat) @location(7) flags: u32,
+    @interpolate(flat) @location(8) mask: vec2f,
+    @interpolate(flat) @location(9) tiling: vec2f,
+    @interpolate(flat) @location(10) custom: vec4f,
 }
 
-@group(0) @binding(0) var<uniform> res: vec2f;
+// fill is blurred backdrop tinted by color
+const BACKDROP_BLUR: u32 = 1u;
+// shape is cut by edge moving in direction mask.y (0 right, 1 left, 2 down, 3 up)
+const MASK_REVEAL: u32 = 2u;
+// shape is intersected with capsule filled from left
+const MASK_FILL: u32 = 4u;
+// shape is ring arc from top going clockwise, mask.y is thickness
+const MASK_ARC: u32 = 8u;
+// fill is texel of cached layer, premultiplied
+const LAYER: u32 = 16u;
+// output is premultiplied, for multiply blending
+const PREMUL_OUT: u32 = 32u;
+// atlas is sampled with nearest filtering instead of linear
+const NEAREST: u32 = 64u;
+// img is repeated tiling times across shape
+const TILE: u32 = 128u;
+// half of stroke is outside of shape edge
+const STROKE_CENTER: u32 = 256u;
+// stroke is outside of shape edge
+const STROKE_OUTER: u32 = 512u;
+// bits of color matrix slot, 0 is none
+const COLOR_MATRIX_SHIFT: u32 = 16u;
+const PI: f32 = 3.14159265;
+
+// 4x5 color matrix, rgba weights and offset
+struct ColorMatrix {
+    m: mat4x4f,
+    offset: vec4f,
+}
+
+struct Globals {
+    res: vec2f,
+    dither: f32,
+    // subpixel offset in ndc, for taa
+    jitter: vec2f,
+    // render target is srgb, so srgb colors are converted to linear before blending
+    linear: u32,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
 @group(0) @binding(1) var atlas: texture_2d<f32>;
+@group(0) @binding(2) var backdrop: texture_2d<f32>;
+@group(0) @binding(3) var backdrop_sampler: sampler;
+@group(0) @binding(4) var layers: texture_2d<f32>;
+@group(0) @binding(5) var nearest_sampler: sampler;
+@group(0) @binding(6) var<uniform> color_matrices: array<ColorMatrix, 16>;
+// copy of render target made by Renderer::grab_pass
+@group(0) @binding(7) var grab: texture_2d<f32>;
+
+fn srgb_to_linear(c: vec4f) -> vec4f {
+    if globals.linear == 0u {
+        return c;
+    }
+    let rgb = select(pow((c.rgb + 0.055) / 1.055, vec3f(2.4)), c.rgb / 12.92, c.rgb <= vec3f(0.04045));
+    return vec4f(rgb, c.a);
+}
+
+// part of stroke width outside of shape edge
+fn stroke_outside(flags: u32) -> f32 {
+    if (flags & STROKE_OUTER) != 0u {
+        return 1.0;
+    }
+    if (flags & STROKE_CENTER) != 0u {
+        return 0.5;
+    }
+    return 0.0;
+}
 
 @vertex
 fn vs_main(@builtin(vertex_index) vert_idx: u32, in: Vertex) -> VSOut {
     var out: VSOut;
     let uv = vec2f(vec2u(vert_idx % 2u, vert_idx / 2u));
-    out.uv = uv * 2.0 - 1.0;
+    // quad grows to fit stroke outside of shape, uv stays -1 to 1 at shape edge
+    let px = in.scale * globals.res;
+    let grow = in.stroke_width * stroke_outside(in.flags) * min(px.x, px.y) / px;
+    out.uv = (uv * 2.0 - 1.0) * (1.0 + grow);
     let suv = out.uv * in.scale;
-    let rot_uv = suv * cos(in.rotation) + vec2f(-1, 1) * suv.yx * res.yx / res * sin(in.rotation);
-    out.pos = vec4f((in.pos * 2.0 - 1.0) + rot_uv * 2.0, 0, 1);
-    out.color = unpack4x8unorm(in.color);
+    let rot_uv = suv * cos(in.rotation) + vec2f(-1, 1) * suv.yx * globals.res.yx / globals.res * sin(in.rotation);
+    out.pos = vec4f((in.pos * 2.0 - 1.0) + rot_uv * 2.0 + globals.jitter, 0, 1);
+    out.color = srgb_to_linear(unpack4x8unorm(in.color));
     out.roundness = in.roundness;
     out.stroke_width = in.stroke_width;
-    out.stroke_color = unpack4x8unorm(in.stroke_color);
-    out.scale = in.scale * res;
+    out.stroke_color = srgb_to_linear(unpack4x8unorm(in.stroke_color));
+    out.flags = in.flags;
+    out.mask = in.mask;
+    out.tiling = in.tiling;
+    out.custom = in.custom;
+    out.scale = in.scale * globals.res;
     out.scale /= min(out.scale.x, out.scale.y);
     if in.tex_coord.x > 0 {
         out.tex_coord = vec4u(in.tex_coord.y >> 16, in.tex_coord.y, in.tex_coord.x >> 48, in.tex_coord.x >> 32) & vec4u(0xFFFF);
@@ -51,6 +163,73 @@ fn elongated_rrect(p: vec2f, r: f32, h: vec2f) -> f32 {
 	return length(max(a, vec2f(0))) + min(max(a.x, a.y), 0.0) - r + min(max(q.x, q.y), 0.0); 
 }
 
+// ring arc symmetric around +y with half aperture of sc, rounded caps
+fn arc(p: vec2f, sc: vec2f, ra: f32, rb: f32) -> f32 {
+    let q = vec2f(abs(p.x), p.y);
+    if sc.y * q.x > sc.x * q.y {
+        return length(q - sc * ra) - rb;
+    }
+    return abs(length(q) - ra) - rb;
+}
+
+// applies progress/reveal masks to shape sdf r
+fn masked(r: f32, p: vec2f, scale: vec2f, flags: u32, mask: vec2f) -> f32 {
+    if (flags & MASK_REVEAL) != 0u {
+        let dir = u32(mask.y);
+        let axis = dir / 2u;
+        let s = scale[axis];
+        let along = p[axis] * select(1.0, -1.0, dir % 2u == 1u);
+        return max(r, along + s - 2.0 * s * mask.x);
+    }
+    if (flags & MASK_FILL) != 0u {
+        // capsule from left edge to fill end, at least as long as it's caps
+        let end = scale.x * (2.0 * mask.x - 1.0);
+        let start = min(-scale.x, end - 2.0);
+        let c = vec2f((start + end) * 0.5, 0.0);
+        let h = vec2f((end - start) * 0.5, scale.y) - 1.0;
+        return max(r, elongated_rrect(p - c, 0.999, h));
+    }
+    if (flags & MASK_ARC) != 0u {
+        // screen y is down, so clockwise from top is (sin, -cos)
+        let half = mask.x * PI;
+        let sc = vec2f(sin(half), cos(half));
+        let mid = vec2f(sc.x, -sc.y);
+        let q = vec2f(dot(p, vec2f(sc.y, sc.x)), dot(p, mid));
+        let t = mask.y * 0.5;
+        return arc(q, sc, 1.0 - t, t);
+    }
+    return r;
+}
+
+// samples atlas sub rect tc (xywh in texels) at uv in [0, 1],
+// clamped half a texel inside so filtering doesn't bleed in neighbouring imgs
+fn sample_atlas(uv: vec2f, tc: vec4u, flags: u32) -> vec4f {
+    let p = clamp(vec2f(tc.xy) + uv * vec2f(tc.zw), vec2f(tc.xy) + 0.5, vec2f(tc.xy + tc.zw) - 0.5);
+    let t = p / vec2f(textureDimensions(atlas));
+    if (flags & NEAREST) != 0u {
+        return textureSampleLevel(atlas, nearest_sampler, t, 0.0);
+    }
+    return textureSampleLevel(atlas, backdrop_sampler, t, 0.0);
+}
+
+// what was drawn before last Renderer::grab_pass at px pos, for custom shaders
+fn grabbed(pos: vec2f) -> vec4f {
+    return textureSampleLevel(grab, backdrop_sampler, pos / vec2f(textureDimensions(grab)), 0.0);
+}
+
+// params of shape set with Renderer::custom, for custom shaders
+var<private> custom: vec4f;
+
+// replaced by custom shaders, see Renderer::add_shader
+fn shade(col: vec4f, uv: vec2f, pos: vec2f) -> vec4f {
+    return col;
+}
+
+// interleaved gradient noise, cheap noise with blue-ish spectrum
+fn ign(p: vec2f) -> f32 {
+    return fract(52.9829189 * fract(dot(p, vec2f(0.06711056, 0.00583715))));
+}
+
 @fragment
 fn fs_main(in: VSOut) -> @location(0) vec4f {
     // problems (hard):
@@ -62,20 +241,50 @@ fn fs_main(in: VSOut) -> @location(0) vec4f {
     } else {
         r = length(in.uv) - 1.0;
     }
+    if (in.flags & (MASK_FILL | MASK_ARC)) != 0u && in.mask.x <= 0.0 {
+        discard;
+    }
+    r = masked(r, in.uv * in.scale, in.scale, in.flags, in.mask);
+    // edge moves out so stroke ends outside of shape
+    r -= in.stroke_width * stroke_outside(in.flags);
 
     var d = max(abs(dpdx(r)), abs(dpdy(r)));
     r -= d * 0.5;
     d *= 1.5;
     let edge = saturate(1.0 - in.roundness * 0.75 - r / d);
     let strk = saturate((r + in.stroke_width) / d);
-    var col = mix(in.color, in.stroke_color, strk);
+    var fill = in.color;
+    if (in.flags & BACKDROP_BLUR) != 0u {
+        let uv = in.pos.xy / globals.res;
+        fill *= vec4f(textureSampleLevel(backdrop, backdrop_sampler, uv, 0.0).rgb, 1.0);
+    }
+    var col = mix(fill, in.stroke_color, strk);
     col.a *= edge;
     if in.tex_coord.x != ~0u {
-        let p = vec2u((in.uv * 0.5 + 0.5) * vec2f(in.tex_coord.zw)) + in.tex_coord.xy;
-        col *= textureLoad(atlas, p, 0);
+        let uv = in.uv * 0.5 + 0.5;
+        if (in.flags & LAYER) != 0u {
+            let p = vec2u(uv * vec2f(in.tex_coord.zw)) + in.tex_coord.xy;
+            let t = textureLoad(layers, p, 0);
+            col *= vec4f(t.rgb / max(t.a, 1e-4), t.a);
+        } else if (in.flags & TILE) != 0u {
+            col *= sample_atlas(fract(uv * in.tiling), in.tex_coord, in.flags);
+        } else {
+            col *= sample_atlas(uv, in.tex_coord, in.flags);
+        }
     }
+    let cm = (in.flags >> COLOR_MATRIX_SHIFT) & 15u;
+    if cm != 0u {
+        col = saturate(color_matrices[cm].m * col + color_matrices[cm].offset);
+    }
+    custom = in.custom;
+    col = shade(col, in.uv, in.pos.xy);
     if col.a < 0.001 {
         discard;
     }
+    // hides banding of large soft gradients
+    col = vec4f(col.rgb + (ign(in.pos.xy) - 0.5) * globals.dither / 255.0, col.a);
+    if (in.flags & PREMUL_OUT) != 0u {
+        col = vec4f(col.rgb * col.a, col.a);
+    }
     return col;
 }
\ No newline at end of file
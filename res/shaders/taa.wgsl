@@ -0,0 +1,43 @@
+struct Params {
+    // weight of current frame, lower is smoother but ghosts more
+    blend: f32,
+    // ignore history, set after resize
+    reset: u32,
+}
+
+@group(0) @binding(0) var src: texture_2d<f32>;
+@group(0) @binding(1) var src_sampler: sampler;
+@group(0) @binding(2) var dst: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(3) var<uniform> params: Params;
+// result of last frame
+@group(0) @binding(4) var history: texture_2d<f32>;
+// result of this frame, read as history next frame
+@group(0) @binding(5) var history_out: texture_storage_2d<rgba16float, write>;
+
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    let size = textureDimensions(dst);
+    if any(id.xy >= size) {
+        return;
+    }
+    let cur = textureLoad(src, id.xy, 0);
+    var col = cur.rgb;
+    if params.reset == 0u {
+        // clamp history to 3x3 neighborhood of current frame, so moved content doesn't ghost
+        var lo = cur.rgb;
+        var hi = cur.rgb;
+        for (var y = -1; y <= 1; y++) {
+            for (var x = -1; x <= 1; x++) {
+                let p = clamp(vec2i(id.xy) + vec2i(x, y), vec2i(0), vec2i(size) - 1);
+                let c = textureLoad(src, p, 0).rgb;
+                lo = min(lo, c);
+                hi = max(hi, c);
+            }
+        }
+        let hist = clamp(textureLoad(history, id.xy, 0).rgb, lo, hi);
+        col = mix(hist, cur.rgb, params.blend);
+    }
+    let out = vec4f(col, cur.a);
+    textureStore(dst, id.xy, out);
+    textureStore(history_out, id.xy, out);
+}
@@ -0,0 +1,66 @@
+struct Params {
+    // 0: linear, 1: reinhard, 2: aces
+    op: u32,
+    exposure: f32,
+    lut_size: f32,
+    lut_strength: f32,
+    dither: f32,
+}
+
+@group(0) @binding(0) var src: texture_2d<f32>;
+@group(0) @binding(1) var src_sampler: sampler;
+@group(0) @binding(2) var dst: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(3) var<uniform> params: Params;
+// blue slices of 3d lut side by side, lut_size^2 x lut_size
+@group(0) @binding(4) var lut: texture_2d<f32>;
+
+// interleaved gradient noise, cheap noise with blue-ish spectrum
+fn ign(p: vec2f) -> f32 {
+    return fract(52.9829189 * fract(dot(p, vec2f(0.06711056, 0.00583715))));
+}
+
+fn reinhard(c: vec3f) -> vec3f {
+    return c / (1.0 + c);
+}
+
+// narkowicz aces filmic fit
+fn aces(c: vec3f) -> vec3f {
+    return saturate((c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14));
+}
+
+fn lut_slice(c: vec3f, slice: f32) -> vec3f {
+    let s = params.lut_size;
+    let uv = vec2f(
+        (slice * s + c.r * (s - 1.0) + 0.5) / (s * s),
+        (c.g * (s - 1.0) + 0.5) / s
+    );
+    return textureSampleLevel(lut, src_sampler, uv, 0.0).rgb;
+}
+
+fn grade(c: vec3f) -> vec3f {
+    let b = c.b * (params.lut_size - 1.0);
+    let b0 = floor(b);
+    let b1 = min(b0 + 1.0, params.lut_size - 1.0);
+    return mix(lut_slice(c, b0), lut_slice(c, b1), b - b0);
+}
+
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    let size = textureDimensions(dst);
+    if id.x >= size.x || id.y >= size.y {
+        return;
+    }
+    let src_col = textureLoad(src, id.xy, 0);
+    var c = max(src_col.rgb * params.exposure, vec3f(0.0));
+    switch params.op {
+        case 1u: { c = reinhard(c); }
+        case 2u: { c = aces(c); }
+        default: { c = saturate(c); }
+    }
+    if params.lut_strength > 0.0 {
+        c = mix(c, grade(c), params.lut_strength);
+    }
+    // +-dither/255 of noise hides 8 bit gradient banding, set when tonemap is last effect
+    c += (ign(vec2f(id.xy)) - 0.5) * params.dither / 255.0 * src_col.a;
+    textureStore(dst, id.xy, vec4f(c, src_col.a));
+}
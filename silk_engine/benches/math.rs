@@ -0,0 +1,117 @@
+#![feature(test)]
+extern crate test;
+
+use silk_engine::prelude::*;
+use test::{Bencher, black_box};
+
+// scalar reference implementations, what `Mat4`/`Vec4` did before simd
+fn scalar_mat4_mul(a: &[[f32; 4]; 4], b: &[[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    let mut cols = [[0.0; 4]; 4];
+    for c in 0..4 {
+        for r in 0..4 {
+            cols[c][r] = (0..4).map(|k| a[k][r] * b[c][k]).sum();
+        }
+    }
+    cols
+}
+
+fn scalar_mat4_vec(m: &[[f32; 4]; 4], v: [f32; 4]) -> [f32; 4] {
+    std::array::from_fn(|r| (0..4).map(|c| m[c][r] * v[c]).sum())
+}
+
+fn scalar_vec4_step(p: [f32; 4], v: [f32; 4], dt: f32) -> [f32; 4] {
+    std::array::from_fn(|i| (p[i] + v[i] * dt).clamp(0.0, 1.0))
+}
+
+fn mats() -> Vec<Mat4> {
+    (0..1024)
+        .map(|i| {
+            let q = Quat::from_axis_angle(Vec3::new(0.0, 0.6, 0.8), i as f32 * 0.01);
+            Mat4::from_trs(Vec3::new(i as f32, 1.0, -2.0), q, Vec3::new(1.0, 2.0, 0.5))
+        })
+        .collect()
+}
+
+fn particles() -> Vec<(Vec4, Vec4)> {
+    (0..4096)
+        .map(|i| {
+            let f = i as f32 * 1e-4;
+            (Vec4::new(f, 0.5, 1.0 - f, 0.0), Vec4::new(0.1, -f, f, 1.0))
+        })
+        .collect()
+}
+
+#[bench]
+fn mat4_mul_scalar(b: &mut Bencher) {
+    let ms = mats();
+    let view = Mat4::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+    let mut out = vec![[[0.0; 4]; 4]; ms.len()];
+    b.iter(|| {
+        for (o, m) in out.iter_mut().zip(black_box(&ms)) {
+            *o = scalar_mat4_mul(&view.cols, &m.cols);
+        }
+        black_box(&out);
+    });
+}
+
+#[bench]
+fn mat4_mul_simd(b: &mut Bencher) {
+    let ms = mats();
+    let view = Mat4::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+    let mut out = vec![Mat4::IDENTITY; ms.len()];
+    b.iter(|| {
+        for (o, m) in out.iter_mut().zip(black_box(&ms)) {
+            *o = view * *m;
+        }
+        black_box(&out);
+    });
+}
+
+#[bench]
+fn mat4_vec4_scalar(b: &mut Bencher) {
+    let m = mats()[7];
+    let ps = particles();
+    let mut out = vec![[0.0; 4]; ps.len()];
+    b.iter(|| {
+        for (o, (p, _)) in out.iter_mut().zip(black_box(&ps)) {
+            *o = scalar_mat4_vec(&m.cols, (*p).into());
+        }
+        black_box(&out);
+    });
+}
+
+#[bench]
+fn mat4_vec4_simd(b: &mut Bencher) {
+    let m = mats()[7];
+    let ps = particles();
+    let mut out = vec![Vec4::ZERO; ps.len()];
+    b.iter(|| {
+        for (o, (p, _)) in out.iter_mut().zip(black_box(&ps)) {
+            *o = m * *p;
+        }
+        black_box(&out);
+    });
+}
+
+#[bench]
+fn vec4_step_scalar(b: &mut Bencher) {
+    let mut ps: Vec<([f32; 4], [f32; 4])> = particles()
+        .into_iter()
+        .map(|(p, v)| (p.into(), v.into()))
+        .collect();
+    b.iter(|| {
+        for (p, v) in black_box(&mut ps) {
+            *p = scalar_vec4_step(*p, *v, 0.016);
+        }
+    });
+}
+
+#[bench]
+fn vec4_step_simd(b: &mut Bencher) {
+    let mut ps = particles();
+    b.iter(|| {
+        for (p, v) in black_box(&mut ps) {
+            *p = (*p + *v * 0.016).saturate();
+        }
+    });
+}
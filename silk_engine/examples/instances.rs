@@ -0,0 +1,33 @@
+use silk_engine::prelude::*;
+
+/// more instances than initial instance vbo capacity (1024), so it's grown while drawing
+const COUNT: usize = 64 * 64;
+
+struct Instances {
+    frame: u32,
+}
+
+impl App for Instances {
+    fn new(_ctx: &mut AppContext<Self>) -> Self {
+        Self { frame: 0 }
+    }
+
+    fn update(&mut self, _ctx: &mut AppContext<Self>) {
+        self.frame += 1;
+    }
+
+    fn render(&mut self, gfx: &mut Renderer) {
+        let side = (COUNT as f32).sqrt() as usize;
+        let size = 1.0 / side as f32;
+        for i in 0..COUNT {
+            let (x, y) = ((i % side) as f32 * size, (i / side) as f32 * size);
+            let hue = (i as u32 + self.frame) % 256;
+            gfx.color = [hue as u8, 128, 255 - hue as u8, 255];
+            gfx.rect(Pc(x), Pc(y), Pc(size * 0.9), Pc(size * 0.9));
+        }
+    }
+}
+
+fn main() {
+    Engine::<Instances>::window("Instances", 800, 600);
+}
@@ -0,0 +1,145 @@
+use std::{fmt::Display, time::Duration};
+
+use crate::gfx::DrawStats;
+
+/// frames that aren't measured, so shader/pipeline creation and first uploads don't skew results
+const WARMUP_FRAMES: u32 = 16;
+
+/// frame time stats in milliseconds
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Timing {
+    pub avg: f32,
+    pub min: f32,
+    pub max: f32,
+    pub p50: f32,
+    pub p99: f32,
+}
+
+impl Timing {
+    fn new(mut ms: Vec<f32>) -> Option<Self> {
+        if ms.is_empty() {
+            return None;
+        }
+        ms.sort_by(f32::total_cmp);
+        let pct = |p: f32| ms[((ms.len() - 1) as f32 * p).round() as usize];
+        Some(Self {
+            avg: ms.iter().sum::<f32>() / ms.len() as f32,
+            min: ms[0],
+            max: ms[ms.len() - 1],
+            p50: pct(0.5),
+            p99: pct(0.99),
+        })
+    }
+
+    fn json(&self) -> String {
+        format!(
+            r#"{{"avg":{},"min":{},"max":{},"p50":{},"p99":{}}}"#,
+            self.avg, self.min, self.max, self.p50, self.p99
+        )
+    }
+}
+
+impl Display for Timing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "avg {:.3} | min {:.3} | p50 {:.3} | p99 {:.3} | max {:.3}",
+            self.avg, self.min, self.p50, self.p99, self.max
+        )
+    }
+}
+
+/// result of `Engine::bench`, `Display` prints summary, `json` is for ci perf tracking
+#[derive(Clone, Debug, Default)]
+pub struct BenchReport {
+    pub frames: u32,
+    /// update + render + submit time
+    pub cpu: Timing,
+    /// none if gpu timestamps weren't available
+    pub gpu: Option<Timing>,
+    /// averages per frame
+    pub draws: f32,
+    pub dispatches: f32,
+    pub instances: f32,
+    pub vertices: f32,
+}
+
+impl BenchReport {
+    pub fn json(&self) -> String {
+        format!(
+            r#"{{"frames":{},"cpu_ms":{},"gpu_ms":{},"draws":{},"dispatches":{},"instances":{},"vertices":{}}}"#,
+            self.frames,
+            self.cpu.json(),
+            self.gpu.map(|g| g.json()).unwrap_or("null".to_string()),
+            self.draws,
+            self.dispatches,
+            self.instances,
+            self.vertices
+        )
+    }
+}
+
+impl Display for BenchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "bench: {} frames", self.frames)?;
+        writeln!(f, "cpu ms: {}", self.cpu)?;
+        match &self.gpu {
+            Some(gpu) => writeln!(f, "gpu ms: {gpu}")?,
+            None => writeln!(f, "gpu ms: unavailable")?,
+        }
+        write!(
+            f,
+            "per frame: {:.1} draws, {:.1} dispatches, {:.0} instances, {:.0} vertices",
+            self.draws, self.dispatches, self.instances, self.vertices
+        )
+    }
+}
+
+/// collects per frame measurements while benchmarking
+pub(crate) struct Bench {
+    frames: u32,
+    warmup: u32,
+    cpu_ms: Vec<f32>,
+    gpu_ms: Vec<f32>,
+    stats: Vec<DrawStats>,
+}
+
+impl Bench {
+    pub(crate) fn new(frames: u32) -> Self {
+        Self {
+            frames,
+            warmup: WARMUP_FRAMES,
+            cpu_ms: Vec::with_capacity(frames as usize),
+            gpu_ms: Vec::with_capacity(frames as usize),
+            stats: Vec::with_capacity(frames as usize),
+        }
+    }
+
+    /// true once all frames were measured
+    pub(crate) fn frame(&mut self, cpu: Duration, gpu: Option<f32>, stats: DrawStats) -> bool {
+        if self.warmup > 0 {
+            self.warmup -= 1;
+            return false;
+        }
+        self.cpu_ms.push(cpu.as_secs_f32() * 1e3);
+        if let Some(gpu) = gpu {
+            self.gpu_ms.push(gpu * 1e3);
+        }
+        self.stats.push(stats);
+        self.stats.len() as u32 >= self.frames
+    }
+
+    pub(crate) fn report(&self) -> BenchReport {
+        let n = self.stats.len().max(1) as f32;
+        let avg = |f: fn(&DrawStats) -> f32| self.stats.iter().map(f).sum::<f32>() / n;
+        BenchReport {
+            frames: self.stats.len() as u32,
+            cpu: Timing::new(self.cpu_ms.clone()).unwrap_or_default(),
+            gpu: Timing::new(self.gpu_ms.clone()),
+            draws: avg(|s| s.draws as f32),
+            dispatches: avg(|s| s.dispatches as f32),
+            instances: avg(|s| s.instances as f32),
+            vertices: avg(|s| s.vertices as f32),
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use winit::{dpi::PhysicalSize, monitor::MonitorHandle, window::WindowAttributes};
+
+use crate::{
+    gfx::{GpuPreference, ValidationFeatures, VulkanConfig, max_supported_samples},
+    warn,
+};
+
+/// how edges are smoothed, shapes are also anti-aliased analytically in render.wgsl
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AntiAliasing {
+    None,
+    /// fast approximate post effect
+    Fxaa,
+    /// multisampling with sample count (2, 4, 8...), with fxaa post effect
+    Msaa(u32),
+    /// temporal, jitters subpixel offset each frame and blends with clamped history
+    Taa,
+}
+
+impl AntiAliasing {
+    /// msaa sample count, 1 if not multisampled
+    pub fn samples(self) -> u32 {
+        match self {
+            Self::Msaa(samples) => samples,
+            _ => 1,
+        }
+    }
+
+    /// clamps msaa samples to what gpu supports
+    pub(crate) fn validated(self) -> Self {
+        match self {
+            Self::Msaa(samples) => {
+                let supported = max_supported_samples(samples);
+                if supported != samples {
+                    warn!("{samples}x msaa is not supported, using {supported}x");
+                }
+                Self::Msaa(supported)
+            }
+            aa => aa,
+        }
+    }
+}
+
+/// display mode a monitor supports, used for exclusive fullscreen
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VideoMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate_millihertz: u32,
+    pub bit_depth: u16,
+}
+
+impl VideoMode {
+    pub fn refresh_rate(&self) -> u32 {
+        (self.refresh_rate_millihertz as f32 / 1000.0).round() as u32
+    }
+
+    pub(crate) fn from_winit(mode: &winit::monitor::VideoModeHandle) -> Self {
+        Self {
+            width: mode.size().width,
+            height: mode.size().height,
+            refresh_rate_millihertz: mode.refresh_rate_millihertz(),
+            bit_depth: mode.bit_depth(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Fullscreen {
+    #[default]
+    Windowed,
+    /// window covering whole monitor, display mode is unchanged
+    Borderless,
+    /// switches monitor to video mode, see [`crate::AppContext::video_modes`]
+    Exclusive(VideoMode),
+}
+
+impl Fullscreen {
+    /// exclusive mode not supported by monitor falls back to borderless
+    pub(crate) fn to_winit(self, monitor: &MonitorHandle) -> Option<winit::window::Fullscreen> {
+        match self {
+            Self::Windowed => None,
+            Self::Borderless => Some(winit::window::Fullscreen::Borderless(Some(
+                monitor.clone(),
+            ))),
+            Self::Exclusive(mode) => {
+                let handle = monitor
+                    .video_modes()
+                    .find(|m| VideoMode::from_winit(m) == mode);
+                if handle.is_none() {
+                    warn!("video mode not supported by monitor, using borderless: {mode:?}");
+                }
+                Some(handle.map_or(
+                    winit::window::Fullscreen::Borderless(Some(monitor.clone())),
+                    winit::window::Fullscreen::Exclusive,
+                ))
+            }
+        }
+    }
+}
+
+impl Default for AntiAliasing {
+    fn default() -> Self {
+        Self::Msaa(8)
+    }
+}
+
+/// window and gfx options passed to [`crate::Engine::run`]
+#[derive(Clone, Debug)]
+pub struct EngineConfig {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub resizable: bool,
+    pub decorations: bool,
+    pub fullscreen: Fullscreen,
+    pub min_size: Option<(u32, u32)>,
+    pub max_size: Option<(u32, u32)>,
+    pub anti_aliasing: AntiAliasing,
+    /// render targets are allocated at monitor size so resizing only rebuilds swapchain
+    pub live_resize: bool,
+    /// update rate while window is minimized or occluded, 0 only updates on events
+    pub background_fps: u32,
+    /// shortcut chord that makes renderdoc capture next frame, bound when renderdoc is loaded
+    pub capture_key: Option<String>,
+    /// renderdoc captures frame with this [`crate::AppContext::frame`], loads renderdoc
+    pub capture_frame: Option<u32>,
+    pub vulkan: VulkanConfig,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            title: "App".to_string(),
+            width: 800,
+            height: 600,
+            resizable: true,
+            decorations: true,
+            fullscreen: Fullscreen::Windowed,
+            min_size: None,
+            max_size: None,
+            anti_aliasing: AntiAliasing::default(),
+            live_resize: true,
+            background_fps: 10,
+            capture_key: Some("Ctrl+F12".to_string()),
+            capture_frame: None,
+            vulkan: VulkanConfig::default(),
+        }
+    }
+}
+
+impl EngineConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    pub fn fullscreen(mut self, fullscreen: Fullscreen) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    pub fn min_size(mut self, width: u32, height: u32) -> Self {
+        self.min_size = Some((width, height));
+        self
+    }
+
+    pub fn max_size(mut self, width: u32, height: u32) -> Self {
+        self.max_size = Some((width, height));
+        self
+    }
+
+    pub fn anti_aliasing(mut self, anti_aliasing: AntiAliasing) -> Self {
+        self.anti_aliasing = anti_aliasing;
+        self
+    }
+
+    /// shorthand for `anti_aliasing(AntiAliasing::Msaa(samples))`
+    pub fn msaa(self, samples: u32) -> Self {
+        self.anti_aliasing(AntiAliasing::Msaa(samples))
+    }
+
+    /// trades vram for smooth window resizing, disable to keep render targets at window size
+    pub fn live_resize(mut self, live_resize: bool) -> Self {
+        self.live_resize = live_resize;
+        self
+    }
+
+    pub fn background_fps(mut self, background_fps: u32) -> Self {
+        self.background_fps = background_fps;
+        self
+    }
+
+    pub fn hdr(mut self, hdr: bool) -> Self {
+        self.vulkan.hdr = hdr;
+        self
+    }
+
+    /// srgb-correct blending, gradients and alpha blends don't darken in between colors
+    pub fn linear(mut self, linear: bool) -> Self {
+        self.vulkan.linear = linear;
+        self
+    }
+
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vulkan.vsync = vsync;
+        self
+    }
+
+    /// see-through window where rendered alpha < 1, clear with `Renderer::clear_color`
+    /// alpha 0 for overlay/widget style apps
+    pub fn transparent(mut self, transparent: bool) -> Self {
+        self.vulkan.transparent = transparent;
+        self
+    }
+
+    /// picks gpu by index, name, luid or type, falls back to best gpu if none matches
+    pub fn gpu(mut self, gpu: GpuPreference) -> Self {
+        self.vulkan.gpu = gpu;
+        self
+    }
+
+    /// shorthand for `gpu(GpuPreference::Name(name))`
+    pub fn preferred_gpu(self, name: &str) -> Self {
+        self.gpu(GpuPreference::Name(name.to_string()))
+    }
+
+    /// shorthand for `gpu(GpuPreference::Integrated)`
+    pub fn integrated_gpu(self) -> Self {
+        self.gpu(GpuPreference::Integrated)
+    }
+
+    pub fn validation(mut self, validation: bool) -> Self {
+        self.vulkan.validation = validation;
+        self
+    }
+
+    /// gpu-assisted, best practices and sync validation, also enables validation
+    pub fn validation_features(mut self, features: ValidationFeatures) -> Self {
+        self.vulkan.validation_features = features;
+        self.validation(true)
+    }
+
+    /// loads renderdoc so frames can be captured without injecting it, needs `renderdoc` feature
+    pub fn renderdoc(mut self, renderdoc: bool) -> Self {
+        self.vulkan.renderdoc = renderdoc;
+        self
+    }
+
+    /// none disables capture shortcut
+    pub fn capture_key(mut self, chord: Option<&str>) -> Self {
+        self.capture_key = chord.map(str::to_string);
+        self
+    }
+
+    pub fn capture_frame(mut self, frame: u32) -> Self {
+        self.capture_frame = Some(frame);
+        self.renderdoc(true)
+    }
+
+    pub(crate) fn window_attribs(&self) -> WindowAttributes {
+        let mut attribs = WindowAttributes::default()
+            .with_title(&self.title)
+            .with_inner_size(PhysicalSize::new(self.width, self.height))
+            .with_resizable(self.resizable)
+            .with_decorations(self.decorations)
+            .with_transparent(self.vulkan.transparent);
+        if let Some((width, height)) = self.min_size {
+            attribs = attribs.with_min_inner_size(PhysicalSize::new(width, height));
+        }
+        if let Some((width, height)) = self.max_size {
+            attribs = attribs.with_max_inner_size(PhysicalSize::new(width, height));
+        }
+        attribs
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+
+use winit::{
+    event::WindowEvent,
+    keyboard::{Key as LogicalKey, NamedKey, PhysicalKey},
+};
+
+use crate::{
+    gfx::{Px, Renderer},
+    info,
+    input::Key,
+    util::{Level, LogBuffer, logger, tweaks},
+    warn,
+};
+
+type Command = Box<dyn FnMut(&[&str])>;
+
+/// lines kept in console scrollback
+const CAPACITY: usize = 256;
+/// fraction of window height console covers when open
+const HEIGHT: f32 = 0.4;
+
+/// drop-down developer console, shows recent log lines and runs registered commands.
+/// commands report back through `info!`/`warn!`, which show up in console
+pub struct Console {
+    open: bool,
+    key: Key,
+    input: String,
+    history: Vec<String>,
+    /// index into history while browsing with up/down
+    history_idx: Option<usize>,
+    commands: HashMap<String, Command>,
+    logs: LogBuffer,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Console {
+    pub fn new() -> Self {
+        let logs = LogBuffer::new(CAPACITY);
+        logger().add_sink(Level::Info, logs.clone());
+        let mut console = Self {
+            open: false,
+            key: Key::Backquote,
+            input: String::new(),
+            history: Vec::new(),
+            history_idx: None,
+            commands: HashMap::new(),
+            logs,
+        };
+        console.register_tweak_commands();
+        console
+    }
+
+    /// `set name value`, `get name`, `tweaks` and `save` for values made with `tweak!`
+    fn register_tweak_commands(&mut self) {
+        self.register("set", |args| match args {
+            [name, value @ ..] if !value.is_empty() => {
+                if tweaks().set(name, &value.join(" ")) {
+                    info!(target: "console", "{name} = {}", tweaks().value(name).unwrap());
+                }
+            }
+            _ => warn!(target: "console", "usage: set <tweak> <value>"),
+        });
+        self.register("get", |args| {
+            for name in args {
+                match tweaks().value(name) {
+                    Some(value) => info!(target: "console", "{name} = {value}"),
+                    None => warn!(target: "console", "tweak not found: {name}"),
+                }
+            }
+        });
+        self.register("tweaks", |_| {
+            for (name, value) in tweaks().iter() {
+                info!(target: "console", "{name} = {value}");
+            }
+        });
+        self.register("save", |_| match tweaks().save() {
+            Ok(()) => info!(target: "console", "saved tweaks"),
+            Err(e) => warn!(target: "console", "failed to save tweaks: {e}"),
+        });
+    }
+
+    /// `f` gets whitespace separated args after command name, replaces command with same name
+    pub fn register(&mut self, name: &str, f: impl FnMut(&[&str]) + 'static) {
+        self.commands.insert(name.to_string(), Box::new(f));
+    }
+
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.commands.remove(name).is_some()
+    }
+
+    /// runs command line as if it was typed in console
+    pub fn run(&mut self, line: &str) {
+        let line = line.trim();
+        info!(target: "console", "> {line}");
+        let mut args = line.split_whitespace();
+        let Some(name) = args.next() else {
+            return;
+        };
+        let args: Vec<&str> = args.collect();
+        match name {
+            "help" => {
+                let mut names: Vec<&str> = self.commands.keys().map(String::as_str).collect();
+                names.sort_unstable();
+                info!(target: "console", "commands: clear help {}", names.join(" "));
+            }
+            "clear" => self.logs.clear(),
+            _ => match self.commands.get_mut(name) {
+                Some(command) => command(&args),
+                None => warn!(target: "console", "unknown command: {name}"),
+            },
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// key that opens/closes console, backquote by default
+    pub fn set_key(&mut self, key: Key) {
+        self.key = key;
+    }
+
+    /// handles typing while open, true if event was consumed and shouldn't reach app
+    pub(crate) fn event(&mut self, event: &WindowEvent) -> bool {
+        let WindowEvent::KeyboardInput { event, .. } = event else {
+            return false;
+        };
+        if !event.state.is_pressed() {
+            return false;
+        }
+        if event.physical_key == PhysicalKey::Code(self.key) {
+            if !event.repeat {
+                self.toggle();
+            }
+            return true;
+        }
+        if !self.open {
+            return false;
+        }
+        match &event.logical_key {
+            LogicalKey::Named(NamedKey::Enter) => {
+                let line = std::mem::take(&mut self.input);
+                if !line.trim().is_empty() && self.history.last() != Some(&line) {
+                    self.history.push(line.clone());
+                }
+                self.history_idx = None;
+                self.run(&line);
+            }
+            LogicalKey::Named(NamedKey::Backspace) => {
+                self.input.pop();
+            }
+            LogicalKey::Named(NamedKey::Escape) => self.open = false,
+            LogicalKey::Named(NamedKey::ArrowUp) if !self.history.is_empty() => {
+                let i = self
+                    .history_idx
+                    .map_or(self.history.len() - 1, |i| i.saturating_sub(1));
+                self.history_idx = Some(i);
+                self.input = self.history[i].clone();
+            }
+            LogicalKey::Named(NamedKey::ArrowDown) => {
+                self.history_idx = self
+                    .history_idx
+                    .map(|i| i + 1)
+                    .filter(|&i| i < self.history.len());
+                self.input = self
+                    .history_idx
+                    .map_or(String::new(), |i| self.history[i].clone());
+            }
+            _ => {
+                if let Some(text) = &event.text {
+                    self.input.extend(text.chars().filter(|c| !c.is_control()));
+                }
+            }
+        }
+        true
+    }
+
+    /// drawn on top of everything with last frame's cull stats,
+    /// text is skipped if no font is loaded
+    pub(crate) fn draw(&self, gfx: &mut Renderer) {
+        if !self.open {
+            return;
+        }
+        let width = gfx.width() as i32;
+        let height = (gfx.height() * HEIGHT) as i32;
+        let size = (16.0 * gfx.scale_factor()).round() as i32;
+        let pad = size / 2;
+        gfx.push_state();
+        gfx.no_img();
+        gfx.z(i32::MAX);
+        gfx.stroke_width = 0.0;
+        gfx.roundness = 0.0;
+        gfx.rotation = 0.0;
+        gfx.color = [16, 16, 20, 224];
+        gfx.rect(Px(0), Px(0), Px(width), Px(height));
+        gfx.color = [32, 32, 40, 240];
+        gfx.rect(Px(0), Px(height - size - pad), Px(width), Px(size + pad));
+
+        let rows = ((height - size - pad * 2) / size).max(0) as usize;
+        let records = self.logs.records();
+        let mut y = height - size - pad * 2 - size * rows.min(records.len()) as i32;
+        for r in records.iter().skip(records.len().saturating_sub(rows)) {
+            gfx.color = match r.level {
+                Level::Error => [255, 96, 96, 255],
+                Level::Warn => [255, 208, 96, 255],
+                _ if r.target == "console" => [160, 200, 255, 255],
+                _ => [220, 220, 220, 255],
+            };
+            if gfx.try_text(&r.msg, Px(pad), Px(y), Px(size)).is_err() {
+                break;
+            }
+            y += size;
+        }
+        self.draw_tweaks(gfx, width, height, size);
+        gfx.color = [255, 255, 255, 255];
+        gfx.try_text(
+            &format!("> {}_", self.input),
+            Px(pad),
+            Px(height - size - pad / 2),
+            Px(size),
+        )
+        .unwrap_or_default();
+        // in input bar, lined up with tweaks panel
+        let cull = gfx.cull_stats();
+        gfx.color = [160, 160, 176, 255];
+        gfx.try_text(
+            &format!("culled {} / {} instances", cull.culled, cull.submitted),
+            Px(width - width / 4 + pad),
+            Px(height - size - pad / 2),
+            Px(size),
+        )
+        .unwrap_or_default();
+        gfx.pop_state();
+    }
+
+    /// generated panel listing every tweak on right side of console
+    fn draw_tweaks(&self, gfx: &mut Renderer, width: i32, height: i32, size: i32) {
+        let lines: Vec<String> = tweaks()
+            .iter()
+            .map(|(name, value)| format!("{name} = {value}"))
+            .collect();
+        if lines.is_empty() {
+            return;
+        }
+        let pad = size / 2;
+        let panel_w = width / 4;
+        let x = width - panel_w;
+        gfx.color = [24, 24, 32, 240];
+        gfx.rect(Px(x), Px(0), Px(panel_w), Px(height - size - pad));
+        gfx.color = [200, 255, 200, 255];
+        let rows = ((height - size - pad * 2) / size).max(0) as usize;
+        for (i, line) in lines.iter().take(rows).enumerate() {
+            let y = pad + i as i32 * size;
+            if gfx.try_text(line, Px(x + pad), Px(y), Px(size)).is_err() {
+                break;
+            }
+        }
+    }
+}
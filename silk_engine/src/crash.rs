@@ -0,0 +1,92 @@
+use std::{
+    fmt::Write,
+    sync::{
+        Arc, Mutex, Weak,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{RES_PATH, diagnostics::Diagnostics, gfx::RenderCtx, util::logger::recent_records};
+
+/// frame being updated/rendered when panic happened
+static FRAME: AtomicU32 = AtomicU32::new(0);
+/// ctx whose resources are dumped, unset until vulkan is initialized
+static CTX: Mutex<Option<Weak<Mutex<RenderCtx>>>> = Mutex::new(None);
+
+pub(crate) fn set_frame(frame: u32) {
+    FRAME.store(frame, Ordering::Relaxed);
+}
+
+pub(crate) fn watch_ctx(ctx: &Arc<Mutex<RenderCtx>>) {
+    *CTX.lock().unwrap_or_else(|e| e.into_inner()) = Some(Arc::downgrade(ctx));
+}
+
+/// utc "yyyy-mm-dd_hh-mm-ss"
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (days, rem) = (secs / 86400, secs % 86400);
+    // days to civil date, from howard hinnant's algorithm
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z % 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as u64;
+    format!(
+        "{year:04}-{month:02}-{day:02}_{:02}-{:02}-{:02}",
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+/// writes panic message, frame, gpu info, render ctx resources and recent logs
+/// into res/crash/<timestamp>.txt, returns its path.
+/// ctx is skipped if panic happened while it was locked, since it may be half modified
+pub(crate) fn dump(msg: &str, backtrace: &str) -> Option<String> {
+    let mut report = String::new();
+    writeln!(report, "panicked: {msg}").ok()?;
+    writeln!(report, "at: {backtrace}").ok()?;
+    writeln!(report, "frame: {}", FRAME.load(Ordering::Relaxed)).ok()?;
+    let ctx = CTX
+        .try_lock()
+        .ok()
+        .and_then(|c| c.as_ref().and_then(Weak::upgrade));
+    // gpu info is only queried once vulkan is initialized, to not init it mid panic
+    if let Some(ctx) = ctx {
+        writeln!(report, "\n{}", Diagnostics::new()).ok()?;
+        match ctx.try_lock() {
+            Ok(ctx) => writeln!(report, "{}", ctx.resource_report()),
+            Err(std::sync::TryLockError::Poisoned(ctx)) => {
+                writeln!(report, "{}", ctx.into_inner().resource_report())
+            }
+            Err(std::sync::TryLockError::WouldBlock) => {
+                writeln!(
+                    report,
+                    "render ctx was locked while panicking, resources skipped"
+                )
+            }
+        }
+        .ok()?;
+    }
+    writeln!(report, "\nlog:").ok()?;
+    for r in recent_records() {
+        writeln!(
+            report,
+            "{:.3} {:?} {}: {}",
+            r.time, r.level, r.target, r.msg
+        )
+        .ok()?;
+    }
+    let dir = format!("{RES_PATH}/crash");
+    std::fs::create_dir_all(&dir).ok()?;
+    let path = format!("{dir}/{}.txt", timestamp());
+    std::fs::write(&path, report).ok()?;
+    Some(path)
+}
@@ -0,0 +1,121 @@
+use std::fmt::{Display, Formatter};
+
+use ash::vk;
+
+use crate::gfx::{caps, enabled_gpu_extensions, gpu_props, vulkan_config};
+
+/// engine, platform and gpu info with active settings, for bug reports,
+/// `Display` pretty prints it
+#[derive(Clone, Debug)]
+pub struct Diagnostics {
+    pub engine_version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub debug_build: bool,
+    pub gpu_name: String,
+    pub gpu_type: vk::PhysicalDeviceType,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub driver_version: String,
+    pub vulkan_version: String,
+    pub validation: bool,
+    pub extensions: Vec<String>,
+    pub features: Vec<&'static str>,
+    /// (name, value)
+    pub settings: Vec<(String, String)>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        let props = gpu_props();
+        let api = props.api_version;
+        Self {
+            engine_version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            debug_build: cfg!(debug_assertions),
+            gpu_name: props
+                .device_name_as_c_str()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            gpu_type: props.device_type,
+            vendor_id: props.vendor_id,
+            device_id: props.device_id,
+            driver_version: driver_version(props.vendor_id, props.driver_version),
+            vulkan_version: format!(
+                "{}.{}.{}",
+                vk::api_version_major(api),
+                vk::api_version_minor(api),
+                vk::api_version_patch(api)
+            ),
+            validation: vulkan_config().validation,
+            extensions: enabled_gpu_extensions()
+                .iter()
+                .map(|e| e.to_string_lossy().into_owned())
+                .collect(),
+            features: caps().enabled_features(),
+            settings: Vec::new(),
+        }
+    }
+
+    pub fn setting(mut self, name: &str, value: impl std::fmt::Debug) -> Self {
+        self.settings.push((name.to_string(), format!("{value:?}")));
+        self
+    }
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// drivers encode their versions differently, falls back to vulkan encoding
+fn driver_version(vendor_id: u32, v: u32) -> String {
+    const NVIDIA: u32 = 0x10DE;
+    const INTEL: u32 = 0x8086;
+    match vendor_id {
+        NVIDIA => format!(
+            "{}.{}.{}.{}",
+            v >> 22,
+            (v >> 14) & 0xFF,
+            (v >> 6) & 0xFF,
+            v & 0x3F
+        ),
+        INTEL if cfg!(target_os = "windows") => format!("{}.{}", v >> 14, v & 0x3FFF),
+        _ => format!(
+            "{}.{}.{}",
+            vk::api_version_major(v),
+            vk::api_version_minor(v),
+            vk::api_version_patch(v)
+        ),
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let profile = if self.debug_build { "debug" } else { "release" };
+        writeln!(f, "silk engine {} ({profile})", self.engine_version)?;
+        writeln!(f, "platform:   {} {}", self.os, self.arch)?;
+        writeln!(f, "gpu:        {} ({:?})", self.gpu_name, self.gpu_type)?;
+        writeln!(
+            f,
+            "ids:        vendor {:#06x}, device {:#06x}",
+            self.vendor_id, self.device_id
+        )?;
+        writeln!(f, "driver:     {}", self.driver_version)?;
+        writeln!(f, "vulkan:     {}", self.vulkan_version)?;
+        writeln!(f, "validation: {}", self.validation)?;
+        writeln!(f, "extensions: {}", self.extensions.join(", "))?;
+        writeln!(f, "features:   {}", self.features.join(", "))?;
+        if !self.settings.is_empty() {
+            writeln!(f, "settings:")?;
+            let width = self.settings.iter().map(|(n, _)| n.len()).max().unwrap();
+            for (name, value) in &self.settings {
+                writeln!(f, "  {name:<width$} {value}")?;
+            }
+        }
+        Ok(())
+    }
+}
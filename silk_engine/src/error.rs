@@ -0,0 +1,53 @@
+use ash::vk;
+
+/// error returned by `try_*` fns and posted as [`crate::event::GpuError`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// named resource doesn't exist, `kind` is like "buffer", "img", "font"
+    NotFound { kind: &'static str, name: String },
+    /// host or gpu memory ran out, with what was being done
+    OutOfMemory(String),
+    /// gpu crashed, was reset or driver was updated
+    DeviceLost,
+    /// other vulkan error, with what was being done
+    Vulkan { context: String, result: vk::Result },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub(crate) fn not_found(kind: &'static str, name: &str) -> Self {
+        Self::NotFound {
+            kind,
+            name: name.to_string(),
+        }
+    }
+
+    pub(crate) fn vk(context: &str, result: vk::Result) -> Self {
+        match result {
+            vk::Result::ERROR_DEVICE_LOST => Self::DeviceLost,
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY
+            | vk::Result::ERROR_OUT_OF_DEVICE_MEMORY
+            | vk::Result::ERROR_OUT_OF_POOL_MEMORY => {
+                Self::OutOfMemory(format!("{context}: {result}"))
+            }
+            result => Self::Vulkan {
+                context: context.to_string(),
+                result,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound { kind, name } => write!(f, "{kind} not found: {name}"),
+            Self::OutOfMemory(context) => write!(f, "out of memory, {context}"),
+            Self::DeviceLost => write!(f, "gpu device lost"),
+            Self::Vulkan { context, result } => write!(f, "{context}: {result}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -93,6 +93,23 @@ macro_rules! event {
     };
 }
 
+use crate::error::Error;
+
 pub trait Event {}
 
 event!(WindowResize, width: u32, height: u32);
+event!(ThemeChange, name: String);
+// vulkan error that didn't panic, like device lost or out of memory
+event!(GpuError, error: Error);
+// async asset (see `Renderer::load_img_async`) was added to atlas
+event!(AssetLoaded, name: String);
+event!(AssetFailed, name: String, error: String);
+// `ready` of `total` queued pipelines are built, see `AppContext::loading`
+event!(PipelineProgress, ready: usize, total: usize);
+// tween in `AppContext::tweens` finished
+event!(TweenDone, name: String);
+// touch gestures, positions and deltas are in same -1..1 space as mouse
+event!(Tap, x: f32, y: f32);
+// `scale` is ratio of finger distance to previous one, around `x`, `y`
+event!(Pinch, scale: f32, x: f32, y: f32);
+event!(Pan, dx: f32, dy: f32);
@@ -0,0 +1,130 @@
+use std::{collections::HashMap, time::Instant};
+
+use winit::event::{TouchPhase, WindowEvent};
+
+/// max seconds between touch start and end for a tap
+const TAP_TIME: f32 = 0.3;
+/// touch moving more than this many pixels is a pan instead of a tap
+const SLOP: f32 = 12.0;
+
+/// recognized gesture, positions and deltas are in same -1..1 space as mouse
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Gesture {
+    Tap {
+        x: f32,
+        y: f32,
+    },
+    /// `scale` is ratio of finger distance to last one, around center `x`, `y`
+    Pinch {
+        scale: f32,
+        x: f32,
+        y: f32,
+    },
+    Pan {
+        dx: f32,
+        dy: f32,
+    },
+}
+
+struct Finger {
+    start: Instant,
+    start_pos: (f32, f32),
+    pos: (f32, f32),
+}
+
+/// turns touch events into taps, single finger pans and two finger pinch + pan
+#[derive(Default)]
+pub(crate) struct Gestures {
+    fingers: HashMap<u64, Finger>,
+    /// most fingers down since first one touched, so lifting a pinch isn't a tap
+    max_fingers: usize,
+    /// any finger moved past `SLOP` since first one touched
+    moved: bool,
+}
+
+impl Gestures {
+    /// two finger (center, distance) in pixels
+    fn pinch(&self) -> Option<((f32, f32), f32)> {
+        let mut fingers = self.fingers.values();
+        let (a, b) = (fingers.next()?.pos, fingers.next()?.pos);
+        let center = ((a.0 + b.0) * 0.5, (a.1 + b.1) * 0.5);
+        Some((center, (a.0 - b.0).hypot(a.1 - b.1)))
+    }
+
+    pub fn event(&mut self, event: &WindowEvent, width: u32, height: u32) -> Vec<Gesture> {
+        let WindowEvent::Touch(touch) = event else {
+            return Vec::new();
+        };
+        let (w, h) = (width.max(1) as f32, height.max(1) as f32);
+        let to_x = |x: f32| x / w * 2.0 - 1.0;
+        let to_y = |y: f32| 1.0 - y / h * 2.0;
+        let pos = (touch.location.x as f32, touch.location.y as f32);
+        let mut gestures = Vec::new();
+        match touch.phase {
+            TouchPhase::Started => {
+                if self.fingers.is_empty() {
+                    self.max_fingers = 0;
+                    self.moved = false;
+                }
+                self.fingers.insert(
+                    touch.id,
+                    Finger {
+                        start: Instant::now(),
+                        start_pos: pos,
+                        pos,
+                    },
+                );
+                self.max_fingers = self.max_fingers.max(self.fingers.len());
+            }
+            TouchPhase::Moved => {
+                let old_pinch = self.pinch();
+                let Some(finger) = self.fingers.get_mut(&touch.id) else {
+                    return gestures;
+                };
+                let old = finger.pos;
+                finger.pos = pos;
+                let (sx, sy) = finger.start_pos;
+                self.moved |= (pos.0 - sx).hypot(pos.1 - sy) > SLOP;
+                if !self.moved {
+                    return gestures;
+                }
+                match (self.fingers.len(), old_pinch, self.pinch()) {
+                    (1, ..) => gestures.push(Gesture::Pan {
+                        dx: (pos.0 - old.0) / w * 2.0,
+                        dy: (old.1 - pos.1) / h * 2.0,
+                    }),
+                    (2, Some((c0, d0)), Some((c1, d1))) => {
+                        if d0 > 0.0 {
+                            gestures.push(Gesture::Pinch {
+                                scale: d1 / d0,
+                                x: to_x(c1.0),
+                                y: to_y(c1.1),
+                            });
+                        }
+                        gestures.push(Gesture::Pan {
+                            dx: (c1.0 - c0.0) / w * 2.0,
+                            dy: (c0.1 - c1.1) / h * 2.0,
+                        });
+                    }
+                    _ => {}
+                }
+            }
+            TouchPhase::Ended => {
+                if let Some(finger) = self.fingers.remove(&touch.id)
+                    && !self.moved
+                    && self.max_fingers == 1
+                    && finger.start.elapsed().as_secs_f32() <= TAP_TIME
+                {
+                    gestures.push(Gesture::Tap {
+                        x: to_x(pos.0),
+                        y: to_y(pos.1),
+                    });
+                }
+            }
+            TouchPhase::Cancelled => {
+                self.fingers.remove(&touch.id);
+            }
+        }
+        gestures
+    }
+}
@@ -1,15 +1,37 @@
+mod asset;
+mod background;
 mod font;
+mod list_view;
 mod packer;
+mod pacing;
+mod post_chain;
+mod registry;
 mod render_ctx;
 mod renderer;
 mod shader;
 mod unit;
+mod video;
 mod vulkan;
 
-pub use font::Font;
+pub use asset::{Handle, Image};
+pub use background::Background;
+pub use font::{Font, Glyph, PlacedGlyph};
+pub use list_view::ListView;
 pub use packer::{Guillotine, Packer, Shelf};
-pub use render_ctx::{BufferImageCopy, DebugScope, RenderCtx, debug_name, debug_tag};
-pub use renderer::Renderer;
+pub(crate) use pacing::FramePacer;
+pub use pacing::PacingStats;
+pub use post_chain::{PostChain, Tonemap};
+pub use registry::{AsName, Id, NameId, SharedRegistry};
+pub(crate) use render_ctx::lock_ctx;
+pub use render_ctx::{
+    BufId, BufferImageCopy, Buffers, DebugScope, DrawStats, Images, ImgId, PipelineId, Pipelines,
+    RenderCtx, debug_name, debug_tag,
+};
+pub use renderer::{
+    BlendMode, Connector, CullStats, DebugColor, DrawDebug, Lod, Renderer, Reveal, Sampling,
+    Scoped, StrokeAlign, TextHinting, Vertex, VertexFormat, Viewport,
+};
 pub use unit::Unit;
 pub use unit::Unit::*;
+pub use video::Video;
 pub use vulkan::*;
@@ -0,0 +1,105 @@
+use std::{
+    collections::HashSet,
+    marker::PhantomData,
+    panic::AssertUnwindSafe,
+    sync::mpsc::{Receiver, Sender, channel},
+};
+
+use super::Font;
+use crate::util::{ImageData, jobs};
+
+/// atlas img asset, see [`super::Renderer::load_img_async`]
+pub struct Image;
+
+/// names asset that might still be loading on a background thread,
+/// check it with [`super::Renderer::loaded`] or wait for `AssetLoaded` event
+pub struct Handle<T> {
+    name: String,
+    _asset: PhantomData<T>,
+}
+
+impl<T> Handle<T> {
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            _asset: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self::new(&self.name)
+    }
+}
+
+impl<T> std::fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle({})", self.name)
+    }
+}
+
+/// decoded on a worker thread, uploaded to atlas in `Renderer::flush`
+pub(crate) enum Asset {
+    Img(ImageData),
+    Font(Font),
+}
+
+/// (name, decoded asset or panic message)
+type Loaded = (String, Result<Asset, String>);
+
+/// decodes assets on background threads, so large files don't stall frames
+pub(crate) struct AssetLoader {
+    tx: Sender<Loaded>,
+    rx: Receiver<Loaded>,
+    pending: HashSet<String>,
+}
+
+impl Default for AssetLoader {
+    fn default() -> Self {
+        let (tx, rx) = channel();
+        Self {
+            tx,
+            rx,
+            pending: HashSet::new(),
+        }
+    }
+}
+
+impl AssetLoader {
+    pub(crate) fn spawn(&mut self, name: &str, load: impl FnOnce() -> Asset + Send + 'static) {
+        assert!(
+            self.pending.insert(name.to_string()),
+            "asset already loading: {name}"
+        );
+        let tx = self.tx.clone();
+        let name = name.to_string();
+        jobs().spawn(move || {
+            // loaders panic on missing/corrupt files, report that instead of losing the asset
+            let asset = std::panic::catch_unwind(AssertUnwindSafe(load)).map_err(|e| {
+                e.downcast_ref::<String>()
+                    .cloned()
+                    .or_else(|| e.downcast_ref::<&str>().map(|s| s.to_string()))
+                    .unwrap_or_else(|| "loader panicked".to_string())
+            });
+            tx.send((name, asset)).unwrap_or_default();
+        });
+    }
+
+    pub(crate) fn is_pending(&self, name: &str) -> bool {
+        self.pending.contains(name)
+    }
+
+    /// assets that finished loading since last call
+    pub(crate) fn ready(&mut self) -> Vec<Loaded> {
+        let ready: Vec<_> = self.rx.try_iter().collect();
+        for (name, _) in &ready {
+            self.pending.remove(name);
+        }
+        ready
+    }
+}
@@ -0,0 +1,53 @@
+use super::{Pc, Px, Renderer};
+use crate::util::Tweenable;
+
+/// drawn under everything each frame before scene and app render,
+/// see [`crate::AppContext::set_background`]
+#[derive(Default)]
+pub enum Background {
+    /// only clear color
+    #[default]
+    None,
+    /// vertical gradient from top to bottom color
+    Gradient([u8; 4], [u8; 4]),
+    /// atlas img stretched over whole window
+    Img(String),
+    /// called every frame with lowest z, can change what it draws per frame
+    Custom(Box<dyn FnMut(&mut Renderer)>),
+}
+
+impl Background {
+    pub(crate) fn draw(&mut self, gfx: &mut Renderer) {
+        if let Self::Custom(f) = self {
+            gfx.z(i32::MIN);
+            f(gfx);
+            gfx.z(0);
+            return;
+        }
+        gfx.push_state();
+        gfx.z(i32::MIN);
+        gfx.roundness = 0.0;
+        gfx.rotation = 0.0;
+        gfx.stroke_width = 0.0;
+        gfx.no_img();
+        match self {
+            Self::Gradient(top, bottom) => {
+                let (width, height) = (gfx.width() as i32, gfx.height() as i32);
+                // 4px bands, dithering hides steps between them
+                let bands = (height / 4).max(1);
+                for i in 0..bands {
+                    gfx.color = top.tween(*bottom, (i as f32 + 0.5) / bands as f32);
+                    let (y0, y1) = (i * height / bands, (i + 1) * height / bands);
+                    gfx.rect(Px(0), Px(y0), Px(width), Px(y1 - y0));
+                }
+            }
+            Self::Img(name) => {
+                gfx.color = [255; 4];
+                gfx.img(name);
+                gfx.rect(Pc(0.0), Pc(0.0), Pc(1.0), Pc(1.0));
+            }
+            Self::None | Self::Custom(_) => {}
+        }
+        gfx.pop_state();
+    }
+}
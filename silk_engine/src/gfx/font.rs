@@ -4,7 +4,58 @@ use super::{
     RenderCtx,
     packer::{Guillotine, Packer, Rect},
 };
-use crate::util::{Bmp, ExtraFns, ImageFormat, Ttf, Vec2, Vec2u, Vec3, Vectorf};
+use crate::{
+    RES_PATH, log,
+    util::{
+        Bmp, ExtraFns, ImageSave, Reader, SvgPath, Ttf, Vec2, Vec2u, Vec3, Vectorf, Writer, jobs,
+        vfs,
+    },
+};
+
+/// bump when sdf generation or cache layout changes, so stale caches are regenerated
+const ATLAS_CACHE_VERSION: u32 = 3;
+const ATLAS_CACHE_MAGIC: &[u8; 4] = b"SLKA";
+/// magic, version, char size, ttf checksum, sdf dim, color dim, units, ascent, glyph count
+const ATLAS_CACHE_HEADER_SIZE: usize = 4 * 3 + 8 + 4 * 5;
+/// glyph idx, rect, off, size, advance, color
+const ATLAS_CACHE_GLYPH_SIZE: usize = 4 + 8 + 4 * 6;
+
+fn atlas_cache_path(name: &str, char_size_px: u32) -> String {
+    format!("{RES_PATH}/cache/atlas/{name}_{char_size_px}.bin")
+}
+
+/// fnv-1a hash of ttf file and extracted chars, so edited fonts don't load stale atlas cache
+fn ttf_checksum(name: &str, chars: &[char], extra: Extra) -> u64 {
+    let bytes = vfs()
+        .read(&format!("fonts/{name}.ttf"))
+        .unwrap_or_else(|_| panic!("font not found: {name}"));
+    let chars = chars
+        .iter()
+        .flat_map(|&c| (c as u32).to_le_bytes())
+        .chain([extra as u8]);
+    bytes
+        .iter()
+        .copied()
+        .chain(chars)
+        .fold(0xcbf29ce484222325, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+/// glyphs generated besides requested chars
+#[derive(Clone, Copy, PartialEq)]
+enum Extra {
+    None,
+    /// color (COLR emoji) glyphs
+    Color,
+    /// every glyph, including ones only reachable by shaping
+    All,
+}
+
+/// private use areas, where icon fonts put their glyphs
+fn is_private_use(c: char) -> bool {
+    matches!(c, '\u{E000}'..='\u{F8FF}' | '\u{F0000}'..='\u{10FFFD}')
+}
 
 // https://www.shadertoy.com/view/ftdGDB
 fn bezier_sdf(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> f32 {
@@ -51,40 +102,236 @@ fn bezier_sdf(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> f32 {
     sgn.signum() * res.sqrt()
 }
 
-pub struct Font;
+#[derive(Clone, Copy, Default)]
+pub struct Glyph {
+    /// glyph rect in font sdf bitmap
+    pub(crate) rect: Rect,
+    /// bottom-left of glyph quad relative to pen position, in font heights
+    pub off: [f32; 2],
+    /// glyph quad size, in font heights
+    pub size: [f32; 2],
+    pub advance: f32,
+    /// glyph is rgba (emoji) in font color bitmap instead of sdf, drawn untinted
+    pub color: bool,
+}
+
+/// glyph placed by [`Font::layout`]
+#[derive(Clone, Copy, Debug)]
+pub struct PlacedGlyph {
+    /// font glyph idx, see [`Font::glyph_idx`]
+    pub glyph: u16,
+    /// pen position relative to top-left of text, with line (or vertical cell) top as y,
+    /// in font heights
+    pub pos: [f32; 2],
+    /// pen advance along line (or column), in font heights
+    pub advance: f32,
+    /// glyph is from right to left run, so it's cluster starts at it's right edge
+    pub rtl: bool,
+    /// line, or column in vertical text
+    pub line: u32,
+    /// byte idx in text of first char glyph was made from
+    pub cluster: usize,
+}
+
+/// punctuation sitting in bottom-left of it's cell, moved to top-right in vertical text
+const VERTICAL_SHIFTED: [char; 4] = ['、', '。', '，', '．'];
+
+pub struct Font {
+    pub(crate) sdf: Vec<u8>,
+    pub(crate) sdf_dim: u32,
+    /// rgba bitmap of color glyphs, y-up like sdf
+    pub(crate) color: Vec<u8>,
+    /// 0 if font has no color glyphs
+    pub(crate) color_dim: u32,
+    /// by glyph idx
+    glyphs: HashMap<u16, Glyph>,
+    /// by glyph idx, includes glyphs without outline like space
+    advances: HashMap<u16, f32>,
+    /// char to glyph idx
+    cmap: HashMap<char, u16>,
+    /// font units per font height
+    pub(crate) units: f32,
+    /// baseline offset from top of line, in font heights
+    pub ascent: f32,
+    /// shaper face of ttf file, none if it failed to parse
+    #[cfg(feature = "shaping")]
+    shaper: Option<Shaper>,
+}
+
+/// ttf file and shaper face parsed from it once, face borrows file
+#[cfg(feature = "shaping")]
+struct Shaper {
+    face: std::mem::ManuallyDrop<rustybuzz::Face<'static>>,
+    /// leaked box, freed in drop after face
+    ttf: *mut [u8],
+}
+
+#[cfg(feature = "shaping")]
+impl Shaper {
+    fn new(ttf: Vec<u8>) -> Option<Self> {
+        let ttf = Box::into_raw(ttf.into_boxed_slice());
+        // SAFETY: ttf is only freed in drop, after face borrowing it is dropped
+        match rustybuzz::Face::from_slice(unsafe { &*ttf }, 0) {
+            Some(face) => Some(Self {
+                face: std::mem::ManuallyDrop::new(face),
+                ttf,
+            }),
+            None => {
+                drop(unsafe { Box::from_raw(ttf) });
+                None
+            }
+        }
+    }
+}
+
+#[cfg(feature = "shaping")]
+impl Drop for Shaper {
+    fn drop(&mut self) {
+        unsafe {
+            std::mem::ManuallyDrop::drop(&mut self.face);
+            drop(Box::from_raw(self.ttf));
+        }
+    }
+}
+
+// SAFETY: ttf is never written, so it can be read from any thread like face
+#[cfg(feature = "shaping")]
+unsafe impl Send for Shaper {}
+#[cfg(feature = "shaping")]
+unsafe impl Sync for Shaper {}
 
 impl Font {
+    /// font with printable ascii glyphs and all color (COLR emoji) glyphs
     pub fn new(name: &str, char_size_px: u32) -> Self {
+        let ascii = (0u8..128)
+            .map(|x| x as char)
+            .filter(|x| x.is_ascii_graphic() || *x == ' ')
+            .collect::<Vec<_>>();
+        Self::load(name, char_size_px, &ascii, Extra::Color)
+    }
+
+    /// font with every glyph, including contextual forms and ligatures
+    /// needed to draw shaped text of complex scripts
+    pub fn all(name: &str, char_size_px: u32) -> Self {
+        Self::load(name, char_size_px, &[], Extra::All)
+    }
+
+    /// font with all glyphs in private use areas, like icon fonts have
+    pub fn icons(name: &str, char_size_px: u32) -> Self {
+        let chars = Ttf::new(name)
+            .idx2uni
+            .into_iter()
+            .filter(|&c| is_private_use(c))
+            .collect::<Vec<_>>();
+        Self::with_chars(name, char_size_px, &chars)
+    }
+
+    /// font with only `chars` glyphs, ones font doesn't have are skipped.
+    /// generating sdfs is slow, so result is cached in `res/cache/atlas`
+    /// keyed by font, char size and checksum of ttf and chars
+    pub fn with_chars(name: &str, char_size_px: u32, chars: &[char]) -> Self {
+        Self::load(name, char_size_px, chars, Extra::None)
+    }
+
+    fn load(name: &str, char_size_px: u32, chars: &[char], extra: Extra) -> Self {
+        let checksum = ttf_checksum(name, chars, extra);
+        let font = if let Some(font) = Self::load_cache(name, char_size_px, checksum) {
+            log!("Font atlas cache loaded: \"{name}_{char_size_px}.bin\"");
+            font
+        } else {
+            let font = Self::generate(name, char_size_px, chars, extra);
+            font.save_cache(name, char_size_px, checksum);
+            font
+        };
+        #[cfg(feature = "shaping")]
+        let font = Self {
+            shaper: Shaper::new(vfs().read(&format!("fonts/{name}.ttf")).unwrap().to_vec()),
+            ..font
+        };
+        font
+    }
+
+    fn generate(name: &str, char_size_px: u32, chars: &[char], extra: Extra) -> Self {
         let t = crate::util::print::ScopeTime::new(&format!("parse font({name})"));
         let mut reader = Ttf::new(name);
-        // extract ascii glyphs
-        reader.head.glob_xmin = i16::MAX;
-        reader.head.glob_ymin = i16::MAX;
-        reader.head.glob_xmax = i16::MIN;
-        reader.head.glob_ymax = i16::MIN;
-        let mut glyphs = Vec::with_capacity(128);
-        let uni2idx: HashMap<char, u32> = reader
+        let uni2idx: HashMap<char, u16> = reader
             .idx2uni
             .iter()
             .enumerate()
-            .map(|(i, uni)| (*uni, i as u32))
+            .filter(|(_, uni)| **uni != '\0')
+            .map(|(i, uni)| (*uni, i as u16))
             .collect();
-        for ascii in (0u8..128)
-            .map(|x| x as char)
-            .filter(|x| x.is_ascii_graphic())
-        {
-            let idx = uni2idx[&ascii] as usize;
-            let glyph = &reader.glyphs[idx];
+        let mut cmap = chars
+            .iter()
+            .filter_map(|c| Some((*c, *uni2idx.get(c)?)))
+            .collect::<HashMap<_, _>>();
+        match extra {
+            Extra::None => {}
+            Extra::Color => cmap.extend(
+                uni2idx
+                    .iter()
+                    .filter(|(_, idx)| reader.color_layers.contains_key(idx)),
+            ),
+            Extra::All => cmap = uni2idx.clone(),
+        }
+        let mut idxs = if extra == Extra::All {
+            (0..reader.glyphs.len() as u16).collect::<Vec<_>>()
+        } else {
+            cmap.values().copied().collect::<Vec<_>>()
+        };
+        idxs.sort_unstable();
+        idxs.dedup();
+        reader.head.glob_xmin = i16::MAX;
+        reader.head.glob_ymin = i16::MAX;
+        reader.head.glob_xmax = i16::MIN;
+        reader.head.glob_ymax = i16::MIN;
+        let mut glyphs = Vec::with_capacity(idxs.len());
+        let mut glyph_idxs = Vec::with_capacity(idxs.len());
+        let mut advance_widths = Vec::with_capacity(idxs.len());
+        // (glyph idx, layer bounds xmin, ymin, xmax, ymax, in font units)
+        let mut color_glyphs = Vec::new();
+        for idx in idxs {
+            let glyph = &reader.glyphs[idx as usize];
+            advance_widths.push((idx, glyph.metric.advance_width));
+            if let Some(layers) = reader.color_layers.get(&idx) {
+                let bounds = layers
+                    .iter()
+                    .map(|&(l, _)| &reader.glyphs[l as usize].metric)
+                    .filter(|m| m.width() > 0 && m.height() > 0)
+                    .fold([i16::MAX, i16::MAX, i16::MIN, i16::MIN], |b, m| {
+                        [
+                            b[0].min(m.xmin),
+                            b[1].min(m.ymin),
+                            b[2].max(m.xmax),
+                            b[3].max(m.ymax),
+                        ]
+                    });
+                if bounds[0] < bounds[2] {
+                    color_glyphs.push((idx, bounds));
+                    reader.head.glob_ymin = reader.head.glob_ymin.min(bounds[1]);
+                    reader.head.glob_ymax = reader.head.glob_ymax.max(bounds[3]);
+                }
+                continue;
+            }
             let (w, h) = (glyph.metric.width(), glyph.metric.height());
             if w == 0 || h == 0 {
                 continue;
             }
             glyphs.push(glyph.clone());
+            glyph_idxs.push(idx);
             reader.head.glob_xmin = reader.head.glob_xmin.min(glyph.metric.xmin);
             reader.head.glob_ymin = reader.head.glob_ymin.min(glyph.metric.ymin);
             reader.head.glob_xmax = reader.head.glob_xmax.max(glyph.metric.xmax);
             reader.head.glob_ymax = reader.head.glob_ymax.max(glyph.metric.ymax);
         }
+        assert!(
+            !glyphs.is_empty() || !color_glyphs.is_empty(),
+            "font has none of requested glyphs: {name}"
+        );
+        if glyphs.is_empty() {
+            reader.head.glob_xmin = 0;
+            reader.head.glob_xmax = reader.head.glob_ymax - reader.head.glob_ymin;
+        }
         reader.head.num_glyphs = glyphs.len() as u16;
 
         let num_glyphs = reader.head.num_glyphs;
@@ -108,7 +355,9 @@ impl Font {
         // NOTE: might need to be multiple of 256 for vulkan image transfer alignment requirements
         //       (also would match work group size in font sdf shader)
 
-        let mut font_sdf_dim = (((area_px as f32).sqrt()) as u32).next_multiple_of(4);
+        let mut font_sdf_dim = (((area_px as f32).sqrt()) as u32)
+            .next_multiple_of(4)
+            .max(4);
 
         // write font bezier points into buffer
         let mut font_points = vec![];
@@ -141,8 +390,28 @@ impl Font {
         });
         font_sdf_dim = packer.width() as u32;
         let mut font_glyphs = vec![[0u32; 4]; num_glyphs as usize];
+        let mut layout_glyphs = HashMap::with_capacity(num_glyphs as usize);
+        // layout is in font heights, bitmap x is normalized by max width
+        let aspect = mx as f32 / my as f32;
         for (i, &(x, y)) in packed.iter().enumerate() {
             let (w, h) = unpacked[i];
+            let metric = &glyphs[i].metric;
+            layout_glyphs.insert(
+                glyph_idxs[i],
+                Glyph {
+                    rect: Rect::new(x, y, w, h),
+                    off: [
+                        metric.xmin as f32 / my as f32 - pad * aspect,
+                        metric.ymin as f32 / my as f32 - pad,
+                    ],
+                    size: [
+                        w as f32 / char_size_px as f32 * aspect,
+                        h as f32 / char_size_px as f32,
+                    ],
+                    advance: metric.advance_width as f32 / my as f32,
+                    color: false,
+                },
+            );
             let r = Rect::new(x, y, w, h).packed_whxy();
             let wh = (r >> 32) as u32;
             let xy = r as u32;
@@ -155,13 +424,14 @@ impl Font {
         let font_sdf_pxs = font_sdf_dim * font_sdf_dim;
         let t = crate::util::print::ScopeTime::new(&format!("{name} sdf gen"));
         let mut font_sdf = vec![0u8; font_sdf_pxs as usize];
-        for [off, size, wh, xy] in font_glyphs {
+        // glyphs are independent, each job fills its glyph's rect, copied into atlas after
+        let glyph_sdfs = jobs().parallel_map(0..font_glyphs.len(), 8, |gi| {
+            let [off, size, wh, _] = font_glyphs[gi];
             let gs = Vec2u::new(wh >> 16, wh & 0xFFFF);
-            let gp = Vec2u::new(xy >> 16, xy & 0xFFFF);
+            let mut glyph_sdf = vec![0u8; (gs.x * gs.y) as usize];
             for y in 0..gs.y {
                 for x in 0..gs.x {
-                    let pu = Vec2u::new(x + gp.x, y + gp.y);
-                    let p = Vec2::from(pu - gp) / Vec2::from(char_size_px);
+                    let p = Vec2::from(Vec2u::new(x, y)) / Vec2::from(char_size_px);
                     let mut d = f32::MAX;
                     for i in 0..size {
                         let off = off as usize + i as usize * 3;
@@ -180,17 +450,452 @@ impl Font {
                     }
                     let d = d * 4.0 + 0.75;
                     if d <= 1.0 {
-                        font_sdf[(pu.y * font_sdf_dim + pu.x) as usize] |=
-                            (d.saturate() * 255.0) as u8;
+                        glyph_sdf[(y * gs.x + x) as usize] = (d.saturate() * 255.0) as u8;
                     }
                 }
             }
+            glyph_sdf
+        });
+        for ([.., wh, xy], glyph_sdf) in font_glyphs.iter().zip(glyph_sdfs) {
+            let gw = (wh >> 16) as usize;
+            let gp = Vec2u::new(xy >> 16, xy & 0xFFFF);
+            for (y, row) in glyph_sdf.chunks_exact(gw.max(1)).enumerate() {
+                let start = ((gp.y + y as u32) * font_sdf_dim + gp.x) as usize;
+                for (dst, src) in font_sdf[start..start + gw].iter_mut().zip(row) {
+                    *dst |= src;
+                }
+            }
         }
 
         drop(t);
         Bmp::save("temp", &font_sdf[..], font_sdf_dim, font_sdf_dim, 1);
+        let (color, color_dim) = Self::rasterize_color(
+            &reader,
+            &color_glyphs,
+            char_size_px,
+            my as f32,
+            &mut layout_glyphs,
+        );
+
+        Self {
+            sdf: font_sdf,
+            sdf_dim: font_sdf_dim,
+            color,
+            color_dim,
+            glyphs: layout_glyphs,
+            advances: advance_widths
+                .into_iter()
+                .map(|(idx, a)| (idx, a as f32 / my as f32))
+                .collect(),
+            cmap,
+            units: my as f32,
+            ascent: reader.head.glob_ymax as f32 / my as f32,
+            #[cfg(feature = "shaping")]
+            shaper: None,
+        }
+    }
 
-        Self
+    /// rasterizes COLR layers of `color_glyphs` (glyph idx, bounds) into rgba bitmap
+    /// and adds them to `layout`, `units` is font units per font height
+    fn rasterize_color(
+        reader: &Ttf,
+        color_glyphs: &[(u16, [i16; 4])],
+        char_size_px: u32,
+        units: f32,
+        layout: &mut HashMap<u16, Glyph>,
+    ) -> (Vec<u8>, u32) {
+        if color_glyphs.is_empty() {
+            return (Vec::new(), 0);
+        }
+        let _t = crate::util::print::ScopeTime::new("color glyphs raster");
+        let px = char_size_px as f32 / units;
+        // 1px transparent border, so filtering doesn't bleed
+        let unpacked = color_glyphs
+            .iter()
+            .map(|(_, b)| {
+                (
+                    ((b[2] - b[0]) as f32 * px).ceil() as u16 + 2,
+                    ((b[3] - b[1]) as f32 * px).ceil() as u16 + 2,
+                )
+            })
+            .collect::<Vec<_>>();
+        let area_px = unpacked
+            .iter()
+            .map(|&(w, h)| w as u32 * h as u32)
+            .sum::<u32>();
+        let dim = ((area_px as f32).sqrt() as u32).next_multiple_of(4) as u16;
+        let mut packer = Guillotine::new(dim, dim);
+        let packed = packer.growing_pack_all_with(&unpacked, |w: u16, h: u16| {
+            (
+                ((w as f32 * 1.02).ceil() as u16).next_multiple_of(4),
+                ((h as f32 * 1.02).ceil() as u16).next_multiple_of(4),
+            )
+        });
+        let dim = packer.width() as usize;
+        let layers = color_glyphs
+            .iter()
+            .map(|(idx, _)| &reader.color_layers[idx])
+            .collect::<Vec<_>>();
+        let bitmaps = jobs().parallel_map(0..color_glyphs.len(), 8, |i| {
+            let (b, (w, h)) = (color_glyphs[i].1, unpacked[i]);
+            let mut rgba = vec![0u8; w as usize * h as usize * 4];
+            for &(layer, [r, g, bl, a]) in layers[i] {
+                let glyph = &reader.glyphs[layer as usize];
+                let mut path = SvgPath::default();
+                let mut start = 0;
+                for &end in &glyph.contour_end_idxs {
+                    let points = &glyph.points[start..end as usize + 1];
+                    start = end as usize + 1;
+                    if points.len() < 3 {
+                        continue;
+                    }
+                    // triples of quadratic bezier points, in font units
+                    let quads = Self::convert_points(points, 0, 0, 1, 1);
+                    path = path.move_to(quads[0].0, quads[0].1);
+                    for q in quads.chunks_exact(3) {
+                        path = path.quad_to(q[1].0, q[1].1, q[2].0, q[2].1);
+                    }
+                }
+                // first row is bottom, so bitmap is y-up
+                let coverage = path
+                    .view_box(
+                        b[0] as f32 - 1.0 / px,
+                        b[1] as f32 - 1.0 / px,
+                        w as f32 / px,
+                        h as f32 / px,
+                    )
+                    .rasterize(w as u32, h as u32);
+                for (dst, cov) in rgba.chunks_exact_mut(4).zip(coverage) {
+                    // straight alpha "over"
+                    let sa = cov as f32 * a as f32 / (255.0 * 255.0);
+                    let da = dst[3] as f32 / 255.0;
+                    let oa = sa + da * (1.0 - sa);
+                    if oa <= 0.0 {
+                        continue;
+                    }
+                    for (d, s) in dst.iter_mut().zip([r, g, bl]) {
+                        *d = ((s as f32 * sa + *d as f32 * da * (1.0 - sa)) / oa).round() as u8;
+                    }
+                    dst[3] = (oa * 255.0).round() as u8;
+                }
+            }
+            rgba
+        });
+        let mut color = vec![0u8; dim * dim * 4];
+        for (i, rgba) in bitmaps.into_iter().enumerate() {
+            let ((x, y), (w, h)) = (packed[i], unpacked[i]);
+            for (row, src) in rgba.chunks_exact(w as usize * 4).enumerate() {
+                let start = ((y as usize + row) * dim + x as usize) * 4;
+                color[start..start + src.len()].copy_from_slice(src);
+            }
+            let (idx, b) = color_glyphs[i];
+            let pad = 1.0 / char_size_px as f32;
+            layout.insert(
+                idx,
+                Glyph {
+                    rect: Rect::new(x, y, w, h),
+                    off: [b[0] as f32 / units - pad, b[1] as f32 / units - pad],
+                    size: [
+                        w as f32 / char_size_px as f32,
+                        h as f32 / char_size_px as f32,
+                    ],
+                    advance: reader.glyphs[idx as usize].metric.advance_width as f32 / units,
+                    color: true,
+                },
+            );
+        }
+        (color, dim as u32)
+    }
+
+    /// layout: header, glyphs, advance count, advances (glyph idx, advance),
+    /// cmap count, cmap (char, glyph idx), sdf, color
+    fn save_cache(&self, name: &str, char_size_px: u32, checksum: u64) {
+        let size = ATLAS_CACHE_HEADER_SIZE
+            + self.glyphs.len() * ATLAS_CACHE_GLYPH_SIZE
+            + 4
+            + self.advances.len() * 8
+            + 4
+            + self.cmap.len() * 8
+            + self.sdf.len()
+            + self.color.len();
+        let mut w = Writer::new(size);
+        w.write(ATLAS_CACHE_MAGIC);
+        w.write32(ATLAS_CACHE_VERSION);
+        w.write32(char_size_px);
+        w.write64(checksum);
+        w.write32(self.sdf_dim);
+        w.write32(self.color_dim);
+        w.write32(self.units.to_bits());
+        w.write32(self.ascent.to_bits());
+        w.write32(self.glyphs.len() as u32);
+        for (&idx, glyph) in &self.glyphs {
+            w.write32(idx as u32);
+            w.write64(glyph.rect.packed_whxy());
+            for v in [glyph.off, glyph.size].as_flattened() {
+                w.write32(v.to_bits());
+            }
+            w.write32(glyph.advance.to_bits());
+            w.write32(glyph.color as u32);
+        }
+        w.write32(self.advances.len() as u32);
+        for (&idx, &advance) in &self.advances {
+            w.write32(idx as u32);
+            w.write32(advance.to_bits());
+        }
+        w.write32(self.cmap.len() as u32);
+        for (&c, &idx) in &self.cmap {
+            w.write32(c as u32);
+            w.write32(idx as u32);
+        }
+        w.write(&self.sdf[..]);
+        w.write(&self.color[..]);
+        let path = atlas_cache_path(name, char_size_px);
+        std::fs::create_dir_all(format!("{RES_PATH}/cache/atlas")).unwrap_or_default();
+        if let Err(e) = std::fs::write(&path, w.finish()) {
+            crate::warn!("failed to write font atlas cache({path}): {e}");
+        }
+    }
+
+    /// none if cache is missing, stale or corrupt
+    fn load_cache(name: &str, char_size_px: u32, checksum: u64) -> Option<Self> {
+        let bytes = std::fs::read(atlas_cache_path(name, char_size_px)).ok()?;
+        let mut r = Reader::new(&bytes);
+        let left = |r: &Reader, n: usize| bytes.len() - r.idx() >= n;
+        if !left(&r, ATLAS_CACHE_HEADER_SIZE)
+            || r.read_arr::<4>() != *ATLAS_CACHE_MAGIC
+            || r.read32() != ATLAS_CACHE_VERSION
+            || r.read32() != char_size_px
+            || r.read64() != checksum
+        {
+            return None;
+        }
+        let sdf_dim = r.read32();
+        let color_dim = r.read32();
+        let units = f32::from_bits(r.read32());
+        let ascent = f32::from_bits(r.read32());
+        let glyph_cnt = r.read32() as usize;
+        if !left(&r, glyph_cnt * ATLAS_CACHE_GLYPH_SIZE + 4) {
+            return None;
+        }
+        let mut glyphs = HashMap::with_capacity(glyph_cnt);
+        for _ in 0..glyph_cnt {
+            let idx = r.read32() as u16;
+            let rect = Rect::from_packed_whxy(r.read64());
+            let mut f = || f32::from_bits(r.read32());
+            let (off, size, advance) = ([f(), f()], [f(), f()], f());
+            let color = r.read32() != 0;
+            glyphs.insert(
+                idx,
+                Glyph {
+                    rect,
+                    off,
+                    size,
+                    advance,
+                    color,
+                },
+            );
+        }
+        let advance_cnt = r.read32() as usize;
+        if !left(&r, advance_cnt * 8 + 4) {
+            return None;
+        }
+        let mut advances = HashMap::with_capacity(advance_cnt);
+        for _ in 0..advance_cnt {
+            let idx = r.read32() as u16;
+            advances.insert(idx, f32::from_bits(r.read32()));
+        }
+        let cmap_cnt = r.read32() as usize;
+        let sdf_len = (sdf_dim * sdf_dim) as usize;
+        let color_len = (color_dim * color_dim * 4) as usize;
+        if bytes.len() - r.idx() != cmap_cnt * 8 + sdf_len + color_len {
+            return None;
+        }
+        let mut cmap = HashMap::with_capacity(cmap_cnt);
+        for _ in 0..cmap_cnt {
+            let c = char::from_u32(r.read32())?;
+            cmap.insert(c, r.read32() as u16);
+        }
+        Some(Self {
+            sdf: r.read(sdf_len).to_vec(),
+            sdf_dim,
+            color: r.read(color_len).to_vec(),
+            color_dim,
+            glyphs,
+            advances,
+            cmap,
+            units,
+            ascent,
+            #[cfg(feature = "shaping")]
+            shaper: None,
+        })
+    }
+
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(self.cmap.get(&c)?)
+    }
+
+    /// glyph by font glyph idx, like shaped text has
+    pub fn glyph_idx(&self, idx: u16) -> Option<&Glyph> {
+        self.glyphs.get(&idx)
+    }
+
+    /// (name, codepoint) of each line in `name codepoint_hex` format
+    /// that icon fonts ship `.codepoints` files in, invalid lines are skipped
+    pub fn parse_codepoints(text: &str) -> Vec<(String, char)> {
+        text.lines()
+            .filter_map(|line| {
+                let (name, hex) = line.trim().split_once(char::is_whitespace)?;
+                let c = char::from_u32(u32::from_str_radix(hex.trim(), 16).ok()?)?;
+                Some((name.to_string(), c))
+            })
+            .collect()
+    }
+
+    pub fn advance(&self, c: char) -> f32 {
+        self.cmap
+            .get(&c)
+            .and_then(|idx| self.advances.get(idx))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// width of single line text, in font heights
+    pub fn text_width(&self, text: &str) -> f32 {
+        text.chars().map(|c| self.advance(c)).sum()
+    }
+
+    /// places glyphs of text, lines are split at '\n'.
+    /// with `shaping` feature runs are reordered by bidi algorithm and shaped,
+    /// so glyphs can be ligatures and contextual forms only fonts made by [`Font::all`] have
+    pub fn layout(&self, text: &str) -> Vec<PlacedGlyph> {
+        let mut glyphs = Vec::with_capacity(text.len());
+        let mut start = 0;
+        for (line, text) in text.split('\n').enumerate() {
+            self.layout_line(text, start, line as u32, &mut glyphs);
+            start += text.len() + 1;
+        }
+        glyphs
+    }
+
+    #[cfg(not(feature = "shaping"))]
+    fn layout_line(&self, text: &str, start: usize, line: u32, glyphs: &mut Vec<PlacedGlyph>) {
+        let mut x = 0.0;
+        for (i, c) in text.char_indices() {
+            if let Some(&glyph) = self.cmap.get(&c) {
+                glyphs.push(PlacedGlyph {
+                    glyph,
+                    pos: [x, line as f32],
+                    advance: self.advance(c),
+                    rtl: false,
+                    line,
+                    cluster: start + i,
+                });
+            }
+            x += self.advance(c);
+        }
+    }
+
+    #[cfg(feature = "shaping")]
+    fn layout_line(&self, text: &str, start: usize, line: u32, glyphs: &mut Vec<PlacedGlyph>) {
+        use rustybuzz::{Direction, UnicodeBuffer};
+        let Some(shaper) = &self.shaper else {
+            return;
+        };
+        // shaper positions are in font units
+        let scale = 1.0 / self.units;
+        let bidi = unicode_bidi::BidiInfo::new(text, None);
+        let mut x = 0.0;
+        for para in &bidi.paragraphs {
+            let (levels, runs) = bidi.visual_runs(para, para.range.clone());
+            for run in runs {
+                let mut buf = UnicodeBuffer::new();
+                buf.push_str(&text[run.clone()]);
+                let rtl = levels[run.start].is_rtl();
+                buf.set_direction(if rtl {
+                    Direction::RightToLeft
+                } else {
+                    Direction::LeftToRight
+                });
+                buf.guess_segment_properties();
+                // rtl runs come out in visual order
+                let shaped = rustybuzz::shape(&shaper.face, &[], buf);
+                let mut clusters: Vec<usize> = shaped
+                    .glyph_infos()
+                    .iter()
+                    .map(|i| i.cluster as usize)
+                    .collect();
+                clusters.sort_unstable();
+                for (info, pos) in shaped.glyph_infos().iter().zip(shaped.glyph_positions()) {
+                    let cluster = info.cluster as usize;
+                    let glyph = info.glyph_id as u16;
+                    if self.glyphs.contains_key(&glyph) {
+                        glyphs.push(PlacedGlyph {
+                            glyph,
+                            pos: [
+                                x + pos.x_offset as f32 * scale,
+                                line as f32 - pos.y_offset as f32 * scale,
+                            ],
+                            advance: pos.x_advance as f32 * scale,
+                            rtl,
+                            line,
+                            cluster: start + run.start + cluster,
+                        });
+                    } else {
+                        // glyph wasn't generated (ligature in font without all glyphs),
+                        // so cluster chars are drawn unshaped in it's advance
+                        let end = clusters[clusters.partition_point(|&c| c <= cluster)..]
+                            .first()
+                            .map_or(run.len(), |&c| c);
+                        let mut cx = x;
+                        for (i, c) in text[run.start + cluster..run.start + end].char_indices() {
+                            if let Some(&glyph) = self.cmap.get(&c) {
+                                glyphs.push(PlacedGlyph {
+                                    glyph,
+                                    pos: [cx, line as f32],
+                                    advance: self.advance(c),
+                                    rtl,
+                                    line,
+                                    cluster: start + run.start + cluster + i,
+                                });
+                            }
+                            cx += self.advance(c);
+                        }
+                    }
+                    x += pos.x_advance as f32 * scale;
+                }
+            }
+        }
+    }
+
+    /// places glyphs of text in columns going top to bottom, right to left,
+    /// columns are split at '\n'. every char takes 1x1 font height cell
+    /// and narrow ones are centered in it, x 0 is left of last column
+    pub fn layout_vertical(&self, text: &str) -> Vec<PlacedGlyph> {
+        let columns = text.split('\n').count();
+        let mut glyphs = Vec::with_capacity(text.len());
+        let mut start = 0;
+        for (line, text) in text.split('\n').enumerate() {
+            let column = (columns - 1 - line) as f32;
+            for (row, (i, c)) in text.char_indices().enumerate() {
+                let Some(&glyph) = self.cmap.get(&c) else {
+                    continue;
+                };
+                let mut pos = [column + (1.0 - self.advance(c)) * 0.5, row as f32];
+                if VERTICAL_SHIFTED.contains(&c) {
+                    pos = [pos[0] + 0.5, pos[1] - 0.5];
+                }
+                glyphs.push(PlacedGlyph {
+                    glyph,
+                    pos,
+                    advance: 1.0,
+                    rtl: false,
+                    line: line as u32,
+                    cluster: start + i,
+                });
+            }
+            start += text.len() + 1;
+        }
+        glyphs
     }
 
     fn convert_points(
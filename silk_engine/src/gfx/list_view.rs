@@ -0,0 +1,129 @@
+use std::ops::Range;
+
+use super::{Pc, Renderer, Unit};
+
+/// inertial scroll velocity decay rate, per second
+const SCROLL_DECAY: f32 = 8.0;
+/// px/s below which inertial scrolling stops
+const MIN_VELOCITY: f32 = 4.0;
+/// items scrolled per mouse wheel line
+const WHEEL_ITEMS: f32 = 3.0;
+
+/// vertical list of same height items where only visible ones are drawn,
+/// so long lists don't submit thousands of offscreen shapes.
+/// wheel and touch flicks keep scrolling with decaying velocity
+#[derive(Clone, Debug, Default)]
+pub struct ListView {
+    pub items: usize,
+    /// px
+    pub item_height: f32,
+    /// px scrolled from top of first item
+    pub scroll: f32,
+    /// px/s of inertial scrolling
+    velocity: f32,
+    /// px height list was last drawn with, scroll is clamped to it
+    view_height: f32,
+}
+
+impl ListView {
+    pub fn new(items: usize, item_height: f32) -> Self {
+        Self {
+            items,
+            item_height,
+            ..Default::default()
+        }
+    }
+
+    /// px list content can scroll
+    pub fn max_scroll(&self) -> f32 {
+        (self.items as f32 * self.item_height - self.view_height).max(0.0)
+    }
+
+    /// scrolls by mouse wheel `lines`, like [`crate::AppContext::mouse_scroll`], positive is up
+    pub fn wheel(&mut self, lines: f32) {
+        // decaying velocity travels velocity / decay px in total
+        self.velocity -= lines * WHEEL_ITEMS * self.item_height * SCROLL_DECAY;
+    }
+
+    /// moves content with finger dragged `dy` px down over `dt` seconds,
+    /// it keeps going with drag's velocity once released
+    pub fn drag(&mut self, dy: f32, dt: f32) {
+        self.scroll -= dy;
+        if dt > 0.0 {
+            self.velocity = -dy / dt;
+        }
+        self.clamp();
+    }
+
+    /// jumps so item `i` is at top, or as close as scroll allows
+    pub fn scroll_to(&mut self, i: usize) {
+        self.scroll = i as f32 * self.item_height;
+        self.velocity = 0.0;
+        self.clamp();
+    }
+
+    /// advances inertial scrolling
+    pub fn update(&mut self, dt: f32) {
+        if self.velocity == 0.0 {
+            return;
+        }
+        // exact integral of exponentially decaying velocity, so it's frame rate independent
+        let decay = (-SCROLL_DECAY * dt).exp();
+        self.scroll += self.velocity * (1.0 - decay) / SCROLL_DECAY;
+        self.velocity *= decay;
+        if self.velocity.abs() < MIN_VELOCITY {
+            self.velocity = 0.0;
+        }
+        self.clamp();
+    }
+
+    fn clamp(&mut self) {
+        let clamped = self.scroll.clamp(0.0, self.max_scroll());
+        if clamped != self.scroll {
+            self.scroll = clamped;
+            self.velocity = 0.0;
+        }
+    }
+
+    /// idxs of items at least partly inside list `view_height` px tall
+    pub fn visible(&self, view_height: f32) -> Range<usize> {
+        if self.item_height <= 0.0 {
+            return 0..0;
+        }
+        let first = (self.scroll / self.item_height).floor().max(0.0) as usize;
+        let end = ((self.scroll + view_height) / self.item_height).ceil() as usize;
+        first.min(self.items)..end.min(self.items)
+    }
+
+    /// draws visible items clipped to x, y, w, h. `item` draws item i into area
+    /// covering it's row, so Pc(0.0) to Pc(1.0) spans item
+    pub fn draw(
+        &mut self,
+        gfx: &mut Renderer,
+        x: Unit,
+        y: Unit,
+        w: Unit,
+        h: Unit,
+        mut item: impl FnMut(&mut Renderer, usize),
+    ) {
+        self.view_height = gfx.px_y(h);
+        self.clamp();
+        gfx.clip(x, y, w, h);
+        gfx.push_area(x, y, w, h);
+        // area offsets are in screen space, sizes in parent area
+        let (screen_h, view_h) = (gfx.height(), self.view_height.max(1.0));
+        for i in self.visible(self.view_height) {
+            let top = i as f32 * self.item_height - self.scroll;
+            gfx.push_area(
+                Pc(0.0),
+                Pc(top / screen_h),
+                Pc(1.0),
+                Pc(self.item_height / view_h),
+            );
+            item(gfx, i);
+            gfx.pop_area();
+        }
+        gfx.pop_area();
+        gfx.no_clip();
+    }
+}
@@ -0,0 +1,105 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// frames stats are computed over
+const STAT_FRAMES: usize = 120;
+/// sleep this much less than needed and spin the rest, since sleep overshoots
+const SPIN_TIME: Duration = Duration::from_micros(1500);
+
+/// frame timing stats over recent frames
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PacingStats {
+    pub frames: u64,
+    /// refresh windows frames were late for, each stays on screen for 1 extra refresh
+    pub missed: u64,
+    /// avg frame time in seconds
+    pub avg: f32,
+    /// std deviation of frame time in seconds, lower is smoother
+    pub jitter: f32,
+}
+
+/// keeps frames evenly spaced at display refresh interval,
+/// so vrr displays refresh at steady rate instead of following irregular submission
+pub(crate) struct FramePacer {
+    pub(crate) enabled: bool,
+    interval: Duration,
+    last: Option<Instant>,
+    /// start of next refresh window
+    next: Instant,
+    frame_times: VecDeque<f32>,
+    stats: PacingStats,
+}
+
+impl FramePacer {
+    pub(crate) fn new(refresh_rate: u32) -> Self {
+        Self {
+            enabled: false,
+            interval: Duration::from_secs_f64(1.0 / refresh_rate.max(1) as f64),
+            last: None,
+            next: Instant::now(),
+            frame_times: VecDeque::with_capacity(STAT_FRAMES),
+            stats: PacingStats::default(),
+        }
+    }
+
+    pub(crate) fn set_refresh_rate(&mut self, refresh_rate: u32) {
+        self.interval = Duration::from_secs_f64(1.0 / refresh_rate.max(1) as f64);
+    }
+
+    pub(crate) fn stats(&self) -> PacingStats {
+        self.stats
+    }
+
+    /// call instead of `frame` when nothing is rendered (minimized, occluded),
+    /// event loop is throttled meanwhile, so this only keeps idle time out of stats
+    pub(crate) fn idle(&mut self) {
+        self.last = None;
+        self.next = Instant::now();
+    }
+
+    /// call once at start of frame, waits for next refresh window if enabled
+    pub(crate) fn frame(&mut self) {
+        if self.enabled {
+            let now = Instant::now();
+            if self.next > now {
+                let wait = self.next - now;
+                if wait > SPIN_TIME {
+                    std::thread::sleep(wait - SPIN_TIME);
+                }
+                while Instant::now() < self.next {
+                    std::hint::spin_loop();
+                }
+            }
+        }
+        let now = Instant::now();
+        if let Some(last) = self.last {
+            let dt = now - last;
+            // frame that took n intervals missed n - 1 windows, half interval tolerance
+            let windows = (dt.as_secs_f32() / self.interval.as_secs_f32() + 0.5) as u64;
+            self.stats.missed += windows.saturating_sub(1);
+            if self.frame_times.len() == STAT_FRAMES {
+                self.frame_times.pop_front();
+            }
+            self.frame_times.push_back(dt.as_secs_f32());
+            let n = self.frame_times.len() as f32;
+            let avg = self.frame_times.iter().sum::<f32>() / n;
+            let var = self
+                .frame_times
+                .iter()
+                .map(|t| (t - avg) * (t - avg))
+                .sum::<f32>()
+                / n;
+            self.stats.avg = avg;
+            self.stats.jitter = var.sqrt();
+        }
+        self.stats.frames += 1;
+        self.last = Some(now);
+        // next window is on interval grid, late frames skip to first window after now
+        self.next += self.interval;
+        if self.next < now {
+            self.next = now + self.interval;
+        }
+    }
+}
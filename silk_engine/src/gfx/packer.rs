@@ -32,6 +32,10 @@ impl Rect {
         self.0
     }
 
+    pub fn from_packed_whxy(packed: u64) -> Self {
+        Self(packed)
+    }
+
     pub fn area(self) -> u32 {
         let (w, h) = self.wh();
         w as u32 * h as u32
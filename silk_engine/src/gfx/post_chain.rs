@@ -0,0 +1,645 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use ash::vk;
+
+use crate::{event::WindowResize, util::CubeLut};
+
+use super::{
+    BufUsage, ImageInfo, ImgLayout, ImgUsage, MemProp, RenderCtx, queue_idle,
+    render_ctx::BufferImageCopy,
+};
+
+/// format of intermediate post images, float so effects can pass hdr values along
+const POST_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+/// size of auto created params ubo of effects
+const PARAMS_SIZE: vk::DeviceSize = 256;
+/// max downsamples of bloom mip chain
+const BLOOM_MIPS: u32 = 6;
+/// length of taa subpixel jitter sequence
+const TAA_SAMPLES: u32 = 8;
+/// effects that dither their output when they're last, with offset of dither in their params
+const DITHERED: [(&str, vk::DeviceSize); 2] = [("fxaa", 0), ("tonemap", 16)];
+
+/// tone mapping operator of `"tonemap"` effect, maps hdr colors to 0-1
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tonemap {
+    /// just clamps
+    Linear,
+    Reinhard,
+    /// filmic curve, desaturates highlights
+    Aces,
+}
+
+/// Chain of compute shader effects applied to rendered image before presenting.
+///
+/// Effect `name` is compute shader `res/shaders/{name}.wgsl` with bindings:
+/// - `@group(0) @binding(0) var src: texture_2d<f32>` output of previous effect
+/// - `@group(0) @binding(1) var src_sampler: sampler` linear sampler
+/// - `@group(0) @binding(2) var dst: texture_storage_2d<rgba16float, write>`
+/// - `@group(0) @binding(3) var<uniform> params: T` optional, written with [`PostChain::params`]
+///
+/// it's dispatched once per pixel, intermediate images are created and resized automatically
+///
+/// built-in effects:
+/// - `"bloom"` threshold, downsample chain, upsample and combine, see [`PostChain::bloom_params`]
+/// - `"taa"` temporal anti-aliasing, should be first, see [`PostChain::taa_params`] and [`PostChain::jitter`]
+/// - `"tonemap"` hdr to sdr with optional lut color grading, see [`PostChain::tonemap`]
+///   and [`PostChain::color_grade`], should come after hdr effects (bloom) and before ldr ones (fxaa)
+pub struct PostChain {
+    ctx: Arc<Mutex<RenderCtx>>,
+    effects: Vec<String>,
+    /// effects whose pipeline/ds/ubo were created
+    loaded: HashSet<String>,
+    dirty: bool,
+    width: u32,
+    height: u32,
+    /// size post imgs are allocated at, can be bigger than window, effects only run over window
+    target_size: (u32, u32),
+    bloom_mips: u32,
+    /// which taa history img is read this frame
+    taa_frame: usize,
+    /// taa history is invalid (after resize or load)
+    taa_reset: bool,
+    /// noise dither strength of last effect, see [`PostChain::dither`]
+    dither: f32,
+}
+
+impl PostChain {
+    pub(crate) fn new(ctx: Arc<Mutex<RenderCtx>>) -> Self {
+        Self {
+            ctx,
+            effects: Vec::new(),
+            loaded: HashSet::new(),
+            dirty: true,
+            width: 0,
+            height: 0,
+            target_size: (0, 0),
+            bloom_mips: 0,
+            taa_frame: 0,
+            taa_reset: true,
+            dither: 0.0,
+        }
+    }
+
+    /// appends effect to end of chain
+    pub fn add(&mut self, effect: &str) -> &mut Self {
+        self.insert(self.effects.len(), effect)
+    }
+
+    pub fn insert(&mut self, idx: usize, effect: &str) -> &mut Self {
+        assert!(!self.has(effect), "post effect already added: {effect}");
+        self.load(effect);
+        self.effects.insert(idx, effect.to_string());
+        self.dirty = true;
+        self
+    }
+
+    pub fn remove(&mut self, effect: &str) -> &mut Self {
+        let idx = self
+            .effects
+            .iter()
+            .position(|e| e == effect)
+            .unwrap_or_else(|| panic!("post effect not found: {effect}"));
+        self.effects.remove(idx);
+        self.dirty = true;
+        self
+    }
+
+    pub fn clear(&mut self) -> &mut Self {
+        self.effects.clear();
+        self.dirty = true;
+        self
+    }
+
+    pub fn has(&self, effect: &str) -> bool {
+        self.effects.iter().any(|e| e == effect)
+    }
+
+    pub fn effects(&self) -> &[String] {
+        &self.effects
+    }
+
+    /// noise dither strength of last effect if it's `"fxaa"` or `"tonemap"`,
+    /// removes 8 bit gradient banding. 0.0 disables it, 1.0 is +-0.5/255 noise
+    pub fn dither(&mut self, strength: f32) -> &mut Self {
+        self.dither = strength;
+        self.dirty = true;
+        self
+    }
+
+    /// last effect dithers it's output, so rendered image shouldn't be dithered
+    pub(crate) fn dithers(&self) -> bool {
+        self.effects
+            .last()
+            .is_some_and(|e| DITHERED.iter().any(|(d, _)| e == d))
+    }
+
+    /// writes effect's params ubo (binding 3)
+    pub fn params<T: ?Sized>(&mut self, effect: &str, data: &T) -> &mut Self {
+        assert!(
+            self.loaded.contains(effect) && effect != "bloom" && effect != "taa",
+            "post effect params not found: {effect}"
+        );
+        assert!(
+            size_of_val(data) as vk::DeviceSize <= PARAMS_SIZE,
+            "post effect params too big: {effect}"
+        );
+        self.ctx
+            .lock()
+            .unwrap()
+            .write_buf(&format!("{effect} ubo"), data);
+        self
+    }
+
+    /// `intensity` scales added glow, pixels brighter than `threshold` (max rgb) glow
+    pub fn bloom_params(&mut self, intensity: f32, threshold: f32) -> &mut Self {
+        self.load("bloom");
+        {
+            let mut ctx = self.ctx.lock().unwrap();
+            // [first down, other downs, ups, combine] each PARAMS_SIZE apart
+            let (threshold, knee) = (threshold.to_bits(), (threshold * 0.5).to_bits());
+            ctx.write_buf_off("bloom ubo", &[threshold, knee, 1], 0);
+            ctx.write_buf_off("bloom ubo", &[threshold, knee, 0], PARAMS_SIZE);
+            ctx.write_buf_off("bloom ubo", &1.0f32, 2 * PARAMS_SIZE);
+            ctx.write_buf_off("bloom ubo", &intensity, 3 * PARAMS_SIZE);
+        }
+        self
+    }
+
+    /// `blend` is weight of current frame (0-1) in `"taa"`, lower is smoother but ghosts more
+    pub fn taa_params(&mut self, blend: f32) -> &mut Self {
+        self.load("taa");
+        self.ctx
+            .lock()
+            .unwrap()
+            .write_buf("taa ubo", &blend.clamp(0.0, 1.0));
+        self
+    }
+
+    /// subpixel offset in px (-0.5 to 0.5) to render frame at for `"taa"`, 0 if taa isn't used
+    pub fn jitter(&self, frame: u32) -> [f32; 2] {
+        if !self.has("taa") {
+            return [0.0; 2];
+        }
+        // halton(2, 3) sequence, skipping 0th element which is (0, 0)
+        let halton = |mut i: u32, base: u32| {
+            let (mut f, mut r) = (1.0, 0.0);
+            while i > 0 {
+                f /= base as f32;
+                r += f * (i % base) as f32;
+                i /= base;
+            }
+            r
+        };
+        let i = frame % TAA_SAMPLES + 1;
+        [halton(i, 2) - 0.5, halton(i, 3) - 0.5]
+    }
+
+    /// sets `"tonemap"` operator and exposure color is multiplied by before mapping
+    pub fn tonemap(&mut self, op: Tonemap, exposure: f32) -> &mut Self {
+        self.load("tonemap");
+        self.ctx
+            .lock()
+            .unwrap()
+            .write_buf("tonemap ubo", &[op as u32, exposure.to_bits()]);
+        self
+    }
+
+    /// grades `"tonemap"` output with 3d lut `res/luts/{lut}.cube`,
+    /// `strength` (0-1) blends between ungraded and graded colors
+    pub fn color_grade(&mut self, lut: &str, strength: f32) -> &mut Self {
+        self.load("tonemap");
+        self.upload_lut(&CubeLut::load(lut));
+        self.grade_strength(strength)
+    }
+
+    pub fn grade_strength(&mut self, strength: f32) -> &mut Self {
+        self.load("tonemap");
+        self.ctx.lock().unwrap().write_buf_off(
+            "tonemap ubo",
+            &strength.clamp(0.0, 1.0),
+            3 * size_of::<f32>() as vk::DeviceSize,
+        );
+        self
+    }
+
+    /// uploads lut as strip of blue slices to `tonemap lut` img
+    fn upload_lut(&mut self, lut: &CubeLut) {
+        let mut ctx = self.ctx.lock().unwrap();
+        let (width, height) = (lut.size * lut.size, lut.size);
+        queue_idle();
+        ctx.try_remove_img("tonemap lut");
+        ctx.add_img(
+            "tonemap lut",
+            &ImageInfo::new()
+                .width(width)
+                .height(height)
+                .format(vk::Format::R8G8B8A8_UNORM)
+                .usage(ImgUsage::DST | ImgUsage::SAMPLED),
+            MemProp::GPU,
+        );
+        ctx.add_img_view("tonemap lut view", "tonemap lut");
+        let strip = lut.strip();
+        let staging = ctx.staging_buf(strip.len() as vk::DeviceSize);
+        ctx.write_buf(&staging, &strip[..]);
+        ctx.begin_cmd();
+        ctx.set_img_layout(
+            "tonemap lut",
+            ImgLayout::DST,
+            vk::PipelineStageFlags2::TOP_OF_PIPE,
+            vk::PipelineStageFlags2::TRANSFER,
+            vk::AccessFlags2::NONE,
+            vk::AccessFlags2::TRANSFER_WRITE,
+        );
+        ctx.copy_buf_to_img(
+            &staging,
+            "tonemap lut",
+            &[BufferImageCopy {
+                buf_off: 0,
+                img_off_x: 0,
+                img_off_y: 0,
+                buf_width: width,
+                buf_height: height,
+            }],
+        );
+        ctx.set_img_layout(
+            "tonemap lut",
+            ImgLayout::SHADER_READ,
+            vk::PipelineStageFlags2::TRANSFER,
+            vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::AccessFlags2::TRANSFER_WRITE,
+            vk::AccessFlags2::SHADER_SAMPLED_READ,
+        );
+        ctx.finish_cmd();
+        ctx.write_ds_img("tonemap ds", "tonemap lut view", ImgLayout::SHADER_READ, 4);
+        ctx.write_buf_off(
+            "tonemap ubo",
+            &(lut.size as f32),
+            2 * size_of::<f32>() as vk::DeviceSize,
+        );
+    }
+
+    fn load(&mut self, effect: &str) {
+        if self.loaded.contains(effect) {
+            return;
+        }
+        self.loaded.insert(effect.to_string());
+        if effect == "taa" {
+            {
+                let mut ctx = self.ctx.lock().unwrap();
+                ctx.add_compute("taa");
+                ctx.add_buf(
+                    "taa ubo",
+                    PARAMS_SIZE,
+                    BufUsage::UNIFORM,
+                    MemProp::CPU_CACHED,
+                );
+                ctx.write_buf("taa ubo", &[0u8; PARAMS_SIZE as usize]);
+                for i in 0..2 {
+                    let ds = format!("taa ds {i}");
+                    ctx.add_desc_set(&ds, "taa", 0);
+                    ctx.write_ds_sampler(&ds, "linear", 1);
+                    ctx.write_ds_buf(&ds, "taa ubo", 3);
+                }
+            }
+            self.taa_params(0.1);
+            let ctx = self.ctx.clone();
+            self.resize_taa(&mut ctx.lock().unwrap());
+            return;
+        }
+        if effect == "bloom" {
+            {
+                let mut ctx = self.ctx.lock().unwrap();
+                ctx.add_compute("bloom_down");
+                ctx.add_compute("bloom_up");
+                ctx.add_buf(
+                    "bloom ubo",
+                    4 * PARAMS_SIZE,
+                    BufUsage::UNIFORM,
+                    MemProp::CPU_CACHED,
+                );
+                for i in 0..BLOOM_MIPS {
+                    let (down, up) = (format!("bloom down {i} ds"), format!("bloom up {i} ds"));
+                    ctx.add_desc_set(&down, "bloom_down", 0);
+                    ctx.add_desc_set(&up, "bloom_up", 0);
+                    ctx.write_ds_sampler(&down, "linear", 1);
+                    ctx.write_ds_sampler(&up, "linear", 1);
+                    let off = if i == 0 { 0 } else { PARAMS_SIZE };
+                    ctx.write_ds_buf_range(&down, "bloom ubo", off..off + PARAMS_SIZE, 3);
+                    let off = if i == 0 { 3 } else { 2 } * PARAMS_SIZE;
+                    ctx.write_ds_buf_range(&up, "bloom ubo", off..off + PARAMS_SIZE, 3);
+                }
+            }
+            self.bloom_params(0.5, 0.8);
+            let ctx = self.ctx.clone();
+            self.resize_bloom(&mut ctx.lock().unwrap());
+            return;
+        }
+        let mut ctx = self.ctx.lock().unwrap();
+        ctx.add_compute(effect);
+        let ds = format!("{effect} ds");
+        ctx.add_desc_set(&ds, effect, 0);
+        ctx.write_ds_sampler(&ds, "linear", 1);
+        let has_params = ctx.shader(effect).dsl_infos()[0]
+            .iter()
+            .any(|b| b.binding == 3 && b.desc_ty == vk::DescriptorType::UNIFORM_BUFFER);
+        if has_params {
+            let ubo = format!("{effect} ubo");
+            ctx.add_buf(&ubo, PARAMS_SIZE, BufUsage::UNIFORM, MemProp::CPU_CACHED);
+            ctx.write_buf(&ubo, &[0u8; PARAMS_SIZE as usize]);
+            ctx.write_ds_buf(&ds, &ubo, 3);
+        }
+        drop(ctx);
+        if effect == "tonemap" {
+            self.upload_lut(&CubeLut::identity(2));
+            self.tonemap(Tonemap::Aces, 1.0);
+        }
+        if effect == "fxaa" {
+            let mut ctx = self.ctx.lock().unwrap();
+            let linear = ctx.linear() as u32;
+            ctx.write_buf_off("fxaa ubo", &linear, size_of::<f32>() as vk::DeviceSize);
+        }
+    }
+
+    fn add_post_img(ctx: &mut RenderCtx, img: &str, width: u32, height: u32) {
+        ctx.try_remove_img(img);
+        ctx.add_img(
+            img,
+            &ImageInfo::new()
+                .width(width)
+                .height(height)
+                .format(POST_FORMAT)
+                .usage(ImgUsage::STORAGE | ImgUsage::SAMPLED | ImgUsage::SRC),
+            MemProp::GPU,
+        );
+        ctx.add_img_view(&format!("{img} view"), img);
+    }
+
+    /// recreates bloom mip chain, `bloom down {i}` is 1/2^(i+1) res, `bloom up {i}` is same size
+    fn resize_bloom(&mut self, ctx: &mut RenderCtx) {
+        let (width, height) = self.target_size;
+        if !self.loaded.contains("bloom") || width == 0 || height == 0 {
+            return;
+        }
+        queue_idle();
+        for i in 0..self.bloom_mips {
+            ctx.try_remove_img(&format!("bloom down {i}"));
+            ctx.try_remove_img(&format!("bloom up {i}"));
+        }
+        self.bloom_mips = BLOOM_MIPS.min(width.min(height).ilog2()).max(1);
+        for i in 0..self.bloom_mips {
+            let (w, h) = ((width >> (i + 1)).max(1), (height >> (i + 1)).max(1));
+            Self::add_post_img(ctx, &format!("bloom down {i}"), w, h);
+            // top mip is upsampled directly from last downsample
+            if i + 1 < self.bloom_mips {
+                Self::add_post_img(ctx, &format!("bloom up {i}"), w, h);
+            }
+        }
+        self.dirty = true;
+    }
+
+    /// recreates taa history imgs, `taa ds {i}` reads `taa history {i}` and writes other one
+    fn resize_taa(&mut self, ctx: &mut RenderCtx) {
+        let (width, height) = self.target_size;
+        if !self.loaded.contains("taa") || width == 0 || height == 0 {
+            return;
+        }
+        queue_idle();
+        for i in 0..2 {
+            Self::add_post_img(ctx, &format!("taa history {i}"), width, height);
+        }
+        for i in 0..2 {
+            let ds = format!("taa ds {i}");
+            let (read, write) = (
+                format!("taa history {i} view"),
+                format!("taa history {} view", 1 - i),
+            );
+            ctx.write_ds_img(&ds, &read, ImgLayout::SHADER_READ, 4);
+            ctx.write_ds_img(&ds, &write, ImgLayout::GENERAL, 5);
+        }
+        self.taa_reset = true;
+        self.dirty = true;
+    }
+
+    /// reads `taa history {i}`, writes other one, `reset` ignores history
+    fn taa(ctx: &mut RenderCtx, src: &str, dst: &str, size: (u32, u32), (i, reset): (usize, bool)) {
+        ctx.write_buf_off(
+            "taa ubo",
+            &(reset as u32),
+            size_of::<f32>() as vk::DeviceSize,
+        );
+        let (read, write) = (format!("taa history {i}"), format!("taa history {}", 1 - i));
+        ctx.set_img_layout(
+            &read,
+            ImgLayout::SHADER_READ,
+            vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::AccessFlags2::SHADER_STORAGE_WRITE,
+            vk::AccessFlags2::SHADER_SAMPLED_READ,
+        );
+        ctx.set_img_layout(
+            &write,
+            ImgLayout::GENERAL,
+            vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::AccessFlags2::NONE,
+            vk::AccessFlags2::SHADER_STORAGE_WRITE,
+        );
+        Self::pass(ctx, "taa", &format!("taa ds {i}"), src, dst, size);
+    }
+
+    /// post imgs are only recreated when `target_size` changes, see [`Renderer::on_resize`]
+    pub(crate) fn on_resize(
+        &mut self,
+        ctx: &mut RenderCtx,
+        e: &WindowResize,
+        target_size: (u32, u32),
+    ) {
+        self.width = e.width;
+        self.height = e.height;
+        if e.width == 0 || e.height == 0 {
+            return;
+        }
+        // history no longer lines up with new size
+        self.taa_reset = true;
+        if self.target_size == target_size {
+            return;
+        }
+        self.target_size = target_size;
+        queue_idle();
+        for img in ["post image 0", "post image 1"] {
+            Self::add_post_img(ctx, img, target_size.0, target_size.1);
+        }
+        self.resize_bloom(ctx);
+        self.resize_taa(ctx);
+        self.dirty = true;
+    }
+
+    /// size effects run over at bloom mip `level`, 0 is window size
+    fn region(&self, level: u32) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+
+    /// barriers src for reading and dst for writing then dispatches over `w` x `h` of dst
+    fn pass(
+        ctx: &mut RenderCtx,
+        pipeline: &str,
+        ds: &str,
+        src: &str,
+        dst: &str,
+        (w, h): (u32, u32),
+    ) {
+        ctx.set_img_layout(
+            src,
+            ImgLayout::SHADER_READ,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT
+                | vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::AccessFlags2::COLOR_ATTACHMENT_WRITE | vk::AccessFlags2::SHADER_STORAGE_WRITE,
+            vk::AccessFlags2::SHADER_SAMPLED_READ,
+        );
+        // dst was last read by previous pass or blitted from in previous frame
+        ctx.set_img_layout(
+            dst,
+            ImgLayout::GENERAL,
+            vk::PipelineStageFlags2::COMPUTE_SHADER | vk::PipelineStageFlags2::BLIT,
+            vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::AccessFlags2::NONE,
+            vk::AccessFlags2::SHADER_STORAGE_WRITE,
+        );
+        ctx.bind_pipeline(pipeline);
+        ctx.bind_ds(ds);
+        ctx.dispatch(w, h, 1);
+    }
+
+    fn bloom_img(&self, i: u32, up: bool) -> String {
+        if up && i + 1 < self.bloom_mips {
+            format!("bloom up {i}")
+        } else {
+            format!("bloom down {i}")
+        }
+    }
+
+    fn write_bloom_ds(&self, ctx: &mut RenderCtx, src_view: &str, dst_view: &str) {
+        let read = ImgLayout::SHADER_READ;
+        for i in 0..self.bloom_mips {
+            let down = format!("bloom down {i} ds");
+            let down_src = if i == 0 {
+                src_view.to_string()
+            } else {
+                format!("bloom down {} view", i - 1)
+            };
+            ctx.write_ds_img(&down, &down_src, read, 0);
+            ctx.write_ds_img(
+                &down,
+                &format!("bloom down {i} view"),
+                ImgLayout::GENERAL,
+                2,
+            );
+            // up i adds upsampled level i to level i - 1, up 0 combines into dst
+            let up = format!("bloom up {i} ds");
+            let (up_dst, up_base) = if i == 0 {
+                (dst_view.to_string(), src_view.to_string())
+            } else {
+                (
+                    format!("{} view", self.bloom_img(i - 1, true)),
+                    format!("bloom down {} view", i - 1),
+                )
+            };
+            ctx.write_ds_img(&up, &format!("{} view", self.bloom_img(i, true)), read, 0);
+            ctx.write_ds_img(&up, &up_dst, ImgLayout::GENERAL, 2);
+            ctx.write_ds_img(&up, &up_base, read, 4);
+        }
+    }
+
+    fn bloom(&self, ctx: &mut RenderCtx, src: &str, dst: &str) {
+        for i in 0..self.bloom_mips {
+            let down_src = if i == 0 {
+                src.to_string()
+            } else {
+                format!("bloom down {}", i - 1)
+            };
+            let ds = format!("bloom down {i} ds");
+            Self::pass(
+                ctx,
+                "bloom_down",
+                &ds,
+                &down_src,
+                &format!("bloom down {i}"),
+                self.region(i + 1),
+            );
+        }
+        for i in (0..self.bloom_mips).rev() {
+            let up_dst = if i == 0 {
+                dst.to_string()
+            } else {
+                self.bloom_img(i - 1, true)
+            };
+            let ds = format!("bloom up {i} ds");
+            Self::pass(
+                ctx,
+                "bloom_up",
+                &ds,
+                &self.bloom_img(i, true),
+                &up_dst,
+                self.region(i),
+            );
+        }
+    }
+
+    /// runs effects on `src` img, returns name of img with final result
+    pub(crate) fn render(&mut self, ctx: &mut RenderCtx, src: &str) -> String {
+        let taa = (self.taa_frame, self.taa_reset);
+        if self.has("taa") {
+            self.taa_frame = 1 - self.taa_frame;
+            self.taa_reset = false;
+        }
+        if self.dirty {
+            self.dirty = false;
+            // only last effect dithers, so noise isn't added twice
+            for (effect, off) in DITHERED {
+                if !self.loaded.contains(effect) {
+                    continue;
+                }
+                let last = self.effects.last().is_some_and(|e| e == effect);
+                let dither = if last { self.dither } else { 0.0 };
+                ctx.write_buf_off(&format!("{effect} ubo"), &dither, off);
+            }
+            let mut src_view = format!("{src} view");
+            for (i, effect) in self.effects.iter().enumerate() {
+                let dst_view = format!("post image {} view", i % 2);
+                if effect == "bloom" {
+                    self.write_bloom_ds(ctx, &src_view, &dst_view);
+                } else if effect == "taa" {
+                    for ds in ["taa ds 0", "taa ds 1"] {
+                        ctx.write_ds_img(ds, &src_view, ImgLayout::SHADER_READ, 0);
+                        ctx.write_ds_img(ds, &dst_view, ImgLayout::GENERAL, 2);
+                    }
+                } else {
+                    let ds = format!("{effect} ds");
+                    ctx.write_ds_img(&ds, &src_view, ImgLayout::SHADER_READ, 0);
+                    ctx.write_ds_img(&ds, &dst_view, ImgLayout::GENERAL, 2);
+                }
+                src_view = dst_view;
+            }
+        }
+
+        let mut src = src.to_string();
+        for (i, effect) in self.effects.iter().enumerate() {
+            let dst = format!("post image {}", i % 2);
+            if effect == "bloom" {
+                self.bloom(ctx, &src, &dst);
+            } else if effect == "taa" {
+                Self::taa(ctx, &src, &dst, self.region(0), taa);
+            } else {
+                let ds = format!("{effect} ds");
+                Self::pass(ctx, effect, &ds, &src, &dst, self.region(0));
+            }
+            src = dst;
+        }
+        src
+    }
+}
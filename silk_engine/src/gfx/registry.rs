@@ -0,0 +1,479 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasherDefault, Hasher},
+    marker::PhantomData,
+    ops::Deref,
+    sync::{Arc, LazyLock, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// interned resource name, hashed and compared as u32 and formatted without allocating,
+/// so names used every frame are made once instead of formatted and hashed each time.
+/// interner owns names, ones no id holds anymore are freed as it grows
+#[derive(Clone, Default)]
+pub struct NameId {
+    id: u32,
+    name: Arc<str>,
+}
+
+/// interned names, "" is always 0 so it's [`NameId::default`]
+struct Names {
+    ids: HashMap<Arc<str>, u32>,
+    /// by id, none if id is free
+    strs: Vec<Option<Arc<str>>>,
+    free: Vec<u32>,
+    /// interned names at which unused ones are freed next
+    sweep_at: usize,
+}
+
+/// interned names are kept until there's this many of them
+const MIN_SWEEP: usize = 256;
+
+static NAMES: LazyLock<RwLock<Names>> = LazyLock::new(|| {
+    let name: Arc<str> = Arc::default();
+    RwLock::new(Names {
+        ids: HashMap::from([(name.clone(), 0)]),
+        strs: vec![Some(name)],
+        free: Vec::new(),
+        sweep_at: MIN_SWEEP,
+    })
+});
+
+impl Names {
+    /// frees names only interner holds, one in `ids` and one in `strs`
+    fn sweep(&mut self) {
+        let Self {
+            ids, strs, free, ..
+        } = self;
+        ids.retain(|name, &mut id| {
+            let used = id == 0 || Arc::strong_count(name) > 2;
+            if !used {
+                strs[id as usize] = None;
+                free.push(id);
+            }
+            used
+        });
+        self.sweep_at = (self.ids.len() * 2).max(MIN_SWEEP);
+    }
+}
+
+impl NameId {
+    /// interns `name`, same name always gets same id while any id of it is held
+    pub fn new(name: &str) -> Self {
+        if let Some(id) = Self::find(name) {
+            return id;
+        }
+        let mut names = NAMES.write().unwrap();
+        // may have been interned by other thread between locks
+        if let Some((name, &id)) = names.ids.get_key_value(name) {
+            return Self {
+                id,
+                name: name.clone(),
+            };
+        }
+        if names.ids.len() >= names.sweep_at {
+            names.sweep();
+        }
+        let name: Arc<str> = name.into();
+        let id = match names.free.pop() {
+            Some(id) => {
+                names.strs[id as usize] = Some(name.clone());
+                id
+            }
+            None => {
+                names.strs.push(Some(name.clone()));
+                names.strs.len() as u32 - 1
+            }
+        };
+        names.ids.insert(name.clone(), id);
+        Self { id, name }
+    }
+
+    /// none if `name` isn't interned, so nothing is named by it
+    pub fn find(name: &str) -> Option<Self> {
+        let names = NAMES.read().unwrap();
+        let (name, &id) = names.ids.get_key_value(name)?;
+        Some(Self {
+            id,
+            name: name.clone(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+}
+
+impl PartialEq for NameId {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for NameId {}
+
+impl std::hash::Hash for NameId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u32(self.id);
+    }
+}
+
+impl Deref for NameId {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl std::fmt::Display for NameId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl std::fmt::Debug for NameId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.name)
+    }
+}
+
+/// ids are unique, so they are used as hash as is
+#[derive(Default)]
+pub(crate) struct NameHasher(u64);
+
+impl Hasher for NameHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 << 8) | b as u64;
+        }
+    }
+
+    fn write_u32(&mut self, id: u32) {
+        self.0 = id as u64;
+    }
+}
+
+pub(crate) type NameMap<V> = HashMap<NameId, V, BuildHasherDefault<NameHasher>>;
+
+/// resource name or it's [`NameId`], ids are looked up without hashing name
+pub trait AsName {
+    fn name(&self) -> &str;
+    /// none if name was never interned, so nothing is named by it
+    fn find(&self) -> Option<NameId>;
+    fn intern(&self) -> NameId;
+}
+
+impl AsName for str {
+    fn name(&self) -> &str {
+        self
+    }
+
+    fn find(&self) -> Option<NameId> {
+        NameId::find(self)
+    }
+
+    fn intern(&self) -> NameId {
+        NameId::new(self)
+    }
+}
+
+impl AsName for String {
+    fn name(&self) -> &str {
+        self
+    }
+
+    fn find(&self) -> Option<NameId> {
+        NameId::find(self)
+    }
+
+    fn intern(&self) -> NameId {
+        NameId::new(self)
+    }
+}
+
+impl AsName for NameId {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn find(&self) -> Option<NameId> {
+        Some(self.clone())
+    }
+
+    fn intern(&self) -> NameId {
+        self.clone()
+    }
+}
+
+impl<K: AsName + ?Sized> AsName for &K {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn find(&self) -> Option<NameId> {
+        (**self).find()
+    }
+
+    fn intern(&self) -> NameId {
+        (**self).intern()
+    }
+}
+
+impl<K: AsName + ?Sized> AsName for &mut K {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn find(&self) -> Option<NameId> {
+        (**self).find()
+    }
+
+    fn intern(&self) -> NameId {
+        (**self).intern()
+    }
+}
+
+/// typed slot of named resource in [`Registry`], cheaper to look up than it's name.
+/// slot generation is bumped on removal and replacement, so stale ids never match new resources
+pub struct Id<T> {
+    idx: u32,
+    generation: u32,
+    _resource: PhantomData<fn() -> T>,
+}
+
+impl<T> Id<T> {
+    fn new(idx: u32, generation: u32) -> Self {
+        Self {
+            idx,
+            generation,
+            _resource: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.idx == other.idx && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> std::hash::Hash for Id<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.idx.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> std::fmt::Debug for Id<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Id({}v{})", self.idx, self.generation)
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    /// (name, resource), none if slot is free
+    value: Option<(NameId, T)>,
+}
+
+/// named resources stored in reusable slots, looked up by name or by [`Id`]
+pub(crate) struct Registry<T> {
+    slots: Vec<Slot<T>>,
+    names: NameMap<u32>,
+    free: Vec<u32>,
+}
+
+impl<T> Default for Registry<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            names: NameMap::default(),
+            free: Vec::new(),
+        }
+    }
+}
+
+impl<T> Registry<T> {
+    /// replaces resource with same name in it's slot,
+    /// bumping slot generation so ids of replaced resource no longer match
+    pub fn insert(&mut self, name: impl AsName, value: T) -> Id<T> {
+        let name = name.intern();
+        if let Some(&idx) = self.names.get(&name) {
+            let slot = &mut self.slots[idx as usize];
+            slot.generation += 1;
+            slot.value = Some((name, value));
+            return Id::new(idx, slot.generation);
+        }
+        let idx = self.free.pop().unwrap_or_else(|| {
+            self.slots.push(Slot {
+                generation: 0,
+                value: None,
+            });
+            self.slots.len() as u32 - 1
+        });
+        let slot = &mut self.slots[idx as usize];
+        slot.value = Some((name.clone(), value));
+        self.names.insert(name, idx);
+        Id::new(idx, slot.generation)
+    }
+
+    pub fn remove(&mut self, name: impl AsName) -> Option<T> {
+        let idx = self.names.remove(&name.find()?)?;
+        let slot = &mut self.slots[idx as usize];
+        slot.generation += 1;
+        self.free.push(idx);
+        slot.value.take().map(|(_, value)| value)
+    }
+
+    /// takes resources of `other`, same named ones are put in their old slots without
+    /// bumping generation, so their ids match recreated resources
+    pub fn refill(&mut self, other: Registry<T>) {
+        let names: Vec<NameId> = self.names.keys().cloned().collect();
+        for name in names {
+            if !other.names.contains_key(&name) {
+                self.remove(name);
+            }
+        }
+        for (name, value) in other.slots.into_iter().filter_map(|s| s.value) {
+            match self.names.get(&name) {
+                Some(&idx) => self.slots[idx as usize].value = Some((name, value)),
+                None => {
+                    self.insert(name, value);
+                }
+            }
+        }
+    }
+
+    pub fn id(&self, name: impl AsName) -> Option<Id<T>> {
+        let &idx = self.names.get(&name.find()?)?;
+        Some(Id::new(idx, self.slots[idx as usize].generation))
+    }
+
+    pub fn contains(&self, name: impl AsName) -> bool {
+        name.find().is_some_and(|n| self.names.contains_key(&n))
+    }
+
+    pub fn get(&self, name: impl AsName) -> Option<&T> {
+        let &idx = self.names.get(&name.find()?)?;
+        self.slots[idx as usize].value.as_ref().map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, name: impl AsName) -> Option<&mut T> {
+        let &idx = self.names.get(&name.find()?)?;
+        self.slots[idx as usize].value.as_mut().map(|(_, v)| v)
+    }
+
+    /// none if resource was removed or replaced
+    pub fn at(&self, id: Id<T>) -> Option<&T> {
+        self.slots
+            .get(id.idx as usize)
+            .filter(|s| s.generation == id.generation)?
+            .value
+            .as_ref()
+            .map(|(_, v)| v)
+    }
+
+    pub fn name(&self, id: Id<T>) -> Option<NameId> {
+        self.slots
+            .get(id.idx as usize)
+            .filter(|s| s.generation == id.generation)?
+            .value
+            .as_ref()
+            .map(|(n, _)| n.clone())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &T)> {
+        self.slots
+            .iter()
+            .filter_map(|s| s.value.as_ref().map(|(n, v)| (n.as_str(), v)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut T)> {
+        self.slots
+            .iter_mut()
+            .filter_map(|s| s.value.as_mut().map(|(n, v)| (n.as_str(), v)))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, v)| v)
+    }
+}
+
+impl<T, K: AsName> std::ops::Index<K> for Registry<T> {
+    type Output = T;
+
+    fn index(&self, name: K) -> &T {
+        self.get(&name)
+            .unwrap_or_else(|| panic!("resource not found: {}", name.name()))
+    }
+}
+
+/// [`Registry`] behind it's own lock, cloned handles share it,
+/// so resources of one type are looked up without locking whole render ctx
+pub struct SharedRegistry<T>(Arc<RwLock<Registry<T>>>);
+
+impl<T> Clone for SharedRegistry<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Default for SharedRegistry<T> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<T> SharedRegistry<T> {
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, Registry<T>> {
+        self.0.read().unwrap()
+    }
+
+    pub(crate) fn write(&self) -> RwLockWriteGuard<'_, Registry<T>> {
+        self.0.write().unwrap()
+    }
+
+    pub fn id(&self, name: impl AsName) -> Option<Id<T>> {
+        self.read().id(name)
+    }
+
+    pub fn contains(&self, name: impl AsName) -> bool {
+        self.read().contains(name)
+    }
+
+    pub fn name(&self, id: Id<T>) -> Option<NameId> {
+        self.read().name(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Clone> SharedRegistry<T> {
+    pub fn get(&self, name: impl AsName) -> Option<T> {
+        self.read().get(name).cloned()
+    }
+
+    /// none if resource was removed or replaced
+    pub fn at(&self, id: Id<T>) -> Option<T> {
+        self.read().at(id).cloned()
+    }
+}
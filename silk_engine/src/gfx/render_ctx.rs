@@ -1,21 +1,38 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{MappedRwLockReadGuard, Mutex, MutexGuard, RwLockReadGuard},
+    time::{Duration, Instant},
+};
 
 use ash::vk::{self, Handle};
 use winit::raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use winit::window::Window;
 
-use crate::{scope_time, util::Mem};
+use crate::{
+    err,
+    error::{Error, Result},
+    scope_time,
+    util::Mem,
+    warn,
+};
 
+#[cfg(feature = "renderdoc")]
+use super::renderdoc;
 use super::{
-    BufUsage, CmdManager, DSLBinding, DSLManager, DescAlloc, GpuAlloc, GraphicsPipelineInfo,
-    ImageInfo, ImgLayout, ImgUsage, MemProp, PipelineLayoutManager, PipelineStageInfo,
-    SamplerManager, alloc_callbacks, create_compute, entry, gpu, gpu_idle, instance, physical_gpu,
-    queue, shader::Shader,
+    BarrierTracker, BufUsage, CmdManager, DSLBinding, DSLManager, DescAlloc, GpuAlloc,
+    GraphicsPipelineInfo, ImageInfo, ImgLayout, ImgUsage, LifetimeTracker, MemProp,
+    PipelineLayoutManager, PipelineStageInfo, SamplerInfo, SamplerManager, alloc_callbacks, caps,
+    cmd_barrier, create_compute, entry, gpu, gpu_idle, gpu_limits, instance, physical_gpu, queue,
+    queue_idle, recreate_gpu,
+    registry::{AsName, Id, NameId, NameMap, SharedRegistry},
+    render_pass, samples_u32_to_vk,
+    shader::Shader,
+    vulkan_config,
 };
 
-#[cfg(debug_assertions)]
-static DEBUG_UTILS_LOADER: std::sync::LazyLock<ash::ext::debug_utils::Device> =
-    std::sync::LazyLock::new(|| ash::ext::debug_utils::Device::new(instance(), gpu()));
+#[cfg(any(debug_assertions, feature = "renderdoc"))]
+static DEBUG_UTILS_LOADER: super::PerGpu<ash::ext::debug_utils::Device> =
+    super::PerGpu::new(|| ash::ext::debug_utils::Device::new(instance(), gpu()));
 
 struct ShaderData {
     shader: Shader,
@@ -24,13 +41,28 @@ struct ShaderData {
 }
 
 #[derive(Debug, Default, Clone)]
-struct PipelineData {
+pub struct PipelineData {
     pipeline: vk::Pipeline,
     info: GraphicsPipelineInfo,
     bind_point: vk::PipelineBindPoint,
     shader_name: String,
 }
 
+/// graphics pipeline waiting to be built, see `RenderCtx::queue_pipeline`
+struct QueuedPipeline {
+    name: String,
+    shader_name: String,
+    info: GraphicsPipelineInfo,
+    vert_input_bindings: Vec<(bool, Vec<u32>)>,
+}
+
+pub type BufId = Id<vk::Buffer>;
+pub type ImgId = Id<ImageData>;
+pub type PipelineId = Id<PipelineData>;
+pub type Buffers = SharedRegistry<vk::Buffer>;
+pub type Images = SharedRegistry<ImageData>;
+pub type Pipelines = SharedRegistry<PipelineData>;
+
 #[derive(Debug, Default)]
 struct CmdInfo {
     pipeline_data: PipelineData,
@@ -38,6 +70,17 @@ struct CmdInfo {
     render_area: vk::Rect2D,
     viewport: vk::Viewport,
     scissor: vk::Rect2D,
+    /// width, height, img view, sampled img view of last begin_render()
+    render_target: (u32, u32, NameId, NameId),
+}
+
+/// per img state of swapchain, made whenever swapchain is (re)created
+struct SwapchainImg {
+    img: NameId,
+    view: NameId,
+    /// signaled by frame rendering into img and waited on by it's present,
+    /// presentation may still wait on it while next frame renders, so each img has it's own
+    render_finished: vk::Semaphore,
 }
 
 #[derive(Default)]
@@ -49,8 +92,24 @@ struct FenceData {
 struct DescSetData {
     desc_set: vk::DescriptorSet,
     binds: Vec<DSLBinding>,
+    /// img views written to ds (binding, img view, layout), used for sync validation
+    imgs: Vec<(u32, String, vk::ImageLayout)>,
+    /// bufs written to ds (binding, buf, range), rewritten when buf is moved by defrag
+    bufs: Vec<(u32, String, std::ops::Range<vk::DeviceSize>)>,
+    /// samplers written to ds (binding, sampler), rewritten when device is recreated
+    samplers: Vec<(u32, vk::Sampler)>,
 }
 
+/// gpu work recorded through RenderCtx in current frame
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DrawStats {
+    pub draws: u32,
+    pub dispatches: u32,
+    pub vertices: u64,
+    pub instances: u64,
+}
+
+#[derive(Clone)]
 pub struct ImageData {
     pub img: vk::Image,
     pub views: Vec<String>,
@@ -67,26 +126,73 @@ pub struct RenderCtx {
     pipeline_layout_manager: PipelineLayoutManager,
     sampler_manager: SamplerManager,
     cmd_manager: CmdManager,
+    barrier_tracker: BarrierTracker,
+    lifetime_tracker: LifetimeTracker,
     // named cached objects
     shaders: HashMap<String, ShaderData>,
-    pipelines: HashMap<String, PipelineData>,
+    pipelines: Pipelines,
+    queued_pipelines: VecDeque<QueuedPipeline>,
+    /// (built, queued) since queue was last empty
+    pipeline_progress: (usize, usize),
     desc_sets: HashMap<String, DescSetData>,
-    bufs: HashMap<String, vk::Buffer>,
+    bufs: Buffers,
+    /// immutable bufs with checksum and size of their data
+    static_bufs: HashMap<String, (u64, u64)>,
+    /// bump offset into "transient" buf, reset each frame
+    transient_head: u64,
+    /// names destroyed once in flight frame finishes, see `destroy_after_frame`
+    pending_destroy: Vec<String>,
+    /// bufs replaced by grown ones, freed once in flight frame finishes
+    retired_bufs: Vec<vk::Buffer>,
+    /// vulkan errors from submits/waits, see `take_errors`
+    errors: Vec<Error>,
+    device_lost: bool,
     fences: HashMap<String, FenceData>,
     semaphores: HashMap<String, vk::Semaphore>,
-    imgs: HashMap<String, ImageData>,
-    img_views: HashMap<String, (vk::ImageView, String)>,
+    imgs: Images,
+    img_views: NameMap<(vk::ImageView, NameId)>,
+    /// render pass fallback framebuffers, (attachment views, width, height)
+    framebuffers: HashMap<(Vec<vk::ImageView>, u32, u32), vk::Framebuffer>,
     samplers: HashMap<String, vk::Sampler>,
     // window context
     surface_caps2_loader: ash::khr::get_surface_capabilities2::Instance,
     pub surface: vk::SurfaceKHR,
     pub surface_format: vk::SurfaceFormatKHR,
     surface_present_modes: Vec<vk::PresentModeKHR>,
+    present_mode: vk::PresentModeKHR,
     swapchain_loader: ash::khr::swapchain::Device,
     pub swapchain: vk::SwapchainKHR,
     pub swapchain_size: vk::Extent2D,
     pub swapchain_img_idx: usize,
+    /// one per img driver returned, names are interned so they aren't formatted every frame
+    swapchain_imgs: Vec<SwapchainImg>,
+    /// recreate swapchain even if size didn't change
+    swapchain_stale: bool,
     frame_cmd: vk::CommandBuffer,
+    /// frame cmd start/end timestamps
+    timestamps: vk::QueryPool,
+    gpu_frame_time: Option<f32>,
+    draw_stats: DrawStats,
+}
+
+/// default size of "transient" buf
+const TRANSIENT_SIZE: u64 = 4 * 1024 * 1024;
+
+/// fnv-1a hash, used to verify static buf contents
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// locks shared ctx, if a panic poisoned it it's logged and cleared,
+/// ctx stays usable since it's state is only changed through it's own methods
+pub(crate) fn lock_ctx(ctx: &Mutex<RenderCtx>) -> MutexGuard<'_, RenderCtx> {
+    ctx.lock().unwrap_or_else(|e| {
+        err!("render ctx was poisoned by a panic while it was locked");
+        ctx.clear_poison();
+        e.into_inner()
+    })
 }
 
 #[derive(Debug)]
@@ -100,8 +206,6 @@ pub struct BufferImageCopy {
 
 impl RenderCtx {
     pub fn new(window: &Window) -> Self {
-        let surface_loader = ash::khr::surface::Instance::new(entry(), instance());
-        let surface_caps2 = ash::khr::get_surface_capabilities2::Instance::new(entry(), instance());
         let surface = unsafe {
             ash_window::create_surface(
                 entry(),
@@ -113,14 +217,47 @@ impl RenderCtx {
             .expect("failed to create surface")
         };
         debug_name("surface", surface);
+        Self::with_surface(surface)
+    }
+
+    /// surface isn't destroyed with ctx, so it's reused when ctx is remade on new device
+    fn with_surface(surface: vk::SurfaceKHR) -> Self {
+        let surface_loader = ash::khr::surface::Instance::new(entry(), instance());
+        let surface_caps2 = ash::khr::get_surface_capabilities2::Instance::new(entry(), instance());
         let surface_formats = unsafe {
             surface_loader
                 .get_physical_device_surface_formats(physical_gpu(), surface)
                 .expect("failed to get surface formats")
         };
-        let surface_format = surface_formats
-            .iter()
-            .find(|&format| format.format == vk::Format::B8G8R8A8_UNORM)
+        let hdr_format = vulkan_config()
+            .hdr
+            .then(|| {
+                surface_formats.iter().find(|&format| {
+                    format.format == vk::Format::R16G16B16A16_SFLOAT
+                        && format.color_space == vk::ColorSpaceKHR::EXTENDED_SRGB_NONLINEAR_EXT
+                })
+            })
+            .flatten();
+        if vulkan_config().hdr && hdr_format.is_none() {
+            warn!("hdr surface format not supported, using sdr");
+        }
+        let srgb_format = (vulkan_config().linear && hdr_format.is_none())
+            .then(|| {
+                surface_formats
+                    .iter()
+                    .find(|&format| format.format == vk::Format::B8G8R8A8_SRGB)
+            })
+            .flatten();
+        if vulkan_config().linear && hdr_format.is_none() && srgb_format.is_none() {
+            warn!("srgb surface format not supported, blending in non-linear space");
+        }
+        let surface_format = hdr_format
+            .or(srgb_format)
+            .or_else(|| {
+                surface_formats
+                    .iter()
+                    .find(|&format| format.format == vk::Format::B8G8R8A8_UNORM)
+            })
             .cloned()
             .unwrap_or(vk::SurfaceFormatKHR {
                 format: vk::Format::B8G8R8A8_UNORM,
@@ -140,25 +277,52 @@ impl RenderCtx {
             pipeline_layout_manager: PipelineLayoutManager::default(),
             sampler_manager: SamplerManager::default(),
             cmd_manager: CmdManager::new(),
+            barrier_tracker: BarrierTracker::default(),
+            lifetime_tracker: LifetimeTracker::default(),
             shaders: Default::default(),
             pipelines: Default::default(),
+            queued_pipelines: VecDeque::new(),
+            pipeline_progress: (0, 0),
             desc_sets: Default::default(),
             bufs: Default::default(),
+            static_bufs: Default::default(),
+            transient_head: 0,
+            pending_destroy: Vec::new(),
+            retired_bufs: Vec::new(),
+            errors: Vec::new(),
+            device_lost: false,
             fences: Default::default(),
             semaphores: Default::default(),
             imgs: Default::default(),
             img_views: Default::default(),
+            framebuffers: Default::default(),
             samplers: Default::default(),
             surface_caps2_loader: surface_caps2,
             surface,
             surface_format,
             surface_present_modes,
+            present_mode: vk::PresentModeKHR::FIFO,
             swapchain_loader,
             swapchain: Default::default(),
             swapchain_size: Default::default(),
             swapchain_img_idx: Default::default(),
+            swapchain_imgs: Vec::new(),
+            swapchain_stale: false,
             frame_cmd: Default::default(),
+            timestamps: unsafe {
+                gpu()
+                    .create_query_pool(
+                        &vk::QueryPoolCreateInfo::default()
+                            .query_type(vk::QueryType::TIMESTAMP)
+                            .query_count(2),
+                        alloc_callbacks(),
+                    )
+                    .unwrap()
+            },
+            gpu_frame_time: None,
+            draw_stats: DrawStats::default(),
         };
+        debug_name("frame timestamps", slf.timestamps);
         {
             slf.add_buf(
                 "staging",
@@ -166,8 +330,13 @@ impl RenderCtx {
                 BufUsage::DST | BufUsage::SRC,
                 MemProp::CPU,
             );
+            slf.add_buf(
+                "transient",
+                TRANSIENT_SIZE,
+                BufUsage::UNIFORM | BufUsage::STORAGE | BufUsage::VERT | BufUsage::INDEX,
+                MemProp::CPU,
+            );
             slf.add_semaphore("img available");
-            slf.add_semaphore("render finished");
             slf.add_sampler(
                 "linear",
                 vk::SamplerAddressMode::REPEAT,
@@ -190,36 +359,143 @@ impl RenderCtx {
 
     pub(crate) fn wait_prev_frame(&mut self) {
         if !self.frame_cmd.is_null() {
-            self.cmd_manager.wait(self.frame_cmd);
+            self.wait_cmd(self.frame_cmd);
+            let mut ticks = [0u64; 2];
+            let read = unsafe {
+                gpu().get_query_pool_results(
+                    self.timestamps,
+                    0,
+                    &mut ticks,
+                    vk::QueryResultFlags::TYPE_64,
+                )
+            };
+            self.gpu_frame_time = read.ok().map(|_| {
+                ticks[1].wrapping_sub(ticks[0]) as f32 * gpu_limits().timestamp_period * 1e-9
+            });
+        }
+        // prev frame is done reading transient data
+        self.transient_head = 0;
+        for name in std::mem::take(&mut self.pending_destroy) {
+            self.destroy(name.as_str());
         }
+        for buf in std::mem::take(&mut self.retired_bufs) {
+            self.gpu_alloc.dealloc_buf(buf);
+        }
+    }
+
+    /// seconds gpu spent on last finished frame, none if it wasn't measured
+    pub fn gpu_frame_time(&self) -> Option<f32> {
+        self.gpu_frame_time
     }
 
-    // might cause a swapchain resize so returns new size
-    pub(crate) fn begin_frame(&mut self) -> vk::Extent2D {
+    /// counts since current frame began, complete once frame ends
+    pub fn draw_stats(&self) -> DrawStats {
+        self.draw_stats
+    }
+
+    /// named bufs, imgs and pipelines with sizes, sorted by name, for crash dumps
+    pub(crate) fn resource_report(&self) -> String {
+        use std::fmt::Write;
+        let mut report = String::new();
+        let bufs = self.bufs.read();
+        let mut bufs: Vec<_> = bufs.iter().collect();
+        bufs.sort_by_key(|&(name, _)| name);
+        writeln!(report, "bufs ({}):", bufs.len()).unwrap();
+        for (name, &buf) in bufs {
+            let size = Mem::b(self.gpu_alloc.buf_size(buf) as usize);
+            writeln!(report, "  {name}: {size}").unwrap();
+        }
+        let imgs = self.imgs.read();
+        let mut imgs: Vec<_> = imgs.iter().collect();
+        imgs.sort_by_key(|&(name, _)| name);
+        writeln!(report, "imgs ({}):", imgs.len()).unwrap();
+        for (name, img) in imgs {
+            let i = &img.info;
+            writeln!(
+                report,
+                "  {name}: {}x{}x{} {:?}, {} layers, {} levels, {}x msaa",
+                i.width, i.height, i.depth, i.format, i.layers, i.levels, i.samples
+            )
+            .unwrap();
+        }
+        let pipelines = self.pipelines.read();
+        let mut pipelines: Vec<_> = pipelines.iter().collect();
+        pipelines.sort_by_key(|&(name, _)| name);
+        writeln!(report, "pipelines ({}):", pipelines.len()).unwrap();
+        for (name, p) in pipelines {
+            writeln!(report, "  {name}: {} ({:?})", p.shader_name, p.bind_point).unwrap();
+        }
+        let bound = &self.cmd_info.pipeline_data;
+        if !bound.pipeline.is_null() {
+            writeln!(report, "bound pipeline shader: {}", bound.shader_name).unwrap();
+        }
+        let mem = self.gpu_alloc.stats();
+        let used = Mem::b(mem.used() as usize);
+        let capacity = Mem::b(mem.capacity() as usize);
+        writeln!(report, "gpu mem: {used} / {capacity}").unwrap();
+        report
+    }
+
+    /// validates img barriers/accesses recorded through RenderCtx and warns about hazards,
+    /// transitions are logged to debug.log
+    pub fn set_sync_validation(&mut self, enabled: bool) {
+        self.barrier_tracker.enabled = enabled;
+    }
+
+    /// warns by name when a buf/img/img view is destroyed while a pending cmd uses it,
+    /// or a desc set referencing it is bound before being rewritten
+    pub fn set_lifetime_validation(&mut self, enabled: bool) {
+        self.lifetime_tracker.enabled = enabled;
+    }
+
+    // might cause a swapchain resize so returns new size,
+    // none if no img could be acquired and frame should be skipped
+    pub(crate) fn begin_frame(&mut self) -> Option<vk::Extent2D> {
         self.cmd_info = Default::default();
         self.cmd_manager.reset();
-        let swapchain_size = self.acquire_img(self.semaphore("img available"));
+        self.lifetime_tracker.finish_all();
+        self.frame_cmd = vk::CommandBuffer::null();
+        self.draw_stats = DrawStats::default();
+        let swapchain_size = self.acquire_img(self.semaphore("img available"))?;
         self.frame_cmd = self.begin_cmd();
-        swapchain_size
+        unsafe {
+            gpu().cmd_reset_query_pool(self.frame_cmd, self.timestamps, 0, 2);
+            gpu().cmd_write_timestamp2(
+                self.frame_cmd,
+                vk::PipelineStageFlags2::TOP_OF_PIPE,
+                self.timestamps,
+                0,
+            );
+        }
+        Some(swapchain_size)
     }
 
     // might cause swapchain resize so returns new optimal size
     pub(crate) fn end_frame(&mut self, window: &Window) -> vk::Extent2D {
+        unsafe {
+            gpu().cmd_write_timestamp2(
+                self.cmd(),
+                vk::PipelineStageFlags2::BOTTOM_OF_PIPE,
+                self.timestamps,
+                1,
+            );
+        }
         let cmd = self.cmd_manager.end();
+        let render_finished = self.swapchain_imgs[self.swapchain_img_idx].render_finished;
         self.submit_cmd(
             cmd,
             &[self.semaphore("img available")],
-            &[self.semaphore("render finished")],
+            &[render_finished],
             &[vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT],
         );
 
         window.pre_present_notify();
-        self.present(&[self.semaphore("render finished")])
+        self.present(&[render_finished])
     }
 
     pub fn begin_render_swapchain(&mut self, resolve_img_view_name: &str) {
         self.set_img_layout(
-            &self.cur_img(),
+            self.cur_img(),
             ImgLayout::COLOR,
             vk::PipelineStageFlags2::TOP_OF_PIPE,
             vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
@@ -229,13 +505,13 @@ impl RenderCtx {
         let width = self.swapchain_size.width;
         let height = self.swapchain_size.height;
         let img_view = self.cur_img_view();
-        self.begin_render(width, height, &img_view, resolve_img_view_name);
+        self.begin_render(width, height, img_view, resolve_img_view_name);
     }
 
     pub fn end_render_swapchain(&mut self) {
         self.end_render();
         self.set_img_layout(
-            &self.cur_img(),
+            self.cur_img(),
             ImgLayout::PRESENT,
             vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
             vk::PipelineStageFlags2::BOTTOM_OF_PIPE,
@@ -244,12 +520,128 @@ impl RenderCtx {
         );
     }
 
+    /// destroys every resource named `name` (pipeline, desc set, shader, buf, img, img view,
+    /// sampler) after gpu finishes current frame, so it's safe to call while they are in use
+    pub fn destroy_after_frame(&mut self, name: &str) {
+        if !self.pending_destroy.iter().any(|n| n == name) {
+            self.pending_destroy.push(name.to_string());
+        }
+    }
+
+    /// destroys every resource named `name` immediately, they must not be in use by gpu
+    pub fn destroy(&mut self, name: &str) {
+        self.warn_ds_refs(name);
+        let mut found = false;
+        if self.pipelines.read().contains(name) {
+            self.remove_pipeline(name);
+            found = true;
+        }
+        if let Some(i) = self.queued_pipelines.iter().position(|p| p.name == name) {
+            self.queued_pipelines.remove(i);
+            self.pipeline_progress.1 -= 1;
+            found = true;
+        }
+        if self.desc_sets.contains_key(name) {
+            self.remove_desc_set(name);
+            found = true;
+        }
+        if self.shaders.contains_key(name) {
+            self.remove_shader(name);
+            found = true;
+        }
+        if self.bufs.read().contains(name) {
+            self.remove_buf(name);
+            found = true;
+        }
+        if NameId::find(name).is_some_and(|n| self.img_views.contains_key(&n)) {
+            self.remove_img_view(name);
+            found = true;
+        }
+        found |= self.try_remove_img(name);
+        if self.samplers.contains_key(name) {
+            self.remove_sampler(name);
+            found = true;
+        }
+        if !found {
+            warn!("nothing to destroy named: {name}");
+        }
+    }
+
+    /// desc sets keep raw handles, so destroyed bufs/img views they reference dangle
+    fn warn_ds_refs(&self, name: &str) {
+        let imgs = self.imgs.read();
+        let views = imgs
+            .get(name)
+            .map(|img| img.views.as_slice())
+            .unwrap_or_default();
+        for (ds_name, ds) in &self.desc_sets {
+            if ds_name == name {
+                continue;
+            }
+            let refs_img = ds
+                .imgs
+                .iter()
+                .any(|(_, view, _)| view == name || views.contains(view));
+            let refs_buf = ds.bufs.iter().any(|(_, buf, _)| buf == name);
+            if refs_img || refs_buf {
+                warn!("destroying {name} that is still referenced by desc set {ds_name}");
+            }
+        }
+    }
+
+    /// lets lifetime tracker report buf/img/img view destroyed while still in use
+    fn track_destroy(&mut self, name: &str) {
+        if !self.lifetime_tracker.enabled {
+            return;
+        }
+        let views = self
+            .imgs
+            .read()
+            .get(name)
+            .map(|img| img.views.clone())
+            .unwrap_or_default();
+        let mut ds_refs = Vec::new();
+        for (ds_name, ds) in &self.desc_sets {
+            let imgs = ds
+                .imgs
+                .iter()
+                .filter(|(_, view, _)| view == name || views.contains(view))
+                .map(|(binding, ..)| *binding);
+            let bufs = ds
+                .bufs
+                .iter()
+                .filter(|(_, buf, _)| buf == name)
+                .map(|(binding, ..)| *binding);
+            ds_refs.extend(imgs.chain(bufs).map(|b| (ds_name.clone(), b)));
+        }
+        self.lifetime_tracker.destroyed(name, &views, ds_refs);
+    }
+
+    /// lets lifetime tracker know recording cmd uses `name`
+    fn track_use(&mut self, name: &str) {
+        if self.lifetime_tracker.enabled {
+            let cmd = self.cmd();
+            self.lifetime_tracker.used(cmd, name);
+        }
+    }
+
+    fn track_use_buf(&mut self, id: BufId) {
+        if self.lifetime_tracker.enabled {
+            let cmd = self.cmd();
+            let name = self.bufs.read().name(id).unwrap_or_default();
+            self.lifetime_tracker.used(cmd, &name);
+        }
+    }
+
     pub fn shader(&self, name: &str) -> &Shader {
-        &self
-            .shaders
+        self.try_shader(name).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_shader(&self, name: &str) -> Result<&Shader> {
+        self.shaders
             .get(name)
-            .unwrap_or_else(|| panic!("shader not found: {name}"))
-            .shader
+            .map(|s| &s.shader)
+            .ok_or_else(|| Error::not_found("shader", name))
     }
 
     pub fn add_shader(&mut self, name: &str) -> &Shader {
@@ -273,6 +665,22 @@ impl RenderCtx {
             .shader
     }
 
+    /// desc sets and pipelines made from shader should be removed first
+    pub fn remove_shader(&mut self, name: &str) {
+        let ShaderData {
+            pipeline_stages, ..
+        } = self
+            .shaders
+            .remove(name)
+            .unwrap_or_else(|| panic!("shader not found: {name}"));
+        let mut modules: Vec<vk::ShaderModule> =
+            pipeline_stages.iter().map(|ps| ps.module).collect();
+        modules.dedup();
+        for module in modules {
+            unsafe { gpu().destroy_shader_module(module, alloc_callbacks()) }
+        }
+    }
+
     pub fn add_fence(&mut self, name: &str, signaled: bool) -> vk::Fence {
         self.fences
             .entry(name.to_string())
@@ -380,33 +788,58 @@ impl RenderCtx {
         info: &ImageInfo,
         mem_props: vk::MemoryPropertyFlags,
     ) -> vk::Image {
-        self.imgs
-            .entry(name.to_string())
-            .or_insert_with(|| {
-                let img = self.gpu_alloc.alloc_img(info, mem_props);
-                debug_name(name, img);
-                ImageData {
-                    img,
-                    views: vec![],
-                    info: info.clone(),
-                }
-            })
-            .img
+        self.try_add_img(name, info, mem_props)
+            .unwrap_or_else(|e| panic!("failed to add img({name}): {e}"))
+    }
+
+    /// add_img() returning id for lookups without hashing name
+    pub fn add_img_h(
+        &mut self,
+        name: &str,
+        info: &ImageInfo,
+        mem_props: vk::MemoryPropertyFlags,
+    ) -> ImgId {
+        self.add_img(name, info, mem_props);
+        self.img_id(name)
+    }
+
+    pub fn try_add_img(
+        &mut self,
+        name: &str,
+        info: &ImageInfo,
+        mem_props: vk::MemoryPropertyFlags,
+    ) -> Result<vk::Image> {
+        if let Some(data) = self.imgs.read().get(name) {
+            return Ok(data.img);
+        }
+        let img = self.gpu_alloc.try_alloc_img(info, mem_props)?;
+        debug_name(name, img);
+        let data = ImageData {
+            img,
+            views: vec![],
+            info: info.clone(),
+        };
+        self.imgs.write().insert(name, data);
+        Ok(img)
     }
 
     pub fn try_remove_img(&mut self, name: &str) -> bool {
+        self.track_destroy(name);
+        let removed = self.imgs.write().remove(name);
         if let Some(ImageData {
             img,
             views,
             info: _,
-        }) = self.imgs.remove(name)
+        }) = removed
         {
+            self.barrier_tracker.forget(name);
             self.gpu_alloc.dealloc_img(img);
             for img_view in views {
                 let (img_view, _) = self
                     .img_views
-                    .remove(&img_view)
+                    .remove(&NameId::new(&img_view))
                     .unwrap_or_else(|| panic!("img view({img_view}) not found, for img({name})"));
+                self.destroy_framebuffers(img_view);
                 unsafe {
                     gpu().destroy_image_view(img_view, alloc_callbacks());
                 }
@@ -423,18 +856,48 @@ impl RenderCtx {
         }
     }
 
-    pub fn img(&self, name: &str) -> &ImageData {
+    /// shared handle to imgs, for lookups without locking render ctx
+    pub fn images(&self) -> Images {
+        self.imgs.clone()
+    }
+
+    /// holds imgs read lock, drop before adding or removing imgs
+    pub fn img(&self, name: impl AsName) -> MappedRwLockReadGuard<'_, ImageData> {
+        self.try_img(name).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_img(&self, name: impl AsName) -> Result<MappedRwLockReadGuard<'_, ImageData>> {
+        RwLockReadGuard::filter_map(self.imgs.read(), |imgs| imgs.get(&name))
+            .map_err(|_| Error::not_found("img", name.name()))
+    }
+
+    /// id stays valid until img is removed, see [`Id`]
+    pub fn img_id(&self, name: impl AsName) -> ImgId {
         self.imgs
-            .get(name)
-            .unwrap_or_else(|| panic!("img not found: {name}"))
+            .read()
+            .id(&name)
+            .unwrap_or_else(|| panic!("img not found: {}", name.name()))
+    }
+
+    /// img and it's current layout, copied out so imgs lock isn't held
+    fn img_layout(&self, name: impl AsName) -> (vk::Image, vk::ImageLayout) {
+        let data = self.img(name);
+        (data.img, data.info.layout)
+    }
+
+    pub fn img_by_id(&self, id: ImgId) -> MappedRwLockReadGuard<'_, ImageData> {
+        RwLockReadGuard::map(self.imgs.read(), |imgs| {
+            imgs.at(id)
+                .unwrap_or_else(|| panic!("img was removed: {id:?}"))
+        })
     }
 
     pub fn add_img_view(&mut self, name: &str, img_name: &str) -> vk::ImageView {
         self.img_views
-            .entry(name.to_string())
+            .entry(NameId::new(name))
             .or_insert_with(|| {
-                let ImageData { img, views, info } = self
-                    .imgs
+                let mut imgs = self.imgs.write();
+                let ImageData { img, views, info } = imgs
                     .get_mut(img_name)
                     .unwrap_or_else(|| panic!("img not found: {img_name}"));
                 views.push(name.to_string());
@@ -462,33 +925,50 @@ impl RenderCtx {
                         .unwrap_or_else(|_| panic!("failed to create img view: {name}"))
                 };
                 debug_name(name, img_view);
-                (img_view, img_name.to_string())
+                (img_view, NameId::new(img_name))
             })
             .0
     }
 
     pub fn remove_img_view(&mut self, name: &str) {
-        let (img_view, img_name) = self.img_views.remove(name).unwrap();
-        let img_views = &mut self.imgs.get_mut(&img_name).unwrap().views;
+        self.track_destroy(name);
+        let (img_view, img_name) = self.img_views.remove(&name.intern()).unwrap();
+        let mut imgs = self.imgs.write();
+        let img_views = &mut imgs.get_mut(&img_name).unwrap().views;
         img_views.remove(
             img_views
                 .iter()
                 .position(|s| s.as_str() == name)
                 .unwrap_or_else(|| panic!("img view({name}) not found for img({img_name})")),
         );
+        drop(imgs);
+        self.destroy_framebuffers(img_view);
         unsafe {
             gpu().destroy_image_view(img_view, alloc_callbacks());
         }
     }
 
-    pub fn img_view(&self, name: &str) -> vk::ImageView {
-        if name.is_empty() {
-            return vk::ImageView::null();
+    /// name of img the img view was made from
+    pub fn img_view_img(&self, name: impl AsName) -> NameId {
+        name.find()
+            .and_then(|n| self.img_views.get(&n))
+            .unwrap_or_else(|| panic!("img view not found: {}", name.name()))
+            .1
+            .clone()
+    }
+
+    pub fn img_view(&self, name: impl AsName) -> vk::ImageView {
+        self.try_img_view(name).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_img_view(&self, name: impl AsName) -> Result<vk::ImageView> {
+        if name.name().is_empty() {
+            return Ok(vk::ImageView::null());
         }
-        self.img_views
-            .get(name)
-            .unwrap_or_else(|| panic!("img view not found: {name}"))
-            .0
+        name.find()
+            .and_then(|n| self.img_views.get(&n))
+            .map(|v| v.0)
+            .ok_or_else(|| Error::not_found("img view", name.name()))
     }
 
     pub fn add_sampler(
@@ -520,10 +1000,48 @@ impl RenderCtx {
     }
 
     pub fn sampler(&self, name: &str) -> vk::Sampler {
-        *self
-            .samplers
+        self.try_sampler(name).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_sampler(&self, name: &str) -> Result<vk::Sampler> {
+        self.samplers
             .get(name)
-            .unwrap_or_else(|| panic!("sampler not found: {name}"))
+            .copied()
+            .ok_or_else(|| Error::not_found("sampler", name))
+    }
+
+    /// shared handle to built pipelines, for lookups without locking render ctx
+    pub fn pipelines(&self) -> Pipelines {
+        self.pipelines.clone()
+    }
+
+    pub fn pipeline(&self, name: &str) -> vk::Pipeline {
+        self.try_pipeline(name).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_pipeline(&self, name: &str) -> Result<vk::Pipeline> {
+        self.pipelines
+            .read()
+            .get(name)
+            .map(|p| p.pipeline)
+            .ok_or_else(|| Error::not_found("pipeline", name))
+    }
+
+    /// builds pipeline first if it's queued
+    pub fn pipeline_id(&mut self, name: &str) -> PipelineId {
+        self.build_if_queued(name);
+        self.pipelines
+            .read()
+            .id(name)
+            .unwrap_or_else(|| panic!("pipeline not found: {name}"))
+    }
+
+    pub fn pipeline_by_id(&self, id: PipelineId) -> vk::Pipeline {
+        self.pipelines
+            .read()
+            .at(id)
+            .unwrap_or_else(|| panic!("pipeline was removed: {id:?}"))
+            .pipeline
     }
 
     pub fn add_pipeline(
@@ -533,27 +1051,144 @@ impl RenderCtx {
         pipeline_info: GraphicsPipelineInfo,
         vert_input_bindings: &[(bool, Vec<u32>)],
     ) -> vk::Pipeline {
-        self.pipelines
-            .entry(name.to_string())
-            .or_insert_with(|| {
-                let shader_data = &self
-                    .shaders
-                    .get(shader_name)
-                    .unwrap_or_else(|| panic!("no shader found: {shader_name}"));
-                let pipeline_info = pipeline_info
-                    .layout(shader_data.pipeline_layout)
-                    .stages(&shader_data.pipeline_stages)
-                    .vert_layout(&shader_data.shader, vert_input_bindings);
-                let pipeline = pipeline_info.build();
-                debug_name(name, pipeline);
-                PipelineData {
-                    pipeline,
-                    info: pipeline_info,
-                    bind_point: vk::PipelineBindPoint::GRAPHICS,
-                    shader_name: shader_name.to_string(),
-                }
-            })
-            .pipeline
+        if let Some(data) = self.pipelines.read().get(name) {
+            return data.pipeline;
+        }
+        let shader_data = &self
+            .shaders
+            .get(shader_name)
+            .unwrap_or_else(|| panic!("no shader found: {shader_name}"));
+        let pipeline_info = pipeline_info
+            .layout(shader_data.pipeline_layout)
+            .stages(&shader_data.pipeline_stages)
+            .vert_layout(&shader_data.shader, vert_input_bindings);
+        let pipeline = pipeline_info.build();
+        debug_name(name, pipeline);
+        self.pipelines.write().insert(
+            name,
+            PipelineData {
+                pipeline,
+                info: pipeline_info,
+                bind_point: vk::PipelineBindPoint::GRAPHICS,
+                shader_name: shader_name.to_string(),
+            },
+        );
+        pipeline
+    }
+
+    /// add_pipeline() returning id for binding without hashing name
+    pub fn add_pipeline_h(
+        &mut self,
+        name: &str,
+        shader_name: &str,
+        pipeline_info: GraphicsPipelineInfo,
+        vert_input_bindings: &[(bool, Vec<u32>)],
+    ) -> PipelineId {
+        self.add_pipeline(name, shader_name, pipeline_info, vert_input_bindings);
+        self.pipeline_id(name)
+    }
+
+    /// like add_pipeline() but pipeline is built later by build_queued_pipelines(),
+    /// so heavy startup work can be spread over frames while app shows loading screen.
+    /// binding it before that builds it immediately
+    pub fn queue_pipeline(
+        &mut self,
+        name: &str,
+        shader_name: &str,
+        pipeline_info: GraphicsPipelineInfo,
+        vert_input_bindings: &[(bool, Vec<u32>)],
+    ) {
+        if self.pipelines.read().contains(name)
+            || self.queued_pipelines.iter().any(|p| p.name == name)
+        {
+            return;
+        }
+        if self.queued_pipelines.is_empty() {
+            self.pipeline_progress = (0, 0);
+        }
+        self.pipeline_progress.1 += 1;
+        self.queued_pipelines.push_back(QueuedPipeline {
+            name: name.to_string(),
+            shader_name: shader_name.to_string(),
+            info: pipeline_info,
+            vert_input_bindings: vert_input_bindings.to_vec(),
+        });
+    }
+
+    /// builds queued pipelines until `budget` runs out (at least one), returns how many were built
+    pub fn build_queued_pipelines(&mut self, budget: Duration) -> usize {
+        let start = Instant::now();
+        let mut built = 0;
+        while let Some(queued) = self.queued_pipelines.pop_front() {
+            self.build_queued(queued);
+            built += 1;
+            if start.elapsed() >= budget {
+                break;
+            }
+        }
+        built
+    }
+
+    fn build_queued(&mut self, queued: QueuedPipeline) {
+        self.add_pipeline(
+            &queued.name,
+            &queued.shader_name,
+            queued.info,
+            &queued.vert_input_bindings,
+        );
+        self.pipeline_progress.0 += 1;
+    }
+
+    fn build_if_queued(&mut self, name: &str) {
+        if let Some(i) = self.queued_pipelines.iter().position(|p| p.name == name) {
+            let queued = self.queued_pipelines.remove(i).unwrap();
+            self.build_queued(queued);
+        }
+    }
+
+    /// (built, total) of pipelines queued since queue was last empty
+    pub fn pipeline_progress(&self) -> (usize, usize) {
+        self.pipeline_progress
+    }
+
+    pub fn pipelines_pending(&self) -> bool {
+        !self.queued_pipelines.is_empty()
+    }
+
+    pub fn remove_pipeline(&mut self, name: &str) {
+        let data = self
+            .pipelines
+            .write()
+            .remove(name)
+            .unwrap_or_else(|| panic!("pipeline not found: {name}"));
+        unsafe { gpu().destroy_pipeline(data.pipeline, alloc_callbacks()) }
+        // new pipeline can have same handle as destroyed one, so don't skip binding it
+        self.cmd_info.pipeline_data = Default::default();
+    }
+
+    /// rebuilds graphics pipeline with new msaa sample count
+    pub fn set_pipeline_samples(&mut self, name: &str, samples: u32) {
+        if let Some(queued) = self.queued_pipelines.iter_mut().find(|p| p.name == name) {
+            queued.info = queued.info.clone().samples(samples);
+            return;
+        }
+        let mut pipelines = self.pipelines.write();
+        let data = pipelines
+            .get_mut(name)
+            .unwrap_or_else(|| panic!("pipeline not found: {name}"));
+        if data.info.rasterization_samples == samples_u32_to_vk(samples) {
+            return;
+        }
+        queue_idle();
+        data.info = data.info.clone().samples(samples);
+        unsafe {
+            gpu().destroy_pipeline(data.pipeline, alloc_callbacks());
+        }
+        data.pipeline = data.info.build();
+        debug_name(name, data.pipeline);
+        drop(pipelines);
+        // new pipeline can have same handle as destroyed one, so don't skip binding it
+        self.cmd_info.pipeline_data = Default::default();
     }
 
     pub fn add_compute(&mut self, name: &str) -> vk::Pipeline {
@@ -561,20 +1196,21 @@ impl RenderCtx {
         let shader = &self.shaders[name];
         let module = shader.pipeline_stages[0].module;
         let layout = shader.pipeline_layout;
-        let entry_name = &shader.pipeline_stages[0].name;
-        self.pipelines
-            .entry(name.to_string())
-            .or_insert_with(|| {
-                let pipeline = create_compute(module, layout, entry_name);
-                debug_name(name, pipeline);
-                PipelineData {
-                    pipeline,
-                    info: GraphicsPipelineInfo::default().layout(layout),
-                    bind_point: vk::PipelineBindPoint::COMPUTE,
-                    shader_name: name.to_string(),
-                }
-            })
-            .pipeline
+        if let Some(data) = self.pipelines.read().get(name) {
+            return data.pipeline;
+        }
+        let pipeline = create_compute(module, layout, &shader.pipeline_stages[0].name);
+        debug_name(name, pipeline);
+        self.pipelines.write().insert(
+            name,
+            PipelineData {
+                pipeline,
+                info: GraphicsPipelineInfo::default().layout(layout),
+                bind_point: vk::PipelineBindPoint::COMPUTE,
+                shader_name: name.to_string(),
+            },
+        );
+        pipeline
     }
 
     /// note: x,y,z are total size, not work group size
@@ -582,6 +1218,7 @@ impl RenderCtx {
         let [wx, wy, wz] = self
             .shader(&self.cmd_info.pipeline_data.shader_name)
             .workgroup_size();
+        self.draw_stats.dispatches += 1;
         unsafe { gpu().cmd_dispatch(self.cmd(), x.div_ceil(wx), y.div_ceil(wy), z.div_ceil(wz)) };
     }
 
@@ -604,19 +1241,39 @@ impl RenderCtx {
                 let dsl = self.dsl_manager.get(&binds);
                 let desc_set = self.desc_alloc.alloc_one(dsl);
                 debug_name(name, desc_set);
-                DescSetData { desc_set, binds }
+                DescSetData {
+                    desc_set,
+                    binds,
+                    imgs: Vec::new(),
+                    bufs: Vec::new(),
+                    samplers: Vec::new(),
+                }
             })
             .desc_set
     }
 
+    pub fn remove_desc_set(&mut self, name: &str) {
+        let DescSetData { desc_set, .. } = self
+            .desc_sets
+            .remove(name)
+            .unwrap_or_else(|| panic!("descriptor set not found: {name}"));
+        self.lifetime_tracker.forget_ds(name);
+        self.desc_alloc.free(&[desc_set]);
+    }
+
     pub fn desc_set(&self, name: &str) -> vk::DescriptorSet {
+        self.try_desc_set(name).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_desc_set(&self, name: &str) -> Result<vk::DescriptorSet> {
         self.desc_sets
             .get(name)
-            .unwrap_or_else(|| panic!("descriptor set not found: {name}"))
-            .desc_set
+            .map(|ds| ds.desc_set)
+            .ok_or_else(|| Error::not_found("descriptor set", name))
     }
 
-    /// if exists with smaller size, grows buf (which invalidates old bufs)
+    /// if exists with smaller size, grows buf (which invalidates old bufs),
+    /// old buf is freed once frame in flight finishes
     pub fn add_buf(
         &mut self,
         name: &str,
@@ -624,44 +1281,196 @@ impl RenderCtx {
         usage: vk::BufferUsageFlags,
         mem_props: vk::MemoryPropertyFlags,
     ) -> vk::Buffer {
-        if let Some(buf) = self.bufs.get(name) {
-            if self.buf_size(name) < size {
-                self.gpu_alloc.dealloc_buf(*buf);
-                let new_buf = self.gpu_alloc.alloc_buf(size, usage, mem_props);
-                let buf_mut = &mut unsafe { *std::ptr::from_ref(buf).cast_mut() };
-                *buf_mut = new_buf;
+        self.assert_not_static(name);
+        let old_buf = self.bufs.read().get(name).copied();
+        if let Some(buf) = old_buf {
+            if self.buf_size(name) >= size {
+                return buf;
             }
-            *buf
+            self.retired_bufs.push(buf);
+            let new_buf = self.gpu_alloc.alloc_buf(size, usage, mem_props);
+            debug_name(name, new_buf);
+            *self.bufs.write().get_mut(name).unwrap() = new_buf;
+            new_buf
         } else {
             let buf = self.gpu_alloc.alloc_buf(size, usage, mem_props);
             debug_name(name, buf);
-            self.bufs.insert(name.to_string(), buf);
+            self.bufs.write().insert(name, buf);
             buf
         }
     }
 
+    /// add_buf() returning id for lookups without hashing name
+    pub fn add_buf_h(
+        &mut self,
+        name: &str,
+        size: u64,
+        usage: vk::BufferUsageFlags,
+        mem_props: vk::MemoryPropertyFlags,
+    ) -> BufId {
+        self.add_buf(name, size, usage, mem_props);
+        self.buf_id(name)
+    }
+
+    /// gpu only buf that is uploaded once and can't be written to after,
+    /// contents are read back and verified with checksum in debug
+    pub fn create_static_buf<T: ?Sized>(
+        &mut self,
+        name: &str,
+        data: &T,
+        usage: vk::BufferUsageFlags,
+    ) -> vk::Buffer {
+        assert!(
+            !self.bufs.read().contains(name),
+            "buffer already exists: {name}"
+        );
+        let size = size_of_val(data) as vk::DeviceSize;
+        assert_ne!(size, 0, "static buffer can't be empty: {name}");
+        let buf = self.add_buf(
+            name,
+            size,
+            usage | BufUsage::DST | BufUsage::SRC,
+            MemProp::GPU,
+        );
+        self.write_buf(name, data);
+        self.static_bufs.insert(
+            name.to_string(),
+            (checksum(crate::util::cast_slice(data)), size),
+        );
+        #[cfg(debug_assertions)]
+        assert!(
+            self.verify_static_buf(name),
+            "static buffer upload corrupted: {name}"
+        );
+        buf
+    }
+
+    /// reads back static buf and compares it's checksum with uploaded data's
+    pub fn verify_static_buf(&mut self, name: &str) -> bool {
+        let (expected, size) = *self
+            .static_bufs
+            .get(name)
+            .unwrap_or_else(|| panic!("static buffer not found: {name}"));
+        let mut data = vec![0u8; size as usize];
+        self.read_buf(name, &mut data[..]);
+        checksum(&data) == expected
+    }
+
+    pub fn is_static_buf(&self, name: &str) -> bool {
+        self.static_bufs.contains_key(name)
+    }
+
+    fn assert_not_static(&self, name: &str) {
+        assert!(
+            !self.static_bufs.contains_key(name),
+            "static buffer can't be written to: {name}"
+        );
+    }
+
     pub fn remove_buf(&mut self, name: &str) {
-        let buf = self.bufs.remove(name).unwrap();
+        self.track_destroy(name);
+        self.static_bufs.remove(name);
+        let buf = self.bufs.write().remove(name).unwrap();
         self.gpu_alloc.dealloc_buf(buf);
     }
 
+    /// doesn't copy contents, old buf is freed once frame in flight finishes
     pub fn recreate_buf(&mut self, name: &str, size: u64) -> vk::Buffer {
-        let buffer = self.bufs.get_mut(name).unwrap();
-        *buffer = self.gpu_alloc.realloc_buf(*buffer, size);
-        debug_name(name, *buffer);
-        *buffer
+        self.assert_not_static(name);
+        let old = self.buf(name);
+        let (usage, props) = (self.gpu_alloc.buf_usage(old), self.gpu_alloc.buf_props(old));
+        self.retired_bufs.push(old);
+        let buffer = self.gpu_alloc.alloc_buf(size, usage, props);
+        debug_name(name, buffer);
+        *self.bufs.write().get_mut(name).unwrap() = buffer;
+        buffer
+    }
+
+    /// moves up to `max_moves` bufs to lower offsets so freed memory merges
+    /// into bigger blocks, patches buf names and desc sets to moved bufs.
+    /// waits for gpu to be idle, so it's meant for idle frames like loading screens.
+    /// returns moved buf count
+    pub fn defrag(&mut self, max_moves: usize) -> usize {
+        queue_idle();
+        let old_bufs: Vec<vk::Buffer> = self.bufs.read().values().copied().collect();
+        let moves = self.gpu_alloc.relocate_bufs(&old_bufs, max_moves);
+        if moves.is_empty() {
+            return 0;
+        }
+        let cmd = self.begin_cmd();
+        for &(old, new) in &moves {
+            let copy_region = vk::BufferCopy::default().size(self.gpu_alloc.buf_size(old));
+            unsafe { gpu().cmd_copy_buffer(cmd, old, new, &[copy_region]) };
+        }
+        self.finish_cmd();
+        let mut moved_names = HashSet::new();
+        for &(old, new) in &moves {
+            self.gpu_alloc.dealloc_buf(old);
+            let mut bufs = self.bufs.write();
+            let (name, buf) = bufs.iter_mut().find(|(_, b)| **b == old).unwrap();
+            *buf = new;
+            debug_name(name, new);
+            moved_names.insert(name.to_string());
+        }
+        let ds_names: Vec<String> = self
+            .desc_sets
+            .iter()
+            .filter(|(_, ds)| ds.bufs.iter().any(|(_, buf, _)| moved_names.contains(buf)))
+            .map(|(ds_name, _)| ds_name.clone())
+            .collect();
+        for ds_name in ds_names {
+            let bufs = self.desc_sets[&ds_name].bufs.clone();
+            let writes: Vec<_> = bufs
+                .iter()
+                .filter(|(_, buf, _)| moved_names.contains(buf))
+                .map(|(bind, buf, rng)| (buf.as_str(), rng.clone(), *bind))
+                .collect();
+            self.write_ds_buf_ranges(&ds_name, &writes);
+        }
+        crate::log!(
+            "Defrag moved {} bufs\n{}",
+            moves.len(),
+            self.gpu_alloc.stats()
+        );
+        moves.len()
+    }
+
+    /// shared handle to bufs, for lookups without locking render ctx
+    pub fn buffers(&self) -> Buffers {
+        self.bufs.clone()
     }
 
     pub fn buf(&self, name: &str) -> vk::Buffer {
+        self.try_buf(name).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_buf(&self, name: &str) -> Result<vk::Buffer> {
         if name.is_empty() {
-            return vk::Buffer::null();
+            return Ok(vk::Buffer::null());
         }
-        *self
-            .bufs
+        self.bufs
+            .read()
             .get(name)
+            .copied()
+            .ok_or_else(|| Error::not_found("buffer", name))
+    }
+
+    /// id stays valid until buf is removed, even if it's grown or moved by defrag
+    pub fn buf_id(&self, name: &str) -> BufId {
+        self.bufs
+            .read()
+            .id(name)
             .unwrap_or_else(|| panic!("buffer not found: {name}"))
     }
 
+    pub fn buf_by_id(&self, id: BufId) -> vk::Buffer {
+        *self
+            .bufs
+            .read()
+            .at(id)
+            .unwrap_or_else(|| panic!("buffer was removed: {id:?}"))
+    }
+
     pub fn buf_size(&self, name: &str) -> u64 {
         self.gpu_alloc.buf_size(self.buf(name))
     }
@@ -689,34 +1498,366 @@ impl RenderCtx {
         signals: &[vk::Semaphore],
         wait_dst_stage_mask: &[vk::PipelineStageFlags],
     ) {
-        self.cmd_manager
+        if let Err(e) = self
+            .cmd_manager
             .submit(cmd, waits, signals, wait_dst_stage_mask)
+        {
+            // failed cmd isn't pending, so there is nothing to wait for
+            self.lifetime_tracker.finish(cmd);
+            if cmd == self.frame_cmd {
+                self.frame_cmd = vk::CommandBuffer::null();
+            }
+            self.report(Error::vk("failed to submit cmd", e));
+        }
     }
 
     pub fn wait_cmd(&mut self, cmd: vk::CommandBuffer) {
-        self.cmd_manager.wait(cmd);
+        if let Err(e) = self.cmd_manager.wait(cmd) {
+            self.report(Error::vk("failed to wait for cmd", e));
+        }
+        self.lifetime_tracker.finish(cmd);
+        self.barrier_tracker.sync_all();
     }
 
     pub fn finish_cmd(&mut self) {
         let cmd = self.cmd_manager.end();
-        self.cmd_manager.submit(cmd, &[], &[], &[]);
-        self.cmd_manager.wait(cmd);
+        match self.cmd_manager.submit(cmd, &[], &[], &[]) {
+            Ok(()) => self.wait_cmd(cmd),
+            Err(e) => self.report(Error::vk("failed to submit cmd", e)),
+        }
+    }
+
+    /// vulkan errors aren't panics, so app can recover (e.g. free memory, recreate device)
+    fn report(&mut self, e: Error) {
+        err!("{e}");
+        self.device_lost |= e == Error::DeviceLost;
+        self.errors.push(e);
+    }
+
+    /// gpu crashed or was reset, nothing can be rendered until `recreate_device` is called
+    pub fn device_lost(&self) -> bool {
+        self.device_lost
+    }
+
+    /// destroys everything made from lost device, then remakes device, swapchain and
+    /// named resources from their recorded infos. ids and shared registries stay valid,
+    /// but buf and img contents are lost so they must be reuploaded,
+    /// static bufs are remade as regular bufs so they can be rewritten.
+    /// surface may have been resized meanwhile, so returns new swapchain size
+    pub fn recreate_device(&mut self) -> vk::Extent2D {
+        scope_time!("recreate device");
+        self.lifetime_tracker.finish_all();
+        for name in std::mem::take(&mut self.pending_destroy) {
+            self.destroy(&name);
+        }
+        // swapchain imgs, views and semaphores are remade with swapchain
+        let swapchain_names: HashSet<NameId> = self
+            .swapchain_imgs
+            .iter()
+            .flat_map(|s| [s.img.clone(), s.view.clone()])
+            .collect();
+        let render_finished: Vec<vk::Semaphore> = self
+            .swapchain_imgs
+            .iter()
+            .map(|s| s.render_finished)
+            .collect();
+        let bufs: Vec<_> = self
+            .bufs
+            .read()
+            .iter()
+            .map(|(name, &buf)| {
+                let alloc = &self.gpu_alloc;
+                let (usage, props) = (alloc.buf_usage(buf), alloc.buf_props(buf));
+                (name.to_string(), alloc.buf_size(buf), usage, props)
+            })
+            .collect();
+        let imgs: Vec<_> = self
+            .imgs
+            .read()
+            .iter()
+            .filter(|&(name, _)| !swapchain_names.contains(&name.intern()))
+            .map(|(name, data)| {
+                let mut info = data.info.clone();
+                info.layout = ImgLayout::UNDEFINED;
+                (name.to_string(), info, self.gpu_alloc.img_props(data.img))
+            })
+            .collect();
+        let img_views: Vec<(NameId, NameId)> = self
+            .img_views
+            .iter()
+            .filter(|&(name, _)| !swapchain_names.contains(name))
+            .map(|(name, (_, img))| (name.clone(), img.clone()))
+            .collect();
+        let samplers: Vec<(String, SamplerInfo)> = self
+            .samplers
+            .iter()
+            .filter_map(|(name, &s)| Some((name.clone(), self.sampler_manager.info(s)?)))
+            .collect();
+        let shaders: Vec<String> = self.shaders.keys().cloned().collect();
+        let pipelines: Vec<(String, PipelineData)> = self
+            .pipelines
+            .read()
+            .iter()
+            .map(|(name, data)| (name.to_string(), data.clone()))
+            .collect();
+        let desc_sets: Vec<_> = std::mem::take(&mut self.desc_sets)
+            .into_iter()
+            .map(|(name, ds)| {
+                let samplers: Vec<(u32, SamplerInfo)> = ds
+                    .samplers
+                    .iter()
+                    .filter_map(|&(bind, s)| Some((bind, self.sampler_manager.info(s)?)))
+                    .collect();
+                (name, ds, samplers)
+            })
+            .collect();
+        let fences: Vec<String> = self.fences.keys().cloned().collect();
+        let semaphores: Vec<String> = self
+            .semaphores
+            .iter()
+            .filter(|&(_, s)| !render_finished.contains(s))
+            .map(|(name, _)| name.clone())
+            .collect();
+        let queued_pipelines = std::mem::take(&mut self.queued_pipelines);
+        let pipeline_progress = self.pipeline_progress;
+        let sync_validation = self.barrier_tracker.enabled;
+        let lifetime_validation = self.lifetime_tracker.enabled;
+        let swapchain_size = self.swapchain_size;
+        let (old_bufs, old_imgs, old_pipelines) =
+            (self.bufs.clone(), self.imgs.clone(), self.pipelines.clone());
+        // shader modules aren't destroyed with ctx
+        for name in &shaders {
+            self.remove_shader(name);
+        }
+
+        let surface = self.surface;
+        let ctx: *mut Self = self;
+        let rebuilt = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+            std::ptr::drop_in_place(ctx);
+            recreate_gpu();
+            Self::with_surface(surface)
+        }));
+        // ctx was dropped, so it can't be left as is if rebuilding it panicked
+        let rebuilt = rebuilt.unwrap_or_else(|_| std::process::abort());
+        unsafe { std::ptr::write(ctx, rebuilt) };
+
+        self.set_sync_validation(sync_validation);
+        self.set_lifetime_validation(lifetime_validation);
+        self.swapchain_size = swapchain_size;
+        self.swapchain_stale = true;
+        let swapchain_size = self.recreate_swapchain();
+        for (name, size, usage, props) in bufs {
+            self.add_buf(&name, size, usage, props);
+        }
+        for (name, info, props) in imgs {
+            self.add_img(&name, &info, props);
+        }
+        for (name, img) in img_views {
+            self.add_img_view(&name, &img);
+        }
+        for (name, info) in samplers {
+            let sampler = self.sampler_manager.get_info(info);
+            debug_name(&name, sampler);
+            self.samplers.insert(name, sampler);
+        }
+        for name in shaders {
+            self.add_shader(&name);
+        }
+        for (name, data) in pipelines {
+            if data.bind_point == vk::PipelineBindPoint::COMPUTE {
+                self.add_compute(&name);
+                continue;
+            }
+            let Some(shader) = self.shaders.get(&data.shader_name) else {
+                let shader_name = &data.shader_name;
+                warn!("shader({shader_name}) of pipeline({name}) was removed");
+                continue;
+            };
+            let mut info = data.info;
+            info.stages.clear();
+            info.render_pass = vk::RenderPass::null();
+            let info = info
+                .layout(shader.pipeline_layout)
+                .stages(&shader.pipeline_stages);
+            let pipeline = info.build();
+            debug_name(&name, pipeline);
+            self.pipelines.write().insert(
+                &name,
+                PipelineData {
+                    pipeline,
+                    info,
+                    bind_point: data.bind_point,
+                    shader_name: data.shader_name,
+                },
+            );
+        }
+        for (name, ds, samplers) in desc_sets {
+            let dsl = self.dsl_manager.get(&ds.binds);
+            let desc_set = self.desc_alloc.alloc_one(dsl);
+            debug_name(&name, desc_set);
+            self.desc_sets.insert(
+                name.clone(),
+                DescSetData {
+                    desc_set,
+                    binds: ds.binds,
+                    imgs: Vec::new(),
+                    bufs: Vec::new(),
+                    samplers: Vec::new(),
+                },
+            );
+            let bufs = self.bufs.read();
+            let buf_binds: Vec<_> = ds
+                .bufs
+                .iter()
+                .filter(|(_, buf, _)| bufs.contains(buf))
+                .map(|(bind, buf, rng)| (buf.as_str(), rng.clone(), *bind))
+                .collect();
+            drop(bufs);
+            // swapchain may have less imgs than before
+            let mut img_binds: Vec<_> = ds
+                .imgs
+                .iter()
+                .filter(|(_, img_view, _)| self.try_img_view(img_view).is_ok())
+                .map(|(bind, img_view, layout)| {
+                    (img_view.as_str(), *layout, vk::Sampler::null(), *bind)
+                })
+                .collect();
+            for (bind, info) in samplers {
+                let sampler = self.sampler_manager.get_info(info);
+                match img_binds.iter_mut().find(|(.., b)| *b == bind) {
+                    Some(img_bind) => img_bind.2 = sampler,
+                    None => img_binds.push(("", ImgLayout::UNDEFINED, sampler, bind)),
+                }
+            }
+            self.writes_ds(&name, &buf_binds, &img_binds);
+        }
+        for name in fences {
+            self.add_fence(&name, true);
+        }
+        for name in semaphores {
+            self.add_semaphore(&name);
+        }
+        self.queued_pipelines = queued_pipelines;
+        self.pipeline_progress = pipeline_progress;
+        // handles given out before device was lost keep working
+        old_bufs
+            .write()
+            .refill(std::mem::take(&mut *self.bufs.write()));
+        old_imgs
+            .write()
+            .refill(std::mem::take(&mut *self.imgs.write()));
+        old_pipelines
+            .write()
+            .refill(std::mem::take(&mut *self.pipelines.write()));
+        self.bufs = old_bufs;
+        self.imgs = old_imgs;
+        self.pipelines = old_pipelines;
+        swapchain_size
+    }
+
+    /// errors since last call, posted each frame as `GpuError` event
+    pub fn take_errors(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
     }
 
     pub fn begin_render(
         &mut self,
         width: u32,
         height: u32,
-        img_view_name: &str,
-        sampled_img_view_name: &str,
+        img_view_name: impl AsName,
+        sampled_img_view_name: impl AsName,
+    ) {
+        self.begin_render_clear(
+            width,
+            height,
+            img_view_name,
+            sampled_img_view_name,
+            [0.0; 4],
+        );
+    }
+
+    /// same as begin_render() but clears to `color` instead of transparent black
+    pub fn begin_render_clear(
+        &mut self,
+        width: u32,
+        height: u32,
+        img_view_name: impl AsName,
+        sampled_img_view_name: impl AsName,
+        color: [f32; 4],
+    ) {
+        self.begin_rendering(
+            width,
+            height,
+            img_view_name,
+            sampled_img_view_name,
+            Some(color),
+        );
+    }
+
+    /// same as begin_render() but keeps previous contents of img instead of clearing
+    pub fn resume_render(
+        &mut self,
+        width: u32,
+        height: u32,
+        img_view_name: impl AsName,
+        sampled_img_view_name: impl AsName,
+    ) {
+        self.begin_rendering(width, height, img_view_name, sampled_img_view_name, None);
+    }
+
+    /// (width, height, img view, sampled img view) of last begin_render()
+    pub fn render_target(&self) -> (u32, u32, NameId, NameId) {
+        self.cmd_info.render_target.clone()
+    }
+
+    fn begin_rendering(
+        &mut self,
+        width: u32,
+        height: u32,
+        img_view_name: impl AsName,
+        sampled_img_view_name: impl AsName,
+        clear: Option<[f32; 4]>,
     ) {
+        let load_op = if clear.is_some() {
+            vk::AttachmentLoadOp::CLEAR
+        } else {
+            vk::AttachmentLoadOp::LOAD
+        };
+        let (img_view_name, sampled_img_view_name) =
+            (img_view_name.intern(), sampled_img_view_name.intern());
+        self.track_use(&img_view_name);
+        self.track_use(&sampled_img_view_name);
+        self.cmd_info.render_target = (
+            width,
+            height,
+            img_view_name.clone(),
+            sampled_img_view_name.clone(),
+        );
         let sampled = !sampled_img_view_name.is_empty();
-        let img_view = self.img_view(img_view_name);
+        let img_view = self.img_view(&img_view_name);
+        if self.barrier_tracker.enabled {
+            for view in [&img_view_name, &sampled_img_view_name] {
+                if let Some((_, img)) = self.img_views.get(view) {
+                    let layout = self.img(img).info.layout;
+                    self.barrier_tracker.access(
+                        img,
+                        layout,
+                        ImgLayout::COLOR,
+                        vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+                        vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+                    );
+                }
+            }
+        }
         self.cmd_info.render_area = vk::Rect2D {
             offset: vk::Offset2D { x: 0, y: 0 },
             extent: vk::Extent2D { width, height },
         };
         self.debug_begin(&format!("Begin Render({width}x{height})"));
+        if !caps().dynamic_rendering() {
+            self.begin_render_pass(&img_view_name, &sampled_img_view_name, load_op, clear);
+            return;
+        }
         unsafe {
             gpu().cmd_begin_rendering(
                 self.cmd(),
@@ -724,11 +1865,11 @@ impl RenderCtx {
                     .render_area(self.cmd_info.render_area)
                     .layer_count(1)
                     .color_attachments(&[vk::RenderingAttachmentInfo::default()
-                        .load_op(vk::AttachmentLoadOp::CLEAR)
+                        .load_op(load_op)
                         .store_op(vk::AttachmentStoreOp::STORE)
                         .clear_value(vk::ClearValue {
                             color: vk::ClearColorValue {
-                                float32: [0.0, 0.0, 0.0, 0.0],
+                                float32: clear.unwrap_or_default(),
                             },
                         })
                         .resolve_mode(if sampled {
@@ -759,11 +1900,83 @@ impl RenderCtx {
         );
         self.cmd_info.render_area = Default::default();
         unsafe {
-            gpu().cmd_end_rendering(self.cmd());
+            if caps().dynamic_rendering() {
+                gpu().cmd_end_rendering(self.cmd());
+            } else {
+                gpu().cmd_end_render_pass(self.cmd());
+            }
         }
         self.debug_end();
     }
 
+    /// begin_rendering() fallback for gpus without dynamic rendering,
+    /// framebuffers are cached until one of their img views is destroyed
+    fn begin_render_pass(
+        &mut self,
+        img_view_name: &str,
+        sampled_img_view_name: &str,
+        load_op: vk::AttachmentLoadOp,
+        clear: Option<[f32; 4]>,
+    ) {
+        let sampled = !sampled_img_view_name.is_empty();
+        let color_view_name = if sampled {
+            sampled_img_view_name
+        } else {
+            img_view_name
+        };
+        let info = self.img(self.img_view_img(color_view_name)).info.clone();
+        let render_pass = render_pass(info.format, samples_u32_to_vk(info.samples), load_op);
+        let mut views = vec![self.img_view(color_view_name)];
+        if sampled {
+            views.push(self.img_view(img_view_name));
+        }
+        let extent = self.cmd_info.render_area.extent;
+        let key = (views.clone(), extent.width, extent.height);
+        let framebuffer = *self.framebuffers.entry(key).or_insert_with(|| unsafe {
+            gpu()
+                .create_framebuffer(
+                    &vk::FramebufferCreateInfo::default()
+                        .render_pass(render_pass)
+                        .attachments(&views)
+                        .width(extent.width)
+                        .height(extent.height)
+                        .layers(1),
+                    alloc_callbacks(),
+                )
+                .expect("failed to create framebuffer")
+        });
+        let clear_values = vec![
+            vk::ClearValue {
+                color: vk::ClearColorValue {
+                    float32: clear.unwrap_or_default(),
+                },
+            };
+            views.len()
+        ];
+        unsafe {
+            gpu().cmd_begin_render_pass(
+                self.cmd(),
+                &vk::RenderPassBeginInfo::default()
+                    .render_pass(render_pass)
+                    .framebuffer(framebuffer)
+                    .render_area(self.cmd_info.render_area)
+                    .clear_values(&clear_values),
+                vk::SubpassContents::INLINE,
+            )
+        };
+    }
+
+    /// destroys cached render pass framebuffers using `img_view`
+    fn destroy_framebuffers(&mut self, img_view: vk::ImageView) {
+        self.framebuffers.retain(|(views, ..), framebuffer| {
+            let keep = !views.contains(&img_view);
+            if !keep {
+                unsafe { gpu().destroy_framebuffer(*framebuffer, alloc_callbacks()) };
+            }
+            keep
+        });
+    }
+
     pub fn set_viewport(&mut self, viewport: vk::Viewport) {
         if self.cmd_info.viewport.width == viewport.width
             && self.cmd_info.viewport.height == viewport.height
@@ -791,15 +2004,20 @@ impl RenderCtx {
     }
 
     pub fn bind_pipeline(&mut self, name: &str) {
-        let pipeline_data = self
-            .pipelines
-            .get(name)
-            .unwrap_or_else(|| panic!("pipeline not found: {name}"))
-            .clone();
+        let id = self.pipeline_id(name);
+        self.bind_pipeline_h(id);
+    }
+
+    pub fn bind_pipeline_h(&mut self, id: PipelineId) {
+        let pipelines = self.pipelines.read();
+        let pipeline_data = pipelines
+            .at(id)
+            .unwrap_or_else(|| panic!("pipeline was removed: {id:?}"));
         if pipeline_data.pipeline == self.cmd_info.pipeline_data.pipeline {
             return;
         }
-        self.cmd_info.pipeline_data = pipeline_data;
+        self.cmd_info.pipeline_data = pipeline_data.clone();
+        drop(pipelines);
 
         unsafe {
             if self.cmd_info.pipeline_data.bind_point == vk::PipelineBindPoint::GRAPHICS {
@@ -834,8 +2052,72 @@ impl RenderCtx {
         }
     }
 
+    /// makes ubo at `group`/`binding` of shader dynamic, so one ds can point at
+    /// different transient allocs with `bind_ds_dynamic`,
+    /// should be called before pipelines and desc sets are made from shader
+    pub fn set_dynamic_ubo(&mut self, shader_name: &str, group: usize, binding: u32) {
+        let shader_data = self
+            .shaders
+            .get_mut(shader_name)
+            .unwrap_or_else(|| panic!("shader not found: {shader_name}"));
+        shader_data
+            .shader
+            .set_desc_ty(group, binding, vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC);
+        let dsls = self.dsl_manager.gets(shader_data.shader.dsl_infos());
+        shader_data.pipeline_layout = self.pipeline_layout_manager.get(&dsls);
+        debug_name(shader_name, shader_data.pipeline_layout);
+    }
+
     pub fn bind_ds(&mut self, name: &str) {
+        self.bind_ds_dynamic(name, &[]);
+    }
+
+    /// `offsets` are for dynamic ubos in binding order, missing ones are 0
+    pub fn bind_ds_dynamic(&mut self, name: &str, offsets: &[u32]) {
         self.cmd_info.desc_sets = vec![self.desc_set(name)];
+        let dynamic_cnt = self.desc_sets[name]
+            .binds
+            .iter()
+            .filter(|b| b.desc_ty == vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
+            .count();
+        let mut offsets = offsets.to_vec();
+        offsets.resize(dynamic_cnt, 0);
+        if self.lifetime_tracker.enabled {
+            self.lifetime_tracker.bound(name);
+            let cmd = self.cmd();
+            let ds = &self.desc_sets[name];
+            let views = ds.imgs.iter().map(|(_, view, _)| view);
+            for used in views.chain(ds.bufs.iter().map(|(_, buf, _)| buf)) {
+                self.lifetime_tracker.used(cmd, used);
+            }
+        }
+        if self.barrier_tracker.enabled {
+            let ds = &self.desc_sets[name];
+            for (binding, view, expected) in ds.imgs.iter() {
+                let Some((_, img)) = NameId::find(view).and_then(|v| self.img_views.get(&v)) else {
+                    continue;
+                };
+                let bind = &ds.binds[*binding as usize];
+                let mut stage = vk::PipelineStageFlags2::NONE;
+                if bind.stage_flags.contains(vk::ShaderStageFlags::VERTEX) {
+                    stage |= vk::PipelineStageFlags2::VERTEX_SHADER;
+                }
+                if bind.stage_flags.contains(vk::ShaderStageFlags::FRAGMENT) {
+                    stage |= vk::PipelineStageFlags2::FRAGMENT_SHADER;
+                }
+                if bind.stage_flags.contains(vk::ShaderStageFlags::COMPUTE) {
+                    stage |= vk::PipelineStageFlags2::COMPUTE_SHADER;
+                }
+                let access = if bind.desc_ty == vk::DescriptorType::STORAGE_IMAGE {
+                    vk::AccessFlags2::SHADER_STORAGE_WRITE
+                } else {
+                    vk::AccessFlags2::SHADER_SAMPLED_READ
+                };
+                let layout = self.imgs.read()[img].info.layout;
+                self.barrier_tracker
+                    .access(img, layout, *expected, stage, access);
+            }
+        }
         unsafe {
             gpu().cmd_bind_descriptor_sets(
                 self.cmd(),
@@ -843,24 +2125,47 @@ impl RenderCtx {
                 self.cmd_info.pipeline_data.info.layout,
                 0,
                 &self.cmd_info.desc_sets,
-                &[],
+                &offsets,
             );
         }
     }
 
-    pub fn bind_vbo(&self, name: &str) {
+    pub fn bind_vbo(&mut self, name: &str) {
+        self.bind_vbo_h(self.buf_id(name));
+    }
+
+    pub fn bind_vbo_h(&mut self, id: BufId) {
+        self.bind_vbos_h(&[id]);
+    }
+
+    /// binds vbos to consecutive bindings starting at 0
+    pub fn bind_vbos(&mut self, names: &[&str]) {
+        let ids = names
+            .iter()
+            .map(|name| self.buf_id(name))
+            .collect::<Vec<_>>();
+        self.bind_vbos_h(&ids);
+    }
+
+    pub fn bind_vbos_h(&mut self, ids: &[BufId]) {
+        for &id in ids {
+            self.track_use_buf(id);
+        }
+        let bufs = ids.iter().map(|&id| self.buf_by_id(id)).collect::<Vec<_>>();
         unsafe {
-            gpu().cmd_bind_vertex_buffers(self.cmd(), 0, &[self.buf(name)], &[0]);
+            gpu().cmd_bind_vertex_buffers(self.cmd(), 0, &bufs, &vec![0; bufs.len()]);
         }
     }
 
-    pub fn bind_ebo(&self, name: &str) {
+    pub fn bind_ebo(&mut self, name: &str) {
+        self.track_use(name);
         unsafe {
             gpu().cmd_bind_index_buffer(self.cmd(), self.buf(name), 0, vk::IndexType::UINT32);
         }
     }
 
-    pub fn bind_vao(&self, name: &str, index_buffer_offset: vk::DeviceSize) {
+    pub fn bind_vao(&mut self, name: &str, index_buffer_offset: vk::DeviceSize) {
+        self.track_use(name);
         unsafe {
             gpu().cmd_bind_vertex_buffers(self.cmd(), 0, &[self.buf(name)], &[0]);
             gpu().cmd_bind_index_buffer(
@@ -872,64 +2177,165 @@ impl RenderCtx {
         }
     }
 
-    pub fn draw(&self, vertices: u32, instances: u32) {
+    pub fn draw(&mut self, vertices: u32, instances: u32) {
+        self.count_draw(vertices, instances);
         unsafe {
             gpu().cmd_draw(self.cmd(), vertices, instances, 0, 0);
         }
     }
 
-    pub fn draw_indexed(&self, indices: u32, instances: u32) {
+    pub fn draw_first(
+        &mut self,
+        vertices: u32,
+        instances: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    ) {
+        self.count_draw(vertices, instances);
+        unsafe {
+            gpu().cmd_draw(
+                self.cmd(),
+                vertices,
+                instances,
+                first_vertex,
+                first_instance,
+            );
+        }
+    }
+
+    pub fn draw_indexed(&mut self, indices: u32, instances: u32) {
+        self.count_draw(indices, instances);
         unsafe {
             gpu().cmd_draw_indexed(self.cmd(), indices, instances, 0, 0, 0);
         }
     }
 
+    fn count_draw(&mut self, vertices: u32, instances: u32) {
+        self.draw_stats.draws += 1;
+        self.draw_stats.vertices += vertices as u64 * instances as u64;
+        self.draw_stats.instances += instances as u64;
+    }
+
+    /// memory barrier without layout transition
+    pub fn img_barrier(
+        &mut self,
+        img_name: impl AsName,
+        src_stage: vk::PipelineStageFlags2,
+        dst_stage: vk::PipelineStageFlags2,
+        src_access: vk::AccessFlags2,
+        dst_access: vk::AccessFlags2,
+    ) {
+        self.track_use(img_name.name());
+        let (img, layout) = self.img_layout(&img_name);
+        self.barrier_tracker.barrier(
+            img_name.name(),
+            layout,
+            layout,
+            (src_stage, src_access),
+            (dst_stage, dst_access),
+        );
+        cmd_barrier(
+            self.cmd(),
+            &[],
+            &[vk::ImageMemoryBarrier2::default()
+                .dst_access_mask(dst_access)
+                .src_access_mask(src_access)
+                .src_stage_mask(src_stage)
+                .dst_stage_mask(dst_stage)
+                .image(img)
+                .subresource_range(
+                    vk::ImageSubresourceRange::default()
+                        .aspect_mask(vk::ImageAspectFlags::COLOR)
+                        .layer_count(1)
+                        .level_count(1),
+                )
+                .old_layout(layout)
+                .new_layout(layout)],
+        );
+    }
+
     pub fn set_img_layout(
         &mut self,
-        img_name: &str,
+        img_name: impl AsName,
         new_layout: vk::ImageLayout,
         src_stage: vk::PipelineStageFlags2,
         dst_stage: vk::PipelineStageFlags2,
         src_access: vk::AccessFlags2,
         dst_access: vk::AccessFlags2,
     ) {
+        self.track_use(img_name.name());
         let cmd = self.cmd();
+        let mut imgs = self.imgs.write();
         let ImageData {
             img,
             views: _,
             info,
-        } = self
-            .imgs
-            .get_mut(img_name)
-            .unwrap_or_else(|| panic!("img not found: {img_name}"));
+        } = imgs
+            .get_mut(&img_name)
+            .unwrap_or_else(|| panic!("img not found: {}", img_name.name()));
         if info.layout == new_layout {
             crate::log!("img layout transition to same layout: {new_layout:?}");
             return;
         }
-        unsafe {
-            gpu().cmd_pipeline_barrier2(
-                cmd,
-                &vk::DependencyInfo::default().image_memory_barriers(&[
-                    vk::ImageMemoryBarrier2::default()
-                        .dst_access_mask(dst_access)
-                        .src_access_mask(src_access)
-                        .src_stage_mask(src_stage)
-                        .dst_stage_mask(dst_stage)
-                        .image(*img)
-                        .subresource_range(
-                            vk::ImageSubresourceRange::default()
-                                .aspect_mask(vk::ImageAspectFlags::COLOR)
-                                .layer_count(1)
-                                .level_count(1),
-                        )
-                        .old_layout(info.layout)
-                        .new_layout(new_layout),
-                ]),
-            );
-        }
+        self.barrier_tracker.barrier(
+            img_name.name(),
+            info.layout,
+            new_layout,
+            (src_stage, src_access),
+            (dst_stage, dst_access),
+        );
+        cmd_barrier(
+            cmd,
+            &[],
+            &[vk::ImageMemoryBarrier2::default()
+                .dst_access_mask(dst_access)
+                .src_access_mask(src_access)
+                .src_stage_mask(src_stage)
+                .dst_stage_mask(dst_stage)
+                .image(*img)
+                .subresource_range(
+                    vk::ImageSubresourceRange::default()
+                        .aspect_mask(vk::ImageAspectFlags::COLOR)
+                        .layer_count(1)
+                        .level_count(1),
+                )
+                .old_layout(info.layout)
+                .new_layout(new_layout)],
+        );
         info.layout = new_layout;
     }
 
+    /// suballocates `size` bytes of per-frame data from "transient" buf,
+    /// valid until next frame starts, offset is aligned for ubo/ssbo binding
+    pub fn transient_alloc(&mut self, size: u64) -> (vk::Buffer, u64) {
+        let limits = gpu_limits();
+        let align = limits
+            .min_uniform_buffer_offset_alignment
+            .max(limits.min_storage_buffer_offset_alignment);
+        let off = self.transient_head.next_multiple_of(align);
+        let capacity = self.buf_size("transient");
+        assert!(
+            off + size <= capacity,
+            "transient buf out of memory ({off} + {size} > {capacity}), increase it with set_transient_size"
+        );
+        self.transient_head = off + size;
+        (self.buf("transient"), off)
+    }
+
+    /// copies `data` into transient memory, see `transient_alloc`
+    pub fn transient_write<T: ?Sized>(&mut self, data: &T) -> (vk::Buffer, u64) {
+        let (buf, off) = self.transient_alloc(size_of_val(data) as u64);
+        self.gpu_alloc.write_mapped_off(buf, data, off);
+        (buf, off)
+    }
+
+    /// recreates "transient" buf, ds writes referencing it must be redone
+    pub fn set_transient_size(&mut self, size: u64) {
+        queue_idle();
+        self.recreate_buf("transient", size);
+        self.transient_head = 0;
+    }
+
     pub fn staging_buf(&mut self, size: vk::DeviceSize) -> String {
         if self.buf_size("staging") < size {
             self.recreate_buf("staging", (size + 1).next_power_of_two());
@@ -946,6 +2352,7 @@ impl RenderCtx {
         src_off: vk::DeviceSize,
         dst_off: vk::DeviceSize,
     ) {
+        self.assert_not_static(dst_buf_name);
         let src_buf = self.buf(src_buf_name);
         let dst_buf = self.buf(dst_buf_name);
         let cmd = self.begin_cmd();
@@ -967,7 +2374,20 @@ impl RenderCtx {
         self.copy_buf_off(src_buf_name, dst_buf_name, 0, 0);
     }
 
+    /// copies are one-off cmds, so this just resolves names of ids
+    pub fn copy_buf_h(&mut self, src: BufId, dst: BufId) {
+        let name = |id| {
+            self.bufs
+                .read()
+                .name(id)
+                .unwrap_or_else(|| panic!("buffer was removed: {id:?}"))
+        };
+        let (src, dst) = (name(src), name(dst));
+        self.copy_buf(&src, &dst);
+    }
+
     pub fn write_buf_off<T: ?Sized>(&mut self, name: &str, data: &T, off: vk::DeviceSize) {
+        self.assert_not_static(name);
         let buffer = self.buf(name);
         if self.gpu_alloc.is_mappable(buffer) {
             self.gpu_alloc.write_mapped_off(buffer, data, off);
@@ -995,6 +2415,35 @@ impl RenderCtx {
         self.write_buf_off(name, data, 0);
     }
 
+    pub fn write_buf_off_h<T: ?Sized>(&mut self, id: BufId, data: &T, off: vk::DeviceSize) {
+        let buffer = self.buf_by_id(id);
+        // name is only looked up when it's needed
+        if !self.static_bufs.is_empty() {
+            self.assert_not_static(&self.bufs.read().name(id).unwrap());
+        }
+        if self.gpu_alloc.is_mappable(buffer) {
+            self.gpu_alloc.write_mapped_off(buffer, data, off);
+        } else {
+            let name = self.bufs.read().name(id).unwrap();
+            self.write_buf_off(&name, data, off);
+        }
+    }
+
+    /// persistently mapped memory of cpu visible buf, valid until buf is grown or removed
+    pub fn map_buf_h(&mut self, id: BufId) -> *mut u8 {
+        let buffer = self.buf_by_id(id);
+        assert!(
+            self.gpu_alloc.is_mappable(buffer),
+            "buffer isn't cpu visible: {}",
+            self.bufs.read().name(id).unwrap()
+        );
+        self.gpu_alloc.map(buffer)
+    }
+
+    pub fn write_buf_h<T: ?Sized>(&mut self, id: BufId, data: &T) {
+        self.write_buf_off_h(id, data, 0);
+    }
+
     pub fn read_buf<T: ?Sized>(&mut self, name: &str, data: &mut T) {
         self.read_buf_off(name, data, 0);
     }
@@ -1005,6 +2454,16 @@ impl RenderCtx {
         dst_img_name: &str,
         copies: &[BufferImageCopy],
     ) {
+        self.track_use(src_buf_name);
+        self.track_use(dst_img_name);
+        let dst_layout = self.img(dst_img_name).info.layout;
+        self.barrier_tracker.access(
+            dst_img_name,
+            dst_layout,
+            ImgLayout::DST,
+            vk::PipelineStageFlags2::TRANSFER,
+            vk::AccessFlags2::TRANSFER_WRITE,
+        );
         let src_buf = self.buf(src_buf_name);
         let dst_img_data = self.img(dst_img_name);
         unsafe {
@@ -1041,13 +2500,81 @@ impl RenderCtx {
         }
     }
 
+    /// img must be in `ImgLayout::SRC`
+    pub fn copy_img_to_buf(
+        &mut self,
+        src_img_name: &str,
+        dst_buf_name: &str,
+        copies: &[BufferImageCopy],
+    ) {
+        self.track_use(src_img_name);
+        self.track_use(dst_buf_name);
+        let src_layout = self.img(src_img_name).info.layout;
+        self.barrier_tracker.access(
+            src_img_name,
+            src_layout,
+            ImgLayout::SRC,
+            vk::PipelineStageFlags2::TRANSFER,
+            vk::AccessFlags2::TRANSFER_READ,
+        );
+        let dst_buf = self.buf(dst_buf_name);
+        let src_img_data = self.img(src_img_name);
+        unsafe {
+            gpu().cmd_copy_image_to_buffer(
+                self.cmd(),
+                src_img_data.img,
+                src_img_data.info.layout,
+                dst_buf,
+                &copies
+                    .iter()
+                    .map(|c| {
+                        vk::BufferImageCopy::default()
+                            .buffer_offset(c.buf_off)
+                            .buffer_row_length(c.buf_width)
+                            .buffer_image_height(c.buf_height)
+                            .image_extent(vk::Extent3D {
+                                width: c.buf_width,
+                                height: c.buf_height,
+                                depth: 1,
+                            })
+                            .image_offset(vk::Offset3D {
+                                x: c.img_off_x as i32,
+                                y: c.img_off_y as i32,
+                                z: 0,
+                            })
+                            .image_subresource(
+                                vk::ImageSubresourceLayers::default()
+                                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                                    .layer_count(1),
+                            )
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+    }
+
+    /// makes transfer writes visible to cpu once cmd finishes, for reading back gpu results
+    pub fn host_read_barrier(&mut self) {
+        cmd_barrier(
+            self.cmd(),
+            &[vk::MemoryBarrier2::default()
+                .src_stage_mask(vk::PipelineStageFlags2::TRANSFER)
+                .src_access_mask(vk::AccessFlags2::TRANSFER_WRITE)
+                .dst_stage_mask(vk::PipelineStageFlags2::HOST)
+                .dst_access_mask(vk::AccessFlags2::HOST_READ)],
+            &[],
+        );
+    }
+
     pub fn writes_ds(
-        &self,
+        &mut self,
         name: &str,
         buf_range_binds: &[(&str, std::ops::Range<vk::DeviceSize>, u32)],
         img_view_img_layout_sampler_binds: &[(&str, vk::ImageLayout, vk::Sampler, u32)],
     ) {
-        let DescSetData { desc_set, binds } = &self
+        let DescSetData {
+            desc_set, binds, ..
+        } = &self
             .desc_sets
             .get(name)
             .unwrap_or_else(|| panic!("descriptor not found: {name}"));
@@ -1100,10 +2627,32 @@ impl RenderCtx {
         let mut desc_writes = desc_buf_writes;
         desc_writes.append(&mut desc_img_writes);
         unsafe { gpu().update_descriptor_sets(&desc_writes, &[]) }
+        let ds = self.desc_sets.get_mut(name).unwrap();
+        for &(img_view, layout, sampler, bind) in img_view_img_layout_sampler_binds {
+            if !img_view.is_empty() {
+                ds.imgs.retain(|(b, ..)| *b != bind);
+                ds.imgs.push((bind, img_view.to_string(), layout));
+            }
+            if !sampler.is_null() {
+                ds.samplers.retain(|(b, _)| *b != bind);
+                ds.samplers.push((bind, sampler));
+            }
+        }
+        for (buf, rng, bind) in buf_range_binds {
+            ds.bufs.retain(|(b, ..)| b != bind);
+            ds.bufs.push((*bind, buf.to_string(), rng.clone()));
+        }
+        let img_binds = img_view_img_layout_sampler_binds
+            .iter()
+            .filter(|(img_view, ..)| !img_view.is_empty())
+            .map(|(.., bind)| *bind);
+        let buf_binds = buf_range_binds.iter().map(|(.., bind)| *bind);
+        self.lifetime_tracker
+            .rewritten(name, buf_binds.chain(img_binds));
     }
 
     pub fn write_ds_buf_ranges(
-        &self,
+        &mut self,
         name: &str,
         buf_range_binds: &[(&str, std::ops::Range<vk::DeviceSize>, u32)],
     ) {
@@ -1111,7 +2660,7 @@ impl RenderCtx {
     }
 
     pub fn write_ds_buf_range(
-        &self,
+        &mut self,
         name: &str,
         buf_name: &str,
         buf_range: std::ops::Range<vk::DeviceSize>,
@@ -1120,7 +2669,7 @@ impl RenderCtx {
         self.write_ds_buf_ranges(name, &[(buf_name, buf_range, binding)]);
     }
 
-    pub fn write_ds_bufs(&self, name: &str, buf_binds: &[(&str, u32)]) {
+    pub fn write_ds_bufs(&mut self, name: &str, buf_binds: &[(&str, u32)]) {
         self.write_ds_buf_ranges(
             name,
             &buf_binds
@@ -1130,12 +2679,12 @@ impl RenderCtx {
         );
     }
 
-    pub fn write_ds_buf(&self, name: &str, buf_name: &str, binding: u32) {
+    pub fn write_ds_buf(&mut self, name: &str, buf_name: &str, binding: u32) {
         self.write_ds_buf_range(name, buf_name, 0..vk::WHOLE_SIZE, binding)
     }
 
     pub fn write_ds_img(
-        &self,
+        &mut self,
         name: &str,
         img_view_name: &str,
         img_layout: vk::ImageLayout,
@@ -1149,7 +2698,7 @@ impl RenderCtx {
         )]);
     }
 
-    pub fn write_ds_sampler(&self, name: &str, sampler_name: &str, binding: u32) {
+    pub fn write_ds_sampler(&mut self, name: &str, sampler_name: &str, binding: u32) {
         self.writes_ds(name, &[], &[(
             "",
             ImgLayout::UNDEFINED,
@@ -1158,6 +2707,22 @@ impl RenderCtx {
         )]);
     }
 
+    /// clears rect of current render target, must be called while rendering
+    pub fn clear_rect(&self, rect: vk::Rect2D, color: [f32; 4]) {
+        unsafe {
+            gpu().cmd_clear_attachments(
+                self.cmd(),
+                &[vk::ClearAttachment::default()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .color_attachment(0)
+                    .clear_value(vk::ClearValue {
+                        color: vk::ClearColorValue { float32: color },
+                    })],
+                &[vk::ClearRect::default().rect(rect).layer_count(1)],
+            );
+        }
+    }
+
     pub fn clear(&self, img: vk::Image, color: [f32; 4]) {
         unsafe {
             gpu().cmd_clear_color_image(
@@ -1173,17 +2738,35 @@ impl RenderCtx {
         }
     }
 
-    pub fn blit(&self, src_img_name: &str, dst_img_name: &str) {
+    pub fn blit(&mut self, src_img_name: &str, dst_img_name: &str) {
+        self.track_use(src_img_name);
+        self.track_use(dst_img_name);
+        let src_layout = self.img(src_img_name).info.layout;
+        let dst_layout = self.img(dst_img_name).info.layout;
+        self.barrier_tracker.access(
+            src_img_name,
+            src_layout,
+            ImgLayout::SRC,
+            vk::PipelineStageFlags2::BLIT,
+            vk::AccessFlags2::TRANSFER_READ,
+        );
+        self.barrier_tracker.access(
+            dst_img_name,
+            dst_layout,
+            ImgLayout::DST,
+            vk::PipelineStageFlags2::BLIT,
+            vk::AccessFlags2::TRANSFER_WRITE,
+        );
         let ImageData {
             img: src,
             views: _,
             info: src_info,
-        } = self.img(src_img_name);
+        } = self.img(src_img_name).clone();
         let ImageData {
             img: dst,
             views: _,
             info: dst_info,
-        } = self.img(dst_img_name);
+        } = self.img(dst_img_name).clone();
         assert_eq!(
             src_info.width == dst_info.width,
             src_info.height == dst_info.height,
@@ -1197,9 +2780,9 @@ impl RenderCtx {
         unsafe {
             gpu().cmd_blit_image(
                 self.cmd(),
-                *src,
+                src,
                 src_info.layout,
-                *dst,
+                dst,
                 dst_info.layout,
                 &[vk::ImageBlit::default()
                     .src_offsets([min, max])
@@ -1211,6 +2794,89 @@ impl RenderCtx {
         };
     }
 
+    /// blits whole src img into whole dst img, scaling if sizes differ
+    pub fn blit_scaled(&mut self, src_img_name: &str, dst_img_name: &str, filter: vk::Filter) {
+        let size = |info: &ImageInfo| (info.width, info.height);
+        let src_size = size(&self.img(src_img_name).info);
+        let dst_size = size(&self.img(dst_img_name).info);
+        self.blit_region(src_img_name, dst_img_name, src_size, dst_size, filter);
+    }
+
+    /// blits top left `src_size` px of src img into top left `dst_size` px of dst img,
+    /// scaling if sizes differ, for imgs bigger than area rendered into
+    pub fn blit_region(
+        &mut self,
+        src_img_name: impl AsName,
+        dst_img_name: impl AsName,
+        (src_width, src_height): (u32, u32),
+        (dst_width, dst_height): (u32, u32),
+        filter: vk::Filter,
+    ) {
+        self.blit_rect(
+            src_img_name,
+            dst_img_name,
+            [0, 0, src_width, src_height],
+            [0, 0, dst_width, dst_height],
+            filter,
+        );
+    }
+
+    /// blits x, y, w, h `src_rect` of src img into `dst_rect` of dst img, scaling if sizes differ
+    pub fn blit_rect(
+        &mut self,
+        src_img_name: impl AsName,
+        dst_img_name: impl AsName,
+        src_rect: [u32; 4],
+        dst_rect: [u32; 4],
+        filter: vk::Filter,
+    ) {
+        self.track_use(src_img_name.name());
+        self.track_use(dst_img_name.name());
+        let (src, src_layout) = self.img_layout(&src_img_name);
+        let (dst, dst_layout) = self.img_layout(&dst_img_name);
+        self.barrier_tracker.access(
+            src_img_name.name(),
+            src_layout,
+            ImgLayout::SRC,
+            vk::PipelineStageFlags2::BLIT,
+            vk::AccessFlags2::TRANSFER_READ,
+        );
+        self.barrier_tracker.access(
+            dst_img_name.name(),
+            dst_layout,
+            ImgLayout::DST,
+            vk::PipelineStageFlags2::BLIT,
+            vk::AccessFlags2::TRANSFER_WRITE,
+        );
+        let offsets = |[x, y, width, height]: [u32; 4]| {
+            [
+                vk::Offset3D::default().x(x as i32).y(y as i32),
+                vk::Offset3D::default()
+                    .x((x + width) as i32)
+                    .y((y + height) as i32)
+                    .z(1),
+            ]
+        };
+        let subres = vk::ImageSubresourceLayers::default()
+            .aspect_mask(vk::ImageAspectFlags::COLOR)
+            .layer_count(1);
+        unsafe {
+            gpu().cmd_blit_image(
+                self.cmd(),
+                src,
+                src_layout,
+                dst,
+                dst_layout,
+                &[vk::ImageBlit::default()
+                    .src_offsets(offsets(src_rect))
+                    .src_subresource(subres)
+                    .dst_offsets(offsets(dst_rect))
+                    .dst_subresource(subres)],
+                filter,
+            )
+        };
+    }
+
     pub fn recreate_swapchain(&mut self) -> vk::Extent2D {
         let surf_caps = self.surface_capabilities();
         let size = self.swapchain_size;
@@ -1221,9 +2887,13 @@ impl RenderCtx {
             },
             _ => surf_caps.current_extent,
         };
-        if surf_res.width == 0 || surf_res.height == 0 || surf_res == size {
+        if surf_res.width == 0
+            || surf_res.height == 0
+            || (surf_res == size && !self.swapchain_stale)
+        {
             return surf_res;
         }
+        self.swapchain_stale = false;
         self.swapchain_size = surf_res;
         scope_time!("resize {}x{}", surf_res.width, surf_res.height);
         let pre_transform = if surf_caps
@@ -1237,15 +2907,42 @@ impl RenderCtx {
         let present_mode = self
             .surface_present_modes
             .iter()
-            .find(|&mode| *mode == vk::PresentModeKHR::MAILBOX)
+            .find(|&mode| !vulkan_config().vsync && *mode == vk::PresentModeKHR::MAILBOX)
             .copied()
             .unwrap_or(vk::PresentModeKHR::FIFO);
+        self.present_mode = present_mode;
         let mut desired_img_cnt = surf_caps.min_image_count + 1;
         if surf_caps.max_image_count > 0 {
             desired_img_cnt = surf_caps.max_image_count.min(desired_img_cnt);
         }
         // Destroy old swap chain images
         let old_swapchain = self.swapchain;
+        let supported_alpha = surf_caps.supported_composite_alpha;
+        let composite_alpha = vulkan_config()
+            .transparent
+            .then(|| {
+                [
+                    vk::CompositeAlphaFlagsKHR::PRE_MULTIPLIED,
+                    vk::CompositeAlphaFlagsKHR::POST_MULTIPLIED,
+                    vk::CompositeAlphaFlagsKHR::INHERIT,
+                ]
+                .into_iter()
+                .find(|&alpha| supported_alpha.contains(alpha))
+            })
+            .flatten();
+        if vulkan_config().transparent && composite_alpha.is_none() && old_swapchain.is_null() {
+            warn!("transparent window is not supported by surface, using opaque");
+        }
+        // lowest supported bit if even opaque isn't supported
+        let composite_alpha = composite_alpha.unwrap_or(
+            if supported_alpha.contains(vk::CompositeAlphaFlagsKHR::OPAQUE) {
+                vk::CompositeAlphaFlagsKHR::OPAQUE
+            } else {
+                vk::CompositeAlphaFlagsKHR::from_raw(
+                    supported_alpha.as_raw() & supported_alpha.as_raw().wrapping_neg(),
+                )
+            },
+        );
         self.swapchain = unsafe {
             self.swapchain_loader
                 .create_swapchain(
@@ -1259,7 +2956,7 @@ impl RenderCtx {
                         .image_usage(ImgUsage::COLOR | ImgUsage::DST)
                         .image_sharing_mode(vk::SharingMode::EXCLUSIVE)
                         .pre_transform(pre_transform)
-                        .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
+                        .composite_alpha(composite_alpha)
                         .present_mode(present_mode)
                         .old_swapchain(old_swapchain)
                         .clipped(true),
@@ -1269,15 +2966,17 @@ impl RenderCtx {
         };
         debug_name("swapchain", self.swapchain);
 
+        let old_img_cnt = self.swapchain_imgs.len();
         if old_swapchain != Default::default() {
-            // FIXME: assumes swapchain image count is constant
-            for i in 0..desired_img_cnt {
-                let img_name = format!("swapchain image {i}");
-                let img_views = self.imgs[&img_name].views.clone();
+            // in flight frame may still use old swapchain imgs
+            queue_idle();
+            self.lifetime_tracker.finish_all();
+            for swap_img in std::mem::take(&mut self.swapchain_imgs) {
+                let img_views = self.imgs.read()[&swap_img.img].views.clone();
                 for img_view in img_views {
                     self.remove_img_view(&img_view);
                 }
-                self.imgs.remove(&img_name).unwrap();
+                self.imgs.write().remove(swap_img.img).unwrap();
             }
             unsafe {
                 self.swapchain_loader
@@ -1290,11 +2989,21 @@ impl RenderCtx {
                 .get_swapchain_images(self.swapchain)
                 .unwrap()
         };
+        // driver may return more imgs than asked for, or a different count after recreation
+        for i in swapchain_imgs.len()..old_img_cnt {
+            self.remove_semaphore(&format!("render finished {i}"));
+        }
         for (i, swap_img) in swapchain_imgs.into_iter().enumerate() {
             let img_name = format!("swapchain image {i}");
             debug_name(&img_name, swap_img);
             let img_view_name = format!("swapchain image view {i}");
-            self.imgs.insert(img_name.clone(), ImageData {
+            let render_finished = self.add_semaphore(&format!("render finished {i}"));
+            self.swapchain_imgs.push(SwapchainImg {
+                img: NameId::new(&img_name),
+                view: NameId::new(&img_view_name),
+                render_finished,
+            });
+            self.imgs.write().insert(&img_name, ImageData {
                 img: swap_img,
                 views: vec![],
                 info: ImageInfo::new()
@@ -1310,45 +3019,107 @@ impl RenderCtx {
         surf_res
     }
 
-    // might cause resize so returns optimal swapchain size
-    pub fn acquire_img(&mut self, signal: vk::Semaphore) -> vk::Extent2D {
-        let extent = if self.swapchain == vk::SwapchainKHR::null() {
+    // might cause resize so returns optimal swapchain size,
+    // recreates out of date swapchain and retries, none if surface is 0x0 (minimized)
+    // or img couldn't be acquired
+    pub fn acquire_img(&mut self, signal: vk::Semaphore) -> Option<vk::Extent2D> {
+        const MAX_RETRIES: usize = 3;
+        let mut extent = if self.swapchain == vk::SwapchainKHR::null() {
             self.recreate_swapchain()
         } else {
             self.swapchain_size
         };
-        unsafe {
-            self.swapchain_img_idx = self
-                .swapchain_loader
-                .acquire_next_image(self.swapchain, u64::MAX, signal, vk::Fence::null())
-                .unwrap()
-                .0 as usize;
+        for _ in 0..=MAX_RETRIES {
+            if extent.width == 0 || extent.height == 0 {
+                return None;
+            }
+            let acquired = unsafe {
+                self.swapchain_loader.acquire_next_image(
+                    self.swapchain,
+                    u64::MAX,
+                    signal,
+                    vk::Fence::null(),
+                )
+            };
+            match acquired {
+                Ok((idx, suboptimal)) => {
+                    self.swapchain_img_idx = idx as usize;
+                    // signal is already pending, so use this img and recreate after present
+                    if suboptimal {
+                        self.swapchain_stale = true;
+                    }
+                    return Some(extent);
+                }
+                Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+                    self.swapchain_stale = true;
+                    extent = self.recreate_swapchain();
+                }
+                Err(e) => {
+                    self.report(Error::vk("failed to acquire swapchain img", e));
+                    return None;
+                }
+            }
         }
-        extent
+        warn!("swapchain still out of date after {MAX_RETRIES} recreations, skipping frame");
+        None
     }
 
     // might cause resize so returns optimal swapchain size
     pub fn present(&mut self, wait: &[vk::Semaphore]) -> vk::Extent2D {
-        unsafe {
-            self.swapchain_loader
-                .queue_present(
-                    queue(),
-                    &vk::PresentInfoKHR::default()
-                        .wait_semaphores(wait)
-                        .swapchains(&[self.swapchain])
-                        .image_indices(&[self.swapchain_img_idx as u32]),
-                )
-                .map(|_| self.swapchain_size)
-                .unwrap_or_else(|_| self.recreate_swapchain())
+        let presented = unsafe {
+            self.swapchain_loader.queue_present(
+                queue(),
+                &vk::PresentInfoKHR::default()
+                    .wait_semaphores(wait)
+                    .swapchains(&[self.swapchain])
+                    .image_indices(&[self.swapchain_img_idx as u32]),
+            )
+        };
+        match presented {
+            Ok(suboptimal) if suboptimal || self.swapchain_stale => {
+                self.swapchain_stale = true;
+                self.recreate_swapchain()
+            }
+            Ok(_) => self.swapchain_size,
+            Err(vk::Result::ERROR_DEVICE_LOST) => {
+                self.report(Error::DeviceLost);
+                self.swapchain_size
+            }
+            Err(_) => self.recreate_swapchain(),
         }
     }
 
-    pub fn cur_img(&self) -> String {
-        format!("swapchain image {}", self.swapchain_img_idx)
+    /// makes next `recreate_swapchain` rebuild it, for changes like display mode switch
+    /// that can make swapchain out of date without resizing
+    pub fn invalidate_swapchain(&mut self) {
+        self.swapchain_stale = true;
+    }
+
+    pub fn present_mode(&self) -> vk::PresentModeKHR {
+        self.present_mode
+    }
+
+    /// vulkan doesn't report if display has variable refresh, so this is true when
+    /// present mode shows frames as soon as they're ready (needed for vrr to vary refresh)
+    /// or fifo relaxed is supported (usually only by vrr capable drivers)
+    /// surface is srgb, so colors are blended in linear space, see `EngineConfig::linear`
+    pub fn linear(&self) -> bool {
+        self.surface_format.format == vk::Format::B8G8R8A8_SRGB
+    }
+
+    pub fn vrr_capable(&self) -> bool {
+        self.present_mode != vk::PresentModeKHR::FIFO
+            || self
+                .surface_present_modes
+                .contains(&vk::PresentModeKHR::FIFO_RELAXED)
     }
 
-    pub fn cur_img_view(&self) -> String {
-        format!("swapchain image view {}", self.swapchain_img_idx)
+    pub fn cur_img(&self) -> NameId {
+        self.swapchain_imgs[self.swapchain_img_idx].img.clone()
+    }
+
+    pub fn cur_img_view(&self) -> NameId {
+        self.swapchain_imgs[self.swapchain_img_idx].view.clone()
     }
 
     fn surface_capabilities(&self) -> vk::SurfaceCapabilitiesKHR {
@@ -1387,7 +3158,7 @@ impl Drop for DebugScope<'_> {
     }
 }
 
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "renderdoc"))]
 impl RenderCtx {
     pub fn debug_begin(&self, label: &str) {
         unsafe {
@@ -1449,7 +3220,7 @@ impl RenderCtx {
 impl Drop for RenderCtx {
     fn drop(&mut self) {
         gpu_idle();
-        for pipeline in self.pipelines.values() {
+        for pipeline in self.pipelines.read().values() {
             let pipeline = pipeline.pipeline;
             if !pipeline.is_null() {
                 unsafe {
@@ -1495,12 +3266,20 @@ impl Drop for RenderCtx {
                 }
             }
         }
+        unsafe {
+            gpu().destroy_query_pool(self.timestamps, alloc_callbacks());
+        }
         if !self.swapchain.is_null() {
             unsafe {
                 self.swapchain_loader
                     .destroy_swapchain(self.swapchain, alloc_callbacks())
             };
         }
+        for &framebuffer in self.framebuffers.values() {
+            unsafe {
+                gpu().destroy_framebuffer(framebuffer, alloc_callbacks());
+            }
+        }
         for &(img_view, _) in self.img_views.values() {
             if !img_view.is_null() {
                 unsafe {
@@ -1511,7 +3290,7 @@ impl Drop for RenderCtx {
     }
 }
 
-#[cfg(not(debug_assertions))]
+#[cfg(not(any(debug_assertions, feature = "renderdoc")))]
 impl RenderCtx {
     pub fn debug_begin_colored(&self, _label: &str, _color: [f32; 4]) {}
     pub fn debug_begin(&self, _label: &str) {}
@@ -1526,6 +3305,39 @@ impl RenderCtx {
     }
 }
 
+#[cfg(feature = "renderdoc")]
+impl RenderCtx {
+    /// app was launched from renderdoc or it was loaded by [`super::VulkanConfig::renderdoc`]
+    pub fn renderdoc_attached(&self) -> bool {
+        renderdoc().is_some()
+    }
+
+    /// renderdoc captures next frame, debug scopes show up as markers in it
+    pub fn trigger_capture(&self) {
+        match renderdoc() {
+            Some(renderdoc) => renderdoc.trigger_capture(),
+            None => warn!("can't capture frame, renderdoc isn't loaded"),
+        }
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        renderdoc().is_some_and(|r| r.is_capturing())
+    }
+}
+
+#[cfg(not(feature = "renderdoc"))]
+impl RenderCtx {
+    pub fn renderdoc_attached(&self) -> bool {
+        false
+    }
+    pub fn trigger_capture(&self) {
+        warn!("can't capture frame, `renderdoc` feature is disabled");
+    }
+    pub fn is_capturing(&self) -> bool {
+        false
+    }
+}
+
 #[cfg(debug_assertions)]
 pub fn debug_name<T: vk::Handle>(name: &str, obj: T) {
     let raw = obj.as_raw();
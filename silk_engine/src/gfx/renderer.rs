@@ -1,20 +1,33 @@
 // TODO: make roundness Unit
 // TODO: make stroke_width Unit
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
+    hash::{DefaultHasher, Hasher},
+    ops::{Deref, DerefMut},
     sync::{Arc, Mutex},
 };
 
 use ash::vk;
 
 use crate::{
+    err,
+    error::{Error, Result},
     event::WindowResize,
-    util::{Bezier, ImageLoader, Tracked},
+    util::{
+        Bezier, Color, ColorMatrix, Hsv, ImageData, ImageLoader, SvgPath, Tracked, Vec2,
+        cast_slice, f32_to_f16, srgb_to_linear, vfs, vfs_mut,
+    },
+    warn,
 };
 
 use super::{
-    BufUsage, GraphicsPipelineInfo, ImageInfo, ImgLayout, ImgUsage, MSAA, MemProp, RenderCtx, Unit,
+    AsName, BufId, BufUsage, Font, GraphicsPipelineInfo, ImageInfo, ImgLayout, ImgUsage, MemProp,
+    NameId, PipelineId, RenderCtx, Unit, Video,
+    asset::{Asset, AssetLoader, Handle, Image},
+    lock_ctx,
     packer::{Guillotine, Packer, Rect},
+    queue_idle,
+    registry::NameMap,
     render_ctx::BufferImageCopy,
 };
 
@@ -29,7 +42,125 @@ pub struct Vertex {
     pub stroke_width: f32,
     pub stroke_color: [u8; 4],
     tex_coord: [u32; 2], // packed whxy
+    flags: u32,
+    mask: [f32; 2],   // progress, mask param
+    tiling: [f32; 2], // img repeats across shape
+    custom: [f32; 4], // read by custom shaders, see Renderer::custom
 }
+
+/// [`Vertex`] packed for [`VertexFormat::Compact`], unpacked by unpack_vertex() in render.wgsl
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct CompactVertex {
+    pos: [i16; 2],
+    scale: [i16; 2],
+    color: [u8; 4],
+    stroke_color: [u8; 4],
+    rotation_stroke: [u16; 2],
+    flags: u32, // top byte is roundness
+    tex_coord: [u32; 2],
+    mask: [u16; 2],
+    tiling: [u16; 2],
+    custom: [u16; 4],
+}
+
+impl From<&Vertex> for CompactVertex {
+    fn from(v: &Vertex) -> Self {
+        let snorm = |x: f32| (x.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+        // f16 loses precision fast, so rotation is wrapped to -pi..pi first
+        let rotation = (v.rotation + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU)
+            - std::f32::consts::PI;
+        // 127.5 keeps 1 (circle) above and 0.999 below 1 after rounding
+        let roundness = (v.roundness * 127.5).round().clamp(0.0, 255.0) as u32;
+        Self {
+            pos: v.pos.map(|p| snorm((p - 0.5) / 1.5)),
+            scale: v.scale.map(|s| snorm(s * 0.5)),
+            color: v.color,
+            stroke_color: v.stroke_color,
+            rotation_stroke: [rotation, v.stroke_width].map(f32_to_f16),
+            flags: v.flags | roundness << 24,
+            tex_coord: v.tex_coord,
+            mask: v.mask.map(f32_to_f16),
+            tiling: v.tiling.map(f32_to_f16),
+            custom: v.custom.map(f32_to_f16),
+        }
+    }
+}
+
+/// instance layout render shaders read, see [`Renderer::set_vertex_format`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum VertexFormat {
+    /// 88 byte [`Vertex`]
+    #[default]
+    Full,
+    /// 48 byte snorm16/f16 packed vertex, nearly halves instance upload bandwidth.
+    /// positions are ~0.2px precise on 4k screen and must be within 1 screen around it,
+    /// sizes are clamped to 2 screens and rotation, stroke, mask, tiling and custom are f16
+    Compact,
+}
+
+impl VertexFormat {
+    /// variant of render `shader` reading this format
+    fn shader(self, shader: &str) -> String {
+        match self {
+            Self::Full => shader.to_string(),
+            Self::Compact => format!("{shader} compact"),
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::Full => size_of::<Vertex>(),
+            Self::Compact => size_of::<CompactVertex>(),
+        }
+    }
+}
+
+const BACKDROP_BLUR: u32 = 1;
+/// shape is cut by edge moving in mask param direction
+const MASK_REVEAL: u32 = 2;
+/// shape is intersected with capsule filled from left
+const MASK_FILL: u32 = 4;
+/// shape is ring arc from top going clockwise, mask param is thickness
+const MASK_ARC: u32 = 8;
+/// fill is texel of cached layer from layers img instead of atlas
+const LAYER: u32 = 16;
+/// output is premultiplied, for multiply blending
+const PREMUL_OUT: u32 = 32;
+/// atlas is sampled with nearest filtering instead of linear
+const NEAREST: u32 = 64;
+/// img is repeated tiling times across shape
+const TILE: u32 = 128;
+/// half of stroke is outside of shape edge
+const STROKE_CENTER: u32 = 256;
+/// stroke is outside of shape edge
+const STROKE_OUTER: u32 = 512;
+/// bits of color matrix slot in flags, 0 is none
+const COLOR_MATRIX_SHIFT: u32 = 16;
+/// color matrix slots in color matrix ubo, including empty slot 0
+const MAX_COLOR_MATRICES: usize = 16;
+/// backdrop is blurred at 1/BACKDROP_SCALE res
+const BACKDROP_SCALE: u32 = 4;
+/// each backdrop blur uses 2 blur passes of backdrop_blur.wgsl
+const MAX_BACKDROP_BLURS: usize = 32;
+/// offset between blur passes in blur ubo, dynamic ubo offsets have to be aligned
+/// to minUniformBufferOffsetAlignment, which is at most 256
+const BLUR_PASS_STRIDE: usize = 256;
+/// backdrop is blurred in compute passes, so it needs storage format
+const BACKDROP_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+
+/// params of blur pass in backdrop_blur.wgsl
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct BlurPass {
+    dir: [f32; 2],
+    /// in backdrop texels
+    radius: f32,
+    _pad: f32,
+    /// x, y, w, h of blurred backdrop region
+    region: [u32; 4],
+}
+
 // TODO: tex_idx and textures
 #[allow(unused)]
 impl Vertex {
@@ -78,44 +209,548 @@ impl Vertex {
             stroke_width: renderer.stroke_width,
             stroke_color: renderer.stroke_color,
             tex_coord: renderer.tex_coord,
+            flags: (if renderer.blur > 0.0 {
+                BACKDROP_BLUR
+            } else {
+                0
+            }) | (if renderer.info.blend == BlendMode::Multiply {
+                PREMUL_OUT
+            } else {
+                0
+            }) | (if renderer.sampling == Sampling::Nearest {
+                NEAREST
+            } else {
+                0
+            }) | (if renderer.tiling != [1.0; 2] { TILE } else { 0 })
+                | renderer.stroke_align.flag()
+                | renderer.color_matrix << COLOR_MATRIX_SHIFT
+                | renderer.mask.0,
+            mask: renderer.mask.1,
+            tiling: renderer.tiling,
+            custom: renderer.custom,
+        }
+    }
+}
+
+/// direction [`Renderer::reveal`] reveals shapes in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Reveal {
+    Right,
+    Left,
+    Down,
+    Up,
+}
+
+/// path [`Renderer::connector`] takes between it's ends
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Connector {
+    Straight,
+    /// s-curve leaving and entering horizontally, for node graphs
+    #[default]
+    Curve,
+    /// horizontal, vertical then horizontal segments meeting at right angles
+    Elbow,
+}
+
+/// how shapes are blended with what's beneath them, see [`Renderer::blend`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BlendMode {
+    #[default]
+    Alpha,
+    /// adds color weighted by alpha, for glow and particles
+    Additive,
+    /// multiplies what's beneath by color, for shading and tinting
+    Multiply,
+    /// color is already premultiplied by alpha
+    Premultiplied,
+}
+
+impl BlendMode {
+    const ALL: [Self; 4] = [
+        Self::Alpha,
+        Self::Additive,
+        Self::Multiply,
+        Self::Premultiplied,
+    ];
+
+    /// name of pipeline made from `shader` with this blend mode
+    fn pipeline(self, shader: &str) -> String {
+        match self {
+            Self::Alpha => shader.to_string(),
+            Self::Additive => format!("{shader} additive"),
+            Self::Multiply => format!("{shader} multiply"),
+            Self::Premultiplied => format!("{shader} premultiplied"),
+        }
+    }
+}
+
+/// how imgs and text are filtered when scaled, see [`Renderer::sampling`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Sampling {
+    #[default]
+    Linear,
+    /// keeps texels sharp, for pixel art
+    Nearest,
+}
+
+/// where stroke is drawn relative to shape edge, see [`Renderer::stroke_align`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum StrokeAlign {
+    /// stroke is inside of shape, so it never bleeds out of it's bounds
+    #[default]
+    Inner,
+    /// stroke straddles shape edge
+    Center,
+    /// stroke is around shape, which keeps it's size
+    Outer,
+}
+
+impl StrokeAlign {
+    fn flag(self) -> u32 {
+        match self {
+            Self::Inner => 0,
+            Self::Center => STROKE_CENTER,
+            Self::Outer => STROKE_OUTER,
+        }
+    }
+
+    /// part of stroke width outside of shape edge
+    fn outside(self) -> f32 {
+        match self {
+            Self::Inner => 0.0,
+            Self::Center => 0.5,
+            Self::Outer => 1.0,
+        }
+    }
+}
+
+/// how glyphs are aligned to px grid, see [`Renderer::hinting`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TextHinting {
+    /// exact positions
+    None,
+    /// baselines snap to whole px and glyphs to quarter px in x,
+    /// so repeated glyphs are sampled the same way and small text looks even
+    #[default]
+    Subpixel,
+    /// baselines and glyphs snap to whole px, sharpest but spacing is uneven
+    Pixel,
+}
+
+impl TextHinting {
+    /// glyph x positions per px
+    fn phases(self) -> f32 {
+        match self {
+            Self::None | Self::Subpixel => 4.0,
+            Self::Pixel => 1.0,
         }
     }
 }
 
+/// cpu side state of instance that isn't uploaded with it
+#[derive(Clone, Copy, Default)]
+struct InstInfo {
+    /// see [`Renderer::z`]
+    z: i32,
+    /// idx into `Renderer::shaders`
+    shader: usize,
+    blend: BlendMode,
+    /// see [`Renderer::pick_id`]
+    pick_id: u32,
+    /// 0 is whole window, otherwise idx + 1 into `Renderer::scissors`
+    viewport: u32,
+}
+
+impl InstInfo {
+    /// instance ranges are drawn with their pipeline
+    fn pipeline(&self) -> (usize, BlendMode) {
+        (self.shader, self.blend)
+    }
+}
+
+/// vs_main of render.wgsl, compact variants take [`CompactVertex`] and unpack it
+const VS_MAIN: &str = "fn vs_main(@builtin(vertex_index) vert_idx: u32, in: Vertex) -> VSOut {";
+const COMPACT_VS_MAIN: &str = concat!(
+    "fn vs_main(@builtin(vertex_index) vert_idx: u32, compact: CompactVertex) -> VSOut {\n",
+    "    let in = unpack_vertex(compact);"
+);
+
+/// default `shade` fn in render.wgsl, custom shaders replace it
+const SHADE_HOOK: &str =
+    "fn shade(col: vec4f, uv: vec2f, pos: vec2f) -> vec4f {\n    return col;\n}";
+
+/// level of detail thresholds in px, below which instances are simplified, 0.0 disables.
+/// disabled by default, text 4.0 and detail 2.0 are barely noticeable
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Lod {
+    /// text with smaller line height is drawn as a faded rect per line instead of glyphs
+    pub text: f32,
+    /// shapes with smaller width or height are drawn without stroke and backdrop blur
+    pub detail: f32,
+}
+
+/// how instances are recolored by [`DrawDebug`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DebugColor {
+    #[default]
+    None,
+    /// by draw call, shows where batching breaks
+    DrawCall,
+    /// by shader and blend mode
+    Pipeline,
+    /// by atlas region, untextured instances are gray
+    Texture,
+}
+
+/// diagnostic view of how instances are drawn, for finding overdraw and batching breaks,
+/// see [`Renderer::debug`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DrawDebug {
+    /// instances are filled with half transparent color of their group, so overdraw adds up
+    pub color: DebugColor,
+    /// outlines instance quads
+    pub wireframe: bool,
+    /// only first N instances in draw order are drawn, for stepping through draws
+    pub limit: Option<usize>,
+}
+
+/// instances culled for being outside of screen or recorded layer, last frame's
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CullStats {
+    pub submitted: u32,
+    pub culled: u32,
+}
+
+/// sub rect of window with it's own camera, see [`Renderer::begin_viewport`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: Unit,
+    pub y: Unit,
+    pub w: Unit,
+    pub h: Unit,
+    /// px offset from window center that's shown at viewport center
+    pub pan: [f32; 2],
+    /// 1.0 draws at window scale
+    pub zoom: f32,
+}
+
+/// fraction of line box lod text rects cover, approximates glyph ink coverage
+const LOD_TEXT_HEIGHT: f32 = 0.5;
+/// alpha lod text rects are faded by, so collapsed text isn't bolder than glyphs
+const LOD_TEXT_ALPHA: f32 = 0.6;
+
+/// cached layer, see [`Renderer::begin_layer`]
+struct Layer {
+    /// where layer is in layers img
+    rect: Rect,
+    /// x, y, w, h on screen in px
+    px: [f32; 4],
+    /// hash of instances and px rect it was rendered with
+    hash: u64,
+    /// instances to render into layers img in next flush
+    pending: Vec<Vertex>,
+}
+
+/// vertex of tessellated svg path, see path.wgsl
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct PathVertex {
+    pos: [f32; 2],
+    color: [u8; 4],
+}
+
+/// filled path drawn between instances, see [`Renderer::path`]
+struct PathDraw {
+    /// drawn before this instance
+    inst: usize,
+    /// range in `Renderer::path_verts`
+    first: usize,
+    count: usize,
+    z: i32,
+    /// same as [`InstInfo::viewport`]
+    viewport: u32,
+}
+
+/// glyph of cached text layout, with what's needed to make it's instance
+struct TextGlyph {
+    /// same as [`crate::gfx::PlacedGlyph`]
+    pos: [f32; 2],
+    line: u32,
+    /// quad offset from pen and baseline, and size, in font heights
+    off: [f32; 2],
+    size: [f32; 2],
+    /// packed whxy atlas rect
+    tex: u64,
+    color: bool,
+}
+
+/// layout of text drawn recently, see [`Renderer::text`]
+struct CachedText {
+    glyphs: Vec<TextGlyph>,
+    /// lines (or columns) of text
+    lines: usize,
+    ascent: f32,
+    /// drawn this frame, unused layouts are dropped at end of frame
+    used: bool,
+}
+
+/// instance vbos, one per frame in flight
+const INSTANCE_BUFS: usize = 2;
+
+/// persistently mapped instance vbos, instances are written straight into them
+/// instead of being copied in flush, next frame writes into next one while gpu reads last one
+struct Instances<T = Vertex> {
+    /// bufs are named "{name} {i}"
+    name: &'static str,
+    bufs: [BufId; INSTANCE_BUFS],
+    /// mapped memory and capacity of each buf
+    mapped: [(*mut T, usize); INSTANCE_BUFS],
+    /// buf of frame being drawn
+    cur: usize,
+}
+
+impl<T: Copy> Instances<T> {
+    fn new(ctx: &mut RenderCtx, name: &'static str, cap: usize) -> Self {
+        let bufs = std::array::from_fn(|i| {
+            ctx.add_buf_h(
+                &format!("{name} {i}"),
+                (cap * size_of::<T>()) as vk::DeviceSize,
+                BufUsage::VERT,
+                MemProp::CPU_CACHED,
+            )
+        });
+        let mapped = bufs.map(|id| (ctx.map_buf_h(id).cast(), cap));
+        Self {
+            name,
+            bufs,
+            mapped,
+            cur: 0,
+        }
+    }
+
+    /// vbo of frame being drawn
+    fn buf(&self) -> BufId {
+        self.bufs[self.cur]
+    }
+
+    /// grows buf of frame being drawn to `cap` instances, keeping first `len`
+    fn grow(&mut self, ctx: &mut RenderCtx, len: usize, cap: usize) {
+        let kept = self[..len].to_vec();
+        ctx.add_buf(
+            &format!("{} {}", self.name, self.cur),
+            (cap * size_of::<T>()) as vk::DeviceSize,
+            BufUsage::VERT,
+            MemProp::CPU_CACHED,
+        );
+        self.mapped[self.cur] = (ctx.map_buf_h(self.buf()).cast(), cap);
+        self[..len].copy_from_slice(&kept);
+    }
+
+    fn next_frame(&mut self) {
+        self.cur = (self.cur + 1) % INSTANCE_BUFS;
+    }
+}
+
+impl<T> Deref for Instances<T> {
+    type Target = [T];
+    fn deref(&self) -> &Self::Target {
+        let (ptr, cap) = self.mapped[self.cur];
+        // every bit pattern is valid vertex, so mapped memory can be read before it's written
+        unsafe { std::slice::from_raw_parts(ptr, cap) }
+    }
+}
+
+impl<T> DerefMut for Instances<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        let (ptr, cap) = self.mapped[self.cur];
+        unsafe { std::slice::from_raw_parts_mut(ptr, cap) }
+    }
+}
+
+/// instances uploaded once by [`Renderer::bake_batch`]
+struct BakedBatch {
+    buf: BufId,
+    verts: Vec<Vertex>,
+    /// instance ranges changed by batch_mut(), uploaded in flush
+    dirty: Vec<std::ops::Range<usize>>,
+}
+
+/// baked batch drawn between instances, see [`Renderer::draw_batch`]
+struct BatchDraw {
+    /// drawn before this instance
+    inst: usize,
+    buf: BufId,
+    count: usize,
+    /// state batch was drawn with
+    info: InstInfo,
+}
+
+/// draw params saved by [`Renderer::push_state`]
+struct State {
+    color: [u8; 4],
+    roundness: f32,
+    rotation: f32,
+    stroke_width: f32,
+    stroke_color: [u8; 4],
+    stroke_align: StrokeAlign,
+    tex_coord: [u32; 2],
+    blur: f32,
+    sampling: Sampling,
+    color_matrix: u32,
+    tiling: [f32; 2],
+    custom: [f32; 4],
+    mask: (u32, [f32; 2]),
+    info: InstInfo,
+    snap: bool,
+    vertical: bool,
+    areas: Vec<[f32; 4]>,
+    font: String,
+    clip: Option<(u32, [f32; 4])>,
+    viewport: Option<(u32, [f32; 4], [f32; 2], f32)>,
+}
+
+/// renderer whose state is popped when dropped, see [`Renderer::scoped`]
+pub struct Scoped<'a>(&'a mut Renderer);
+
+impl Deref for Scoped<'_> {
+    type Target = Renderer;
+
+    fn deref(&self) -> &Renderer {
+        self.0
+    }
+}
+
+impl DerefMut for Scoped<'_> {
+    fn deref_mut(&mut self) -> &mut Renderer {
+        self.0
+    }
+}
+
+impl Drop for Scoped<'_> {
+    fn drop(&mut self) {
+        self.0.pop_state();
+    }
+}
+
 // modify this in batch.wgsl too
 pub struct Renderer {
     ctx: Arc<Mutex<RenderCtx>>,
     vertices: Vec<Vertex>,
     vert_cnt: usize,
-    instances: Vec<Vertex>,
+    instances: Instances,
+    /// instances converted for [`VertexFormat::Compact`], made once it's first set
+    compact_instances: Option<Instances<CompactVertex>>,
+    vertex_format: VertexFormat,
+    /// first `compact_shaders` of `shaders` have compact variant
+    compact_shaders: usize,
     inst_cnt: usize,
+    inst_infos: Vec<InstInfo>,
+    /// state following instances are drawn with
+    info: InstInfo,
+    /// render shader and custom variants, see [`Renderer::add_shader`]
+    shaders: Vec<String>,
+    /// ids of `shaders` pipelines per blend mode, looked up on first use
+    pipeline_ids: Vec<[Option<PipelineId>; BlendMode::ALL.len()]>,
+    samples: u32,
     pub color: [u8; 4],
     pub roundness: f32,
     pub rotation: f32,
     pub stroke_width: f32,
     pub stroke_color: [u8; 4],
+    pub lod: Lod,
+    /// draw call debug view, applied to whole frame
+    pub debug: DrawDebug,
+    /// instances submitted before debug view changed them
+    debug_submitted: Option<usize>,
+    /// skip instances whose bounds are fully outside of screen or recorded layer
+    pub culling: bool,
+    /// px grid alignment of text glyphs
+    pub hinting: TextHinting,
+    /// text is laid out in columns, see [`Renderer::text_vertical`]
+    vertical: bool,
+    /// shape edges are rounded to whole px, see [`Renderer::snap`]
+    snap: bool,
+    /// px pos pick() asked for this frame
+    pick_pos: Option<[u32; 2]>,
+    /// pick readback buf is written by last frame
+    pick_pending: bool,
+    /// pick id read back last
+    picked: u32,
+    /// instances culled this frame
+    culled: u32,
+    cull_stats: CullStats,
     tex_coord: [u32; 2], // packed whxy
+    blur: f32,
+    sampling: Sampling,
+    stroke_align: StrokeAlign,
+    /// slot of color matrix applied to following shapes, 0 is none
+    color_matrix: u32,
+    /// names of color matrices in slots after empty slot 0
+    color_matrices: Vec<String>,
+    /// times img repeats across shape in x and y
+    tiling: [f32; 2],
+    custom: [f32; 4],
+    /// (first instance, radius) of backdrop blurred instance runs
+    blurs: Vec<(usize, f32)>,
+    /// x, y, w, h of backdrop texels each blur run covers, with blur radius around it
+    blur_regions: Vec<[u32; 4]>,
+    /// (instance, z) render target is copied into grab img before, see [`Renderer::grab_pass`]
+    grabs: Vec<(usize, i32)>,
+    /// (mask flag, mask params)
+    mask: (u32, [f32; 2]),
     areas: Vec<[f32; 4]>,
-    old_color: [u8; 4],
-    old_roundness: f32,
-    old_rotation: f32,
-    old_stroke_width: f32,
-    old_stroke_color: [u8; 4],
-    old_tex_coord: [u32; 2],
+    /// premultiplied
+    clear_color: [f32; 4],
+    /// saved by push_state()
+    states: Vec<State>,
     width: f32,
     height: f32,
+    /// size of window sized imgs (backdrop, layers, pick), can be bigger than window
+    target_size: (u32, u32),
+    scale_factor: f32,
     packer: Guillotine,
-    imgs: HashMap<String, (Tracked<Vec<u8>>, Rect)>,
+    imgs: NameMap<(Tracked<Vec<u8>>, Rect)>,
+    fonts: HashMap<String, Font>,
+    /// (sdf, color) atlas img of each font, color one is "" if font has no color glyphs
+    font_imgs: HashMap<String, (NameId, NameId)>,
+    font: String,
+    /// icon name to (font, codepoint), see [`Renderer::icon`]
+    icons: HashMap<String, (String, char)>,
+    /// by name of atlas img they play in
+    videos: NameMap<Video>,
+    loader: AssetLoader,
+    /// async assets added to atlas in last flush, (name, error)
+    loaded: Vec<(String, Option<String>)>,
+    layers: HashMap<String, Layer>,
+    layer_packer: Guillotine,
+    /// layer being recorded (name, first instance, px rect)
+    layer: Option<(String, usize, [f32; 4])>,
+    viewports: BTreeMap<String, Viewport>,
+    /// viewport being drawn to (scissor idx + 1, px rect, pan, zoom)
+    viewport: Option<(u32, [f32; 4], [f32; 2], f32)>,
+    /// clip rect of following shapes (scissor idx + 1, px rect), see [`Renderer::clip`]
+    clip: Option<(u32, [f32; 4])>,
+    /// px rect of each viewport drawn to this frame
+    scissors: Vec<vk::Rect2D>,
+    /// triangles of paths drawn this frame
+    path_verts: Vec<PathVertex>,
+    paths: Vec<PathDraw>,
+    /// first instance of batch being recorded, see [`Renderer::begin_batch`]
+    batch: Option<usize>,
+    baked: HashMap<String, BakedBatch>,
+    /// text layouts by hash of font, text and writing mode
+    text_cache: HashMap<u64, CachedText>,
+    batches: Vec<BatchDraw>,
 }
 
 impl Renderer {
-    pub fn new(ctx: Arc<Mutex<RenderCtx>>) -> Self {
+    /// `samples` is msaa sample count of render target
+    pub fn new(ctx: Arc<Mutex<RenderCtx>>, samples: u32) -> Self {
         let vertices = vec![Vertex::default(); 1024];
-        let instances = vec![Vertex::default(); 1024];
 
         // TODO: resizable packer
         let packer = Guillotine::new(1024, 1024);
-        {
+        let instances = {
             let mut ctx = ctx.lock().unwrap();
             ctx.add_buf(
                 "batch vbo",
@@ -123,70 +758,646 @@ impl Renderer {
                 BufUsage::VERT,
                 MemProp::CPU_CACHED,
             );
-            ctx.add_buf(
-                "instance vbo",
-                (instances.len() * size_of::<Vertex>()) as vk::DeviceSize,
-                BufUsage::VERT,
-                MemProp::CPU_CACHED,
-            );
+            let instances = Instances::new(&mut ctx, "instance vbo", 1024);
             ctx.add_shader("render");
+            Self::add_render_pipelines(&mut ctx, "render", samples);
             let format = ctx.surface_format.format;
-            ctx.add_pipeline(
-                "render",
-                "render",
-                GraphicsPipelineInfo::new()
-                    .blend_attachment_standard()
-                    .dyn_size()
-                    .samples(MSAA)
-                    .color_attachment(format)
-                    .topology(vk::PrimitiveTopology::TRIANGLE_STRIP),
-                &[(true, vec![])],
-            );
             ctx.add_desc_set("render ds", "render", 0);
             ctx.add_buf(
                 "render ubo",
-                2 * size_of::<f32>() as vk::DeviceSize,
+                8 * size_of::<f32>() as vk::DeviceSize,
                 BufUsage::UNIFORM,
                 MemProp::CPU_CACHED,
             );
+            ctx.write_buf("render ubo", &[0.0f32; 8]);
+            let linear = ctx.linear();
+            ctx.write_buf_off(
+                "render ubo",
+                &(linear as u32),
+                6 * size_of::<f32>() as vk::DeviceSize,
+            );
             ctx.write_ds_buf("render ds", "render ubo", 0);
+            ctx.add_buf(
+                "color matrix ubo",
+                (MAX_COLOR_MATRICES * 20 * size_of::<f32>()) as vk::DeviceSize,
+                BufUsage::UNIFORM,
+                MemProp::CPU_CACHED,
+            );
+            ctx.write_ds_buf("render ds", "color matrix ubo", 6);
             ctx.add_img(
                 "atlas",
                 &ImageInfo::new()
                     .width(packer.width() as u32)
                     .height(packer.height() as u32)
-                    .format(vk::Format::R8G8B8A8_UNORM)
+                    // srgb so sampling decodes it when blending in linear space
+                    .format(if linear {
+                        vk::Format::R8G8B8A8_SRGB
+                    } else {
+                        vk::Format::R8G8B8A8_UNORM
+                    })
                     .usage(ImgUsage::DST | ImgUsage::SAMPLED),
                 MemProp::GPU,
             );
             ctx.add_img_view("atlas view", "atlas");
 
             ctx.write_ds_img("render ds", "atlas view", ImgLayout::SHADER_READ, 1);
-        }
-        Self {
+            ctx.write_ds_sampler("render ds", "linear", 3);
+            ctx.write_ds_sampler("render ds", "nearest", 5);
+
+            // backdrop is blurred by compute passes, each with it's own blur ubo offset
+            ctx.add_shader("backdrop_blur");
+            ctx.set_dynamic_ubo("backdrop_blur", 0, 3);
+            ctx.add_compute("backdrop_blur");
+            ctx.add_buf(
+                "blur ubo",
+                (2 * MAX_BACKDROP_BLURS * BLUR_PASS_STRIDE) as vk::DeviceSize,
+                BufUsage::UNIFORM,
+                MemProp::CPU_CACHED,
+            );
+            for ds in ["blur h ds", "blur v ds"] {
+                ctx.add_desc_set(ds, "backdrop_blur", 0);
+                ctx.write_ds_sampler(ds, "linear", 1);
+                ctx.write_ds_buf_range(
+                    ds,
+                    "blur ubo",
+                    0..size_of::<BlurPass>() as vk::DeviceSize,
+                    3,
+                );
+            }
+
+            // pipelines not needed for plain shapes are built over first frames
+            // layers are rendered without msaa into premultiplied layers img
+            ctx.queue_pipeline(
+                "layer",
+                "render",
+                GraphicsPipelineInfo::new()
+                    .blend_attachment_premultiplied()
+                    .dyn_size()
+                    .color_attachment(format)
+                    .topology(vk::PrimitiveTopology::TRIANGLE_STRIP),
+                &[(true, vec![])],
+            );
+            ctx.add_desc_set("layer ds", "render", 0);
+            ctx.write_ds_buf("layer ds", "render ubo", 0);
+            ctx.write_ds_buf("layer ds", "color matrix ubo", 6);
+            ctx.write_ds_img("layer ds", "atlas view", ImgLayout::SHADER_READ, 1);
+            ctx.write_ds_sampler("layer ds", "linear", 3);
+            ctx.write_ds_sampler("layer ds", "nearest", 5);
+
+            // svg paths are tessellated into triangles on cpu
+            ctx.add_buf(
+                "path vbo",
+                (1024 * size_of::<PathVertex>()) as vk::DeviceSize,
+                BufUsage::VERT,
+                MemProp::CPU_CACHED,
+            );
+            ctx.add_shader("path");
+            ctx.queue_pipeline(
+                "path",
+                "path",
+                GraphicsPipelineInfo::new()
+                    .blend_attachment_standard()
+                    .dyn_size()
+                    .samples(samples)
+                    .color_attachment(format)
+                    .topology(vk::PrimitiveTopology::TRIANGLE_LIST),
+                &[(false, vec![])],
+            );
+            ctx.add_desc_set("path ds", "path", 0);
+            ctx.write_ds_buf("path ds", "render ubo", 0);
+            instances
+        };
+        let mut renderer = Self {
             ctx,
             vertices,
             vert_cnt: 0,
+            inst_infos: vec![InstInfo::default(); instances.len()],
             instances,
+            compact_instances: None,
+            vertex_format: VertexFormat::Full,
+            compact_shaders: 0,
             inst_cnt: 0,
+            info: InstInfo::default(),
+            shaders: vec!["render".to_string()],
+            pipeline_ids: vec![[None; BlendMode::ALL.len()]],
+            samples,
             color: [255, 255, 255, 255],
             roundness: 0.0,
             rotation: 0.0,
             stroke_width: 0.0,
             stroke_color: [0, 0, 0, 0],
+            lod: Lod::default(),
+            debug: DrawDebug::default(),
+            debug_submitted: None,
+            culling: true,
+            hinting: TextHinting::default(),
+            vertical: false,
+            snap: false,
+            pick_pos: None,
+            pick_pending: false,
+            picked: 0,
+            culled: 0,
+            cull_stats: CullStats::default(),
             tex_coord: [0, 0],
-            old_color: [255, 255, 255, 255],
-            old_roundness: 0.0,
-            old_rotation: 0.0,
-            old_stroke_width: 0.0,
-            old_stroke_color: [0, 0, 0, 0],
-            old_tex_coord: [0, 0],
+            blur: 0.0,
+            sampling: Sampling::Linear,
+            stroke_align: StrokeAlign::Inner,
+            color_matrix: 0,
+            color_matrices: Vec::new(),
+            tiling: [1.0; 2],
+            custom: [0.0; 4],
+            blurs: Vec::new(),
+            blur_regions: Vec::new(),
+            grabs: Vec::new(),
+            mask: (0, [0.0; 2]),
+            states: Vec::new(),
             areas: Vec::new(),
+            clear_color: [0.0; 4],
             width: 0.0,
             height: 0.0,
+            scale_factor: 1.0,
             packer,
-            imgs: HashMap::new(),
+            imgs: NameMap::default(),
+            fonts: HashMap::new(),
+            font_imgs: HashMap::new(),
+            font: String::new(),
+            icons: HashMap::new(),
+            videos: NameMap::default(),
+            loader: AssetLoader::default(),
+            loaded: Vec::new(),
+            layers: HashMap::new(),
+            layer_packer: Guillotine::new(1, 1),
+            target_size: (0, 0),
+            layer: None,
+            viewports: BTreeMap::new(),
+            clip: None,
+            viewport: None,
+            scissors: Vec::new(),
+            path_verts: Vec::new(),
+            batch: None,
+            baked: HashMap::new(),
+            text_cache: HashMap::new(),
+            batches: Vec::new(),
+            paths: Vec::new(),
+        };
+        renderer.add_color_matrix("grayscale", ColorMatrix::grayscale());
+        renderer.add_color_matrix("sepia", ColorMatrix::sepia());
+        renderer.add_color_matrix("invert", ColorMatrix::invert());
+        renderer
+    }
+
+    /// pipeline for each blend mode made from render `shader`,
+    /// only alpha blended one is built right away, others are queued
+    fn add_render_pipelines(ctx: &mut RenderCtx, shader: &str, samples: u32) {
+        let format = ctx.surface_format.format;
+        for blend in BlendMode::ALL {
+            let info = GraphicsPipelineInfo::new();
+            let info = match blend {
+                BlendMode::Alpha => info.blend_attachment_standard(),
+                BlendMode::Additive => info.blend_attachment_additive(),
+                BlendMode::Multiply => info.blend_attachment_multiply(),
+                BlendMode::Premultiplied => info.blend_attachment_over_premultiplied(),
+            };
+            let info = info
+                .dyn_size()
+                .samples(samples)
+                .color_attachment(format)
+                .topology(vk::PrimitiveTopology::TRIANGLE_STRIP);
+            let name = blend.pipeline(shader);
+            if blend == BlendMode::Alpha {
+                ctx.add_pipeline(&name, shader, info, &[(true, vec![])]);
+            } else {
+                ctx.queue_pipeline(&name, shader, info, &[(true, vec![])]);
+            }
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// physical pixels per `Dp`
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    pub(crate) fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// color render target is cleared to each frame, transparent black by default.
+    /// alpha < 255 shows what's behind window if it's transparent, see `EngineConfig::transparent`
+    pub fn clear_color(&mut self, r: u8, g: u8, b: u8, a: u8) {
+        let a = a as f32 / 255.0;
+        // clear values of srgb targets are linear
+        let linear = self.ctx.lock().unwrap().linear();
+        let [r, g, b] = [r, g, b].map(|c| {
+            let c = c as f32 / 255.0;
+            if linear { srgb_to_linear(c) } else { c }
+        });
+        self.clear_color = [r * a, g * a, b * a, a];
+    }
+
+    pub(crate) fn clear_value(&self) -> [f32; 4] {
+        self.clear_color
+    }
+
+    /// dither strength applied to batch output to reduce banding, 1.0 is +-0.5/255 noise
+    pub fn dither(&mut self, strength: f32) {
+        self.write_dither(&mut self.ctx.lock().unwrap(), strength);
+    }
+
+    pub(crate) fn write_dither(&self, ctx: &mut RenderCtx, strength: f32) {
+        ctx.write_buf_off(
+            "render ubo",
+            &strength,
+            2 * size_of::<f32>() as vk::DeviceSize,
+        );
+    }
+
+    /// msaa sample count of render target
+    pub(crate) fn set_samples(&mut self, samples: u32) {
+        self.samples = samples;
+        let mut ctx = self.ctx.lock().unwrap();
+        let compact = self.shaders[..self.compact_shaders]
+            .iter()
+            .map(|s| VertexFormat::Compact.shader(s));
+        for shader in self.shaders.iter().cloned().chain(compact) {
+            for blend in BlendMode::ALL {
+                ctx.set_pipeline_samples(&blend.pipeline(&shader), samples);
+            }
+        }
+        ctx.set_pipeline_samples("path", samples);
+    }
+
+    /// subpixel offset in px everything is rendered at, used by taa
+    pub(crate) fn jitter(&mut self, ctx: &mut RenderCtx, [x, y]: [f32; 2]) {
+        let jitter = [x * 2.0 / self.width, y * 2.0 / self.height];
+        ctx.write_buf_off(
+            "render ubo",
+            &jitter,
+            4 * size_of::<f32>() as vk::DeviceSize,
+        );
+    }
+
+    /// following shapes are filled with everything drawn beneath them blurred by radius px
+    /// (tinted by color), 0.0 disables it
+    pub fn backdrop_blur(&mut self, radius: f32) {
+        self.blur = radius;
+    }
+
+    /// copies everything drawn so far this frame (by z order) into grab img,
+    /// custom shaders drawn after it can sample it with `grabbed(px_pos)`
+    /// for distortion and refraction, see add_shader()
+    pub fn grab_pass(&mut self) {
+        assert!(self.layer.is_none(), "grab_pass() can't be used in layers");
+        assert!(self.batch.is_none(), "grab_pass() can't be used in batches");
+        let mut ctx = self.ctx.lock().unwrap();
+        if ctx.try_img("grab").is_err() {
+            let (width, height) = self.target_size;
+            Self::add_grab_img(&mut ctx, width, height);
+        }
+        self.grabs.push((self.inst_cnt, self.info.z));
+    }
+
+    fn add_grab_img(ctx: &mut RenderCtx, width: u32, height: u32) {
+        ctx.try_remove_img("grab");
+        ctx.add_img(
+            "grab",
+            &ImageInfo::new()
+                .width(width)
+                .height(height)
+                .format(BACKDROP_FORMAT)
+                .usage(ImgUsage::SAMPLED | ImgUsage::DST),
+            MemProp::GPU,
+        );
+        ctx.add_img_view("grab view", "grab");
+        ctx.write_ds_img("render ds", "grab view", ImgLayout::SHADER_READ, 7);
+    }
+
+    /// only shows `progress` (0-1) of following shapes/imgs, revealing them in `dir`
+    pub fn reveal(&mut self, progress: f32, dir: Reveal) {
+        self.mask = (MASK_REVEAL, [progress.clamp(0.0, 1.0), dir as u32 as f32]);
+    }
+
+    /// filtering of following imgs and text, nearest keeps pixel art sharp
+    pub fn sampling(&mut self, sampling: Sampling) {
+        self.sampling = sampling;
+    }
+
+    /// adds color matrix that can be applied to following shapes with color_matrix(),
+    /// "grayscale", "sepia" and "invert" are added by default
+    pub fn add_color_matrix(&mut self, name: &str, matrix: ColorMatrix) {
+        assert!(
+            !self.color_matrices.iter().any(|m| m == name),
+            "color matrix already exists: {name}"
+        );
+        assert!(
+            self.color_matrices.len() + 1 < MAX_COLOR_MATRICES,
+            "too many color matrices, max is {}",
+            MAX_COLOR_MATRICES - 1
+        );
+        self.color_matrices.push(name.to_string());
+        self.set_color_matrix(name, matrix);
+    }
+
+    /// changes color matrix `name`, shapes drawn with it this frame change too
+    pub fn set_color_matrix(&mut self, name: &str, matrix: ColorMatrix) {
+        let slot = self.color_matrix_slot(name);
+        self.ctx.lock().unwrap().write_buf_off(
+            "color matrix ubo",
+            &matrix.to_cols(),
+            (slot as usize * 20 * size_of::<f32>()) as vk::DeviceSize,
+        );
+    }
+
+    /// following shapes, imgs and text have their color transformed by color matrix `name`,
+    /// like grayscale for disabled icons or hue_rotate() on hover
+    pub fn color_matrix(&mut self, name: &str) {
+        self.color_matrix = self.color_matrix_slot(name);
+    }
+
+    pub fn no_color_matrix(&mut self) {
+        self.color_matrix = 0;
+    }
+
+    fn color_matrix_slot(&self, name: &str) -> u32 {
+        let i = self
+            .color_matrices
+            .iter()
+            .position(|m| m == name)
+            .unwrap_or_else(|| panic!("color matrix not found: {name}"));
+        i as u32 + 1
+    }
+
+    /// stops revealing shapes set by reveal()
+    pub fn no_reveal(&mut self) {
+        self.mask = (0, [0.0; 2]);
+    }
+
+    /// blend mode of following shapes, ignored inside layers
+    pub fn blend(&mut self, mode: BlendMode) {
+        self.info.blend = mode;
+    }
+
+    /// adds variant of render shader where `snippet` replaces it's default
+    /// `fn shade(col: vec4f, uv: vec2f, pos: vec2f) -> vec4f { return col; }`,
+    /// which gets shape's color, uv (-1 to 1) and px pos and returns final color,
+    /// shape's params set with custom() are in private `custom: vec4f`,
+    /// it can't add bindings, use it with shader()
+    pub fn add_shader(&mut self, name: &str, snippet: &str) {
+        let shader = format!("render {name}");
+        assert!(
+            !self.shaders.contains(&shader),
+            "shader already exists: {name}"
+        );
+        let source = vfs().read_to_string("shaders/render.wgsl").unwrap();
+        assert!(source.contains(SHADE_HOOK), "render.wgsl has no shade fn");
+        let source = source.replace(SHADE_HOOK, snippet);
+        vfs_mut().mount_file(&format!("shaders/{shader}.wgsl"), source.into_bytes());
+        let mut ctx = self.ctx.lock().unwrap();
+        ctx.add_shader(&shader);
+        Self::add_render_pipelines(&mut ctx, &shader, self.samples);
+        if self.vertex_format == VertexFormat::Compact {
+            Self::add_compact_shader(&mut ctx, &shader, self.samples);
+            self.compact_shaders += 1;
+        }
+        self.shaders.push(shader);
+        self.pipeline_ids.push([None; BlendMode::ALL.len()]);
+    }
+
+    /// variant of render `shader` whose vs_main reads [`CompactVertex`]
+    fn add_compact_shader(ctx: &mut RenderCtx, shader: &str, samples: u32) {
+        let source = vfs()
+            .read_to_string(&format!("shaders/{shader}.wgsl"))
+            .unwrap();
+        assert!(source.contains(VS_MAIN), "{shader}.wgsl has no vs_main");
+        let source = source.replace(VS_MAIN, COMPACT_VS_MAIN);
+        let variant = VertexFormat::Compact.shader(shader);
+        vfs_mut().mount_file(&format!("shaders/{variant}.wgsl"), source.into_bytes());
+        ctx.add_shader(&variant);
+        Self::add_render_pipelines(ctx, &variant, samples);
+    }
+
+    /// instance layout of following frames, compact one nearly halves instance upload
+    /// bandwidth at cost of precision, see [`VertexFormat::Compact`].
+    /// picking and cached layers always use full vertices
+    pub fn set_vertex_format(&mut self, format: VertexFormat) {
+        if format == self.vertex_format {
+            return;
+        }
+        self.vertex_format = format;
+        self.pipeline_ids.fill([None; BlendMode::ALL.len()]);
+        let mut ctx = self.ctx.lock().unwrap();
+        if format == VertexFormat::Compact {
+            for shader in &self.shaders[self.compact_shaders..] {
+                Self::add_compact_shader(&mut ctx, shader, self.samples);
+            }
+            self.compact_shaders = self.shaders.len();
+            if self.compact_instances.is_none() {
+                let cap = self.instances.len();
+                self.compact_instances =
+                    Some(Instances::new(&mut ctx, "compact instance vbo", cap));
+            }
+        }
+        if !self.baked.is_empty() {
+            // baked batches are reuploaded in new format, old bufs may still be used
+            queue_idle();
+            for (name, batch) in self.baked.iter_mut() {
+                ctx.remove_buf(&format!("baked batch {name}"));
+                let buf = Self::upload_baked(&mut ctx, format, name, &batch.verts);
+                // batches already drawn this frame are drawn from new buf
+                for draw in self.batches.iter_mut().filter(|d| d.buf == batch.buf) {
+                    draw.buf = buf;
+                }
+                batch.buf = buf;
+            }
+        }
+    }
+
+    pub fn vertex_format(&self) -> VertexFormat {
+        self.vertex_format
+    }
+
+    /// creates gpu only buf of baked batch `name` holding `verts` in `format`
+    fn upload_baked(
+        ctx: &mut RenderCtx,
+        format: VertexFormat,
+        name: &str,
+        verts: &[Vertex],
+    ) -> BufId {
+        let buf = format!("baked batch {name}");
+        ctx.add_buf(
+            &buf,
+            (verts.len() * format.size()) as vk::DeviceSize,
+            BufUsage::VERT | BufUsage::DST,
+            MemProp::GPU,
+        );
+        match format {
+            VertexFormat::Full => ctx.write_buf(&buf, verts),
+            VertexFormat::Compact => {
+                let verts: Vec<CompactVertex> = verts.iter().map(Into::into).collect();
+                ctx.write_buf(&buf, &verts[..]);
+            }
         }
+        ctx.buf_id(&buf)
+    }
+
+    /// following shapes are drawn with custom shader made with add_shader(), ignored inside layers
+    pub fn shader(&mut self, name: &str) {
+        let shader = format!("render {name}");
+        self.info.shader = self
+            .shaders
+            .iter()
+            .position(|s| *s == shader)
+            .unwrap_or_else(|| panic!("shader not found: {name}"));
+    }
+
+    /// following shapes are drawn with default render shader
+    pub fn no_shader(&mut self) {
+        self.info.shader = 0;
+    }
+
+    /// params of following shapes passed to custom shaders as `custom`, see add_shader()
+    pub fn custom(&mut self, a: f32, b: f32, c: f32, d: f32) {
+        self.custom = [a, b, c, d];
+    }
+
+    /// following shapes are reported by pick() with `id`, 0 makes them unpickable (default)
+    pub fn pick_id(&mut self, id: u32) {
+        self.info.pick_id = id;
+    }
+
+    /// pick id of topmost shape at x, y, None if there is no pickable shape.
+    /// ids are rendered into separate target only on frames this is called
+    /// and read back without stalling, so result is from a previous frame
+    pub fn pick(&mut self, x: Unit, y: Unit) -> Option<u32> {
+        let (x, y) = (self.px_x(x), self.px_y(y));
+        if x >= 0.0 && y >= 0.0 && x < self.width && y < self.height {
+            let mut ctx = self.ctx.lock().unwrap();
+            if ctx.try_pipeline("pick").is_err() {
+                let (width, height) = self.target_size;
+                Self::init_picking(&mut ctx, width, height);
+            }
+            self.pick_pos = Some([x as u32, y as u32]);
+        }
+        (self.picked != 0).then_some(self.picked)
+    }
+
+    fn init_picking(ctx: &mut RenderCtx, width: u32, height: u32) {
+        ctx.add_shader("pick");
+        ctx.add_pipeline(
+            "pick",
+            "pick",
+            GraphicsPipelineInfo::new()
+                .blend_attachment_empty()
+                .dyn_size()
+                .color_attachment(vk::Format::R32_UINT)
+                .topology(vk::PrimitiveTopology::TRIANGLE_STRIP),
+            // pick ids are in separate vbo
+            &[(true, vec![]), (true, vec![12])],
+        );
+        ctx.add_desc_set("pick ds", "pick", 0);
+        ctx.write_ds_buf("pick ds", "render ubo", 0);
+        ctx.add_buf(
+            "pick readback",
+            size_of::<u32>() as vk::DeviceSize,
+            BufUsage::DST,
+            MemProp::CPU_CACHED,
+        );
+        Self::add_pick_img(ctx, width, height);
+    }
+
+    fn add_pick_img(ctx: &mut RenderCtx, width: u32, height: u32) {
+        ctx.try_remove_img("pick");
+        ctx.add_img(
+            "pick",
+            &ImageInfo::new()
+                .width(width)
+                .height(height)
+                .format(vk::Format::R32_UINT)
+                .usage(ImgUsage::COLOR | ImgUsage::SRC),
+            MemProp::GPU,
+        );
+        ctx.add_img_view("pick view", "pick");
+    }
+
+    /// renders pick ids into pixel at `pos` of pick img and copies it to pick readback buf
+    fn render_pick(ctx: &mut RenderCtx, instances: BufId, inst_cnt: usize, [x, y]: [u32; 2]) {
+        let (width, height, img_view, sampled_img_view) = ctx.render_target();
+        ctx.end_render();
+        ctx.set_img_layout(
+            "pick",
+            ImgLayout::COLOR,
+            vk::PipelineStageFlags2::TRANSFER,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            vk::AccessFlags2::NONE,
+            vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+        );
+        ctx.begin_render(width, height, "pick view", "");
+        ctx.bind_pipeline("pick");
+        ctx.bind_ds("pick ds");
+        ctx.set_scissor(vk::Rect2D {
+            offset: vk::Offset2D {
+                x: x as i32,
+                y: y as i32,
+            },
+            extent: vk::Extent2D {
+                width: 1,
+                height: 1,
+            },
+        });
+        if inst_cnt != 0 {
+            let ids = ctx.buf_id("pick id vbo");
+            ctx.bind_vbos_h(&[instances, ids]);
+            ctx.draw(4, inst_cnt as u32);
+        }
+        ctx.end_render();
+        ctx.set_img_layout(
+            "pick",
+            ImgLayout::SRC,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            vk::PipelineStageFlags2::TRANSFER,
+            vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+            vk::AccessFlags2::TRANSFER_READ,
+        );
+        ctx.copy_img_to_buf(
+            "pick",
+            "pick readback",
+            &[BufferImageCopy {
+                buf_off: 0,
+                img_off_x: x,
+                img_off_y: y,
+                buf_width: 1,
+                buf_height: 1,
+            }],
+        );
+        ctx.host_read_barrier();
+        ctx.resume_render(width, height, img_view, sampled_img_view);
+        ctx.set_scissor(vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent: vk::Extent2D { width, height },
+        });
+    }
+
+    /// following shapes are drawn on top of ones with lower z,
+    /// same z keeps submission order, resets to 0 each frame
+    pub fn z(&mut self, depth: i32) {
+        self.info.z = depth;
+    }
+
+    /// fill color of following shapes, takes [`Color`], [`Hsv`], [`Hsl`], [`Oklab`], `[u8; 4]`...
+    pub fn color(&mut self, color: impl Into<Color>) {
+        self.color = color.into().into();
+    }
+
+    /// stroke color of following shapes, see [`Renderer::color`]
+    pub fn stroke(&mut self, color: impl Into<Color>) {
+        self.stroke_color = color.into().into();
     }
 
     pub fn rgb(&mut self, r: u8, g: u8, b: u8) {
@@ -201,6 +1412,12 @@ impl Renderer {
         self.color = hex.to_be_bytes()
     }
 
+    /// whether stroke of following shapes is inside, centered on or outside of their edge,
+    /// centered and outer strokes make shapes cover more than their rect
+    pub fn stroke_align(&mut self, align: StrokeAlign) {
+        self.stroke_align = align;
+    }
+
     pub fn stroke_rgb(&mut self, r: u8, g: u8, b: u8) {
         self.color = [r, g, b, 255];
     }
@@ -214,11 +1431,11 @@ impl Renderer {
     }
 
     pub fn add_img(&mut self, name: &str, width: u32, height: u32) -> &mut Tracked<Vec<u8>> {
-        assert!(!self.imgs.contains_key(name), "img already in atlas");
+        assert!(!self.has_img(name), "img already in atlas");
         if let Some((x, y)) = self.packer.pack(width as u16, height as u16) {
             let tracked_img_data = &mut self
                 .imgs
-                .entry(name.to_string())
+                .entry(NameId::new(name))
                 .or_insert((
                     Tracked::new(vec![0; width as usize * height as usize * 4]),
                     Rect::new(x, y, width as u16, height as u16),
@@ -230,26 +1447,610 @@ impl Renderer {
         }
     }
 
+    /// adds grayscale atlas img from `f(uv)`, uv is in [0, 1] and result is clamped to [0, 1],
+    /// e.g. `bake_noise("clouds", 256, 256, |uv| (uv * 8.0).fbm_with(5, Noise::perlin) * 0.5 + 0.5)`
+    pub fn bake_noise(
+        &mut self,
+        name: &str,
+        width: u32,
+        height: u32,
+        f: impl Fn(Vec2) -> f32,
+    ) -> &mut Tracked<Vec<u8>> {
+        let img = self.add_img(name, width, height);
+        for (i, px) in img.chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i as u32 % width, i as u32 / width);
+            let uv = Vec2::new(x as f32 / width as f32, y as f32 / height as f32);
+            let v = (f(uv).clamp(0.0, 1.0) * 255.0).round() as u8;
+            px.copy_from_slice(&[v, v, v, 255]);
+        }
+        img
+    }
+
     pub fn load_img(&mut self, name: &str) -> &mut Tracked<Vec<u8>> {
+        let img_data = Self::decode_img(name);
+        self.insert_img(name, img_data)
+    }
+
+    /// decodes img on a background thread, it's added to atlas in flush once ready
+    pub fn load_img_async(&mut self, name: &str) -> Handle<Image> {
+        assert!(!self.has_img(name), "img already in atlas");
+        let file = name.to_string();
+        self.loader
+            .spawn(name, move || Asset::Img(Self::decode_img(&file)));
+        Handle::new(name)
+    }
+
+    fn decode_img(name: &str) -> ImageData {
         let mut img_data = ImageLoader::load(name);
         if img_data.channels != 4 {
             img_data.img = ImageLoader::make4(&mut img_data.img);
         }
+        img_data
+    }
+
+    fn insert_img(&mut self, name: &str, img_data: ImageData) -> &mut Tracked<Vec<u8>> {
         let tracked_img_data = self.add_img(name, img_data.width, img_data.height);
         tracked_img_data.copy_from_slice(&img_data.img);
         tracked_img_data
     }
 
-    pub fn img(&mut self, name: &str) -> &mut Tracked<Vec<u8>> {
-        let img_data = self
-            .imgs
-            .get_mut(name)
-            .unwrap_or_else(|| panic!("img not found in atlas: {name}"));
+    /// async asset finished loading and was added to atlas
+    pub fn loaded<T>(&self, handle: &Handle<T>) -> bool {
+        let name = handle.name();
+        !self.loader.is_pending(name) && (self.has_img(name) || self.fonts.contains_key(name))
+    }
+
+    fn has_img(&self, name: &str) -> bool {
+        NameId::find(name).is_some_and(|n| self.imgs.contains_key(&n))
+    }
+
+    /// async assets that finished loading in last flush, (name, error)
+    pub(crate) fn take_loaded(&mut self) -> Vec<(String, Option<String>)> {
+        std::mem::take(&mut self.loaded)
+    }
+
+    /// `name` can be [`NameId`] to skip hashing it every frame
+    pub fn img(&mut self, name: impl AsName) -> &mut Tracked<Vec<u8>> {
+        let img_data = name
+            .find()
+            .and_then(|n| self.imgs.get_mut(&n))
+            .unwrap_or_else(|| panic!("img not found in atlas: {}", name.name()));
         let r = img_data.1.packed_whxy();
         self.tex_coord = [(r >> 32) as u32, r as u32];
         &mut img_data.0
     }
 
+    /// like `img`, but only maps uv sub rect (0-1, top-left to bottom-right) of it onto shapes,
+    /// for sprite sheets, uvs are rounded to whole texels
+    pub fn img_uv(
+        &mut self,
+        name: impl AsName,
+        u0: f32,
+        v0: f32,
+        u1: f32,
+        v1: f32,
+    ) -> &mut Tracked<Vec<u8>> {
+        let img_data = name
+            .find()
+            .and_then(|n| self.imgs.get_mut(&n))
+            .unwrap_or_else(|| panic!("img not found in atlas: {}", name.name()));
+        let (x, y, w, h) = img_data.1.xywh();
+        let texel = |t: f32, size: u16| (t.clamp(0.0, 1.0) * size as f32).round() as u16;
+        let (x0, x1) = (texel(u0.min(u1), w), texel(u0.max(u1), w));
+        let (y0, y1) = (texel(v0.min(v1), h), texel(v0.max(v1), h));
+        let (x0, y0) = (x0.min(w - 1), y0.min(h - 1));
+        let r = Rect::new(x + x0, y + y0, (x1 - x0).max(1), (y1 - y0).max(1)).packed_whxy();
+        self.tex_coord = [(r >> 32) as u32, r as u32];
+        &mut img_data.0
+    }
+
+    /// following imgs repeat `x` by `y` times across shapes instead of stretching,
+    /// [1.0, 1.0] draws img once
+    pub fn tiling(&mut self, x: f32, y: f32) {
+        self.tiling = [x, y];
+    }
+
+    /// following shapes are untextured again after `img`
+    pub fn no_img(&mut self) {
+        self.tex_coord = [0, 0];
+    }
+
+    /// adds atlas img `name` that shows mjpeg video frames as it plays, see [`Video::new`]
+    pub fn add_video(&mut self, name: &str, file: &str, fps: f32) -> &mut Video {
+        let id = NameId::new(name);
+        assert!(
+            !self.videos.contains_key(&id),
+            "video already added: {name}"
+        );
+        let video = Video::new(file, fps);
+        self.add_img(name, video.width(), video.height());
+        self.videos.entry(id).or_insert(video)
+    }
+
+    pub fn video(&mut self, name: &str) -> &mut Video {
+        NameId::find(name)
+            .and_then(|id| self.videos.get_mut(&id))
+            .unwrap_or_else(|| panic!("video not found: {name}"))
+    }
+
+    /// loads font and packs it's sdf into atlas, first added font becomes current font
+    pub fn add_font(&mut self, name: &str, char_size_px: u32) {
+        assert!(!self.fonts.contains_key(name), "font already added: {name}");
+        let font = Font::new(name, char_size_px);
+        self.insert_font(name, font);
+    }
+
+    /// like add_font, but packs every glyph of font, for text shaped with `shaping` feature
+    pub fn add_font_all(&mut self, name: &str, char_size_px: u32) {
+        assert!(!self.fonts.contains_key(name), "font already added: {name}");
+        let font = Font::all(name, char_size_px);
+        self.insert_font(name, font);
+    }
+
+    /// generates font sdf on a background thread, it's added to atlas in flush once ready
+    pub fn add_font_async(&mut self, name: &str, char_size_px: u32) -> Handle<Font> {
+        assert!(!self.fonts.contains_key(name), "font already added: {name}");
+        let file = name.to_string();
+        self.loader
+            .spawn(name, move || Asset::Font(Font::new(&file, char_size_px)));
+        Handle::new(name)
+    }
+
+    fn insert_font(&mut self, name: &str, font: Font) {
+        let dim = font.sdf_dim;
+        let sdf_img = format!("font {name}");
+        let img = self.add_img(&sdf_img, dim, dim);
+        // sdf bitmap is y-up, atlas is y-down
+        for (y, row) in font.sdf.chunks_exact(dim as usize).rev().enumerate() {
+            for (x, &a) in row.iter().enumerate() {
+                let i = (y * dim as usize + x) * 4;
+                img[i..i + 4].copy_from_slice(&[255, 255, 255, a]);
+            }
+        }
+        let mut color_img = NameId::default();
+        if font.color_dim != 0 {
+            let dim = font.color_dim;
+            color_img = NameId::new(&format!("font {name} color"));
+            let img = self.add_img(&color_img, dim, dim);
+            for (y, row) in font.color.chunks_exact(dim as usize * 4).rev().enumerate() {
+                let i = y * dim as usize * 4;
+                img[i..i + row.len()].copy_from_slice(row);
+            }
+        }
+        if self.font.is_empty() {
+            self.font = name.to_string();
+        }
+        self.fonts.insert(name.to_string(), font);
+        self.font_imgs
+            .insert(name.to_string(), (NameId::new(&sdf_img), color_img));
+    }
+
+    /// loads private use area glyphs of icon font, doesn't change current font.
+    /// icons in `fonts/{name}.codepoints` (`icon_name codepoint_hex` lines) are added too
+    pub fn add_icon_font(&mut self, name: &str, char_size_px: u32) {
+        assert!(!self.fonts.contains_key(name), "font already added: {name}");
+        let font = std::mem::take(&mut self.font);
+        self.insert_font(name, Font::icons(name, char_size_px));
+        self.font = font;
+        if let Ok(codepoints) = vfs().read_to_string(&format!("fonts/{name}.codepoints")) {
+            for (icon, c) in Font::parse_codepoints(&codepoints) {
+                self.icons.insert(icon, (name.to_string(), c));
+            }
+        }
+    }
+
+    /// names `icons` (name, codepoint) of `font` for [`Renderer::icon`]
+    pub fn add_icons(&mut self, font: &str, icons: &[(&str, char)]) {
+        assert!(self.fonts.contains_key(font), "font not found: {font}");
+        for &(icon, c) in icons {
+            self.icons.insert(icon.to_string(), (font.to_string(), c));
+        }
+    }
+
+    /// draws icon centered in size x size square with top-left at x, y.
+    /// it's centered by glyph bounds, not baseline, since icons don't sit on text lines
+    pub fn icon(&mut self, name: &str, x: Unit, y: Unit, size: Unit) {
+        self.try_icon(name, x, y, size)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    pub fn try_icon(&mut self, name: &str, x: Unit, y: Unit, size: Unit) -> Result<()> {
+        let (font, c) = self
+            .icons
+            .get(name)
+            .ok_or_else(|| Error::not_found("icon", name))?;
+        let g = *self
+            .fonts
+            .get(font)
+            .ok_or_else(|| Error::not_found("font", font))?
+            .glyph(*c)
+            .ok_or_else(|| Error::not_found("icon glyph", name))?;
+        let (sdf_img, color_img) = &self.font_imgs[font];
+        let (img, dim) = if g.color {
+            (color_img, self.fonts[font].color_dim)
+        } else {
+            (sdf_img, self.fonts[font].sdf_dim)
+        };
+        let dim = dim as u16;
+        let (ax, ay, ..) = self.imgs[img].1.xywh();
+        let (gx, gy, gw, gh) = g.rect.xywh();
+        let tex = Rect::new(ax + gx, ay + dim - gy - gh, gw, gh).packed_whxy();
+        let (x, y, size) = (self.px_x(x), self.px_y(y), self.px_y(size));
+        // glyph quad is padded evenly around glyph bounds, so their centers match
+        let (w, h) = (g.size[0] * size * 0.5, g.size[1] * size * 0.5);
+        let (cx, cy) = (x + size * 0.5, y + size * 0.5);
+        let old_tex_coord =
+            std::mem::replace(&mut self.tex_coord, [(tex >> 32) as u32, tex as u32]);
+        let old_tiling = std::mem::replace(&mut self.tiling, [1.0; 2]);
+        let old_color = self.color;
+        if g.color {
+            self.color = [255, 255, 255, old_color[3]];
+        }
+        let (rw, rh) = (self.width, self.height);
+        self.instance(cx / rw, cy / rh, w / rw, h / rh);
+        self.tex_coord = old_tex_coord;
+        self.tiling = old_tiling;
+        self.color = old_color;
+        Ok(())
+    }
+
+    pub fn font(&mut self, name: &str) {
+        assert!(self.fonts.contains_key(name), "font not found: {name}");
+        self.font = name.to_string();
+    }
+
+    pub fn try_font(&mut self, name: &str) -> Result<()> {
+        if !self.fonts.contains_key(name) {
+            return Err(Error::not_found("font", name));
+        }
+        self.font = name.to_string();
+        Ok(())
+    }
+
+    pub fn current_font(&self) -> &str {
+        &self.font
+    }
+
+    pub fn get_font(&self, name: &str) -> &Font {
+        self.fonts
+            .get(name)
+            .unwrap_or_else(|| panic!("font not found: {name}"))
+    }
+
+    /// draws text with top-left at x, y with line height of size
+    /// text smaller than `lod.text` px is drawn as a rect per line
+    /// errors instead of panicking if current font doesn't exist
+    pub fn try_text(&mut self, text: &str, x: Unit, y: Unit, size: Unit) -> Result<()> {
+        if !self.fonts.contains_key(&self.font) {
+            return Err(Error::not_found("font", &self.font));
+        }
+        self.text(text, x, y, size);
+        Ok(())
+    }
+
+    /// lays out following text in columns going top to bottom, right to left,
+    /// like vertical cjk text, every '\n' starts new column to the left.
+    /// x, y stays top-left of text, carets of text_caret_positions() sit on top of chars
+    pub fn text_vertical(&mut self, vertical: bool) {
+        self.vertical = vertical;
+    }
+
+    /// laid out glyphs of `text` in current font and writing mode, with their atlas rects
+    fn layout_text(&self, text: &str) -> CachedText {
+        let font = self
+            .fonts
+            .get(&self.font)
+            .unwrap_or_else(|| panic!("font not found: {}", self.font));
+        let (sdf_img, color_img) = &self.font_imgs[&self.font];
+        let (ax, ay, ..) = self.imgs[sdf_img].1.xywh();
+        let dim = font.sdf_dim as u16;
+        let color_img = self.imgs.get(color_img);
+        let (cx, cy, ..) = color_img.map_or((0, 0, 0, 0), |i| i.1.xywh());
+        let color_dim = font.color_dim as u16;
+        let layout = if self.vertical {
+            font.layout_vertical(text)
+        } else {
+            font.layout(text)
+        };
+        let glyphs = layout
+            .into_iter()
+            .filter_map(|placed| {
+                let g = font.glyph_idx(placed.glyph)?;
+                let (gx, gy, gw, gh) = g.rect.xywh();
+                let tex = if g.color {
+                    Rect::new(cx + gx, cy + color_dim - gy - gh, gw, gh)
+                } else {
+                    Rect::new(ax + gx, ay + dim - gy - gh, gw, gh)
+                };
+                Some(TextGlyph {
+                    pos: placed.pos,
+                    line: placed.line,
+                    off: g.off,
+                    size: g.size,
+                    tex: tex.packed_whxy(),
+                    color: g.color,
+                })
+            })
+            .collect();
+        CachedText {
+            glyphs,
+            lines: text.split('\n').count(),
+            ascent: font.ascent,
+            used: true,
+        }
+    }
+
+    /// rounds edges of following shapes to whole px, so 1px lines and borders at
+    /// fractional positions stay sharp. rotated shapes, shapes in zoomed viewports
+    /// and text (see [`Renderer::hinting`]) aren't snapped
+    pub fn snap(&mut self, snap: bool) {
+        self.snap = snap;
+    }
+
+    /// layout of unchanged text (same string, font and writing mode) is cached between frames
+    pub fn text(&mut self, text: &str, x: Unit, y: Unit, size: Unit) {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(self.font.as_bytes());
+        hasher.write(text.as_bytes());
+        hasher.write_u8(self.vertical as u8);
+        let key = hasher.finish();
+        let layout = match self.text_cache.remove(&key) {
+            Some(layout) => layout,
+            None => self.layout_text(text),
+        };
+        let (x, y, size) = (self.px_x(x), self.px_y(y), self.px_y(size));
+        let collapse = size < self.lod.text;
+        let mut quads = Vec::with_capacity(layout.glyphs.len());
+        // line, start, end along it and baseline (center if column) of each line when collapsed
+        let mut lines: Vec<(u32, f32, f32, f32)> = Vec::new();
+        let vertical = self.vertical;
+        let ascent = layout.ascent;
+        // snapping is done on screen, through area and viewport transforms
+        let (rw, rh) = (self.width, self.height);
+        let [ox, oy, kx, ky] = self.transform(0.0, 0.0, 1.0, 1.0);
+        let hinting = self.hinting;
+        let snap = |v: f32, off: f32, k: f32, phases: f32| {
+            if hinting == TextHinting::None || k == 0.0 {
+                return v;
+            }
+            ((v * k + off) * phases).round() / phases / k - off / k
+        };
+        for g in &layout.glyphs {
+            let pen_x = x + g.pos[0] * size;
+            let baseline = y + (ascent + g.pos[1]) * size;
+            let (w, h) = (g.size[0] * size, g.size[1] * size);
+            let left = snap(pen_x + g.off[0] * size, ox * rw, kx, hinting.phases());
+            let bottom = snap(baseline, oy * rh, ky, 1.0) - g.off[1] * size;
+            if collapse {
+                // marks can be shifted off baseline, so lines are placed unshifted
+                let (start, end, cross) = if vertical {
+                    let column = (layout.lines - 1 - g.line as usize) as f32;
+                    (bottom - h, bottom, x + (column + 0.5) * size)
+                } else {
+                    let baseline = y + (ascent + g.line as f32) * size;
+                    (left, left + w, baseline)
+                };
+                match lines.last_mut() {
+                    Some((line, s, e, _)) if *line == g.line => {
+                        *s = s.min(start);
+                        *e = e.max(end);
+                    }
+                    _ => lines.push((g.line, start, end, cross)),
+                }
+            } else {
+                quads.push((
+                    left + w * 0.5,
+                    bottom - h * 0.5,
+                    w * 0.5,
+                    h * 0.5,
+                    g.tex,
+                    g.color,
+                ));
+            }
+        }
+        self.text_cache.insert(
+            key,
+            CachedText {
+                used: true,
+                ..layout
+            },
+        );
+        let old_tex_coord = self.tex_coord;
+        // glyphs are never tiled, and they're aligned by hinting
+        let old_tiling = std::mem::replace(&mut self.tiling, [1.0; 2]);
+        let old_snap = std::mem::replace(&mut self.snap, false);
+        let text_color = self.color;
+        for (x, y, w, h, tex, color) in quads {
+            self.tex_coord = [(tex >> 32) as u32, tex as u32];
+            // color glyphs keep their colors, only alpha applies
+            self.color = if color {
+                [255, 255, 255, text_color[3]]
+            } else {
+                text_color
+            };
+            self.instance(x / rw, y / rh, w / rw, h / rh);
+        }
+        self.color = text_color;
+        if !lines.is_empty() {
+            let old_color = self.color;
+            self.color[3] = (self.color[3] as f32 * LOD_TEXT_ALPHA) as u8;
+            self.tex_coord = [0, 0];
+            let h = ascent * size * LOD_TEXT_HEIGHT * 0.5;
+            for (_, start, end, cross) in lines {
+                let len = (end - start) * 0.5;
+                if vertical {
+                    self.instance(cross / rw, (start + len) / rh, h / rw, len / rh);
+                } else {
+                    self.instance((start + len) / rw, (cross - h) / rh, len / rw, h / rh);
+                }
+            }
+            self.color = old_color;
+        }
+        self.tex_coord = old_tex_coord;
+        self.tiling = old_tiling;
+        self.snap = old_snap;
+    }
+
+    /// (leading, trailing) edge along line (or column) and line of each char of `text`
+    /// and of it's end, in font heights, from glyphs laid out like text() lays them out.
+    /// chars of ligature split it's advance, rtl chars lead on their right edge,
+    /// newlines and end sit at trailing edge of last char of their line
+    fn text_char_spans(&self, text: &str) -> Vec<(f32, f32, u32)> {
+        let font = self.get_font(&self.font);
+        let layout = if self.vertical {
+            font.layout_vertical(text)
+        } else {
+            font.layout(text)
+        };
+        // left and right (top and bottom) edge of glyphs of each cluster and if it's rtl,
+        // by byte idx in text
+        let mut clusters: BTreeMap<usize, (f32, f32, bool)> = BTreeMap::new();
+        for g in &layout {
+            // shifted punctuation sits half a cell up in it's cell
+            let start = if self.vertical {
+                g.pos[1].ceil()
+            } else {
+                g.pos[0]
+            };
+            let (left, right) = (start, start + g.advance);
+            clusters
+                .entry(g.cluster)
+                .and_modify(|(l, r, _)| (*l, *r) = (l.min(left), r.max(right)))
+                .or_insert((left, right, g.rtl));
+        }
+        let mut spans = Vec::with_capacity(text.len() + 1);
+        let mut start = 0;
+        for (line, text) in text.split('\n').enumerate() {
+            let line = line as u32;
+            let end = start + text.len();
+            let clusters: Vec<_> = clusters.range(start..end).map(|(&c, &e)| (c, e)).collect();
+            // chars before first glyph of line stay at line start
+            let mut prev = 0.0;
+            for (i, _) in text.char_indices() {
+                let k = clusters.partition_point(|&(c, _)| c <= start + i);
+                let Some(&(c, (left, right, rtl))) = k.checked_sub(1).map(|k| &clusters[k]) else {
+                    spans.push((prev, prev, line));
+                    continue;
+                };
+                // rtl chars go right to left through their cluster
+                let (lead, trail) = if rtl { (right, left) } else { (left, right) };
+                let c = c - start;
+                let cluster_end = clusters.get(k).map_or(text.len(), |&(c, _)| c - start);
+                let chars = text[c..cluster_end].chars().count() as f32;
+                let step = (trail - lead) / chars;
+                let lead = lead + step * text[c..i].chars().count() as f32;
+                spans.push((lead, lead + step, line));
+                prev = lead + step;
+            }
+            spans.push((prev, prev, line));
+            start = end + 1;
+        }
+        spans
+    }
+
+    /// px x and line top y (column left x and y in vertical text) of caret
+    /// before each char of `text` and after it's last char,
+    /// laid out like text() with same args draws it
+    pub fn text_caret_positions(&self, text: &str, x: Unit, y: Unit, size: Unit) -> Vec<[f32; 2]> {
+        let (x, y, size) = (self.px_x(x), self.px_y(y), self.px_y(size));
+        let lines = text.split('\n').count() as u32;
+        self.text_char_spans(text)
+            .into_iter()
+            .map(|(lead, _, line)| {
+                if self.vertical {
+                    [x + (lines - 1 - line) as f32 * size, y + lead * size]
+                } else {
+                    [x + lead * size, y + line as f32 * size]
+                }
+            })
+            .collect()
+    }
+
+    /// char idx of caret closest to px, py, for placing caret where text is clicked
+    pub fn text_caret_at(
+        &self,
+        text: &str,
+        x: Unit,
+        y: Unit,
+        size: Unit,
+        px: Unit,
+        py: Unit,
+    ) -> usize {
+        let spans = self.text_char_spans(text);
+        let (x, y, size) = (self.px_x(x), self.px_y(y), self.px_y(size));
+        let (px, py) = ((self.px_x(px) - x) / size, (self.px_y(py) - y) / size);
+        let last_line = spans.last().map_or(0, |s| s.2);
+        // columns go right to left
+        let (along, line) = if self.vertical {
+            let column = (px.floor().max(0.0) as u32).min(last_line);
+            (py, last_line - column)
+        } else {
+            (px, (py.floor().max(0.0) as u32).min(last_line))
+        };
+        spans
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.2 == line)
+            .min_by(|(_, a), (_, b)| (a.0 - along).abs().total_cmp(&(b.0 - along).abs()))
+            .map_or(0, |(i, _)| i)
+    }
+
+    /// draws rect per line (or column) with current color, covering chars in `range` (char idxs)
+    /// of `text` laid out like text() with same args draws it
+    pub fn text_selection(
+        &mut self,
+        text: &str,
+        x: Unit,
+        y: Unit,
+        size: Unit,
+        range: std::ops::Range<usize>,
+    ) {
+        let spans = self.text_char_spans(text);
+        let (x, y, size) = (self.px_x(x), self.px_y(y), self.px_y(size));
+        let last_line = spans.last().map_or(0, |s| s.2);
+        let end = range.end.min(spans.len() - 1);
+        // (line, left, right) of selected chars, newlines select nothing
+        let mut sel: Vec<(u32, f32, f32)> = spans[range.start.min(end)..end]
+            .iter()
+            .filter(|s| s.0 != s.1)
+            .map(|&(lead, trail, line)| (line, lead.min(trail), lead.max(trail)))
+            .collect();
+        sel.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        // touching chars of line are merged into one rect
+        let mut runs: Vec<(u32, f32, f32)> = Vec::new();
+        for (line, left, right) in sel {
+            match runs.last_mut() {
+                Some((l, _, r)) if *l == line && left <= *r + 1e-3 => *r = r.max(right),
+                _ => runs.push((line, left, right)),
+            }
+        }
+        let (rw, rh) = (self.width, self.height);
+        for (line, start, end) in runs {
+            let (x, y, w, h) = if self.vertical {
+                let column = (last_line - line) as f32;
+                (
+                    x + column * size,
+                    y + start * size,
+                    size,
+                    (end - start) * size,
+                )
+            } else {
+                (
+                    x + start * size,
+                    y + line as f32 * size,
+                    (end - start) * size,
+                    size,
+                )
+            };
+            self.rect(
+                Unit::Pc(x / rw),
+                Unit::Pc(y / rh),
+                Unit::Pc(w / rw),
+                Unit::Pc(h / rh),
+            );
+        }
+    }
+
     pub fn verts(&mut self, verts: &[Vertex]) {
         let new_vert_cnt = self.vert_cnt + verts.len();
         if new_vert_cnt >= self.vertices.len() {
@@ -267,6 +2068,7 @@ impl Renderer {
     fn pc_x(&self, unit: Unit) -> f32 {
         match unit {
             Unit::Px(px) => px as f32 / self.width,
+            Unit::Dp(dp) => dp * self.scale_factor / self.width,
             Unit::Mn(mn) => mn * self.width.min(self.height) / self.width,
             Unit::Mx(mx) => mx * self.width.max(self.height) / self.width,
             Unit::Pc(pc) => pc,
@@ -276,41 +2078,285 @@ impl Renderer {
     fn pc_y(&self, unit: Unit) -> f32 {
         match unit {
             Unit::Px(px) => px as f32 / self.height,
+            Unit::Dp(dp) => dp * self.scale_factor / self.height,
             Unit::Mn(mn) => mn * self.width.min(self.height) / self.height,
             Unit::Mx(mx) => mx * self.width.max(self.height) / self.height,
             Unit::Pc(pc) => pc,
         }
     }
 
-    fn px_x(&self, unit: Unit) -> f32 {
+    pub(crate) fn px_x(&self, unit: Unit) -> f32 {
         match unit {
             Unit::Px(px) => px as f32,
+            Unit::Dp(dp) => dp * self.scale_factor,
             Unit::Mn(mn) => mn * self.width.min(self.height),
             Unit::Mx(mx) => mx * self.width.max(self.height),
             Unit::Pc(pc) => pc * self.width,
         }
     }
 
-    fn px_y(&self, unit: Unit) -> f32 {
+    pub(crate) fn px_y(&self, unit: Unit) -> f32 {
         match unit {
             Unit::Px(px) => px as f32,
+            Unit::Dp(dp) => dp * self.scale_factor,
             Unit::Mn(mn) => mn * self.width.min(self.height),
             Unit::Mx(mx) => mx * self.width.max(self.height),
             Unit::Pc(pc) => pc * self.height,
         }
     }
 
-    fn instance(&mut self, mut x: f32, mut y: f32, mut w: f32, mut h: f32) {
+    /// maps pc rect through current area and viewport to screen pc
+    fn transform(&self, mut x: f32, mut y: f32, mut w: f32, mut h: f32) -> [f32; 4] {
         let area = self.areas.last().unwrap_or(&[0.0, 0.0, 1.0, 1.0]);
         x = x * area[2] + area[0];
         y = y * area[3] + area[1];
         w *= area[2];
         h *= area[3];
-        self.instances[self.inst_cnt] = Vertex::with(self).pos(x, y).scale(w, h);
+        if let Some((_, [vx, vy, vw, vh], [px, py], zoom)) = self.viewport {
+            let (rw, rh) = (self.width, self.height);
+            x = (vx + vw * 0.5 + (x * rw - rw * 0.5 - px) * zoom) / rw;
+            y = (vy + vh * 0.5 + (y * rh - rh * 0.5 - py) * zoom) / rh;
+            w *= zoom;
+            h *= zoom;
+        }
+        [x, y, w, h]
+    }
+
+    fn instance(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        let [x, y, w, h] = self.transform(x, y, w, h);
+        let zoomed = self.viewport.is_some_and(|v| v.3 != 1.0);
+        let [x, y, w, h] = if self.snap && self.rotation == 0.0 && !zoomed {
+            self.snap_rect(x, y, w, h)
+        } else {
+            [x, y, w, h]
+        };
+        // batches are baked to be drawn on later frames, when they can be on screen
+        if self.culling && self.batch.is_none() && self.offscreen(x, y, w, h) {
+            self.culled += 1;
+            return;
+        }
+        let small = 2.0 * (w * self.width).min(h * self.height) < self.lod.detail;
+        if self.blur > 0.0 && !small {
+            // consecutive shapes with same blur share backdrop
+            let prev_blurred = self.inst_cnt > 0
+                && self.instances[self.inst_cnt - 1].flags & BACKDROP_BLUR != 0
+                && self.blurs.last().is_some_and(|&(_, r)| r == self.blur);
+            if !prev_blurred {
+                assert!(
+                    self.blurs.len() < MAX_BACKDROP_BLURS,
+                    "too many backdrop blurs, max is {MAX_BACKDROP_BLURS}"
+                );
+                self.blurs.push((self.inst_cnt, self.blur));
+            }
+        }
+        let mut vert = Vertex::with(self).pos(x, y).scale(w, h);
+        if small {
+            vert.stroke_width = 0.0;
+            vert.flags &= !BACKDROP_BLUR;
+        }
+        self.push_instance(vert);
+    }
+
+    /// rounds edges of centered screen pc rect to whole px, non empty rect stays at least 1px
+    fn snap_rect(&self, x: f32, y: f32, w: f32, h: f32) -> [f32; 4] {
+        let edges = |c: f32, e: f32, r: f32| {
+            let (lo, hi) = (((c - e) * r).round(), ((c + e) * r).round());
+            let hi = if e > 0.0 { hi.max(lo + 1.0) } else { hi };
+            ((lo + hi) * 0.5 / r, (hi - lo) * 0.5 / r)
+        };
+        let (x, w) = edges(x, w, self.width);
+        let (y, h) = edges(y, h, self.height);
+        [x, y, w, h]
+    }
+
+    /// whether rotated bounds of centered instance are outside of clip
+    /// (screen, viewport, clip rect or recorded layer)
+    fn offscreen(&self, x: f32, y: f32, w: f32, h: f32) -> bool {
+        let (sin, cos) = self.rotation.sin_cos();
+        // stroke width is relative to smaller half extent
+        let grow =
+            self.stroke_width * self.stroke_align.outside() * (w * self.width).min(h * self.height);
+        let (hw, hh) = (w * self.width + grow, h * self.height + grow);
+        // 1px margin for antialiased edges
+        let ex = (hw * cos).abs() + (hh * sin).abs() + 1.0;
+        let ey = (hw * sin).abs() + (hh * cos).abs() + 1.0;
+        let (x, y) = (x * self.width, y * self.height);
+        let [cx, cy, cw, ch] = match (&self.layer, &self.clip, &self.viewport) {
+            (Some(layer), ..) => layer.2,
+            (None, Some(clip), _) => clip.1,
+            (None, None, Some(viewport)) => viewport.1,
+            (None, None, None) => [0.0, 0.0, self.width, self.height],
+        };
+        x + ex < cx || x - ex > cx + cw || y + ey < cy || y - ey > cy + ch
+    }
+
+    /// instances submitted and culled last frame
+    pub fn cull_stats(&self) -> CullStats {
+        self.cull_stats
+    }
+
+    /// instances drawn so far this frame, they live in mapped instance vbo,
+    /// so patching them in place needs no upload, they are z sorted in flush
+    pub fn instances_mut(&mut self) -> &mut [Vertex] {
+        &mut self.instances[..self.inst_cnt]
+    }
+
+    fn push_instance(&mut self, vert: Vertex) {
+        self.instances[self.inst_cnt] = vert;
+        self.inst_infos[self.inst_cnt] = InstInfo {
+            viewport: self.scissor(),
+            ..self.info
+        };
         self.inst_cnt += 1;
         if self.inst_cnt >= self.instances.len() {
-            self.instances
-                .resize((self.inst_cnt + 1).next_power_of_two(), Vertex::default());
+            let len = (self.inst_cnt + 1).next_power_of_two();
+            let mut ctx = self.ctx.lock().unwrap();
+            self.instances.grow(&mut ctx, self.inst_cnt, len);
+        }
+        if self.inst_cnt >= self.inst_infos.len() {
+            let len = (self.inst_cnt + 1).next_power_of_two();
+            self.inst_infos.resize(len, InstInfo::default());
+        }
+    }
+
+    /// applies [`Renderer::debug`] to sorted instances
+    fn debug_draws(&mut self, ctx: &mut RenderCtx) {
+        let DrawDebug {
+            color,
+            wireframe,
+            limit,
+        } = self.debug;
+        self.debug_submitted = Some(self.inst_cnt);
+        if let Some(n) = limit
+            && n < self.inst_cnt
+        {
+            self.inst_cnt = n;
+            self.blurs.retain(|b| b.0 < n);
+            self.paths.retain(|p| p.inst <= n);
+            self.batches.retain(|b| b.inst <= n);
+            self.grabs.retain(|g| g.0 <= n);
+        }
+        let n = self.inst_cnt;
+        // spread out hues of group ids
+        let group_color = |id: u64| {
+            let hue = (id.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 40) as f32 / (1 << 24) as f32;
+            let [r, g, b, _]: [u8; 4] = Color::from(Hsv::new(hue * 360.0, 0.75, 1.0)).into();
+            [r, g, b, 128]
+        };
+        if color != DebugColor::None {
+            let mut draw = 0;
+            for i in 0..n {
+                let info = self.inst_infos[i];
+                let tex = self.instances[i].tex_coord;
+                let id = match color {
+                    DebugColor::None => unreachable!(),
+                    DebugColor::DrawCall => {
+                        // same splits as in render()
+                        let prev = self.inst_infos[i.saturating_sub(1)];
+                        if i > 0
+                            && (prev.pipeline() != info.pipeline()
+                                || prev.viewport != info.viewport
+                                || self.blurs.iter().any(|b| b.0 == i)
+                                || self.paths.iter().any(|p| p.inst == i)
+                                || self.batches.iter().any(|b| b.inst == i)
+                                || self.grabs.iter().any(|g| g.0 == i))
+                        {
+                            draw += 1;
+                        }
+                        Some(draw)
+                    }
+                    DebugColor::Pipeline => {
+                        Some((info.shader * BlendMode::ALL.len() + info.blend as usize) as u64)
+                    }
+                    DebugColor::Texture => {
+                        (tex != [0, 0]).then_some((tex[0] as u64) << 32 | tex[1] as u64)
+                    }
+                };
+                let v = &mut self.instances[i];
+                v.color = id.map_or([128, 128, 128, 128], group_color);
+                v.tex_coord = [0, 0];
+                v.stroke_width = 0.0;
+                v.flags &= !(BACKDROP_BLUR | LAYER);
+            }
+        }
+        if wireframe {
+            // outlines are drawn after all instances, over whole window
+            let len = 2 * n + 1;
+            if len > self.instances.len() {
+                self.instances.grow(ctx, n, len.next_power_of_two());
+            }
+            if len > self.inst_infos.len() {
+                self.inst_infos
+                    .resize(len.next_power_of_two(), InstInfo::default());
+            }
+            for i in 0..n {
+                let v = self.instances[i];
+                // 1px, stroke width is relative to smaller half extent
+                let half = (v.scale[0] * self.width).min(v.scale[1] * self.height);
+                self.instances[n + i] = Vertex {
+                    pos: v.pos,
+                    scale: v.scale,
+                    rotation: v.rotation,
+                    color: [255, 255, 255, 0],
+                    stroke_color: [255, 255, 255, 192],
+                    stroke_width: 1.0 / half.max(1e-3),
+                    ..Default::default()
+                };
+                self.inst_infos[n + i] = InstInfo::default();
+            }
+            self.inst_cnt = 2 * n;
+        }
+    }
+
+    /// stable sorts instances by z, backdrop blur runs are rebuilt since sorting can split or merge them
+    fn sort_instances(&mut self) {
+        let infos = &self.inst_infos[..self.inst_cnt];
+        if self.paths.is_empty()
+            && self.batches.is_empty()
+            && self.grabs.is_empty()
+            && infos.windows(2).all(|i| i[0].z <= i[1].z)
+        {
+            return;
+        }
+        let mut order = (0..self.inst_cnt).collect::<Vec<_>>();
+        order.sort_by_key(|&i| infos[i].z);
+        // paths go before first instance that sorts after them
+        for path in &mut self.paths {
+            path.inst = order.partition_point(|&j| (infos[j].z, j) < (path.z, path.inst));
+        }
+        self.paths.sort_by_key(|p| (p.inst, p.z));
+        for batch in &mut self.batches {
+            batch.inst = order.partition_point(|&j| (infos[j].z, j) < (batch.info.z, batch.inst));
+        }
+        self.batches.sort_by_key(|b| (b.inst, b.info.z));
+        for grab in &mut self.grabs {
+            grab.0 = order.partition_point(|&j| (infos[j].z, j) < (grab.1, grab.0));
+        }
+        self.grabs.sort();
+        // blur radius of each instance, from run it was in
+        let mut radii = vec![0.0; self.inst_cnt];
+        for (run, &(start, r)) in self.blurs.iter().enumerate() {
+            let end = self.blurs.get(run + 1).map_or(self.inst_cnt, |b| b.0);
+            radii[start..end].fill(r);
+        }
+        let insts = order.iter().map(|&i| self.instances[i]).collect::<Vec<_>>();
+        let infos = order.iter().map(|&i| infos[i]).collect::<Vec<_>>();
+        self.instances[..self.inst_cnt].copy_from_slice(&insts);
+        self.inst_infos[..self.inst_cnt].copy_from_slice(&infos);
+        self.blurs.clear();
+        let mut prev_blur = None;
+        for (i, inst) in insts.iter().enumerate() {
+            let blur = (inst.flags & BACKDROP_BLUR != 0).then_some(radii[order[i]]);
+            if let Some(r) = blur
+                && blur != prev_blur
+            {
+                assert!(
+                    self.blurs.len() < MAX_BACKDROP_BLURS,
+                    "too many backdrop blurs after z sorting, max is {MAX_BACKDROP_BLURS}"
+                );
+                self.blurs.push((i, r));
+            }
+            prev_blur = blur;
         }
     }
 
@@ -357,6 +2403,24 @@ impl Renderer {
         self.roundness -= 1.0;
     }
 
+    /// circular progress arc with rounded caps, starts at top and goes clockwise (rotated by rotation)
+    /// `thickness` is ring width, `progress` is 0-1 of full circle
+    pub fn arc(&mut self, x: Unit, y: Unit, r: Unit, thickness: Unit, progress: f32) {
+        let t = (self.px_x(thickness) / self.px_x(r)).clamp(0.0, 1.0);
+        let old_mask = self.mask;
+        self.mask = (MASK_ARC, [progress.clamp(0.0, 1.0), t]);
+        self.circle(x, y, r);
+        self.mask = old_mask;
+    }
+
+    /// horizontal progress bar fill with rounded caps, filled from left by `progress` (0-1)
+    pub fn bar(&mut self, x: Unit, y: Unit, w: Unit, h: Unit, progress: f32) {
+        let old_mask = self.mask;
+        self.mask = (MASK_FILL, [progress.clamp(0.0, 1.0), 0.0]);
+        self.rrect(x, y, w, h, 0.999);
+        self.mask = old_mask;
+    }
+
     pub fn line(&mut self, x0: Unit, y0: Unit, x1: Unit, y1: Unit, w: Unit) {
         let (x0, y0) = (self.px_x(x0), self.px_y(y0));
         let (x1, y1) = (self.px_x(x1), self.px_y(y1));
@@ -403,6 +2467,107 @@ impl Renderer {
         self.roundness = old_roundness;
     }
 
+    /// rounded line from x0, y0 with arrowhead at x1, y1, `head_size` is length of head's sides
+    pub fn arrow(&mut self, x0: Unit, y0: Unit, x1: Unit, y1: Unit, w: Unit, head_size: Unit) {
+        self.rline(x0, y0, x1, y1, w);
+        let (x0, y0) = (self.pc_x(x0), self.pc_y(y0));
+        let (x1, y1) = (self.pc_x(x1), self.pc_y(y1));
+        self.arrow_head([x0, y0], [x1, y1], w, head_size);
+    }
+
+    /// connects x0, y0 to x1, y1 with `kind` path, ending in arrowhead unless `head_size` is 0
+    #[allow(clippy::too_many_arguments)]
+    pub fn connector(
+        &mut self,
+        x0: Unit,
+        y0: Unit,
+        x1: Unit,
+        y1: Unit,
+        w: Unit,
+        head_size: Unit,
+        kind: Connector,
+    ) {
+        let (x0, y0) = (self.pc_x(x0), self.pc_y(y0));
+        let (x1, y1) = (self.pc_x(x1), self.pc_y(y1));
+        let (mx, my) = ((x0 + x1) * 0.5, (y0 + y1) * 0.5);
+        use Unit::Pc;
+        // point head points from, end of path is always at x1, y1
+        let from = match kind {
+            Connector::Straight => {
+                self.rline(Pc(x0), Pc(y0), Pc(x1), Pc(y1), w);
+                [x0, y0]
+            }
+            // 2 quadratic halves meeting at center, tangents are horizontal at ends
+            Connector::Curve => {
+                self.bezier(Pc(x0), Pc(y0), Pc(mx), Pc(y0), Pc(mx), Pc(my), w);
+                self.bezier(Pc(mx), Pc(my), Pc(mx), Pc(y1), Pc(x1), Pc(y1), w);
+                [mx, y1]
+            }
+            Connector::Elbow => {
+                self.rline(Pc(x0), Pc(y0), Pc(mx), Pc(y0), w);
+                self.rline(Pc(mx), Pc(y0), Pc(mx), Pc(y1), w);
+                self.rline(Pc(mx), Pc(y1), Pc(x1), Pc(y1), w);
+                [mx, y1]
+            }
+        };
+        if self.px_x(head_size) > 0.0 {
+            // vertically aligned ends have no horizontal tangent to follow
+            let from = if from == [x1, y1] { [x0, y0] } else { from };
+            self.arrow_head(from, [x1, y1], w, head_size);
+        }
+    }
+
+    /// 2 rounded lines forming arrowhead at `tip`, pointing away from `from`, both in pc
+    fn arrow_head(&mut self, from: [f32; 2], tip: [f32; 2], w: Unit, head_size: Unit) {
+        let (rw, rh) = (self.width, self.height);
+        // angle is computed in px, since pc isn't square
+        let (dx, dy) = ((tip[0] - from[0]) * rw, (tip[1] - from[1]) * rh);
+        if dx == 0.0 && dy == 0.0 {
+            return;
+        }
+        let an = dy.atan2(dx);
+        let len = self.px_x(head_size);
+        use Unit::Pc;
+        for side in [-1.0, 1.0] {
+            // sides are 30 degrees off shaft
+            let a = an + std::f32::consts::PI + side * std::f32::consts::FRAC_PI_6;
+            let (x, y) = (tip[0] + a.cos() * len / rw, tip[1] + a.sin() * len / rh);
+            self.rline(Pc(tip[0]), Pc(tip[1]), Pc(x), Pc(y), w);
+        }
+    }
+
+    /// fills svg `path` with color, mapping its view box (bounds if unset) onto x, y, w, h.
+    /// path is tessellated every call, rotation, stroke and imgs don't apply to it
+    pub fn path(&mut self, path: &SvgPath, x: Unit, y: Unit, w: Unit, h: Unit) {
+        assert!(self.layer.is_none(), "paths can't be drawn into layers");
+        let [vx, vy, vw, vh] = path.rect();
+        if vw <= 0.0 || vh <= 0.0 {
+            return;
+        }
+        let (x, y, w, h) = (self.pc_x(x), self.pc_y(y), self.pc_x(w), self.pc_y(h));
+        let (sx, sy) = (w / vw, h / vh);
+        // flatten curves to within quarter px on screen
+        let [_, _, tw, th] = self.transform(x, y, w, h);
+        let px_per_unit = (tw * self.width / vw).max(th * self.height / vh);
+        let first = self.path_verts.len();
+        for [px, py] in path.fill(0.25 / px_per_unit) {
+            let [x, y, ..] = self.transform(x + (px - vx) * sx, y + (py - vy) * sy, 0.0, 0.0);
+            self.path_verts.push(PathVertex {
+                pos: [x, y],
+                color: self.color,
+            });
+        }
+        if self.path_verts.len() > first {
+            self.paths.push(PathDraw {
+                inst: self.inst_cnt,
+                first,
+                count: self.path_verts.len() - first,
+                z: self.info.z,
+                viewport: self.scissor(),
+            });
+        }
+    }
+
     pub fn area(&mut self, x: Unit, y: Unit, w: Unit, h: Unit) {
         let area = [self.pc_x(x), self.pc_y(y), self.pc_x(w), self.pc_y(h)];
         if self.areas.is_empty() {
@@ -428,59 +2593,959 @@ impl Renderer {
         self.areas.pop();
     }
 
-    /// saves old render params to reset to when end_temp() is called
-    pub fn begin_temp(&mut self) {
-        self.old_color = self.color;
-        self.old_stroke_color = self.stroke_color;
-        self.old_stroke_width = self.stroke_width;
-        self.old_roundness = self.roundness;
-        self.old_rotation = self.rotation;
-        self.old_tex_coord = self.tex_coord;
+    /// adds sub rect of window that can be drawn to with it's own camera,
+    /// replaces rect of existing viewport but keeps it's camera
+    pub fn add_viewport(
+        &mut self,
+        name: &str,
+        x: Unit,
+        y: Unit,
+        w: Unit,
+        h: Unit,
+    ) -> &mut Viewport {
+        let viewport = self.viewports.entry(name.to_string()).or_insert(Viewport {
+            x,
+            y,
+            w,
+            h,
+            pan: [0.0; 2],
+            zoom: 1.0,
+        });
+        (viewport.x, viewport.y, viewport.w, viewport.h) = (x, y, w, h);
+        viewport
     }
 
-    /// resets render params to values before begin_temp() was called
-    pub fn end_temp(&mut self) {
-        self.color = self.old_color;
-        self.stroke_color = self.old_stroke_color;
-        self.stroke_width = self.old_stroke_width;
-        self.roundness = self.old_roundness;
-        self.rotation = self.old_rotation;
-        self.tex_coord = self.old_tex_coord;
+    pub fn viewport(&mut self, name: &str) -> &mut Viewport {
+        self.viewports
+            .get_mut(name)
+            .unwrap_or_else(|| panic!("viewport not found: {name}"))
     }
 
-    pub(crate) fn render(&mut self) {
-        if self.vert_cnt != 0 && self.inst_cnt == 0 {
+    pub fn remove_viewport(&mut self, name: &str) -> bool {
+        self.viewports.remove(name).is_some()
+    }
+
+    /// viewport names, sorted
+    pub fn viewports(&self) -> impl Iterator<Item = &str> {
+        self.viewports.keys().map(String::as_str)
+    }
+
+    /// shapes drawn until end_viewport() are clipped to viewport `name`,
+    /// window center + pan is shown at it's center scaled by zoom
+    pub fn begin_viewport(&mut self, name: &str) {
+        assert!(self.viewport.is_none(), "viewports can't be nested: {name}");
+        let v = *self
+            .viewports
+            .get(name)
+            .unwrap_or_else(|| panic!("viewport not found: {name}"));
+        let px = [
+            self.px_x(v.x),
+            self.px_y(v.y),
+            self.px_x(v.w),
+            self.px_y(v.h),
+        ]
+        .map(f32::round);
+        let (x0, y0) = (px[0].max(0.0), px[1].max(0.0));
+        let x1 = (px[0] + px[2]).min(self.width).max(x0);
+        let y1 = (px[1] + px[3]).min(self.height).max(y0);
+        self.scissors.push(vk::Rect2D {
+            offset: vk::Offset2D {
+                x: x0 as i32,
+                y: y0 as i32,
+            },
+            extent: vk::Extent2D {
+                width: (x1 - x0) as u32,
+                height: (y1 - y0) as u32,
+            },
+        });
+        self.viewport = Some((self.scissors.len() as u32, px, v.pan, v.zoom));
+    }
+
+    pub fn end_viewport(&mut self) {
+        self.viewport = None;
+        self.clip = None;
+    }
+
+    /// clips following shapes to x, y, w, h (through current area and viewport)
+    /// until no_clip(), shapes fully outside are culled. ignored inside layers
+    pub fn clip(&mut self, x: Unit, y: Unit, w: Unit, h: Unit) {
+        let [x, y, w, h] = self.transform(self.pc_x(x), self.pc_y(y), self.pc_x(w), self.pc_y(h));
+        let (rw, rh) = (self.width, self.height);
+        let [bx, by, bw, bh] = self.viewport.map_or([0.0, 0.0, rw, rh], |v| v.1);
+        let x0 = (x * rw).round().clamp(bx.max(0.0), (bx + bw).min(rw));
+        let y0 = (y * rh).round().clamp(by.max(0.0), (by + bh).min(rh));
+        let x1 = ((x + w) * rw).round().clamp(x0, (bx + bw).min(rw).max(x0));
+        let y1 = ((y + h) * rh).round().clamp(y0, (by + bh).min(rh).max(y0));
+        self.scissors.push(vk::Rect2D {
+            offset: vk::Offset2D {
+                x: x0 as i32,
+                y: y0 as i32,
+            },
+            extent: vk::Extent2D {
+                width: (x1 - x0) as u32,
+                height: (y1 - y0) as u32,
+            },
+        });
+        self.clip = Some((self.scissors.len() as u32, [x0, y0, x1 - x0, y1 - y0]));
+    }
+
+    pub fn no_clip(&mut self) {
+        self.clip = None;
+    }
+
+    /// scissor idx + 1 of following shapes, 0 is whole window
+    fn scissor(&self) -> u32 {
+        match (self.clip, self.viewport) {
+            (Some(clip), _) => clip.0,
+            (None, Some(viewport)) => viewport.0,
+            (None, None) => 0,
+        }
+    }
+
+    /// starts recording cached layer `name` covering x, y, w, h of screen,
+    /// shapes drawn until end_layer() are rendered into layer img once
+    /// and it's reused while they stay the same, backdrop blur is ignored inside layers
+    pub fn begin_layer(&mut self, name: &str, x: Unit, y: Unit, w: Unit, h: Unit) {
+        assert!(self.layer.is_none(), "layers can't be nested: {name}");
+        let px = [self.px_x(x), self.px_y(y), self.px_x(w), self.px_y(h)].map(f32::round);
+        self.layer = Some((name.to_string(), self.inst_cnt, px));
+    }
+
+    /// replaces shapes drawn since begin_layer() with cached layer,
+    /// layer is rerendered if they changed since last frame
+    pub fn end_layer(&mut self) {
+        let (name, start, px) = self
+            .layer
+            .take()
+            .expect("end_layer() called without begin_layer()");
+        let insts = &mut self.instances[start..self.inst_cnt];
+        for inst in insts.iter_mut() {
+            inst.flags &= !BACKDROP_BLUR;
+        }
+        self.blurs.retain(|&(i, _)| i < start);
+        let mut order = (start..self.inst_cnt).collect::<Vec<_>>();
+        order.sort_by_key(|&i| self.inst_infos[i].z);
+        let insts = order.iter().map(|&i| self.instances[i]).collect::<Vec<_>>();
+        let mut hasher = DefaultHasher::new();
+        hasher.write(cast_slice(&insts));
+        hasher.write(cast_slice(&px));
+        let hash = hasher.finish();
+        if self.layers.get(&name).is_none_or(|l| l.hash != hash)
+            && !self.cache_layer(&name, px, hash, insts)
+        {
             return;
         }
+        self.inst_cnt = start;
+        self.draw_layer(&name);
+    }
+
+    /// draws cached layer without redrawing it's content,
+    /// returns false if it isn't cached (never recorded, invalidated or window resized)
+    pub fn draw_layer(&mut self, name: &str) -> bool {
+        let Some(layer) = self.layers.get(name) else {
+            return false;
+        };
+        let [x, y, w, h] = layer.px;
+        let tex = layer.rect.packed_whxy();
+        let (rw, rh) = (self.width, self.height);
+        self.push_instance(Vertex {
+            pos: [(x + w * 0.5) / rw, (y + h * 0.5) / rh],
+            scale: [w * 0.5 / rw, h * 0.5 / rh],
+            color: [255; 4],
+            tex_coord: [(tex >> 32) as u32, tex as u32],
+            flags: LAYER,
+            ..Default::default()
+        });
+        true
+    }
+
+    /// frees cached layer, next end_layer() rerenders it
+    pub fn invalidate_layer(&mut self, name: &str) {
+        if let Some(layer) = self.layers.remove(name) {
+            let (x, y, w, h) = layer.rect.xywh();
+            self.layer_packer.unpack(x, y, w, h);
+        }
+    }
+
+    /// following shapes are recorded into batch instead of being drawn, until end_batch()
+    pub fn begin_batch(&mut self) {
+        assert!(self.batch.is_none(), "batches can't be nested");
+        self.batch = Some(self.inst_cnt);
+    }
+
+    /// instances of shapes drawn since begin_batch() sorted by z, for bake_batch().
+    /// they aren't culled, but lose backdrop blur, shader, blend mode and pick id
+    pub fn end_batch(&mut self) -> Vec<Vertex> {
+        let start = self
+            .batch
+            .take()
+            .expect("end_batch() called without begin_batch()");
+        let mut order = (start..self.inst_cnt).collect::<Vec<_>>();
+        order.sort_by_key(|&i| self.inst_infos[i].z);
+        let mut insts = order.iter().map(|&i| self.instances[i]).collect::<Vec<_>>();
+        for inst in insts.iter_mut() {
+            inst.flags &= !BACKDROP_BLUR;
+        }
+        self.blurs.retain(|&(i, _)| i < start);
+        // paths aren't batched, they stay drawn in this frame
+        for path in &mut self.paths {
+            path.inst = path.inst.min(start);
+        }
+        self.inst_cnt = start;
+        insts
+    }
+
+    /// uploads `verts` (usually from end_batch()) once into gpu only buf,
+    /// so draw_batch() doesn't copy them every frame, baking existing batch replaces it.
+    /// few changing instances can be updated with batch_mut()
+    pub fn bake_batch(&mut self, name: &str, verts: &[Vertex]) {
+        assert!(!verts.is_empty(), "baked batch can't be empty: {name}");
         let mut ctx = self.ctx.lock().unwrap();
+        if self.baked.remove(name).is_some() {
+            // old buf may still be used by frame in flight
+            queue_idle();
+            ctx.remove_buf(&format!("baked batch {name}"));
+        }
+        let buf = Self::upload_baked(&mut ctx, self.vertex_format, name, verts);
+        self.baked.insert(
+            name.to_string(),
+            BakedBatch {
+                buf,
+                verts: verts.to_vec(),
+                dirty: Vec::new(),
+            },
+        );
+    }
+
+    /// `range` of baked batch's instances, marked dirty so only changed ranges
+    /// are uploaded in next flush instead of whole batch
+    pub fn batch_mut(&mut self, name: &str, range: std::ops::Range<usize>) -> &mut [Vertex] {
+        let batch = self
+            .baked
+            .get_mut(name)
+            .unwrap_or_else(|| panic!("baked batch not found: {name}"));
+        batch.dirty.push(range.clone());
+        &mut batch.verts[range]
+    }
+
+    /// draws baked batch with current z, shader, blend mode and viewport scissor,
+    /// instances keep positions and params they were recorded with
+    pub fn draw_batch(&mut self, name: &str) {
+        assert!(
+            self.layer.is_none(),
+            "baked batches can't be drawn into layers"
+        );
+        let batch = self
+            .baked
+            .get(name)
+            .unwrap_or_else(|| panic!("baked batch not found: {name}"));
+        self.batches.push(BatchDraw {
+            inst: self.inst_cnt,
+            buf: batch.buf,
+            count: batch.verts.len(),
+            info: InstInfo {
+                viewport: self.scissor(),
+                ..self.info
+            },
+        });
+    }
+
+    /// frees baked batch's buf
+    pub fn remove_batch(&mut self, name: &str) {
+        if self.baked.remove(name).is_some() {
+            let mut ctx = self.ctx.lock().unwrap();
+            queue_idle();
+            ctx.remove_buf(&format!("baked batch {name}"));
+        }
+    }
+
+    /// packs layer into layers img, false if it doesn't fit (then it's drawn uncached)
+    fn cache_layer(&mut self, name: &str, px: [f32; 4], hash: u64, pending: Vec<Vertex>) -> bool {
+        let (w, h) = (px[2].max(1.0) as u16, px[3].max(1.0) as u16);
+        let rect = match self.layers.get(name) {
+            Some(layer) if layer.rect.wh() == (w, h) => layer.rect,
+            _ => {
+                self.invalidate_layer(name);
+                let Some((x, y)) = self.layer_packer.pack(w, h) else {
+                    warn!("layer doesn't fit in layers img, drawing it uncached: {name}");
+                    return false;
+                };
+                Rect::new(x, y, w, h)
+            }
+        };
+        self.layers.insert(
+            name.to_string(),
+            Layer {
+                rect,
+                px,
+                hash,
+                pending,
+            },
+        );
+        true
+    }
+
+    /// renders changed layers into their rect of layers img
+    fn render_layers(
+        ctx: &mut RenderCtx,
+        layers: &mut HashMap<String, Layer>,
+        width: f32,
+        height: f32,
+    ) {
+        let dirty = layers
+            .values_mut()
+            .filter(|l| !l.pending.is_empty())
+            .collect::<Vec<_>>();
+        if dirty.is_empty() {
+            return;
+        }
+        let insts = dirty
+            .iter()
+            .flat_map(|l| l.pending.iter().copied())
+            .collect::<Vec<_>>();
+        ctx.add_buf(
+            "layer vbo",
+            (insts.len() * size_of::<Vertex>()) as vk::DeviceSize,
+            BufUsage::VERT,
+            MemProp::CPU_CACHED,
+        );
+        ctx.write_buf("layer vbo", &insts[..]);
+        let (lw, lh) = (width as u32, height as u32);
+        ctx.begin_cmd();
+        ctx.set_img_layout(
+            "layers",
+            ImgLayout::COLOR,
+            vk::PipelineStageFlags2::FRAGMENT_SHADER,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            vk::AccessFlags2::NONE,
+            vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+        );
+        ctx.resume_render(lw, lh, "layers view", "");
+        ctx.bind_pipeline("layer");
+        ctx.bind_ds("layer ds");
+        ctx.bind_vbo("layer vbo");
+        let mut first = 0;
+        for layer in dirty {
+            let (x, y, w, h) = layer.rect.xywh();
+            // screen sized viewport offset so layer's px rect lands on it's rect in layers img
+            ctx.set_viewport(vk::Viewport {
+                x: x as f32 - layer.px[0],
+                y: y as f32 - layer.px[1],
+                width,
+                height,
+                min_depth: 0.0,
+                max_depth: 1.0,
+            });
+            let rect = vk::Rect2D {
+                offset: vk::Offset2D {
+                    x: x as i32,
+                    y: y as i32,
+                },
+                extent: vk::Extent2D {
+                    width: w as u32,
+                    height: h as u32,
+                },
+            };
+            ctx.set_scissor(rect);
+            ctx.clear_rect(rect, [0.0; 4]);
+            let cnt = layer.pending.len();
+            ctx.draw_first(4, cnt as u32, 0, first as u32);
+            first += cnt;
+            layer.pending.clear();
+        }
+        ctx.end_render();
+        ctx.set_img_layout(
+            "layers",
+            ImgLayout::SHADER_READ,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            vk::PipelineStageFlags2::FRAGMENT_SHADER,
+            vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+            vk::AccessFlags2::SHADER_SAMPLED_READ,
+        );
+        ctx.finish_cmd();
+    }
+
+    /// saves draw params (color, stroke, img, blend, z, areas, font, clip, viewport...)
+    /// to be restored by pop_state(), states can be nested
+    pub fn push_state(&mut self) {
+        self.states.push(State {
+            color: self.color,
+            roundness: self.roundness,
+            rotation: self.rotation,
+            stroke_width: self.stroke_width,
+            stroke_color: self.stroke_color,
+            stroke_align: self.stroke_align,
+            tex_coord: self.tex_coord,
+            blur: self.blur,
+            sampling: self.sampling,
+            color_matrix: self.color_matrix,
+            tiling: self.tiling,
+            custom: self.custom,
+            mask: self.mask,
+            info: self.info,
+            snap: self.snap,
+            vertical: self.vertical,
+            areas: self.areas.clone(),
+            font: self.font.clone(),
+            clip: self.clip,
+            viewport: self.viewport,
+        });
+    }
+
+    /// restores draw params saved by last push_state()
+    pub fn pop_state(&mut self) {
+        let state = self.states.pop().expect("pop_state() without push_state()");
+        self.color = state.color;
+        self.roundness = state.roundness;
+        self.rotation = state.rotation;
+        self.stroke_width = state.stroke_width;
+        self.stroke_color = state.stroke_color;
+        self.stroke_align = state.stroke_align;
+        self.tex_coord = state.tex_coord;
+        self.blur = state.blur;
+        self.sampling = state.sampling;
+        self.color_matrix = state.color_matrix;
+        self.tiling = state.tiling;
+        self.custom = state.custom;
+        self.mask = state.mask;
+        self.info = state.info;
+        self.snap = state.snap;
+        self.vertical = state.vertical;
+        self.areas = state.areas;
+        self.font = state.font;
+        self.clip = state.clip;
+        self.viewport = state.viewport;
+    }
+
+    /// push_state() that's popped when returned guard is dropped,
+    /// guard derefs to renderer: `let mut gfx = gfx.scoped();`
+    pub fn scoped(&mut self) -> Scoped<'_> {
+        self.push_state();
+        Scoped(self)
+    }
+
+    pub(crate) fn render(&mut self, ctx: &mut RenderCtx) {
+        if self.vert_cnt != 0 && self.inst_cnt == 0 {
+            return;
+        }
+        let format = self.vertex_format;
         ctx.bind_pipeline("render");
         ctx.bind_ds("render ds");
         if self.vert_cnt != 0 {
             ctx.bind_vbo("batch vbo");
             ctx.draw(self.vert_cnt as u32, 1);
         }
-        if self.inst_cnt != 0 {
-            ctx.bind_vbo("instance vbo");
-            ctx.draw(4, self.inst_cnt as u32);
+        if self.inst_cnt != 0 || !self.paths.is_empty() || !self.batches.is_empty() {
+            let instance_vbo = match (&self.compact_instances, self.vertex_format) {
+                (Some(compact), VertexFormat::Compact) => compact.buf(),
+                _ => self.instances.buf(),
+            };
+            ctx.bind_vbo_h(instance_vbo);
+            let (width, height, ..) = ctx.render_target();
+            let full = vk::Rect2D {
+                offset: vk::Offset2D::default(),
+                extent: vk::Extent2D { width, height },
+            };
+            // instance ranges are split where backdrop blurs start, paths, pipeline or viewport changes
+            let mut first = 0;
+            let mut blurs = self.blurs.iter().enumerate().peekable();
+            let mut paths = self.paths.iter().peekable();
+            let mut batches = self.batches.iter().peekable();
+            let mut grabs = self.grabs.iter().peekable();
+            for i in 0..=self.inst_cnt {
+                let blur = blurs.next_if(|(_, b)| b.0 == i);
+                let mut grab = false;
+                while grabs.next_if(|g| g.0 == i).is_some() {
+                    grab = true;
+                }
+                let path = paths.peek().is_some_and(|p| p.inst == i);
+                let batch = batches.peek().is_some_and(|b| b.inst == i);
+                let split = i == self.inst_cnt
+                    || blur.is_some()
+                    || grab
+                    || path
+                    || batch
+                    || self.inst_infos[i].pipeline() != self.inst_infos[first].pipeline()
+                    || self.inst_infos[i].viewport != self.inst_infos[first].viewport;
+                if split && i > first {
+                    let (shader, blend) = self.inst_infos[first].pipeline();
+                    let id = *self.pipeline_ids[shader][blend as usize].get_or_insert_with(|| {
+                        ctx.pipeline_id(&blend.pipeline(&format.shader(&self.shaders[shader])))
+                    });
+                    ctx.bind_pipeline_h(id);
+                    // after binding, pipeline changes reset scissor
+                    let viewport = self.inst_infos[first].viewport as usize;
+                    ctx.set_scissor(viewport.checked_sub(1).map_or(full, |v| self.scissors[v]));
+                    ctx.draw_first(4, (i - first) as u32, 0, first as u32);
+                    first = i;
+                }
+                if path {
+                    ctx.bind_pipeline("path");
+                    ctx.bind_ds("path ds");
+                    ctx.bind_vbo("path vbo");
+                    while let Some(p) = paths.next_if(|p| p.inst == i) {
+                        let viewport = p.viewport as usize;
+                        ctx.set_scissor(viewport.checked_sub(1).map_or(full, |v| self.scissors[v]));
+                        ctx.draw_first(p.count as u32, 1, p.first as u32, 0);
+                    }
+                    ctx.bind_ds("render ds");
+                    ctx.bind_vbo_h(instance_vbo);
+                }
+                if batch {
+                    while let Some(b) = batches.next_if(|b| b.inst == i) {
+                        let (shader, blend) = b.info.pipeline();
+                        let id =
+                            *self.pipeline_ids[shader][blend as usize].get_or_insert_with(|| {
+                                ctx.pipeline_id(
+                                    &blend.pipeline(&format.shader(&self.shaders[shader])),
+                                )
+                            });
+                        ctx.bind_pipeline_h(id);
+                        let viewport = b.info.viewport as usize;
+                        ctx.set_scissor(viewport.checked_sub(1).map_or(full, |v| self.scissors[v]));
+                        ctx.bind_vbo_h(b.buf);
+                        ctx.draw(4, b.count as u32);
+                    }
+                    ctx.bind_vbo_h(instance_vbo);
+                }
+                if grab {
+                    Self::grab(ctx);
+                    ctx.bind_pipeline("render");
+                    ctx.bind_ds("render ds");
+                    ctx.bind_vbo_h(instance_vbo);
+                }
+                if let Some((b, _)) = blur {
+                    Self::blur_backdrop(ctx, b as u32, self.blur_regions[b]);
+                    ctx.bind_pipeline("render");
+                    ctx.bind_ds("render ds");
+                    ctx.bind_vbo_h(instance_vbo);
+                }
+            }
+            ctx.set_scissor(full);
+        }
+        if let Some(pos) = self.pick_pos.take() {
+            Self::render_pick(ctx, self.instances.buf(), self.inst_cnt, pos);
+            self.pick_pending = true;
+        }
+    }
+
+    /// copies x, y, w, h `region` (in backdrop texels) of render target into backdrop img
+    /// and blurs it there with compute passes 2i and 2i+1
+    fn blur_backdrop(ctx: &mut RenderCtx, i: u32, region: [u32; 4]) {
+        Self::copy_target(ctx, |ctx, target, width, height| {
+            Self::copy_backdrop(ctx, target, (width, height), i, region)
+        });
+    }
+
+    fn copy_backdrop(
+        ctx: &mut RenderCtx,
+        target: &str,
+        (width, height): (u32, u32),
+        i: u32,
+        region: [u32; 4],
+    ) {
+        // downsample region of render target into backdrop
+        ctx.set_img_layout(
+            "backdrop",
+            ImgLayout::DST,
+            vk::PipelineStageFlags2::FRAGMENT_SHADER | vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::PipelineStageFlags2::BLIT,
+            vk::AccessFlags2::NONE,
+            vk::AccessFlags2::TRANSFER_WRITE,
+        );
+        // rendered area fills whole backdrop
+        let (bw, bh) = {
+            let backdrop = ctx.img("backdrop");
+            (backdrop.info.width, backdrop.info.height)
+        };
+        let [x, y, w, h] = region;
+        let to_px =
+            |t: u32, size: u32, backdrop: u32| (t as u64 * size as u64 / backdrop as u64) as u32;
+        let (x0, y0) = (to_px(x, width, bw), to_px(y, height, bh));
+        let (x1, y1) = (to_px(x + w, width, bw), to_px(y + h, height, bh));
+        ctx.blit_rect(
+            target,
+            "backdrop",
+            [x0, y0, (x1 - x0).max(1), (y1 - y0).max(1)],
+            region,
+            vk::Filter::LINEAR,
+        );
+
+        Self::blur_pass(ctx, "blur h ds", "backdrop", "backdrop temp", 2 * i, (w, h));
+        Self::blur_pass(
+            ctx,
+            "blur v ds",
+            "backdrop temp",
+            "backdrop",
+            2 * i + 1,
+            (w, h),
+        );
+        ctx.set_img_layout(
+            "backdrop",
+            ImgLayout::SHADER_READ,
+            vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::PipelineStageFlags2::FRAGMENT_SHADER,
+            vk::AccessFlags2::SHADER_STORAGE_WRITE,
+            vk::AccessFlags2::SHADER_SAMPLED_READ,
+        );
+    }
+
+    /// copies render target into grab img, see [`Renderer::grab_pass`]
+    fn grab(ctx: &mut RenderCtx) {
+        Self::copy_target(ctx, |ctx, target, width, height| {
+            ctx.set_img_layout(
+                "grab",
+                ImgLayout::DST,
+                vk::PipelineStageFlags2::FRAGMENT_SHADER,
+                vk::PipelineStageFlags2::BLIT,
+                vk::AccessFlags2::NONE,
+                vk::AccessFlags2::TRANSFER_WRITE,
+            );
+            let size = (width, height);
+            ctx.blit_region(target, "grab", size, size, vk::Filter::NEAREST);
+            ctx.set_img_layout(
+                "grab",
+                ImgLayout::SHADER_READ,
+                vk::PipelineStageFlags2::BLIT,
+                vk::PipelineStageFlags2::FRAGMENT_SHADER,
+                vk::AccessFlags2::TRANSFER_WRITE,
+                vk::AccessFlags2::SHADER_SAMPLED_READ,
+            );
+        });
+    }
+
+    /// pauses rendering so `copy` can read render target (name, width, height) as blit src
+    fn copy_target(ctx: &mut RenderCtx, copy: impl FnOnce(&mut RenderCtx, &str, u32, u32)) {
+        let (width, height, img_view, sampled_img_view) = ctx.render_target();
+        let target = ctx.img_view_img(&img_view);
+        ctx.end_render();
+        ctx.set_img_layout(
+            &target,
+            ImgLayout::SRC,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            vk::PipelineStageFlags2::BLIT,
+            vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+            vk::AccessFlags2::TRANSFER_READ,
+        );
+        copy(ctx, &target, width, height);
+
+        // continue rendering into render target
+        ctx.set_img_layout(
+            &target,
+            ImgLayout::COLOR,
+            vk::PipelineStageFlags2::BLIT,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            vk::AccessFlags2::TRANSFER_READ,
+            vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+        );
+        if !sampled_img_view.is_empty() {
+            // multisampled img is loaded, so it's prev writes must be visible
+            let sampled = ctx.img_view_img(&sampled_img_view);
+            ctx.img_barrier(
+                sampled,
+                vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+                vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+                vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+                vk::AccessFlags2::COLOR_ATTACHMENT_READ | vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+            );
+        }
+        ctx.resume_render(width, height, img_view, sampled_img_view);
+    }
+
+    /// blurs `src` into `dst` over `w` x `h` backdrop region of blur pass `pass`
+    fn blur_pass(
+        ctx: &mut RenderCtx,
+        ds: &str,
+        src: &str,
+        dst: &str,
+        pass: u32,
+        (w, h): (u32, u32),
+    ) {
+        ctx.set_img_layout(
+            src,
+            ImgLayout::SHADER_READ,
+            vk::PipelineStageFlags2::BLIT | vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::AccessFlags2::TRANSFER_WRITE | vk::AccessFlags2::SHADER_STORAGE_WRITE,
+            vk::AccessFlags2::SHADER_SAMPLED_READ,
+        );
+        ctx.set_img_layout(
+            dst,
+            ImgLayout::GENERAL,
+            vk::PipelineStageFlags2::FRAGMENT_SHADER | vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::AccessFlags2::NONE,
+            vk::AccessFlags2::SHADER_STORAGE_WRITE,
+        );
+        ctx.bind_pipeline("backdrop_blur");
+        ctx.bind_ds_dynamic(ds, &[pass * BLUR_PASS_STRIDE as u32]);
+        ctx.dispatch(w, h, 1);
+    }
+
+    /// renders shapes drawn by `draw` into temporary `width` x `height` img and reads it back
+    /// as rgba8 pixels (premultiplied alpha), for thumbnails and previews.
+    /// blocks until gpu is done, so call it outside of frame drawing (e.g. in update()),
+    /// cached layers aren't supported inside `draw`
+    pub fn render_to_image(
+        &mut self,
+        width: u32,
+        height: u32,
+        draw: impl FnOnce(&mut Self),
+    ) -> Vec<u8> {
+        assert!(
+            width != 0 && height != 0,
+            "can't render to {width}x{height} img"
+        );
+        assert!(
+            self.inst_cnt == 0 && self.vert_cnt == 0,
+            "render_to_image() called while drawing frame"
+        );
+        let (old_width, old_height) = (self.width, self.height);
+        let cull_stats = self.cull_stats;
+        let pick_pos = self.pick_pos.take();
+        // instance vbo and render ubo may still be used by frame in flight
+        queue_idle();
+        self.width = width as f32;
+        self.height = height as f32;
+        draw(self);
+        let ctx = self.ctx.clone();
+        let mut ctx = lock_ctx(&ctx);
+        ctx.write_buf("render ubo", &[self.width, self.height]);
+        self.jitter(&mut ctx, [0.0; 2]);
+        self.flush(&mut ctx);
+
+        let multisampled = self.samples > 1;
+        let size = width as vk::DeviceSize * height as vk::DeviceSize * 4;
+        let format = ctx.surface_format.format;
+        let info = ImageInfo::new().width(width).height(height).format(format);
+        ctx.add_img(
+            "thumbnail",
+            &info.clone().usage(ImgUsage::COLOR | ImgUsage::SRC),
+            MemProp::GPU,
+        );
+        ctx.add_img_view("thumbnail view", "thumbnail");
+        if multisampled {
+            ctx.add_img(
+                "sampled thumbnail",
+                &info
+                    .samples(self.samples)
+                    .usage(ImgUsage::COLOR | ImgUsage::TRANSIENT),
+                MemProp::GPU,
+            );
+            ctx.add_img_view("sampled thumbnail view", "sampled thumbnail");
+        }
+        ctx.add_buf(
+            "thumbnail readback",
+            size,
+            BufUsage::DST,
+            MemProp::CPU_CACHED,
+        );
+        ctx.begin_cmd();
+        ctx.set_img_layout(
+            "thumbnail",
+            ImgLayout::COLOR,
+            vk::PipelineStageFlags2::TOP_OF_PIPE,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            vk::AccessFlags2::NONE,
+            vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+        );
+        ctx.begin_render_clear(
+            width,
+            height,
+            "thumbnail view",
+            if multisampled {
+                "sampled thumbnail view"
+            } else {
+                ""
+            },
+            self.clear_color,
+        );
+        self.render(&mut ctx);
+        ctx.end_render();
+        ctx.set_img_layout(
+            "thumbnail",
+            ImgLayout::SRC,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            vk::PipelineStageFlags2::TRANSFER,
+            vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+            vk::AccessFlags2::TRANSFER_READ,
+        );
+        ctx.copy_img_to_buf(
+            "thumbnail",
+            "thumbnail readback",
+            &[BufferImageCopy {
+                buf_off: 0,
+                img_off_x: 0,
+                img_off_y: 0,
+                buf_width: width,
+                buf_height: height,
+            }],
+        );
+        // end_cmd() instead of finish_cmd() so cached bindings don't leak into next frame
+        let cmd = ctx.end_cmd();
+        ctx.submit_cmd(cmd, &[], &[], &[]);
+        ctx.wait_cmd(cmd);
+        let mut pixels = vec![0u8; size as usize];
+        ctx.read_buf("thumbnail readback", &mut pixels[..]);
+        ctx.remove_img("thumbnail");
+        ctx.try_remove_img("sampled thumbnail");
+        ctx.remove_buf("thumbnail readback");
+        if matches!(
+            format,
+            vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB
+        ) {
+            for px in pixels.chunks_exact_mut(4) {
+                px.swap(0, 2);
+            }
         }
+
+        self.width = old_width;
+        self.height = old_height;
+        ctx.write_buf("render ubo", &[old_width, old_height]);
+        drop(ctx);
+        self.reset();
+        self.cull_stats = cull_stats;
+        self.pick_pos = pick_pos;
+        pixels
     }
 
-    pub(crate) fn on_resize(&mut self, e: &WindowResize) {
+    /// `target_size` is size window sized imgs are allocated at, they're only recreated
+    /// when it changes, so resizes within it don't stall gpu
+    pub(crate) fn on_resize(
+        &mut self,
+        ctx: &mut RenderCtx,
+        e: &WindowResize,
+        target_size: (u32, u32),
+    ) {
         if e.width == 0 || e.height == 0 {
             return;
         }
         self.width = e.width as f32;
         self.height = e.height as f32;
         let resolution = [e.width as f32, e.height as f32];
-        self.ctx
-            .lock()
-            .unwrap()
-            .write_buf("render ubo", &resolution);
+        ctx.write_buf("render ubo", &resolution);
+        // cached layers are rerecorded at new size
+        self.layers.clear();
+        self.layer_packer = Guillotine::new(e.width as u16, e.height as u16);
+        if self.target_size == target_size {
+            return;
+        }
+        self.target_size = target_size;
+        let (width, height) = target_size;
+
+        // resize backdrop blur imgs
+        queue_idle();
+        let format = ctx.surface_format.format;
+        for img in ["backdrop", "backdrop temp"] {
+            ctx.try_remove_img(img);
+            ctx.add_img(
+                img,
+                &ImageInfo::new()
+                    .width(width.div_ceil(BACKDROP_SCALE))
+                    .height(height.div_ceil(BACKDROP_SCALE))
+                    .format(BACKDROP_FORMAT)
+                    .usage(ImgUsage::STORAGE | ImgUsage::SAMPLED | ImgUsage::DST),
+                MemProp::GPU,
+            );
+            ctx.add_img_view(&format!("{img} view"), img);
+        }
+        ctx.write_ds_img("render ds", "backdrop view", ImgLayout::SHADER_READ, 2);
+        // grab img is made on first grab_pass(), backdrop is bound just to be valid till then
+        if ctx.try_img("grab").is_ok() {
+            Self::add_grab_img(ctx, width, height);
+        } else {
+            ctx.write_ds_img("render ds", "backdrop view", ImgLayout::SHADER_READ, 7);
+        }
+        ctx.write_ds_img("layer ds", "backdrop view", ImgLayout::SHADER_READ, 7);
+        ctx.write_ds_img("blur h ds", "backdrop view", ImgLayout::SHADER_READ, 0);
+        ctx.write_ds_img("blur h ds", "backdrop temp view", ImgLayout::GENERAL, 2);
+        ctx.write_ds_img("blur v ds", "backdrop view", ImgLayout::GENERAL, 2);
+
+        ctx.try_remove_img("layers");
+        ctx.add_img(
+            "layers",
+            &ImageInfo::new()
+                .width(width)
+                .height(height)
+                .format(format)
+                .usage(ImgUsage::COLOR | ImgUsage::SAMPLED),
+            MemProp::GPU,
+        );
+        ctx.add_img_view("layers view", "layers");
+        ctx.write_ds_img("render ds", "layers view", ImgLayout::SHADER_READ, 4);
+        // layers aren't sampled while rendering layers, backdrop is bound just to be valid
+        ctx.write_ds_img("layer ds", "backdrop view", ImgLayout::SHADER_READ, 2);
+        ctx.write_ds_img("layer ds", "backdrop view", ImgLayout::SHADER_READ, 4);
+        ctx.write_ds_img("blur v ds", "backdrop temp view", ImgLayout::SHADER_READ, 0);
+
+        if ctx.try_img("pick").is_ok() {
+            Self::add_pick_img(ctx, width, height);
+        }
     }
 
-    pub(crate) fn flush(&mut self) {
+    pub(crate) fn flush(&mut self, ctx: &mut RenderCtx) {
+        assert!(
+            self.layer.is_none(),
+            "begin_layer() called without end_layer()"
+        );
+        for (name, asset) in self.loader.ready() {
+            match asset {
+                Ok(Asset::Img(img_data)) => {
+                    self.insert_img(&name, img_data);
+                }
+                Ok(Asset::Font(font)) => self.insert_font(&name, font),
+                Err(e) => {
+                    err!("failed to load asset({name}): {e}");
+                    self.loaded.push((name, Some(e)));
+                    continue;
+                }
+            }
+            self.loaded.push((name, None));
+        }
+        for (name, video) in self.videos.iter_mut() {
+            if let Some(frame) = video.update() {
+                self.imgs
+                    .get_mut(name)
+                    .unwrap()
+                    .0
+                    .copy_from_slice(&frame.img);
+            }
+        }
+        self.sort_instances();
+        if self.debug != DrawDebug::default() {
+            self.debug_draws(ctx);
+        }
+        if let Some(compact) = self
+            .compact_instances
+            .as_mut()
+            .filter(|_| self.vertex_format == VertexFormat::Compact)
+        {
+            if compact.len() < self.inst_cnt {
+                compact.grow(ctx, 0, self.instances.len());
+            }
+            for (c, v) in compact.iter_mut().zip(&self.instances[..self.inst_cnt]) {
+                *c = v.into();
+            }
+        }
         // update instance buffers
-        let mut ctx = self.ctx.lock().unwrap();
+        if std::mem::take(&mut self.pick_pending) {
+            let mut picked = [0u32];
+            ctx.read_buf("pick readback", &mut picked);
+            self.picked = picked[0];
+        }
+        if self.pick_pos.is_some() && self.inst_cnt != 0 {
+            let ids = self.inst_infos[..self.inst_cnt]
+                .iter()
+                .map(|i| i.pick_id)
+                .collect::<Vec<_>>();
+            ctx.add_buf(
+                "pick id vbo",
+                (ids.len() * size_of::<u32>()) as vk::DeviceSize,
+                BufUsage::VERT,
+                MemProp::CPU_CACHED,
+            );
+            ctx.write_buf("pick id vbo", &ids[..]);
+        }
         if self.vert_cnt != 0 {
             let vbo_size = (self.vertices.len() * size_of::<Vertex>()) as vk::DeviceSize;
             if ctx.buf_size("batch vbo") < vbo_size {
@@ -488,12 +3553,88 @@ impl Renderer {
             }
             ctx.write_buf("batch vbo", &self.vertices[..self.vert_cnt]);
         }
-        if self.inst_cnt != 0 {
-            let inst_vbo_size = (self.instances.len() * size_of::<Vertex>()) as vk::DeviceSize;
-            if ctx.buf_size("instance vbo") < inst_vbo_size {
-                ctx.recreate_buf("instance vbo", inst_vbo_size);
+        if !self.path_verts.is_empty() {
+            let path_vbo_size = (self.path_verts.len().next_power_of_two()
+                * size_of::<PathVertex>()) as vk::DeviceSize;
+            if ctx.buf_size("path vbo") < path_vbo_size {
+                ctx.recreate_buf("path vbo", path_vbo_size);
+            }
+            ctx.write_buf("path vbo", &self.path_verts[..]);
+        }
+        // upload changed ranges of baked batches, merged so overlapping ones are written once
+        for batch in self.baked.values_mut().filter(|b| !b.dirty.is_empty()) {
+            batch.dirty.sort_by_key(|r| r.start);
+            let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+            for range in batch.dirty.drain(..) {
+                match ranges.last_mut() {
+                    Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                    _ => ranges.push(range),
+                }
+            }
+            for range in ranges.into_iter().filter(|r| !r.is_empty()) {
+                let off = (range.start * self.vertex_format.size()) as vk::DeviceSize;
+                let verts = &batch.verts[range];
+                match self.vertex_format {
+                    VertexFormat::Full => ctx.write_buf_off_h(batch.buf, verts, off),
+                    VertexFormat::Compact => {
+                        let verts: Vec<CompactVertex> = verts.iter().map(Into::into).collect();
+                        ctx.write_buf_off_h(batch.buf, &verts[..], off);
+                    }
+                }
+            }
+        }
+        // update backdrop blur passes
+        self.blur_regions.clear();
+        if !self.blurs.is_empty() {
+            // px to backdrop texels, rendered area is stretched over whole backdrop
+            let (bw, bh) = {
+                let backdrop = ctx.img("backdrop");
+                (backdrop.info.width, backdrop.info.height)
+            };
+            let (sx, sy) = (bw as f32 / self.width, bh as f32 / self.height);
+            for (run, &(start, r)) in self.blurs.iter().enumerate() {
+                let end = self.blurs.get(run + 1).map_or(self.inst_cnt, |b| b.0);
+                let mut bounds = [f32::MAX, f32::MAX, f32::MIN, f32::MIN];
+                for v in self.instances[start..end]
+                    .iter()
+                    .take_while(|v| v.flags & BACKDROP_BLUR != 0)
+                {
+                    let (sin, cos) = v.rotation.sin_cos();
+                    let (hw, hh) = (v.scale[0] * self.width, v.scale[1] * self.height);
+                    let ex = (hw * cos).abs() + (hh * sin).abs();
+                    let ey = (hw * sin).abs() + (hh * cos).abs();
+                    let (x, y) = (v.pos[0] * self.width, v.pos[1] * self.height);
+                    bounds = [
+                        bounds[0].min(x - ex),
+                        bounds[1].min(y - ey),
+                        bounds[2].max(x + ex),
+                        bounds[3].max(y + ey),
+                    ];
+                }
+                // blur reads radius px around shapes, 1px more for filtering
+                let m = r + 1.0;
+                // at least 1 texel, so there's always something to copy
+                let span = |lo: f32, hi: f32, size: u32| {
+                    let lo = (lo.floor().max(0.0) as u32).min(size - 1);
+                    let hi = (hi.ceil().max(0.0) as u32).clamp(lo + 1, size);
+                    (lo, hi - lo)
+                };
+                let (x, w) = span((bounds[0] - m) * sx, (bounds[2] + m) * sx, bw);
+                let (y, h) = span((bounds[1] - m) * sy, (bounds[3] + m) * sy, bh);
+                let region = [x, y, w, h];
+                self.blur_regions.push(region);
+                let passes =
+                    [([1.0, 0.0], r * sx), ([0.0, 1.0], r * sy)].map(|(dir, radius)| BlurPass {
+                        dir,
+                        radius,
+                        _pad: 0.0,
+                        region,
+                    });
+                for (i, pass) in passes.iter().enumerate() {
+                    let off = ((2 * run + i) * BLUR_PASS_STRIDE) as vk::DeviceSize;
+                    ctx.write_buf_off("blur ubo", pass, off);
+                }
             }
-            ctx.write_buf("instance vbo", &self.instances[..self.inst_cnt]);
         }
         // update atlas
         let img_datas = self.imgs.values_mut().filter(|i| i.0.is_dirty());
@@ -519,10 +3660,25 @@ impl Renderer {
             ctx.write_buf_off(staging, &data[..], copy.buf_off);
         }
         let wrong_layout = ctx.img("atlas").info.layout != ImgLayout::SHADER_READ;
+        // backdrop and layers are sampled in render.wgsl even when unused, so they need a valid layout
+        let undefined = ["backdrop", "layers"]
+            .into_iter()
+            .filter(|&img| ctx.img(img).info.layout == ImgLayout::UNDEFINED)
+            .collect::<Vec<_>>();
         let copy = !buf_copies.is_empty();
-        if copy || wrong_layout {
+        if copy || wrong_layout || !undefined.is_empty() {
             ctx.begin_cmd();
         }
+        for img in undefined.iter() {
+            ctx.set_img_layout(
+                img,
+                ImgLayout::SHADER_READ,
+                vk::PipelineStageFlags2::TOP_OF_PIPE,
+                vk::PipelineStageFlags2::FRAGMENT_SHADER,
+                vk::AccessFlags2::NONE,
+                vk::AccessFlags2::SHADER_SAMPLED_READ,
+            );
+        }
         if copy {
             ctx.set_img_layout(
                 "atlas",
@@ -563,26 +3719,52 @@ impl Renderer {
                 vk::AccessFlags2::TRANSFER_WRITE,
                 vk::AccessFlags2::SHADER_READ,
             );
+        }
+        if copy || wrong_layout || !undefined.is_empty() {
             ctx.finish_cmd();
         }
+        Self::render_layers(ctx, &mut self.layers, self.width, self.height);
     }
 
     pub(crate) fn reset(&mut self) {
+        self.cull_stats = CullStats {
+            submitted: self.debug_submitted.take().unwrap_or(self.inst_cnt) as u32,
+            culled: self.culled,
+        };
+        self.culled = 0;
         self.vert_cnt = 0;
         self.inst_cnt = 0;
+        self.instances.next_frame();
+        if let Some(compact) = &mut self.compact_instances {
+            compact.next_frame();
+        }
         self.color = [255, 255, 255, 255];
         self.stroke_color = [0; 4];
         self.stroke_width = 0.0;
         self.roundness = 0.0;
         self.rotation = 0.0;
         self.areas = Vec::new();
+        self.viewport = None;
+        self.clip = None;
+        self.scissors.clear();
         self.tex_coord = [0, 0];
-
-        self.old_color = self.color;
-        self.old_stroke_color = self.stroke_color;
-        self.old_stroke_width = self.stroke_width;
-        self.old_roundness = self.roundness;
-        self.old_rotation = self.rotation;
-        self.old_tex_coord = self.tex_coord;
+        self.blur = 0.0;
+        self.sampling = Sampling::Linear;
+        self.stroke_align = StrokeAlign::Inner;
+        self.color_matrix = 0;
+        self.tiling = [1.0; 2];
+        self.custom = [0.0; 4];
+        self.blurs.clear();
+        self.path_verts.clear();
+        self.paths.clear();
+        self.grabs.clear();
+        self.batches.clear();
+        self.text_cache.retain(|_, t| std::mem::take(&mut t.used));
+        self.mask = (0, [0.0; 2]);
+        self.info = InstInfo::default();
+        assert!(
+            self.states.is_empty(),
+            "push_state() without pop_state() this frame"
+        );
     }
 }
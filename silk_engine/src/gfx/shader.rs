@@ -4,12 +4,12 @@ use super::{
     alloc_callbacks, format_size, gpu,
     vulkan::{DSLBinding, PipelineStageInfo},
 };
-use crate::{RES_PATH, log};
+use crate::{RES_PATH, log, util::vfs};
 use ash::vk;
 use naga::Module;
 
 fn shader_path(name: &str) -> String {
-    format!("{RES_PATH}/shaders/{name}.wgsl")
+    format!("shaders/{name}.wgsl")
 }
 
 fn shader_cache_path(name: &str) -> String {
@@ -25,7 +25,9 @@ pub struct Shader {
 impl Shader {
     pub fn new(name: &str) -> Self {
         // TODO: save/load reflection (using naga's serde serialize feature) (only if bottlenecked)
-        let source = std::fs::read_to_string(shader_path(name)).unwrap();
+        let source = vfs()
+            .read_to_string(&shader_path(name))
+            .unwrap_or_else(|_| panic!("shader not found: {name}"));
         let ir_module = naga::front::wgsl::parse_str(&source).unwrap_or_else(|e| {
             panic!("WGSL {}", e.emit_to_string(&source));
         });
@@ -178,6 +180,14 @@ impl Shader {
         &self.dsl_infos
     }
 
+    pub(crate) fn set_desc_ty(&mut self, group: usize, binding: u32, desc_ty: vk::DescriptorType) {
+        let bind = self.dsl_infos[group]
+            .iter_mut()
+            .find(|b| b.binding == binding)
+            .unwrap_or_else(|| panic!("binding not found: {group}:{binding}"));
+        bind.desc_ty = desc_ty;
+    }
+
     pub fn create_module(&self) -> vk::ShaderModule {
         unsafe {
             gpu()
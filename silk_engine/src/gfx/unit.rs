@@ -2,6 +2,8 @@
 pub enum Unit {
     /// pixels
     Px(i32),
+    /// device independent pixels, scaled by window scale factor
+    Dp(f32),
     /// 1.0 is min(width, height) pixels
     Mn(f32),
     /// 1.0 is max(width, height) pixels
@@ -0,0 +1,182 @@
+use std::{ops::Range, time::Instant};
+
+use crate::{
+    util::{FileBytes, ImageData, Jpeg, vfs},
+    warn,
+};
+
+/// returns end of jpeg starting at `start` (after EOI), walks segments
+/// so EOI bytes inside app segments (like exif thumbnails) aren't matched.
+/// none if jpeg is truncated
+fn jpeg_end(data: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 2;
+    while i + 1 < data.len() {
+        if data[i] != 0xFF {
+            i += 1;
+            continue;
+        }
+        let marker = data[i + 1];
+        match marker {
+            0xD9 => return Some(i + 2),
+            0xFF | 0x00 | 0xD0..=0xD7 => i += if marker == 0xFF { 1 } else { 2 },
+            0xDA => {
+                if i + 3 >= data.len() {
+                    return None;
+                }
+                // skip header, then entropy coded data up to next marker
+                let len = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+                i += 2 + len;
+                while i + 1 < data.len()
+                    && !(data[i] == 0xFF
+                        && data[i + 1] != 0
+                        && !(0xD0..=0xD7).contains(&data[i + 1]))
+                {
+                    i += 1;
+                }
+            }
+            _ => {
+                if i + 3 >= data.len() {
+                    return None;
+                }
+                let len = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+                i += 2 + len;
+            }
+        }
+    }
+    None
+}
+
+/// motion jpeg video, stream of concatenated jpeg frames (`ffmpeg -i in.mp4 -f mjpeg out.mjpeg`)
+pub struct Video {
+    /// mapped for large videos, so frames are decoded straight from file
+    data: FileBytes,
+    frames: Vec<Range<usize>>,
+    width: u32,
+    height: u32,
+    fps: f32,
+    pub looping: bool,
+    playing: bool,
+    time: f32,
+    last: Instant,
+    /// last decoded frame
+    frame: Option<usize>,
+}
+
+impl Video {
+    /// loads `res/videos/{name}.mjpeg`
+    pub fn new(name: &str, fps: f32) -> Self {
+        crate::scope_time!("MJPEG load");
+        assert!(fps > 0.0, "video fps must be positive: {fps}");
+        let data = vfs()
+            .read(&format!("videos/{name}.mjpeg"))
+            .unwrap_or_else(|_| panic!("video not found: {name}"));
+        let mut frames = Vec::new();
+        let mut i = 0;
+        while i + 1 < data.len() {
+            if data[i] == 0xFF && data[i + 1] == 0xD8 {
+                let Some(end) = jpeg_end(&data, i) else {
+                    warn!("skipping truncated last frame of video: {name}");
+                    break;
+                };
+                frames.push(i..end);
+                i = end;
+            } else {
+                i += 1;
+            }
+        }
+        assert!(!frames.is_empty(), "video has no frames: {name}");
+        let first = Jpeg::decode(&data[frames[0].clone()]);
+        Self {
+            data,
+            frames,
+            width: first.width,
+            height: first.height,
+            fps,
+            looping: true,
+            playing: true,
+            time: 0.0,
+            last: Instant::now(),
+            frame: None,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// in seconds
+    pub fn duration(&self) -> f32 {
+        self.frames.len() as f32 / self.fps
+    }
+
+    /// playback position in seconds
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// reached end and isn't looping
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.time >= self.duration()
+    }
+
+    pub fn play(&mut self) {
+        if !self.playing {
+            self.playing = true;
+            self.last = Instant::now();
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn seek(&mut self, time: f32) {
+        self.time = time.clamp(0.0, self.duration());
+    }
+
+    /// advances playback, returns decoded frame if it changed since last call
+    pub(crate) fn update(&mut self) -> Option<ImageData> {
+        let now = Instant::now();
+        if self.playing {
+            self.time += now.duration_since(self.last).as_secs_f32();
+        }
+        self.last = now;
+        let duration = self.duration();
+        if self.looping {
+            self.time %= duration;
+        } else {
+            self.time = self.time.min(duration);
+        }
+        let idx = ((self.time * self.fps) as usize).min(self.frames.len() - 1);
+        if self.frame == Some(idx) {
+            return None;
+        }
+        self.frame = Some(idx);
+        let frame = Jpeg::decode(&self.data[self.frames[idx].clone()]);
+        assert!(
+            frame.width == self.width && frame.height == self.height,
+            "video frame size changed: {}x{} -> {}x{}",
+            self.width,
+            self.height,
+            frame.width,
+            frame.height
+        );
+        Some(frame)
+    }
+}
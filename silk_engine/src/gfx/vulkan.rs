@@ -2,6 +2,8 @@
 use std::ffi::c_void;
 use std::sync::LazyLock;
 
+mod barrier_tracker;
+mod caps;
 mod cmd_alloc;
 mod cmd_manager;
 mod config;
@@ -11,25 +13,40 @@ mod gpu;
 mod gpu_alloc;
 mod image;
 mod instance;
+mod lifetime_tracker;
 mod pipeline;
 mod pipeline_layout_manager;
+mod render_pass;
+#[cfg(feature = "renderdoc")]
+mod renderdoc;
 mod sampler_manager;
 
-pub use config::MSAA;
+pub use caps::{Caps, caps};
+pub use config::{GpuPreference, ValidationFeatures, VulkanConfig, vulkan_config};
+pub use gpu::{GpuInfo, enabled_gpu_extensions, gpu_info, gpu_props, gpus};
+pub use gpu_alloc::{GpuAllocStats, MemPoolStats};
 pub use image::ImageInfo;
 pub use pipeline::{Enable, GraphicsPipelineInfo};
 
+pub(super) use barrier_tracker::BarrierTracker;
+pub(super) use caps::cmd_barrier;
 pub(super) use cmd_alloc::CmdAlloc;
 pub(super) use cmd_manager::CmdManager;
+pub(crate) use config::{set_display, set_vulkan_config};
 pub(super) use ds_alloc::DescAlloc;
 pub(super) use dsl_manager::{DSLBinding, DSLManager};
-pub(super) use gpu::{gpu, gpu_mem_props, physical_gpu};
+pub(super) use gpu::{PerGpu, gpu, gpu_limits, gpu_mem_props, physical_gpu};
 pub(super) use gpu_alloc::GpuAlloc;
 pub(super) use instance::instance;
+pub use instance::{filter_validation_message, unfilter_validation_message};
+pub(super) use lifetime_tracker::LifetimeTracker;
 pub(super) use pipeline::PipelineStageInfo;
 pub(super) use pipeline::create_compute;
 pub(super) use pipeline_layout_manager::PipelineLayoutManager;
-pub(super) use sampler_manager::SamplerManager;
+pub(super) use render_pass::render_pass;
+#[cfg(feature = "renderdoc")]
+pub(crate) use renderdoc::renderdoc;
+pub(super) use sampler_manager::{SamplerInfo, SamplerManager};
 
 use crate::err;
 #[cfg(debug_assertions)]
@@ -209,8 +226,11 @@ pub fn alloc_callbacks() -> Option<&'static vk::AllocationCallbacks<'static>> {
     None
 }
 
-static ENTRY: LazyLock<ash::Entry> =
-    LazyLock::new(|| unsafe { ash::Entry::load().expect("Failed to load Vulkan") });
+static ENTRY: LazyLock<ash::Entry> = LazyLock::new(|| {
+    #[cfg(feature = "renderdoc")]
+    renderdoc::load();
+    unsafe { ash::Entry::load().expect("Failed to load Vulkan") }
+});
 
 static QUEUE_FAMILY_PROPS: LazyLock<Vec<vk::QueueFamilyProperties>> = LazyLock::new(|| unsafe {
     let queue_family_props_len =
@@ -236,7 +256,7 @@ static QUEUE_FAMILY_INDEX: LazyLock<u32> = LazyLock::new(|| {
         .unwrap_or_default() as u32
 });
 
-static QUEUE: LazyLock<vk::Queue> = LazyLock::new(|| {
+static QUEUE: PerGpu<vk::Queue> = PerGpu::new(|| {
     let queue = unsafe { gpu().get_device_queue(*QUEUE_FAMILY_INDEX, 0) };
     debug_name("main queue", queue);
     queue
@@ -244,12 +264,25 @@ static QUEUE: LazyLock<vk::Queue> = LazyLock::new(|| {
 
 pub fn gpu_idle() {
     crate::scope_time!("GPU idle");
-    unsafe { gpu().device_wait_idle().unwrap() };
+    // fails only if device is lost, then there is nothing to wait for
+    if let Err(e) = unsafe { gpu().device_wait_idle() } {
+        err!("failed to wait for gpu idle: {e}");
+    }
 }
 
 pub fn queue_idle() {
     crate::scope_time!("Queue idle");
-    unsafe { gpu().queue_wait_idle(*QUEUE).unwrap() };
+    if let Err(e) = unsafe { gpu().queue_wait_idle(*QUEUE) } {
+        err!("failed to wait for queue idle: {e}");
+    }
+}
+
+/// destroys lost device and makes new one, everything made from lost device must be destroyed
+/// first, device level caches, loaders and queue are remade from new one
+pub(super) fn recreate_gpu() {
+    render_pass::destroy_render_passes();
+    pipeline::destroy_pipeline_cache();
+    gpu::replace_gpu();
 }
 
 pub fn entry() -> &'static ash::Entry {
@@ -264,6 +297,16 @@ pub fn queue() -> vk::Queue {
     *QUEUE
 }
 
+/// largest sample count up to `samples` that gpu supports for color attachments
+pub fn max_supported_samples(samples: u32) -> u32 {
+    let supported = gpu_limits().framebuffer_color_sample_counts;
+    let mut samples = 1 << samples.clamp(1, 64).ilog2();
+    while samples > 1 && !supported.contains(samples_u32_to_vk(samples)) {
+        samples /= 2;
+    }
+    samples
+}
+
 pub fn samples_u32_to_vk(samples: u32) -> vk::SampleCountFlags {
     match samples {
         1 => vk::SampleCountFlags::TYPE_1,
@@ -449,7 +492,7 @@ impl BufUsage {
 pub struct ImgUsage;
 impl ImgUsage {
     pub const SAMPLED: vk::ImageUsageFlags = vk::ImageUsageFlags::SAMPLED;
-    pub const STORAGE: vk::ImageUsageFlags = vk::ImageUsageFlags::SAMPLED;
+    pub const STORAGE: vk::ImageUsageFlags = vk::ImageUsageFlags::STORAGE;
     pub const COLOR: vk::ImageUsageFlags = vk::ImageUsageFlags::COLOR_ATTACHMENT;
     pub const SRC: vk::ImageUsageFlags = vk::ImageUsageFlags::TRANSFER_SRC;
     pub const DST: vk::ImageUsageFlags = vk::ImageUsageFlags::TRANSFER_DST;
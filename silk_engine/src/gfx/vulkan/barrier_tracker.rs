@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use ash::vk;
+
+use crate::{log, warn};
+
+const WRITE_ACCESS: vk::AccessFlags2 = vk::AccessFlags2::from_raw(
+    vk::AccessFlags2::SHADER_WRITE.as_raw()
+        | vk::AccessFlags2::SHADER_STORAGE_WRITE.as_raw()
+        | vk::AccessFlags2::COLOR_ATTACHMENT_WRITE.as_raw()
+        | vk::AccessFlags2::DEPTH_STENCIL_ATTACHMENT_WRITE.as_raw()
+        | vk::AccessFlags2::TRANSFER_WRITE.as_raw()
+        | vk::AccessFlags2::HOST_WRITE.as_raw()
+        | vk::AccessFlags2::MEMORY_WRITE.as_raw(),
+);
+
+fn covers_stage(mask: vk::PipelineStageFlags2, stage: vk::PipelineStageFlags2) -> bool {
+    mask.contains(stage)
+        || mask.contains(vk::PipelineStageFlags2::ALL_COMMANDS)
+        || (mask.contains(vk::PipelineStageFlags2::ALL_GRAPHICS)
+            && !stage.intersects(
+                vk::PipelineStageFlags2::COMPUTE_SHADER
+                    | vk::PipelineStageFlags2::ALL_TRANSFER
+                    | vk::PipelineStageFlags2::TRANSFER
+                    | vk::PipelineStageFlags2::BLIT
+                    | vk::PipelineStageFlags2::COPY
+                    | vk::PipelineStageFlags2::HOST,
+            ))
+}
+
+fn covers_access(mask: vk::AccessFlags2, access: vk::AccessFlags2) -> bool {
+    mask.contains(access)
+        || (mask.contains(vk::AccessFlags2::MEMORY_WRITE) && WRITE_ACCESS.contains(access))
+        || (mask.contains(vk::AccessFlags2::MEMORY_READ) && !access.intersects(WRITE_ACCESS))
+}
+
+#[derive(Default)]
+struct ImgState {
+    /// last write not yet made available by a barrier
+    write: Option<(vk::PipelineStageFlags2, vk::AccessFlags2)>,
+    /// reads since last write or barrier
+    read_stages: vk::PipelineStageFlags2,
+    /// what last barrier made img visible to, None if no barrier was recorded yet
+    visible: Option<(vk::PipelineStageFlags2, vk::AccessFlags2)>,
+}
+
+/// Tracks image accesses/transitions recorded through RenderCtx and reports
+/// hazards by img name, disabled by default since it costs a hashmap lookup per access
+#[derive(Default)]
+pub(crate) struct BarrierTracker {
+    pub(crate) enabled: bool,
+    imgs: HashMap<String, ImgState>,
+}
+
+impl BarrierTracker {
+    pub(crate) fn barrier(
+        &mut self,
+        img: &str,
+        old_layout: vk::ImageLayout,
+        new_layout: vk::ImageLayout,
+        (src_stage, src_access): (vk::PipelineStageFlags2, vk::AccessFlags2),
+        (dst_stage, dst_access): (vk::PipelineStageFlags2, vk::AccessFlags2),
+    ) {
+        if !self.enabled {
+            return;
+        }
+        log!(
+            "[sync] {img}: {old_layout:?} -> {new_layout:?} | {src_stage:?}({src_access:?}) -> {dst_stage:?}({dst_access:?})"
+        );
+        let state = self.imgs.entry(img.to_string()).or_default();
+        if let Some((stage, access)) = state.write {
+            if !covers_stage(src_stage, stage) {
+                warn!(
+                    "[sync] {img}: barrier src stage {src_stage:?} doesn't wait for prior write at {stage:?}, add {stage:?} to src stage"
+                );
+            }
+            if !covers_access(src_access, access) {
+                warn!(
+                    "[sync] {img}: barrier src access {src_access:?} doesn't make prior {access:?} available, add {access:?} to src access"
+                );
+            }
+        }
+        if old_layout != new_layout
+            && !state.read_stages.is_empty()
+            && !covers_stage(src_stage, state.read_stages)
+        {
+            warn!(
+                "[sync] {img}: write-after-read, layout transition to {new_layout:?} doesn't wait for reads at {:?}, add them to src stage",
+                state.read_stages
+            );
+        }
+        state.write = None;
+        state.read_stages = vk::PipelineStageFlags2::NONE;
+        state.visible = Some((dst_stage, dst_access));
+    }
+
+    /// records img access, `layout` is the one img is in, `expected` is the one access needs
+    pub(crate) fn access(
+        &mut self,
+        img: &str,
+        layout: vk::ImageLayout,
+        expected: vk::ImageLayout,
+        stage: vk::PipelineStageFlags2,
+        access: vk::AccessFlags2,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let state = self.imgs.entry(img.to_string()).or_default();
+        let is_write = access.intersects(WRITE_ACCESS);
+        if layout != expected && layout != vk::ImageLayout::GENERAL {
+            warn!(
+                "[sync] {img}: used as {expected:?} at {stage:?} while in {layout:?}, missing set_img_layout() before use"
+            );
+        }
+        if let Some((prev_stage, prev_access)) = state.write {
+            warn!(
+                "[sync] {img}: {} at {stage:?}({access:?}) after {prev_access:?} at {prev_stage:?} without barrier",
+                if is_write {
+                    "write-after-write"
+                } else {
+                    "read-after-write"
+                }
+            );
+        } else if is_write && !state.read_stages.is_empty() {
+            warn!(
+                "[sync] {img}: write-after-read at {stage:?}({access:?}) after reads at {:?} without barrier",
+                state.read_stages
+            );
+        } else if let Some((vis_stage, vis_access)) = state.visible
+            && (!covers_stage(vis_stage, stage) || !covers_access(vis_access, access))
+        {
+            warn!(
+                "[sync] {img}: accessed at {stage:?}({access:?}) but last barrier only made it visible to {vis_stage:?}({vis_access:?})"
+            );
+        }
+        if is_write {
+            state.write = Some((stage, access));
+            state.read_stages = vk::PipelineStageFlags2::NONE;
+        } else {
+            state.read_stages |= stage;
+        }
+    }
+
+    /// whole queue was waited on, so all prior accesses are complete and visible
+    pub(crate) fn sync_all(&mut self) {
+        for state in self.imgs.values_mut() {
+            state.write = None;
+            state.read_stages = vk::PipelineStageFlags2::NONE;
+            state.visible = None;
+        }
+    }
+
+    pub(crate) fn forget(&mut self, img: &str) {
+        self.imgs.remove(img);
+    }
+}
@@ -0,0 +1,187 @@
+use std::{ffi::CStr, sync::LazyLock};
+
+use ash::{khr, vk};
+
+use super::{enabled_gpu_extensions, gpu, gpu_props, instance, physical_gpu};
+use crate::{info, warn};
+
+/// optional gpu features, negotiated when gpu is created.
+/// missing ones are replaced by fallbacks (render passes, sync1 barriers) instead of failing
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Caps {
+    dynamic_rendering: bool,
+    synchronization2: bool,
+    sampler_anisotropy: bool,
+    pipeline_executable_info: bool,
+    portability_subset: bool,
+}
+
+impl Caps {
+    /// begin_render() uses `vkCmdBeginRendering`, otherwise cached render passes and framebuffers
+    pub fn dynamic_rendering(&self) -> bool {
+        self.dynamic_rendering
+    }
+
+    /// barriers use `vkCmdPipelineBarrier2`, otherwise they are translated to sync1 barriers
+    pub fn synchronization2(&self) -> bool {
+        self.synchronization2
+    }
+
+    pub fn sampler_anisotropy(&self) -> bool {
+        self.sampler_anisotropy
+    }
+
+    /// shader statistics are logged for created pipelines (debug builds)
+    pub fn pipeline_executable_info(&self) -> bool {
+        self.pipeline_executable_info
+    }
+
+    /// gpu is a portability implementation (MoltenVK on macOS), which lacks some vulkan features
+    pub fn portability_subset(&self) -> bool {
+        self.portability_subset
+    }
+
+    /// names of enabled features
+    pub fn enabled_features(&self) -> Vec<&'static str> {
+        [
+            ("dynamic_rendering", self.dynamic_rendering),
+            ("synchronization2", self.synchronization2),
+            ("sampler_anisotropy", self.sampler_anisotropy),
+            ("pipeline_executable_info", self.pipeline_executable_info),
+        ]
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+        .collect()
+    }
+}
+
+static CAPS: LazyLock<Caps> = LazyLock::new(|| {
+    // dynamic rendering and sync2 are used through vulkan 1.3 core entry points
+    let vk13 = gpu_props().api_version >= vk::API_VERSION_1_3;
+    let enabled = |ext: &CStr| enabled_gpu_extensions().iter().any(|e| e.as_c_str() == ext);
+    let exec_ext = enabled(khr::pipeline_executable_properties::NAME);
+    let mut dyn_render = vk::PhysicalDeviceDynamicRenderingFeatures::default();
+    let mut sync2 = vk::PhysicalDeviceSynchronization2Features::default();
+    let mut exec = vk::PhysicalDevicePipelineExecutablePropertiesFeaturesKHR::default();
+    let mut features = vk::PhysicalDeviceFeatures2::default();
+    if vk13 {
+        features = features.push_next(&mut dyn_render).push_next(&mut sync2);
+    }
+    if exec_ext {
+        features = features.push_next(&mut exec);
+    }
+    unsafe { instance().get_physical_device_features2(physical_gpu(), &mut features) };
+    let sampler_anisotropy = features.features.sampler_anisotropy != 0;
+    let caps = Caps {
+        dynamic_rendering: dyn_render.dynamic_rendering != 0,
+        synchronization2: sync2.synchronization2 != 0,
+        sampler_anisotropy,
+        pipeline_executable_info: exec.pipeline_executable_info != 0,
+        portability_subset: enabled(khr::portability_subset::NAME),
+    };
+    if caps.portability_subset {
+        info!("gpu is a portability implementation, only features it supports are used");
+    }
+    if !caps.dynamic_rendering {
+        warn!("dynamic rendering is not supported, using render passes");
+    }
+    if !caps.synchronization2 {
+        warn!("synchronization2 is not supported, using sync1 barriers");
+    }
+    caps
+});
+
+/// features supported by gpu, the ones enabled when creating it
+pub fn caps() -> &'static Caps {
+    &CAPS
+}
+
+/// records barrier with `vkCmdPipelineBarrier2`, or it's sync1 equivalent if sync2 is missing
+pub(crate) fn cmd_barrier(
+    cmd: vk::CommandBuffer,
+    memory_barriers: &[vk::MemoryBarrier2],
+    img_barriers: &[vk::ImageMemoryBarrier2],
+) {
+    if caps().synchronization2() {
+        unsafe {
+            gpu().cmd_pipeline_barrier2(
+                cmd,
+                &vk::DependencyInfo::default()
+                    .memory_barriers(memory_barriers)
+                    .image_memory_barriers(img_barriers),
+            )
+        };
+        return;
+    }
+    let mut src_stage = vk::PipelineStageFlags2::NONE;
+    let mut dst_stage = vk::PipelineStageFlags2::NONE;
+    let memory_barriers = memory_barriers
+        .iter()
+        .map(|b| {
+            src_stage |= b.src_stage_mask;
+            dst_stage |= b.dst_stage_mask;
+            vk::MemoryBarrier::default()
+                .src_access_mask(sync1_access(b.src_access_mask))
+                .dst_access_mask(sync1_access(b.dst_access_mask))
+        })
+        .collect::<Vec<_>>();
+    let img_barriers = img_barriers
+        .iter()
+        .map(|b| {
+            src_stage |= b.src_stage_mask;
+            dst_stage |= b.dst_stage_mask;
+            vk::ImageMemoryBarrier::default()
+                .src_access_mask(sync1_access(b.src_access_mask))
+                .dst_access_mask(sync1_access(b.dst_access_mask))
+                .old_layout(b.old_layout)
+                .new_layout(b.new_layout)
+                .src_queue_family_index(b.src_queue_family_index)
+                .dst_queue_family_index(b.dst_queue_family_index)
+                .image(b.image)
+                .subresource_range(b.subresource_range)
+        })
+        .collect::<Vec<_>>();
+    unsafe {
+        gpu().cmd_pipeline_barrier(
+            cmd,
+            sync1_stage(src_stage, vk::PipelineStageFlags::TOP_OF_PIPE),
+            sync1_stage(dst_stage, vk::PipelineStageFlags::BOTTOM_OF_PIPE),
+            vk::DependencyFlags::empty(),
+            &memory_barriers,
+            &[],
+            &img_barriers,
+        )
+    };
+}
+
+/// sync2 only stages are widened to the sync1 stage containing them, `none` is used if empty
+fn sync1_stage(
+    stage: vk::PipelineStageFlags2,
+    none: vk::PipelineStageFlags,
+) -> vk::PipelineStageFlags {
+    use vk::PipelineStageFlags2 as S;
+    let mut sync1 = vk::PipelineStageFlags::from_raw(stage.as_raw() as u32);
+    if stage.intersects(S::COPY | S::RESOLVE | S::BLIT | S::CLEAR) {
+        sync1 |= vk::PipelineStageFlags::TRANSFER;
+    }
+    if stage.intersects(S::INDEX_INPUT | S::VERTEX_ATTRIBUTE_INPUT) {
+        sync1 |= vk::PipelineStageFlags::VERTEX_INPUT;
+    }
+    if stage.intersects(S::PRE_RASTERIZATION_SHADERS) {
+        sync1 |= vk::PipelineStageFlags::VERTEX_SHADER;
+    }
+    if sync1.is_empty() { none } else { sync1 }
+}
+
+/// sync2 only accesses are widened to the sync1 access containing them
+fn sync1_access(access: vk::AccessFlags2) -> vk::AccessFlags {
+    use vk::AccessFlags2 as A;
+    let mut sync1 = vk::AccessFlags::from_raw(access.as_raw() as u32);
+    if access.intersects(A::SHADER_SAMPLED_READ | A::SHADER_STORAGE_READ) {
+        sync1 |= vk::AccessFlags::SHADER_READ;
+    }
+    if access.intersects(A::SHADER_STORAGE_WRITE) {
+        sync1 |= vk::AccessFlags::SHADER_WRITE;
+    }
+    sync1
+}
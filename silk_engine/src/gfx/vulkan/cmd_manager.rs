@@ -65,7 +65,7 @@ impl CmdManager {
         waits: &[vk::Semaphore],
         signals: &[vk::Semaphore],
         wait_dst_stage_mask: &[vk::PipelineStageFlags],
-    ) {
+    ) -> Result<(), vk::Result> {
         let exec_cmd_idx = self
             .exec_cmds
             .iter()
@@ -77,45 +77,51 @@ impl CmdManager {
                 .create_fence(&vk::FenceCreateInfo::default(), alloc_callbacks())
                 .unwrap()
         });
-        self.pending_cmds.push((cmd, fence));
-        unsafe {
-            gpu()
-                .queue_submit(
-                    queue(),
-                    &[vk::SubmitInfo {
-                        wait_semaphore_count: waits.len() as u32,
-                        p_wait_semaphores: if waits.is_empty() {
-                            null()
-                        } else {
-                            waits.as_ptr()
-                        },
-                        signal_semaphore_count: signals.len() as u32,
-                        p_signal_semaphores: if signals.is_empty() {
-                            null()
-                        } else {
-                            signals.as_ptr()
-                        },
-                        ..Default::default()
-                    }
-                    .command_buffers(&[cmd])
-                    .wait_dst_stage_mask(wait_dst_stage_mask)],
-                    fence,
-                )
-                .unwrap()
+        let result = unsafe {
+            gpu().queue_submit(
+                queue(),
+                &[vk::SubmitInfo {
+                    wait_semaphore_count: waits.len() as u32,
+                    p_wait_semaphores: if waits.is_empty() {
+                        null()
+                    } else {
+                        waits.as_ptr()
+                    },
+                    signal_semaphore_count: signals.len() as u32,
+                    p_signal_semaphores: if signals.is_empty() {
+                        null()
+                    } else {
+                        signals.as_ptr()
+                    },
+                    ..Default::default()
+                }
+                .command_buffers(&[cmd])
+                .wait_dst_stage_mask(wait_dst_stage_mask)],
+                fence,
+            )
         };
+        match result {
+            Ok(()) => self.pending_cmds.push((cmd, fence)),
+            Err(_) => {
+                self.finished_fences.push(fence);
+                self.invalid_cmds.push(cmd);
+            }
+        }
+        result
     }
 
-    pub fn wait(&mut self, cmd: vk::CommandBuffer) {
+    pub fn wait(&mut self, cmd: vk::CommandBuffer) -> Result<(), vk::Result> {
         let pending_cmd_idx = self
             .pending_cmds
             .iter()
             .position(|(pc, _)| *pc == cmd)
             .unwrap_or_else(|| panic!("can't wait on cmd that isn't pending"));
         let (cmd, fence) = self.pending_cmds.remove(pending_cmd_idx);
-        unsafe { gpu().wait_for_fences(&[fence], false, u64::MAX).unwrap() };
-        unsafe { gpu().reset_fences(&[fence]).unwrap() };
-        self.finished_fences.push(fence);
         self.invalid_cmds.push(cmd);
+        unsafe { gpu().wait_for_fences(&[fence], false, u64::MAX)? };
+        unsafe { gpu().reset_fences(&[fence])? };
+        self.finished_fences.push(fence);
+        Ok(())
     }
 
     pub fn reset(&mut self) {
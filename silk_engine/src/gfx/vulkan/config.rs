@@ -1,43 +1,165 @@
-use std::ffi::{CStr, CString};
+use std::{
+    ffi::{CStr, CString},
+    sync::OnceLock,
+};
 
 use ash::khr;
+use winit::raw_window_handle::RawDisplayHandle;
 
-pub const MSAA: u32 = 8;
+use super::instance::instance_extensions;
+use crate::{fatal, warn};
+
+/// gpu picked by [`VulkanConfig`], falls back to `Best` if no gpu matches
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum GpuPreference {
+    /// highest scoring gpu, discrete gpus are preferred
+    #[default]
+    Best,
+    /// index into [`crate::gfx::gpus`]
+    Index(usize),
+    /// gpu whose name contains this (case insensitive)
+    Name(String),
+    /// gpu with this locally unique id, e.g. to match adapter chosen by another api
+    Luid([u8; 8]),
+    /// integrated gpu, draws less power, for battery friendly tools
+    Integrated,
+}
+
+/// extra checks of khronos validation layer, each slows down gpu/cpu noticeably
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ValidationFeatures {
+    /// validates shader accesses (out of bounds, uninitialized descriptors) on gpu
+    pub gpu_assisted: bool,
+    /// warns about valid but slow api usage
+    pub best_practices: bool,
+    /// detects missing/wrong barriers between gpu accesses
+    pub synchronization: bool,
+}
+
+/// options read when vulkan instance, gpu and swapchain are created
+#[derive(Clone, Debug)]
+pub struct VulkanConfig {
+    /// enables khronos validation layer, on by default in debug builds
+    pub validation: bool,
+    /// ignored without validation
+    pub validation_features: ValidationFeatures,
+    /// which gpu is used when several are available
+    pub gpu: GpuPreference,
+    /// prefer extended range swapchain if display supports it
+    pub hdr: bool,
+    /// blend and filter in linear space, render targets and swapchain are srgb so gpu
+    /// decodes/encodes them, ignored with hdr
+    pub linear: bool,
+    /// present with fifo, otherwise mailbox is used when supported
+    pub vsync: bool,
+    /// composite swapchain with window's alpha, so pixels with alpha < 1 show what's behind window
+    pub transparent: bool,
+    /// loads renderdoc lib before vulkan so frames can be captured without launching app
+    /// from renderdoc ui, needs `renderdoc` feature
+    pub renderdoc: bool,
+}
+
+impl Default for VulkanConfig {
+    fn default() -> Self {
+        Self {
+            validation: cfg!(debug_assertions),
+            validation_features: ValidationFeatures::default(),
+            gpu: GpuPreference::Best,
+            hdr: false,
+            linear: false,
+            vsync: false,
+            transparent: false,
+            renderdoc: false,
+        }
+    }
+}
+
+static VULKAN_CONFIG: OnceLock<VulkanConfig> = OnceLock::new();
+
+/// should be called before vulkan is first used, later calls are ignored
+pub(crate) fn set_vulkan_config(config: VulkanConfig) {
+    #[cfg(not(feature = "renderdoc"))]
+    if config.renderdoc {
+        warn!("renderdoc needs `renderdoc` feature, ignoring it");
+    }
+    if VULKAN_CONFIG.set(config).is_err() {
+        warn!("vulkan config already set, ignoring new one");
+    }
+}
+
+pub fn vulkan_config() -> &'static VulkanConfig {
+    VULKAN_CONFIG.get_or_init(VulkanConfig::default)
+}
+
+/// surface extensions of display windows are created on, see [`set_display`]
+static SURFACE_EXTENSIONS: OnceLock<Vec<CString>> = OnceLock::new();
+
+/// enables surface extensions matching `display` (xlib/xcb/wayland/win32/metal),
+/// should be called before vulkan is first used
+pub(crate) fn set_display(display: RawDisplayHandle) {
+    let exts = ash_window::enumerate_required_extensions(display)
+        .unwrap_or_else(|_| fatal!("no vulkan surface extension for display: {display:?}"))
+        .iter()
+        .map(|&e| unsafe { CStr::from_ptr(e) }.to_owned())
+        .collect::<Vec<_>>();
+    if let Some(ext) = exts.iter().find(|e| !instance_extensions().contains(e)) {
+        fatal!("vulkan driver doesn't support {ext:?}, needed to present to display: {display:?}");
+    }
+    if SURFACE_EXTENSIONS.set(exts).is_err() {
+        warn!("display already set, ignoring new one");
+    }
+}
 
 pub fn required_vulkan_instance_extensions() -> Vec<CString> {
-    [
-        khr::surface::NAME,
-        khr::get_surface_capabilities2::NAME,
-        #[cfg(target_os = "windows")]
-        khr::win32_surface::NAME,
-        #[cfg(target_os = "linux")]
-        khr::wayland_surface::NAME,
-        #[cfg(target_os = "macos")]
-        mvk::macos_surface::NAME,
-    ]
-    .into_iter()
-    .map(|e| e.to_owned())
-    .collect()
+    let mut exts = vec![
+        khr::surface::NAME.to_owned(),
+        khr::get_surface_capabilities2::NAME.to_owned(),
+    ];
+    // without display (no window yet) every supported surface extension of platform is enabled
+    let surface_exts = SURFACE_EXTENSIONS.get().cloned().unwrap_or_else(|| {
+        [
+            #[cfg(target_os = "windows")]
+            khr::win32_surface::NAME,
+            #[cfg(target_os = "linux")]
+            khr::wayland_surface::NAME,
+            #[cfg(target_os = "linux")]
+            khr::xlib_surface::NAME,
+            #[cfg(target_os = "linux")]
+            khr::xcb_surface::NAME,
+            #[cfg(target_os = "macos")]
+            ash::ext::metal_surface::NAME,
+        ]
+        .into_iter()
+        .filter(|&e| instance_extensions().iter().any(|ie| ie.as_c_str() == e))
+        .map(|e| e.to_owned())
+        .collect()
+    });
+    for ext in surface_exts {
+        if !exts.contains(&ext) {
+            exts.push(ext);
+        }
+    }
+    exts
 }
 
 pub fn preferred_vulkan_instance_extensions() -> Vec<CString> {
-    [
-        #[cfg(debug_assertions)]
+    let mut exts: Vec<&CStr> = vec![
+        // labels mark debug scopes in renderdoc captures
+        #[cfg(any(debug_assertions, feature = "renderdoc"))]
         ash::ext::debug_utils::NAME,
-    ]
-    .into_iter()
-    .map(|e: &CStr| e.to_owned())
-    .collect()
+    ];
+    if vulkan_config().hdr {
+        exts.push(ash::ext::swapchain_colorspace::NAME);
+    }
+    exts.into_iter().map(|e| e.to_owned()).collect()
 }
 
 pub fn enabled_layers() -> Vec<CString> {
-    [
-        #[cfg(debug_assertions)]
-        "VK_LAYER_KHRONOS_validation",
-    ]
-    .into_iter()
-    .map(|e: &str| CString::new(e).unwrap())
-    .collect()
+    let mut layers = vec![];
+    if vulkan_config().validation {
+        layers.push(c"VK_LAYER_KHRONOS_validation".to_owned());
+    }
+    layers
 }
 
 pub fn required_vulkan_gpu_extensions() -> Vec<CString> {
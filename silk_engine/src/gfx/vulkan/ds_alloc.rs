@@ -28,14 +28,21 @@ macro_rules! dps {
 
 impl DescAlloc {
     pub fn new() -> Self {
-        const MAX_SETS: u32 = 16;
-        const POOL_SIZES: [vk::DescriptorPoolSize; 2] =
-            [dps!(UNIFORM_BUFFER, 32), dps!(STORAGE_BUFFER, 16)];
+        const MAX_SETS: u32 = 64;
+        const POOL_SIZES: [vk::DescriptorPoolSize; 6] = [
+            dps!(UNIFORM_BUFFER, 64),
+            dps!(UNIFORM_BUFFER_DYNAMIC, 16),
+            dps!(STORAGE_BUFFER, 32),
+            dps!(SAMPLED_IMAGE, 64),
+            dps!(SAMPLER, 64),
+            dps!(STORAGE_IMAGE, 32),
+        ];
         Self {
             pool: unsafe {
                 gpu()
                     .create_descriptor_pool(
                         &vk::DescriptorPoolCreateInfo::default()
+                            .flags(vk::DescriptorPoolCreateFlags::FREE_DESCRIPTOR_SET)
                             .max_sets(MAX_SETS)
                             .pool_sizes(&POOL_SIZES),
                         alloc_callbacks(),
@@ -64,6 +71,10 @@ impl DescAlloc {
     pub fn alloc_one(&self, dsl: vk::DescriptorSetLayout) -> vk::DescriptorSet {
         self.alloc(&[dsl])[0]
     }
+
+    pub fn free(&self, desc_sets: &[vk::DescriptorSet]) {
+        unsafe { gpu().free_descriptor_sets(self.pool, desc_sets).unwrap() }
+    }
 }
 
 impl Drop for DescAlloc {
@@ -1,37 +1,109 @@
-use super::{alloc_callbacks, config::*, instance, queue_family_index};
-use ash::vk;
-use std::{ffi::CString, sync::LazyLock};
+use super::{alloc_callbacks, caps, config::*, instance, queue_family_index};
+use crate::{info, warn};
+use ash::{khr, vk};
+use std::{
+    ffi::CString,
+    sync::{
+        LazyLock, RwLock,
+        atomic::{AtomicPtr, AtomicU32, Ordering},
+    },
+};
 
-static GPU_STUFF: LazyLock<(
-    vk::PhysicalDevice,
-    vk::PhysicalDeviceProperties,
-    vk::PhysicalDeviceFeatures,
-)> = LazyLock::new(|| {
-    let (gpu, gpu_props) = unsafe {
+/// gpu detected on this machine, see [`gpus`]
+#[derive(Clone, Debug)]
+pub struct GpuInfo {
+    /// index used by [`GpuPreference::Index`]
+    pub index: usize,
+    pub name: String,
+    pub device_type: vk::PhysicalDeviceType,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    /// locally unique id (matches dxgi adapter luid on windows), none if driver doesn't report it
+    pub luid: Option<[u8; 8]>,
+    pub props: vk::PhysicalDeviceProperties,
+}
+
+impl GpuInfo {
+    fn score(&self) -> u32 {
+        let limits = &self.props.limits;
+        let mut score = 0;
+        score += (self.device_type == vk::PhysicalDeviceType::DISCRETE_GPU) as u32 * 1_000_000;
+        score += limits.max_image_dimension2_d;
+        score += limits.max_uniform_buffer_range / 64;
+        score += limits.max_push_constants_size / 4;
+        score += limits.max_compute_shared_memory_size / 16;
+        score += limits.max_compute_work_group_invocations;
+        score
+    }
+
+    fn matches(&self, preference: &GpuPreference) -> bool {
+        match preference {
+            GpuPreference::Best => true,
+            GpuPreference::Index(index) => self.index == *index,
+            GpuPreference::Name(name) => self.name.to_lowercase().contains(&name.to_lowercase()),
+            GpuPreference::Luid(luid) => self.luid == Some(*luid),
+            GpuPreference::Integrated => self.device_type == vk::PhysicalDeviceType::INTEGRATED_GPU,
+        }
+    }
+}
+
+static GPUS: LazyLock<Vec<(vk::PhysicalDevice, GpuInfo)>> = LazyLock::new(|| {
+    unsafe {
         instance()
             .enumerate_physical_devices()
             .expect("No GPU found")
     }
-    .iter()
-    .map(|&gpu| {
-        let mut props = vk::PhysicalDeviceProperties2::default();
+    .into_iter()
+    .enumerate()
+    .map(|(index, gpu)| {
+        let mut id_props = vk::PhysicalDeviceIDProperties::default();
+        let mut props = vk::PhysicalDeviceProperties2::default().push_next(&mut id_props);
         unsafe { instance().get_physical_device_properties2(gpu, &mut props) };
         let props = props.properties;
-        let mut score = 0;
-        score += (props.device_type == vk::PhysicalDeviceType::DISCRETE_GPU) as u32 * 1_000_000;
-        score += props.limits.max_image_dimension2_d;
-        score += props.limits.max_uniform_buffer_range / 64;
-        score += props.limits.max_push_constants_size / 4;
-        score += props.limits.max_compute_shared_memory_size / 16;
-        score += props.limits.max_compute_work_group_invocations;
-        (gpu, props, score)
+        let info = GpuInfo {
+            index,
+            name: props
+                .device_name_as_c_str()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            device_type: props.device_type,
+            vendor_id: props.vendor_id,
+            device_id: props.device_id,
+            luid: (id_props.device_luid_valid != 0).then_some(id_props.device_luid),
+            props,
+        };
+        (gpu, info)
     })
-    .max_by_key(|(_, _, score)| *score)
-    .map(|(gpu, props, _)| (gpu, props))
-    .unwrap();
+    .collect()
+});
+
+static GPU_STUFF: LazyLock<(
+    vk::PhysicalDevice,
+    &'static GpuInfo,
+    vk::PhysicalDeviceFeatures,
+)> = LazyLock::new(|| {
+    assert!(!GPUS.is_empty(), "No GPU found");
+    let best = |preference: &GpuPreference| {
+        GPUS.iter()
+            .filter(|(_, info)| info.matches(preference))
+            .max_by_key(|(_, info)| info.score())
+    };
+    let preference = &vulkan_config().gpu;
+    let (gpu, info) = best(preference).unwrap_or_else(|| {
+        warn!("no gpu matches {preference:?}, using best gpu");
+        best(&GpuPreference::Best).unwrap()
+    });
+    for (_, gpu) in GPUS.iter() {
+        let selected = if gpu.index == info.index { '*' } else { ' ' };
+        info!(
+            "{selected} gpu {}: {} ({:?})",
+            gpu.index, gpu.name, gpu.device_type
+        );
+    }
     let mut features = vk::PhysicalDeviceFeatures2::default();
-    unsafe { instance().get_physical_device_features2(gpu, &mut features) };
-    (gpu, gpu_props, features.features)
+    unsafe { instance().get_physical_device_features2(*gpu, &mut features) };
+    (*gpu, info, features.features)
 });
 static GPU_EXTENSIONS: LazyLock<Vec<CString>> = LazyLock::new(|| unsafe {
     instance()
@@ -41,12 +113,40 @@ static GPU_EXTENSIONS: LazyLock<Vec<CString>> = LazyLock::new(|| unsafe {
         .map(|e| e.extension_name_as_c_str().unwrap().to_owned())
         .collect()
 });
+/// required extensions and supported preferred extensions
+static ENABLED_GPU_EXTENSIONS: LazyLock<Vec<CString>> = LazyLock::new(|| {
+    let required_gpu_extensions = required_vulkan_gpu_extensions();
+    required_gpu_extensions
+        .iter()
+        .filter(|re| !GPU_EXTENSIONS.contains(re))
+        .for_each(|re| panic!("Required vulkan gpu extension not found: {re:?}"));
+    let mut preferred_gpu_extensions = preferred_vulkan_gpu_extensions();
+    preferred_gpu_extensions.retain(|pe| {
+        GPU_EXTENSIONS
+            .contains(pe)
+            .then_some(true)
+            .unwrap_or_else(|| {
+                println!("Preferred vulkan gpu extension not found: {pe:?}");
+                false
+            })
+    });
+    // portability implementations (MoltenVK) require it to be enabled if exposed
+    let portability_subset = GPU_EXTENSIONS
+        .iter()
+        .find(|e| e.as_c_str() == khr::portability_subset::NAME)
+        .cloned();
+    required_gpu_extensions
+        .into_iter()
+        .chain(preferred_gpu_extensions)
+        .chain(portability_subset)
+        .collect()
+});
 static GPU_MEMORY_PROPS: LazyLock<vk::PhysicalDeviceMemoryProperties> = LazyLock::new(|| unsafe {
     let mut mem_props = vk::PhysicalDeviceMemoryProperties2::default();
     instance().get_physical_device_memory_properties2(physical_gpu(), &mut mem_props);
     mem_props.memory_properties
 });
-static GPU: LazyLock<ash::Device> = LazyLock::new(|| unsafe {
+fn create_gpu() -> ash::Device {
     #[cfg(debug_assertions)]
     crate::log_file!(
         format!("{}/gpu.log", crate::util::print::log_path()),
@@ -60,63 +160,114 @@ static GPU: LazyLock<ash::Device> = LazyLock::new(|| unsafe {
         gpu_extensions()
     );
 
-    let required_gpu_extensions = required_vulkan_gpu_extensions();
-    required_gpu_extensions
-        .iter()
-        .filter(|re| !GPU_EXTENSIONS.contains(re))
-        .for_each(|re| panic!("Required vulkan gpu extension not found: {re:?}"));
-    let mut preferred_gpu_extensions = preferred_vulkan_gpu_extensions();
-    preferred_gpu_extensions.retain(|pe| {
-        GPU_EXTENSIONS
-            .contains(pe)
-            .then_some(true)
-            .unwrap_or_else(|| {
-                println!("Preferred vulkan gpu extension not found: {pe:?}");
-                false
-            })
-    });
-
-    let mut dyn_render =
-        vk::PhysicalDeviceDynamicRenderingFeatures::default().dynamic_rendering(true);
-    let mut sync2 = vk::PhysicalDeviceSynchronization2Features::default().synchronization2(true);
-    #[cfg(debug_assertions)]
+    // only features negotiated in caps() are enabled, missing ones use fallback paths
+    let caps = caps();
+    let mut dyn_render = vk::PhysicalDeviceDynamicRenderingFeatures::default()
+        .dynamic_rendering(caps.dynamic_rendering());
+    let mut sync2 = vk::PhysicalDeviceSynchronization2Features::default()
+        .synchronization2(caps.synchronization2());
     let mut pipeline_exec_props =
         vk::PhysicalDevicePipelineExecutablePropertiesFeaturesKHR::default()
-            .pipeline_executable_info(true);
+            .pipeline_executable_info(caps.pipeline_executable_info());
 
-    let gpu_exts: Vec<*const i8> = required_gpu_extensions
+    let gpu_exts: Vec<*const i8> = enabled_gpu_extensions()
         .iter()
-        .chain(preferred_gpu_extensions.iter())
-        .filter(|ext| GPU_EXTENSIONS.contains(ext))
         .map(|ext| ext.as_ptr())
         .collect();
     let queue_priorities = [1.0];
     let queue_infos = [vk::DeviceQueueCreateInfo::default()
         .queue_family_index(queue_family_index())
         .queue_priorities(&queue_priorities)];
-    let sampler_anisotropy = vk::PhysicalDeviceFeatures::default().sampler_anisotropy(true);
-    let info = vk::DeviceCreateInfo::default()
+    let features =
+        vk::PhysicalDeviceFeatures::default().sampler_anisotropy(caps.sampler_anisotropy());
+    let mut info = vk::DeviceCreateInfo::default()
         .queue_create_infos(&queue_infos)
         .enabled_extension_names(&gpu_exts)
-        .enabled_features(&sampler_anisotropy)
-        .push_next(&mut dyn_render)
-        .push_next(&mut sync2);
-    #[cfg(debug_assertions)]
-    let info = info.push_next(&mut pipeline_exec_props);
-    instance()
-        .create_device(physical_gpu(), &info, alloc_callbacks())
+        .enabled_features(&features);
+    if caps.dynamic_rendering() {
+        info = info.push_next(&mut dyn_render);
+    }
+    if caps.synchronization2() {
+        info = info.push_next(&mut sync2);
+    }
+    if caps.pipeline_executable_info() {
+        info = info.push_next(&mut pipeline_exec_props);
+    }
+    unsafe { instance().create_device(physical_gpu(), &info, alloc_callbacks()) }
         .expect("Failed to create VkDevice")
-});
+}
+
+/// devices are leaked, so `gpu()` can hand out `&'static` while device is replaced
+static GPU: LazyLock<AtomicPtr<ash::Device>> =
+    LazyLock::new(|| AtomicPtr::new(Box::leak(Box::new(create_gpu()))));
+/// bumped each time lost device is replaced, see [`PerGpu`]
+static GPU_GENERATION: AtomicU32 = AtomicU32::new(0);
+
+/// destroys lost device and makes new one on same physical gpu,
+/// everything made from lost device must be destroyed first
+pub(super) fn replace_gpu() {
+    unsafe { gpu().destroy_device(alloc_callbacks()) };
+    GPU.store(Box::leak(Box::new(create_gpu())), Ordering::Release);
+    GPU_GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+/// value made lazily from current device, remade once device is replaced.
+/// replaced values are leaked, since they may still be borrowed
+pub(crate) struct PerGpu<T: 'static> {
+    init: fn() -> T,
+    value: RwLock<Option<(u32, &'static T)>>,
+}
+
+impl<T> PerGpu<T> {
+    pub(crate) const fn new(init: fn() -> T) -> Self {
+        Self {
+            init,
+            value: RwLock::new(None),
+        }
+    }
+}
+
+impl<T> std::ops::Deref for PerGpu<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        let generation = GPU_GENERATION.load(Ordering::Acquire);
+        if let Some((made_in, value)) = *self.value.read().unwrap()
+            && made_in == generation
+        {
+            return value;
+        }
+        let mut value = self.value.write().unwrap();
+        match *value {
+            // may have been made by other thread between locks
+            Some((made_in, value)) if made_in == generation => value,
+            _ => {
+                let made = Box::leak(Box::new((self.init)()));
+                *value = Some((generation, made));
+                made
+            }
+        }
+    }
+}
 
 pub fn physical_gpu() -> vk::PhysicalDevice {
     GPU_STUFF.0
 }
 
 pub fn gpu_props() -> vk::PhysicalDeviceProperties {
+    GPU_STUFF.1.props
+}
+
+/// gpu vulkan is running on
+pub fn gpu_info() -> &'static GpuInfo {
     GPU_STUFF.1
 }
 
-#[allow(unused)]
+/// all gpus detected on this machine, in [`GpuPreference::Index`] order
+pub fn gpus() -> impl Iterator<Item = &'static GpuInfo> {
+    GPUS.iter().map(|(_, info)| info)
+}
+
 pub fn gpu_limits() -> vk::PhysicalDeviceLimits {
     gpu_props().limits
 }
@@ -129,10 +280,14 @@ pub fn gpu_extensions() -> &'static [CString] {
     &GPU_EXTENSIONS
 }
 
+pub fn enabled_gpu_extensions() -> &'static [CString] {
+    &ENABLED_GPU_EXTENSIONS
+}
+
 pub fn gpu_mem_props() -> vk::PhysicalDeviceMemoryProperties {
     *GPU_MEMORY_PROPS
 }
 
 pub fn gpu() -> &'static ash::Device {
-    &GPU
+    unsafe { &*GPU.load(Ordering::Acquire) }
 }
@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
 use super::{ImageInfo, alloc_callbacks, gpu, gpu_mem_props, queue_family_index};
-use crate::util::{BuddyAlloc, ContainRange};
+use crate::{
+    error::{Error, Result},
+    util::{BuddyAlloc, ContainRange},
+};
 use ash::vk;
 use vk::Handle;
 
@@ -68,6 +71,10 @@ struct MemPool {
     mems: Vec<MemBlock>,
     buddy: BuddyAlloc,
     mem_type_idx: u32,
+    /// buddy rounded sizes of live allocations
+    used: vk::DeviceSize,
+    peak: vk::DeviceSize,
+    allocs: usize,
 }
 
 impl Default for MemPool {
@@ -77,6 +84,9 @@ impl Default for MemPool {
             mems: vec![],
             buddy: BuddyAlloc::new(0),
             mem_type_idx: 0,
+            used: 0,
+            peak: 0,
+            allocs: 0,
         }
     }
 }
@@ -124,16 +134,49 @@ impl MemPool {
 
     // TODO: make resizing work
     fn alloc(&mut self, size: vk::DeviceSize) -> (vk::DeviceSize, &MemBlock) {
+        let props = self.props;
+        self.try_alloc(size)
+            .unwrap_or_else(|| panic!("Mem Pool({props:?}) out of memory"))
+    }
+
+    fn try_alloc(&mut self, size: vk::DeviceSize) -> Option<(vk::DeviceSize, &MemBlock)> {
         self.init();
         let off = self.buddy.alloc(size as usize);
+        if off == usize::MAX {
+            return None;
+        }
         crate::log!(
             "Mem Pool({:?}) Alloc: off({}), size({})",
             self.props,
             crate::util::Mem::b(off),
             crate::util::Mem::b(size as usize)
         );
-        assert_ne!(off, usize::MAX);
-        (off as vk::DeviceSize, &self.mems[0])
+        self.track_alloc(size);
+        Some((off as vk::DeviceSize, &self.mems[0]))
+    }
+
+    /// allocates only if there is free space at lower offset than `below`
+    fn alloc_below(
+        &mut self,
+        size: vk::DeviceSize,
+        below: vk::DeviceSize,
+    ) -> Option<vk::DeviceSize> {
+        let off = self.buddy.alloc(size as usize);
+        if off == usize::MAX {
+            return None;
+        }
+        if off as vk::DeviceSize >= below {
+            self.buddy.dealloc(off, size as usize);
+            return None;
+        }
+        self.track_alloc(size);
+        Some(off as vk::DeviceSize)
+    }
+
+    fn track_alloc(&mut self, size: vk::DeviceSize) {
+        self.used += size.next_power_of_two();
+        self.peak = self.peak.max(self.used);
+        self.allocs += 1;
     }
 
     fn dealloc(&mut self, offset: vk::DeviceSize, size: vk::DeviceSize) {
@@ -143,8 +186,24 @@ impl MemPool {
             crate::util::Mem::b(offset as usize),
             crate::util::Mem::b(size as usize)
         );
+        self.used -= size.next_power_of_two();
+        self.allocs -= 1;
         self.buddy.dealloc(offset as usize, size as usize)
     }
+
+    fn stats(&self) -> MemPoolStats {
+        MemPoolStats {
+            mem_type_idx: self.mem_type_idx,
+            props: self.props,
+            blocks: self.mems.len(),
+            capacity: self.buddy.len() as vk::DeviceSize,
+            used: self.used,
+            peak: self.peak,
+            allocs: self.allocs,
+            free: self.buddy.free_size() as vk::DeviceSize,
+            largest_free: self.buddy.largest_free() as vk::DeviceSize,
+        }
+    }
 }
 
 impl Drop for MemPool {
@@ -157,6 +216,69 @@ impl Drop for MemPool {
     }
 }
 
+/// usage of one memory type's pool, see [`GpuAlloc::stats`]
+#[derive(Clone, Debug)]
+pub struct MemPoolStats {
+    pub mem_type_idx: u32,
+    pub props: vk::MemoryPropertyFlags,
+    pub blocks: usize,
+    pub capacity: vk::DeviceSize,
+    /// includes padding from rounding allocations to power of 2
+    pub used: vk::DeviceSize,
+    pub peak: vk::DeviceSize,
+    pub allocs: usize,
+    pub free: vk::DeviceSize,
+    pub largest_free: vk::DeviceSize,
+}
+
+impl MemPoolStats {
+    /// 0 when all free memory is one block, approaches 1 as it's split into small blocks
+    pub fn fragmentation(&self) -> f32 {
+        if self.free == 0 {
+            return 0.0;
+        }
+        1.0 - self.largest_free as f32 / self.free as f32
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GpuAllocStats {
+    /// only pools that were allocated from
+    pub pools: Vec<MemPoolStats>,
+}
+
+impl GpuAllocStats {
+    pub fn used(&self) -> vk::DeviceSize {
+        self.pools.iter().map(|p| p.used).sum()
+    }
+
+    pub fn capacity(&self) -> vk::DeviceSize {
+        self.pools.iter().map(|p| p.capacity).sum()
+    }
+}
+
+impl std::fmt::Display for GpuAllocStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use crate::util::Mem;
+        for p in &self.pools {
+            writeln!(
+                f,
+                "mem type {} {:?}: {} blocks, {} allocs, used {}/{} (peak {}), largest free {}, {:.1}% fragmented",
+                p.mem_type_idx,
+                p.props,
+                p.blocks,
+                p.allocs,
+                Mem::b(p.used as usize),
+                Mem::b(p.capacity as usize),
+                Mem::b(p.peak as usize),
+                Mem::b(p.largest_free as usize),
+                p.fragmentation() * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Copy)]
 struct BufferAlloc {
     mem_type_idx: u32,
@@ -206,23 +328,42 @@ impl GpuAlloc {
         img_info: &ImageInfo,
         mem_props: vk::MemoryPropertyFlags,
     ) -> vk::Image {
-        let image = img_info.build();
+        self.try_alloc_img(img_info, mem_props)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_alloc_img(
+        &mut self,
+        img_info: &ImageInfo,
+        mem_props: vk::MemoryPropertyFlags,
+    ) -> Result<vk::Image> {
+        let image = img_info
+            .try_build()
+            .map_err(|e| Error::vk("failed to create img", e))?;
         let mem_reqs = unsafe { gpu().get_image_memory_requirements(image) };
         let mem_type_idx = Self::find_mem_type_idx(mem_reqs.memory_type_bits, mem_props);
         let pool = &mut self.mem_pools[mem_type_idx as usize];
         let aligned_size = mem_reqs.size;
-        let (alloc_off, mem_block) = pool.alloc(aligned_size);
-        unsafe {
-            gpu()
-                .bind_image_memory(image, mem_block.mem, alloc_off)
-                .unwrap()
+        let destroy = || unsafe { gpu().destroy_image(image, alloc_callbacks()) };
+        let Some((alloc_off, mem_block)) = pool.try_alloc(aligned_size) else {
+            destroy();
+            return Err(Error::OutOfMemory(format!(
+                "img needs {}",
+                crate::util::Mem::b(aligned_size as usize)
+            )));
         };
+        let (mem, block_off) = (mem_block.mem, mem_block.off);
+        if let Err(e) = unsafe { gpu().bind_image_memory(image, mem, alloc_off) } {
+            destroy();
+            pool.dealloc(alloc_off, aligned_size);
+            return Err(Error::vk("failed to bind img memory", e));
+        }
         self.img_allocs.insert(image.as_raw(), ImageAlloc {
             mem_type_idx,
-            buddy_off: alloc_off + mem_block.off,
+            buddy_off: alloc_off + block_off,
             aligned_size,
         });
-        image
+        Ok(image)
     }
 
     pub fn dealloc_img(&mut self, image: vk::Image) {
@@ -247,7 +388,27 @@ impl GpuAlloc {
         usage: vk::BufferUsageFlags,
         mem_props: vk::MemoryPropertyFlags,
     ) -> vk::Buffer {
-        let buffer = unsafe {
+        let buffer = Self::create_buf(size, usage);
+        let mem_reqs = unsafe { gpu().get_buffer_memory_requirements(buffer) };
+        let mem_type_idx = Self::find_mem_type_idx(mem_reqs.memory_type_bits, mem_props);
+        let pool = &mut self.mem_pools[mem_type_idx as usize];
+        let aligned_size = mem_reqs.size;
+        let (alloc_off, mem_block) = pool.alloc(aligned_size);
+        let (mem, block_off) = (mem_block.mem, mem_block.off);
+        self.bind_buf(buffer, mem, BufferAlloc {
+            mem_type_idx,
+            off: alloc_off,
+            buddy_off: block_off + alloc_off,
+            size,
+            aligned_size,
+            usage,
+            mapped_range: (0, 0),
+        });
+        buffer
+    }
+
+    fn create_buf(size: vk::DeviceSize, usage: vk::BufferUsageFlags) -> vk::Buffer {
+        unsafe {
             gpu()
                 .create_buffer(
                     &vk::BufferCreateInfo::default()
@@ -258,30 +419,68 @@ impl GpuAlloc {
                     alloc_callbacks(),
                 )
                 .unwrap()
-        };
-        let mem_reqs = unsafe { gpu().get_buffer_memory_requirements(buffer) };
-        let mem_type_idx = Self::find_mem_type_idx(mem_reqs.memory_type_bits, mem_props);
-        let pool = &mut self.mem_pools[mem_type_idx as usize];
-        let aligned_size = mem_reqs.size;
-        let (alloc_off, mem_block) = pool.alloc(aligned_size);
+        }
+    }
+
+    fn bind_buf(&mut self, buffer: vk::Buffer, mem: vk::DeviceMemory, buf_alloc: BufferAlloc) {
         unsafe {
             gpu()
-                .bind_buffer_memory(buffer, mem_block.mem, alloc_off)
+                .bind_buffer_memory(buffer, mem, buf_alloc.off)
                 .unwrap()
         };
-        self.buf_allocs.insert(buffer.as_raw(), BufferAlloc {
-            mem_type_idx,
-            off: alloc_off,
-            buddy_off: mem_block.off + alloc_off,
-            size,
-            aligned_size,
-            usage,
-            mapped_range: (0, 0),
-        });
-        buffer
+        self.buf_allocs.insert(buffer.as_raw(), buf_alloc);
+    }
+
+    /// allocs new bufs at lower offsets in their pools so freed gaps can merge,
+    /// highest bufs are tried first. returns (old, new) pairs, caller copies contents
+    /// and deallocs old bufs. only unmapped bufs with transfer src/dst usage are moved
+    pub fn relocate_bufs(
+        &mut self,
+        buffers: &[vk::Buffer],
+        max_moves: usize,
+    ) -> Vec<(vk::Buffer, vk::Buffer)> {
+        let transfer = vk::BufferUsageFlags::TRANSFER_SRC | vk::BufferUsageFlags::TRANSFER_DST;
+        let mut candidates: Vec<(vk::Buffer, BufferAlloc)> = buffers
+            .iter()
+            .map(|&buf| (buf, *self.buf_alloc(buf)))
+            .filter(|(_, a)| a.usage.contains(transfer) && a.mapped_range.1 == 0)
+            .collect();
+        candidates.sort_unstable_by_key(|(_, a)| std::cmp::Reverse(a.buddy_off));
+        let mut moves = Vec::new();
+        for (old, old_alloc) in candidates {
+            if moves.len() >= max_moves {
+                break;
+            }
+            let pool = &mut self.mem_pools[old_alloc.mem_type_idx as usize];
+            let Some(off) = pool.alloc_below(old_alloc.aligned_size, old_alloc.buddy_off) else {
+                continue;
+            };
+            let mem_block = pool.find_off_mem_block(off);
+            let (mem, block_off) = (mem_block.mem, mem_block.off);
+            let new = Self::create_buf(old_alloc.size, old_alloc.usage);
+            self.bind_buf(new, mem, BufferAlloc {
+                off: off - block_off,
+                buddy_off: off,
+                ..old_alloc
+            });
+            moves.push((old, new));
+        }
+        moves
+    }
+
+    pub fn stats(&self) -> GpuAllocStats {
+        GpuAllocStats {
+            pools: self
+                .mem_pools
+                .iter()
+                .filter(|p| !p.mems.is_empty())
+                .map(MemPool::stats)
+                .collect(),
+        }
     }
 
     pub fn dealloc_buf(&mut self, buf: vk::Buffer) {
+        self.unmap(buf);
         let buf_alloc = self.buf_allocs.remove(&buf.as_raw()).unwrap();
         self.mem_pools[buf_alloc.mem_type_idx as usize]
             .dealloc(buf_alloc.off, buf_alloc.aligned_size);
@@ -298,74 +497,50 @@ impl GpuAlloc {
         self.alloc_buf(new_size, buf_alloc.usage, pool_props)
     }
 
+    /// memory block of buf is mapped whole once and stays mapped while any of it's bufs are,
+    /// so returned ptr stays valid until buf is unmapped or freed
     pub fn map(&mut self, buffer: vk::Buffer) -> *mut u8 {
-        self.map_range(buffer, 0, vk::WHOLE_SIZE)
-    }
-
-    pub fn map_range(
-        &mut self,
-        buffer: vk::Buffer,
-        off: vk::DeviceSize,
-        size: vk::DeviceSize,
-    ) -> *mut u8 {
         let buf_alloc = self.buf_allocs.get_mut(&buffer.as_raw()).unwrap();
         let pool = &mut self.mem_pools[buf_alloc.mem_type_idx as usize];
         let mem_block = pool.find_off_mem_block(buf_alloc.buddy_off);
-        let mapped_range = mem_block.mapped_ranges.range();
-        let off = off + buf_alloc.off;
-        let size = if size == vk::WHOLE_SIZE {
-            buf_alloc.size
-        } else {
-            size
-        };
-        let (start, end) = (off as usize, (off + size) as usize);
-        if start >= mapped_range.start && end <= mapped_range.end {
-            return unsafe { mem_block.map_ptr.byte_add(off as usize) };
-        }
-        if !(mapped_range.start == 0 && mapped_range.end == 0) {
-            unsafe { gpu().unmap_memory(mem_block.mem) }
+        // mapped bufs aren't relocated, since their ptrs would dangle
+        if buf_alloc.mapped_range.1 == 0 {
+            let (start, end) = (
+                buf_alloc.off as usize,
+                (buf_alloc.off + buf_alloc.size) as usize,
+            );
+            buf_alloc.mapped_range = (start, end);
+            mem_block.mapped_ranges.add(start, end);
         }
-        let (old_start, old_end) = buf_alloc.mapped_range;
-        if old_end > 0 {
-            mem_block.mapped_ranges.remove(old_start, old_end);
+        if mem_block.map_ptr.is_null() {
+            mem_block.map_ptr = unsafe {
+                gpu()
+                    .map_memory(
+                        mem_block.mem,
+                        0,
+                        vk::WHOLE_SIZE,
+                        vk::MemoryMapFlags::empty(),
+                    )
+                    .unwrap()
+            } as *mut u8;
+            crate::log!("Mapped({:x})", mem_block.mem.as_raw());
         }
-        buf_alloc.mapped_range = (start, end);
-        mem_block.mapped_ranges.add(start, end);
-        let mapped_range = mem_block.mapped_ranges.range();
-        let mapped_ptr = unsafe {
-            gpu()
-                .map_memory(
-                    mem_block.mem,
-                    mapped_range.start as u64,
-                    (mapped_range.end - mapped_range.start) as u64,
-                    vk::MemoryMapFlags::empty(),
-                )
-                .unwrap()
-        } as *mut u8;
-        mem_block.map_ptr = unsafe { mapped_ptr.byte_offset(-(mapped_range.start as isize)) };
-        crate::log!(
-            "Mapped({:x}): off({off}), size({})",
-            mem_block.mem.as_raw(),
-            if size == vk::WHOLE_SIZE {
-                "WHOLE".to_string()
-            } else {
-                size.to_string()
-            }
-        );
-        unsafe { mem_block.map_ptr.byte_add(off as usize) }
+        unsafe { mem_block.map_ptr.byte_add(buf_alloc.off as usize) }
     }
 
     pub fn unmap(&mut self, buffer: vk::Buffer) {
         let buf_alloc = self.buf_allocs.get_mut(&buffer.as_raw()).unwrap();
+        let (start, end) = std::mem::take(&mut buf_alloc.mapped_range);
+        if end == 0 {
+            return;
+        }
         let pool = &mut self.mem_pools[buf_alloc.mem_type_idx as usize];
         let mem_block = pool.find_off_mem_block(buf_alloc.buddy_off);
-        mem_block.mapped_ranges.remove(
-            buf_alloc.off as usize,
-            (buf_alloc.off + buf_alloc.size) as usize,
-        );
+        mem_block.mapped_ranges.remove(start, end);
         let mapped_range = mem_block.mapped_ranges.range();
         if mapped_range.start == 0 && mapped_range.end == 0 {
             unsafe { gpu().unmap_memory(mem_block.mem) }
+            mem_block.map_ptr = std::ptr::null_mut();
         }
     }
 
@@ -431,7 +606,7 @@ impl GpuAlloc {
         &self.mem_pools[self.img_alloc(image).mem_type_idx as usize]
     }
 
-    fn img_props(&self, image: vk::Image) -> vk::MemoryPropertyFlags {
+    pub fn img_props(&self, image: vk::Image) -> vk::MemoryPropertyFlags {
         self.img_pool(image).props
     }
 
@@ -97,32 +97,35 @@ impl ImageInfo {
     }
 
     pub fn build(&self) -> vk::Image {
+        self.try_build()
+            .unwrap_or_else(|e| panic!("failed to create img: {e}"))
+    }
+
+    pub fn try_build(&self) -> Result<vk::Image, vk::Result> {
         unsafe {
-            gpu()
-                .create_image(
-                    &vk::ImageCreateInfo::default()
-                        .extent(vk::Extent3D {
-                            width: self.width.max(1),
-                            height: self.height.max(1),
-                            depth: self.depth.max(1),
-                        })
-                        .image_type(match (self.width, self.height, self.depth) {
-                            (_, 0, 0) => vk::ImageType::TYPE_1D,
-                            (_, _, 0) => vk::ImageType::TYPE_2D,
-                            (_, _, _) => vk::ImageType::TYPE_3D,
-                        })
-                        .array_layers(self.layers.max(1))
-                        .mip_levels(self.levels.max(1))
-                        .samples(samples_u32_to_vk(self.samples.max(1)))
-                        .format(self.format)
-                        .flags(self.flags)
-                        .usage(self.usage)
-                        .initial_layout(self.layout)
-                        .queue_family_indices(&[queue_family_index()])
-                        .sharing_mode(vk::SharingMode::EXCLUSIVE),
-                    alloc_callbacks(),
-                )
-                .unwrap()
+            gpu().create_image(
+                &vk::ImageCreateInfo::default()
+                    .extent(vk::Extent3D {
+                        width: self.width.max(1),
+                        height: self.height.max(1),
+                        depth: self.depth.max(1),
+                    })
+                    .image_type(match (self.width, self.height, self.depth) {
+                        (_, 0, 0) => vk::ImageType::TYPE_1D,
+                        (_, _, 0) => vk::ImageType::TYPE_2D,
+                        (_, _, _) => vk::ImageType::TYPE_3D,
+                    })
+                    .array_layers(self.layers.max(1))
+                    .mip_levels(self.levels.max(1))
+                    .samples(samples_u32_to_vk(self.samples.max(1)))
+                    .format(self.format)
+                    .flags(self.flags)
+                    .usage(self.usage)
+                    .initial_layout(self.layout)
+                    .queue_family_indices(&[queue_family_index()])
+                    .sharing_mode(vk::SharingMode::EXCLUSIVE),
+                alloc_callbacks(),
+            )
         }
     }
 }
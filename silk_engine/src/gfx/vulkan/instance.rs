@@ -1,10 +1,37 @@
 use std::ffi::CString;
-use std::sync::LazyLock;
+use std::sync::{LazyLock, RwLock};
 
 use super::ENTRY;
 use super::config::*;
 use crate::{fatal, warn};
-use ash::vk;
+use ash::{khr, vk};
+
+/// validation message ids that aren't logged, see [`filter_validation_message`]
+static FILTERED_MESSAGES: LazyLock<RwLock<Vec<i32>>> = LazyLock::new(|| {
+    RwLock::new(vec![
+        601872502,  // validation active warn
+        615892639,  // GPU assisted validation active warn
+        2132353751, // GPU assisted + core validation active warn
+        1734198062, // pipeline exec props ext active warn
+        -222910232, // not using combined image samplers warn (no wgsl support)
+    ])
+});
+
+/// stops logging validation messages with this id, id is logged after each message
+pub fn filter_validation_message(id: i32) {
+    let mut filtered = FILTERED_MESSAGES.write().unwrap();
+    if !filtered.contains(&id) {
+        filtered.push(id);
+    }
+}
+
+/// logs validation messages with this id again, also works for ones filtered by default
+pub fn unfilter_validation_message(id: i32) -> bool {
+    let mut filtered = FILTERED_MESSAGES.write().unwrap();
+    let len = filtered.len();
+    filtered.retain(|&f| f != id);
+    len != filtered.len()
+}
 
 #[cfg(debug_assertions)]
 unsafe extern "system" fn vulkan_debug_callback(
@@ -18,12 +45,7 @@ unsafe extern "system" fn vulkan_debug_callback(
     if message_severity == vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE
         || (message_severity == vk::DebugUtilsMessageSeverityFlagsEXT::INFO
             && message_type == vk::DebugUtilsMessageTypeFlagsEXT::GENERAL)
-        || msg_id == 601872502  // validation active warn
-        || msg_id == 615892639 // GPU assisted validation active warn
-        || msg_id == 2132353751 // GPU assisted + core validation active warn
-        || msg_id == 1734198062 // pipeline exec props ext active warn
-        // not using combined image samplers warn (no wgsl support)
-        || msg_id == -222910232
+        || FILTERED_MESSAGES.read().unwrap().contains(&msg_id)
     {
         return vk::FALSE;
     }
@@ -31,7 +53,6 @@ unsafe extern "system" fn vulkan_debug_callback(
         .unwrap_or_default()
         .to_string_lossy()
         .to_string();
-    let full_message = message.clone();
     if let Some(i) = message.find(" (http") {
         message.truncate(i);
     }
@@ -45,35 +66,36 @@ unsafe extern "system" fn vulkan_debug_callback(
         .replace("the ", "");
 
     type Severity = vk::DebugUtilsMessageSeverityFlagsEXT;
-    use crate::util::print;
-    let ansi_message = match message_severity {
-        Severity::ERROR => print::err(&message),
-        Severity::WARNING => print::warn(&message),
-        Severity::INFO => print::info(&message),
-        _ => message,
+    use crate::util::{Level, logger, print};
+    let level = match message_severity {
+        Severity::ERROR => Level::Error,
+        Severity::WARNING => Level::Warn,
+        Severity::INFO => Level::Debug,
+        _ => Level::Trace,
     };
+    // filtered out, e.g. with `logger().filter("vulkan", Level::Error)`
+    if !logger().enabled(level, "vulkan") {
+        return vk::FALSE;
+    }
 
     let mut backtrace = print::backtrace_callers();
     backtrace.pop();
     let backtrace = backtrace.join(" > ");
-    crate::log!("{full_message}\n|> {backtrace}\n");
-    let ansi_backtrace = print::trace(&["|> ", &backtrace].concat());
-    let print_str = format!("{ansi_message}\n{ansi_backtrace}");
+    logger().log(
+        level,
+        "vulkan",
+        format_args!("{message} ({msg_id})\n|> {backtrace}"),
+    );
 
     use std::sync::atomic::{AtomicU32, Ordering};
     static ERROR_COUNT: AtomicU32 = AtomicU32::new(0);
     match message_severity {
         Severity::ERROR => {
-            eprintln!("{print_str}");
             let err_cnt = ERROR_COUNT.fetch_add(1, Ordering::SeqCst);
             if err_cnt > 8 {
                 panic!("too many vulkan errors");
             }
         }
-        Severity::WARNING => {
-            println!("{print_str}");
-            ERROR_COUNT.store(0, Ordering::SeqCst);
-        }
         _ => ERROR_COUNT.store(0, Ordering::SeqCst),
     }
 
@@ -119,7 +141,15 @@ static INSTANCE: LazyLock<ash::Instance> = LazyLock::new(|| {
                 })
         })
         .collect();
-    let enabled_extensions = [required_instance_extensions, preferred_instance_extensions].concat();
+    let mut enabled_extensions =
+        [required_instance_extensions, preferred_instance_extensions].concat();
+    // portability implementations (MoltenVK) are only enumerated when asked for
+    let portability = INSTANCE_EXTENSIONS
+        .iter()
+        .any(|e| e.as_c_str() == khr::portability_enumeration::NAME);
+    if portability {
+        enabled_extensions.push(khr::portability_enumeration::NAME.to_owned());
+    }
 
     let enabled_exts = enabled_extensions
         .iter()
@@ -127,7 +157,12 @@ static INSTANCE: LazyLock<ash::Instance> = LazyLock::new(|| {
         .collect::<Vec<_>>();
     let info = vk::InstanceCreateInfo::default()
         .application_info(&app_info)
-        .enabled_extension_names(&enabled_exts);
+        .enabled_extension_names(&enabled_exts)
+        .flags(if portability {
+            vk::InstanceCreateFlags::ENUMERATE_PORTABILITY_KHR
+        } else {
+            vk::InstanceCreateFlags::empty()
+        });
 
     let layers: Vec<CString> = unsafe {
         ENTRY
@@ -138,13 +173,49 @@ static INSTANCE: LazyLock<ash::Instance> = LazyLock::new(|| {
             .collect()
     };
     let mut enabled_layers = enabled_layers();
-    enabled_layers.retain(|e| layers.contains(e));
+    enabled_layers.retain(|e| {
+        let supported = layers.contains(e);
+        if !supported {
+            warn!("Unsupported vulkan layer: {e:?}");
+        }
+        supported
+    });
     let enabled_layers = enabled_layers
         .iter()
         .map(|e| e.as_ptr())
         .collect::<Vec<_>>();
     let info = info.enabled_layer_names(&enabled_layers);
 
+    let features = vulkan_config().validation_features;
+    let enabled_features: Vec<vk::ValidationFeatureEnableEXT> = [
+        (
+            features.gpu_assisted,
+            vk::ValidationFeatureEnableEXT::GPU_ASSISTED,
+        ),
+        (
+            features.gpu_assisted,
+            vk::ValidationFeatureEnableEXT::GPU_ASSISTED_RESERVE_BINDING_SLOT,
+        ),
+        (
+            features.best_practices,
+            vk::ValidationFeatureEnableEXT::BEST_PRACTICES,
+        ),
+        (
+            features.synchronization,
+            vk::ValidationFeatureEnableEXT::SYNCHRONIZATION_VALIDATION,
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, feature)| enabled.then_some(feature))
+    .collect();
+    let mut validation_features =
+        vk::ValidationFeaturesEXT::default().enabled_validation_features(&enabled_features);
+    let info = if vulkan_config().validation && !enabled_features.is_empty() {
+        info.push_next(&mut validation_features)
+    } else {
+        info
+    };
+
     let instance = unsafe {
         ENTRY
             .create_instance(&info, None)
@@ -152,31 +223,38 @@ static INSTANCE: LazyLock<ash::Instance> = LazyLock::new(|| {
     };
 
     #[cfg(debug_assertions)]
-    unsafe {
-        ash::ext::debug_utils::Instance::new(&ENTRY, &instance)
-            .create_debug_utils_messenger(
-                &vk::DebugUtilsMessengerCreateInfoEXT::default()
-                    .message_severity(
-                        vk::DebugUtilsMessageSeverityFlagsEXT::ERROR
-                            | vk::DebugUtilsMessageSeverityFlagsEXT::WARNING
-                            | vk::DebugUtilsMessageSeverityFlagsEXT::INFO
-                            | vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE,
-                    )
-                    .message_type(
-                        vk::DebugUtilsMessageTypeFlagsEXT::GENERAL
-                            | vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION
-                            | vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE
-                            | vk::DebugUtilsMessageTypeFlagsEXT::DEVICE_ADDRESS_BINDING,
-                    )
-                    .pfn_user_callback(Some(vulkan_debug_callback)),
-                super::alloc_callbacks(),
-            )
-            .unwrap();
+    if vulkan_config().validation {
+        unsafe {
+            ash::ext::debug_utils::Instance::new(&ENTRY, &instance)
+                .create_debug_utils_messenger(
+                    &vk::DebugUtilsMessengerCreateInfoEXT::default()
+                        .message_severity(
+                            vk::DebugUtilsMessageSeverityFlagsEXT::ERROR
+                                | vk::DebugUtilsMessageSeverityFlagsEXT::WARNING
+                                | vk::DebugUtilsMessageSeverityFlagsEXT::INFO
+                                | vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE,
+                        )
+                        .message_type(
+                            vk::DebugUtilsMessageTypeFlagsEXT::GENERAL
+                                | vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION
+                                | vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE
+                                | vk::DebugUtilsMessageTypeFlagsEXT::DEVICE_ADDRESS_BINDING,
+                        )
+                        .pfn_user_callback(Some(vulkan_debug_callback)),
+                    super::alloc_callbacks(),
+                )
+                .unwrap();
+        }
     }
 
     instance
 });
 
+/// instance extensions supported by vulkan driver
+pub(super) fn instance_extensions() -> &'static [CString] {
+    &INSTANCE_EXTENSIONS
+}
+
 pub fn instance() -> &'static ash::Instance {
     &INSTANCE
 }
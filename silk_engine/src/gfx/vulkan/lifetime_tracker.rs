@@ -0,0 +1,92 @@
+use std::collections::{HashMap, HashSet};
+
+use ash::vk;
+
+use crate::warn;
+
+/// Tracks which named resources recorded/submitted cmds and stored desc sets use,
+/// so destroying one still in use is reported by name instead of by a raw handle
+/// in a validation error later, disabled by default since it costs a hashset insert per use
+#[derive(Default)]
+pub(crate) struct LifetimeTracker {
+    pub(crate) enabled: bool,
+    /// names used by cmds that weren't waited on yet
+    pending: HashMap<vk::CommandBuffer, HashSet<String>>,
+    /// desc sets whose bindings reference destroyed resources (binding, name)
+    dangling: HashMap<String, Vec<(u32, String)>>,
+}
+
+impl LifetimeTracker {
+    pub(crate) fn used(&mut self, cmd: vk::CommandBuffer, name: &str) {
+        if !self.enabled || name.is_empty() {
+            return;
+        }
+        let names = self.pending.entry(cmd).or_default();
+        if !names.contains(name) {
+            names.insert(name.to_string());
+        }
+    }
+
+    /// cmd finished executing or was discarded
+    pub(crate) fn finish(&mut self, cmd: vk::CommandBuffer) {
+        self.pending.remove(&cmd);
+    }
+
+    pub(crate) fn finish_all(&mut self) {
+        self.pending.clear();
+    }
+
+    /// `aliases` are other names of the resource (img views of destroyed img),
+    /// `ds_refs` are (desc set, binding) that reference it
+    pub(crate) fn destroyed(
+        &mut self,
+        name: &str,
+        aliases: &[String],
+        ds_refs: Vec<(String, u32)>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        for (cmd, names) in &self.pending {
+            if let Some(used) = std::iter::once(name)
+                .chain(aliases.iter().map(String::as_str))
+                .find(|n| names.contains(*n))
+            {
+                warn!(
+                    "[lifetime] {name} destroyed while {used} is used by pending cmd {cmd:?}, wait for it first or use destroy_after_frame()"
+                );
+            }
+        }
+        for (ds, binding) in ds_refs {
+            self.dangling
+                .entry(ds)
+                .or_default()
+                .push((binding, name.to_string()));
+        }
+    }
+
+    pub(crate) fn rewritten(&mut self, ds: &str, bindings: impl Iterator<Item = u32>) {
+        if let Some(dangling) = self.dangling.get_mut(ds) {
+            for binding in bindings {
+                dangling.retain(|(b, _)| *b != binding);
+            }
+            if dangling.is_empty() {
+                self.dangling.remove(ds);
+            }
+        }
+    }
+
+    pub(crate) fn bound(&self, ds: &str) {
+        if let Some(dangling) = self.dangling.get(ds) {
+            for (binding, name) in dangling {
+                warn!(
+                    "[lifetime] desc set {ds} bound while binding {binding} references destroyed {name}, rewrite it first"
+                );
+            }
+        }
+    }
+
+    pub(crate) fn forget_ds(&mut self, ds: &str) {
+        self.dangling.remove(ds);
+    }
+}
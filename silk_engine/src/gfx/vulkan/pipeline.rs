@@ -1,19 +1,23 @@
 use crate::{
     RES_PATH,
-    gfx::{alloc_callbacks, debug_name, gpu, instance, samples_u32_to_vk, shader::Shader},
+    gfx::{
+        alloc_callbacks, caps, debug_name, gpu, instance, render_pass, samples_u32_to_vk,
+        shader::Shader,
+    },
 };
 use ash::vk;
-use std::sync::LazyLock;
+
+use super::PerGpu;
 
 fn pipeline_cache_path() -> String {
     format!("{RES_PATH}/cache/pipeline_cache")
 }
 
 #[cfg(debug_assertions)]
-static PIPELINE_EXEC_PROPS_LOADER: LazyLock<ash::khr::pipeline_executable_properties::Device> =
-    LazyLock::new(|| ash::khr::pipeline_executable_properties::Device::new(instance(), gpu()));
+static PIPELINE_EXEC_PROPS_LOADER: PerGpu<ash::khr::pipeline_executable_properties::Device> =
+    PerGpu::new(|| ash::khr::pipeline_executable_properties::Device::new(instance(), gpu()));
 
-static PIPELINE_CACHE: LazyLock<vk::PipelineCache> = LazyLock::new(|| {
+static PIPELINE_CACHE: PerGpu<vk::PipelineCache> = PerGpu::new(|| {
     let cache = std::fs::read(pipeline_cache_path()).unwrap_or_default();
     let pipeline_cache = unsafe {
         gpu()
@@ -27,6 +31,11 @@ static PIPELINE_CACHE: LazyLock<vk::PipelineCache> = LazyLock::new(|| {
     pipeline_cache
 });
 
+/// cache is made from device, so it's destroyed before lost device is
+pub(super) fn destroy_pipeline_cache() {
+    unsafe { gpu().destroy_pipeline_cache(*PIPELINE_CACHE, alloc_callbacks()) }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct PipelineStageInfo {
     pub stage: vk::ShaderStageFlags,
@@ -208,7 +217,26 @@ impl GraphicsPipelineInfo {
 
     pub fn blend_attachment_standard(mut self) -> Self {
         // rgb = src.rgb * src.a + dst.rgb * (1 - src.a)
-        // a   = src.a   * src.a + dst.a   * (1 - src.a)
+        // a   = src.a           + dst.a   * (1 - src.a)
+        // so drawing onto transparent clear gives premultiplied output for transparent windows
+        self.attachments.push(
+            vk::PipelineColorBlendAttachmentState::default()
+                .blend_enable(true)
+                .alpha_blend_op(vk::BlendOp::ADD)
+                .color_blend_op(vk::BlendOp::ADD)
+                .color_write_mask(vk::ColorComponentFlags::RGBA)
+                .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+                .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+                .src_alpha_blend_factor(vk::BlendFactor::ONE)
+                .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA),
+        );
+        self
+    }
+
+    pub fn blend_attachment_premultiplied(mut self) -> Self {
+        // rgb = src.rgb * src.a + dst.rgb * (1 - src.a)
+        // a   = src.a           + dst.a   * (1 - src.a)
+        // so result is premultiplied and can be composited again later
         self.attachments.push(
             vk::PipelineColorBlendAttachmentState::default()
                 .blend_enable(true)
@@ -217,12 +245,65 @@ impl GraphicsPipelineInfo {
                 .color_write_mask(vk::ColorComponentFlags::RGBA)
                 .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
                 .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
-                .src_alpha_blend_factor(vk::BlendFactor::SRC_ALPHA)
+                .src_alpha_blend_factor(vk::BlendFactor::ONE)
+                .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA),
+        );
+        self
+    }
+
+    pub fn blend_attachment_additive(mut self) -> Self {
+        // rgb = src.rgb * src.a + dst.rgb
+        // a   = dst.a
+        self.attachments.push(
+            vk::PipelineColorBlendAttachmentState::default()
+                .blend_enable(true)
+                .alpha_blend_op(vk::BlendOp::ADD)
+                .color_blend_op(vk::BlendOp::ADD)
+                .color_write_mask(vk::ColorComponentFlags::RGBA)
+                .dst_alpha_blend_factor(vk::BlendFactor::ONE)
+                .dst_color_blend_factor(vk::BlendFactor::ONE)
+                .src_alpha_blend_factor(vk::BlendFactor::ZERO)
                 .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA),
         );
         self
     }
 
+    pub fn blend_attachment_multiply(mut self) -> Self {
+        // rgb = src.rgb * dst.rgb + dst.rgb * (1 - src.a)
+        // a   = dst.a
+        // src must be premultiplied, so it's dst.rgb * mix(1, src.rgb, src.a)
+        self.attachments.push(
+            vk::PipelineColorBlendAttachmentState::default()
+                .blend_enable(true)
+                .alpha_blend_op(vk::BlendOp::ADD)
+                .color_blend_op(vk::BlendOp::ADD)
+                .color_write_mask(vk::ColorComponentFlags::RGBA)
+                .dst_alpha_blend_factor(vk::BlendFactor::ONE)
+                .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+                .src_alpha_blend_factor(vk::BlendFactor::ZERO)
+                .src_color_blend_factor(vk::BlendFactor::DST_COLOR),
+        );
+        self
+    }
+
+    pub fn blend_attachment_over_premultiplied(mut self) -> Self {
+        // rgb = src.rgb + dst.rgb * (1 - src.a)
+        // a   = src.a   + dst.a   * (1 - src.a)
+        // for src that is already premultiplied
+        self.attachments.push(
+            vk::PipelineColorBlendAttachmentState::default()
+                .blend_enable(true)
+                .alpha_blend_op(vk::BlendOp::ADD)
+                .color_blend_op(vk::BlendOp::ADD)
+                .color_write_mask(vk::ColorComponentFlags::RGBA)
+                .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+                .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+                .src_alpha_blend_factor(vk::BlendFactor::ONE)
+                .src_color_blend_factor(vk::BlendFactor::ONE),
+        );
+        self
+    }
+
     pub fn blend_attachment_empty(mut self) -> Self {
         self.attachments.push(
             vk::PipelineColorBlendAttachmentState::default()
@@ -381,13 +462,25 @@ impl GraphicsPipelineInfo {
             .layout(self.layout)
             .render_pass(self.render_pass)
             .subpass(self.subpass)
-            .flags(if cfg!(debug_assertions) {
-                vk::PipelineCreateFlags::CAPTURE_STATISTICS_KHR
-            } else {
-                vk::PipelineCreateFlags::empty()
-            });
+            .flags(
+                if cfg!(debug_assertions) && caps().pipeline_executable_info() {
+                    vk::PipelineCreateFlags::CAPTURE_STATISTICS_KHR
+                } else {
+                    vk::PipelineCreateFlags::empty()
+                },
+            );
         if self.render_pass == Default::default() {
-            info = info.push_next(&mut rendering_info);
+            if caps().dynamic_rendering() {
+                info = info.push_next(&mut rendering_info);
+            } else {
+                // any load op gives compatible render pass
+                let format = self.color_attachment_formats[0];
+                info = info.render_pass(render_pass(
+                    format,
+                    self.rasterization_samples,
+                    vk::AttachmentLoadOp::LOAD,
+                ));
+            }
         }
         let graphics_pipelines = unsafe {
             gpu()
@@ -431,11 +524,13 @@ pub fn create_compute(
                             .specialization_info(&vk::SpecializationInfo::default()),
                     )
                     .layout(layout)
-                    .flags(if cfg!(debug_assertions) {
-                        vk::PipelineCreateFlags::CAPTURE_STATISTICS_KHR
-                    } else {
-                        vk::PipelineCreateFlags::empty()
-                    })],
+                    .flags(
+                        if cfg!(debug_assertions) && caps().pipeline_executable_info() {
+                            vk::PipelineCreateFlags::CAPTURE_STATISTICS_KHR
+                        } else {
+                            vk::PipelineCreateFlags::empty()
+                        },
+                    )],
                 alloc_callbacks(),
             )
             .unwrap_or_default()
@@ -456,6 +551,9 @@ pub fn create_compute(
 
 #[cfg(debug_assertions)]
 fn log_pipeline_info(pipeline: vk::Pipeline) {
+    if !caps().pipeline_executable_info() {
+        return;
+    }
     unsafe {
         let exec_props = PIPELINE_EXEC_PROPS_LOADER
             .get_pipeline_executable_properties(&vk::PipelineInfoKHR::default().pipeline(pipeline))
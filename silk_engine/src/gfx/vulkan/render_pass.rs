@@ -0,0 +1,83 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+};
+
+use ash::vk;
+
+use super::{ImgLayout, alloc_callbacks, gpu};
+use crate::gfx::debug_name;
+
+/// (color format, samples, load op)
+type RenderPassKey = (vk::Format, vk::SampleCountFlags, vk::AttachmentLoadOp);
+
+static RENDER_PASSES: LazyLock<Mutex<HashMap<RenderPassKey, vk::RenderPass>>> =
+    LazyLock::new(Default::default);
+
+/// render passes are made from device, so they're destroyed before lost device is
+pub(super) fn destroy_render_passes() {
+    for (_, render_pass) in RENDER_PASSES.lock().unwrap().drain() {
+        unsafe { gpu().destroy_render_pass(render_pass, alloc_callbacks()) };
+    }
+}
+
+/// render pass used instead of dynamic rendering when gpu doesn't support it.
+/// one color attachment kept in color layout, multisampled ones also get a resolve attachment.
+/// render passes differing only in `load_op` are compatible, so pipelines work with all of them
+pub(crate) fn render_pass(
+    format: vk::Format,
+    samples: vk::SampleCountFlags,
+    load_op: vk::AttachmentLoadOp,
+) -> vk::RenderPass {
+    *RENDER_PASSES
+        .lock()
+        .unwrap()
+        .entry((format, samples, load_op))
+        .or_insert_with(|| {
+            let multisampled = samples != vk::SampleCountFlags::TYPE_1;
+            let color = vk::AttachmentDescription::default()
+                .format(format)
+                .samples(samples)
+                .load_op(load_op)
+                .store_op(vk::AttachmentStoreOp::STORE)
+                .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+                .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+                .initial_layout(ImgLayout::COLOR)
+                .final_layout(ImgLayout::COLOR);
+            let resolve = color
+                .samples(vk::SampleCountFlags::TYPE_1)
+                .load_op(vk::AttachmentLoadOp::DONT_CARE);
+            let attachments = if multisampled {
+                &[color, resolve][..]
+            } else {
+                &[color][..]
+            };
+            let color_refs = [vk::AttachmentReference::default()
+                .attachment(0)
+                .layout(ImgLayout::COLOR)];
+            let resolve_refs = [vk::AttachmentReference::default()
+                .attachment(1)
+                .layout(ImgLayout::COLOR)];
+            let mut subpass = vk::SubpassDescription::default()
+                .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+                .color_attachments(&color_refs);
+            if multisampled {
+                subpass = subpass.resolve_attachments(&resolve_refs);
+            }
+            let render_pass = unsafe {
+                gpu()
+                    .create_render_pass(
+                        &vk::RenderPassCreateInfo::default()
+                            .attachments(attachments)
+                            .subpasses(&[subpass]),
+                        alloc_callbacks(),
+                    )
+                    .expect("failed to create render pass")
+            };
+            debug_name(
+                &format!("render pass({format:?}, {samples:?}, {load_op:?})"),
+                render_pass,
+            );
+            render_pass
+        })
+}
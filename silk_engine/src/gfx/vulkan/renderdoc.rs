@@ -0,0 +1,106 @@
+use std::{ffi::c_void, sync::LazyLock};
+
+use libloading::Library;
+
+use super::vulkan_config;
+use crate::{log, warn};
+
+#[cfg(target_os = "windows")]
+const LIB_NAME: &str = "renderdoc.dll";
+#[cfg(not(target_os = "windows"))]
+const LIB_NAME: &str = "librenderdoc.so";
+
+/// eRENDERDOC_API_Version_1_1_2
+const API_VERSION: i32 = 10102;
+
+type GetApi = unsafe extern "C" fn(version: i32, out: *mut *mut c_void) -> i32;
+
+/// prefix of RENDERDOC_API_1_1_2 fn table, unused entries are skipped
+#[repr(C)]
+struct Api {
+    get_api_version: unsafe extern "C" fn(*mut i32, *mut i32, *mut i32),
+    _options: [usize; 14],
+    trigger_capture: unsafe extern "C" fn(),
+    _ui: [usize; 4],
+    is_frame_capturing: unsafe extern "C" fn() -> u32,
+}
+
+pub(crate) struct RenderDoc {
+    api: &'static Api,
+    _lib: Library,
+}
+
+// api fns are thread safe
+unsafe impl Send for RenderDoc {}
+unsafe impl Sync for RenderDoc {}
+
+impl RenderDoc {
+    pub fn trigger_capture(&self) {
+        unsafe { (self.api.trigger_capture)() }
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        unsafe { (self.api.is_frame_capturing)() != 0 }
+    }
+}
+
+/// lib injected by renderdoc ui, so app launched from it is captured without config
+fn open_loaded() -> Option<Library> {
+    #[cfg(target_os = "windows")]
+    {
+        libloading::os::windows::Library::open_already_loaded(LIB_NAME)
+            .ok()
+            .map(Library::from)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        const RTLD_NOLOAD: i32 = 4;
+        unsafe {
+            libloading::os::unix::Library::open(
+                Some(LIB_NAME),
+                libloading::os::unix::RTLD_NOW | RTLD_NOLOAD,
+            )
+        }
+        .ok()
+        .map(Library::from)
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    None
+}
+
+static RENDERDOC: LazyLock<Option<RenderDoc>> = LazyLock::new(|| {
+    let lib = match open_loaded() {
+        Some(lib) => lib,
+        None if vulkan_config().renderdoc => match unsafe { Library::new(LIB_NAME) } {
+            Ok(lib) => lib,
+            Err(e) => {
+                warn!("failed to load {LIB_NAME}: {e}");
+                return None;
+            }
+        },
+        None => return None,
+    };
+    let api = unsafe {
+        let get_api = lib.get::<GetApi>(b"RENDERDOC_GetAPI\0").ok()?;
+        let mut api = std::ptr::null_mut();
+        if get_api(API_VERSION, &mut api) != 1 || api.is_null() {
+            warn!("renderdoc api 1.1.2 is not supported");
+            return None;
+        }
+        &*(api as *const Api)
+    };
+    let (mut major, mut minor, mut patch) = (0, 0, 0);
+    unsafe { (api.get_api_version)(&mut major, &mut minor, &mut patch) };
+    log!("RenderDoc: api {major}.{minor}.{patch}");
+    Some(RenderDoc { api, _lib: lib })
+});
+
+/// renderdoc hooks vulkan only if loaded before vulkan is, so it's loaded along with vulkan lib
+pub(super) fn load() {
+    LazyLock::force(&RENDERDOC);
+}
+
+/// none unless app was launched from renderdoc or [`super::VulkanConfig::renderdoc`] is set
+pub(crate) fn renderdoc() -> Option<&'static RenderDoc> {
+    RENDERDOC.as_ref()
+}
@@ -21,37 +21,53 @@ impl SamplerManager {
         mag_filter: vk::Filter,
         mip_filter: vk::SamplerMipmapMode,
     ) -> vk::Sampler {
-        *self
-            .samplers
-            .entry(SamplerInfo {
-                addr_mode_u,
-                addr_mode_v,
-                min_filter,
-                mag_filter,
-                mip_filter,
-            })
-            .or_insert(unsafe {
-                gpu()
-                    .create_sampler(
-                        &vk::SamplerCreateInfo::default()
-                            .address_mode_u(addr_mode_u)
-                            .address_mode_v(addr_mode_v)
-                            .address_mode_w(vk::SamplerAddressMode::REPEAT)
-                            .min_filter(min_filter)
-                            .mag_filter(mag_filter)
-                            .mipmap_mode(mip_filter)
-                            .max_anisotropy(16.0)
-                            .border_color(vk::BorderColor::FLOAT_TRANSPARENT_BLACK)
-                            .compare_enable(false)
-                            .compare_op(vk::CompareOp::ALWAYS)
-                            .mip_lod_bias(0.0)
-                            .min_lod(0.0)
-                            .max_lod(1.0)
-                            .unnormalized_coordinates(false),
-                        alloc_callbacks(),
-                    )
-                    .unwrap()
-            })
+        self.get_info(SamplerInfo {
+            addr_mode_u,
+            addr_mode_v,
+            min_filter,
+            mag_filter,
+            mip_filter,
+        })
+    }
+
+    pub fn get_info(&mut self, info: SamplerInfo) -> vk::Sampler {
+        let SamplerInfo {
+            addr_mode_u,
+            addr_mode_v,
+            min_filter,
+            mag_filter,
+            mip_filter,
+        } = info;
+        *self.samplers.entry(info).or_insert_with(|| unsafe {
+            gpu()
+                .create_sampler(
+                    &vk::SamplerCreateInfo::default()
+                        .address_mode_u(addr_mode_u)
+                        .address_mode_v(addr_mode_v)
+                        .address_mode_w(vk::SamplerAddressMode::REPEAT)
+                        .min_filter(min_filter)
+                        .mag_filter(mag_filter)
+                        .mipmap_mode(mip_filter)
+                        .max_anisotropy(16.0)
+                        .border_color(vk::BorderColor::FLOAT_TRANSPARENT_BLACK)
+                        .compare_enable(false)
+                        .compare_op(vk::CompareOp::ALWAYS)
+                        .mip_lod_bias(0.0)
+                        .min_lod(0.0)
+                        .max_lod(1.0)
+                        .unnormalized_coordinates(false),
+                    alloc_callbacks(),
+                )
+                .unwrap()
+        })
+    }
+
+    /// info sampler was made with, none if it wasn't made by this manager
+    pub fn info(&self, sampler: vk::Sampler) -> Option<SamplerInfo> {
+        self.samplers
+            .iter()
+            .find(|&(_, &s)| s == sampler)
+            .map(|(&info, _)| info)
     }
 }
 
@@ -65,7 +81,7 @@ impl Drop for SamplerManager {
     }
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct SamplerInfo {
     addr_mode_u: vk::SamplerAddressMode,
     addr_mode_v: vk::SamplerAddressMode,
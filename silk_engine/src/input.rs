@@ -2,6 +2,72 @@ pub type Key = winit::keyboard::KeyCode;
 pub type Mouse = winit::event::MouseButton;
 type Event = winit::event::WindowEvent;
 
+use winit::event::TouchPhase;
+
+use crate::util::{Reader, Writer};
+
+/// keys that can be named in settings and shortcuts, see [`parse_key`]
+#[rustfmt::skip]
+const NAMED_KEYS: [Key; 101] = [
+    Key::Backquote, Key::Backslash, Key::BracketLeft, Key::BracketRight, Key::Comma, Key::Digit0,
+    Key::Digit1, Key::Digit2, Key::Digit3, Key::Digit4, Key::Digit5, Key::Digit6, Key::Digit7,
+    Key::Digit8, Key::Digit9, Key::Equal, Key::KeyA, Key::KeyB, Key::KeyC, Key::KeyD, Key::KeyE,
+    Key::KeyF, Key::KeyG, Key::KeyH, Key::KeyI, Key::KeyJ, Key::KeyK, Key::KeyL, Key::KeyM,
+    Key::KeyN, Key::KeyO, Key::KeyP, Key::KeyQ, Key::KeyR, Key::KeyS, Key::KeyT, Key::KeyU,
+    Key::KeyV, Key::KeyW, Key::KeyX, Key::KeyY, Key::KeyZ, Key::Minus, Key::Period, Key::Quote,
+    Key::Semicolon, Key::Slash, Key::AltLeft, Key::AltRight, Key::Backspace, Key::CapsLock,
+    Key::ControlLeft, Key::ControlRight, Key::Enter, Key::SuperLeft, Key::SuperRight,
+    Key::ShiftLeft, Key::ShiftRight, Key::Space, Key::Tab, Key::Delete, Key::End, Key::Home,
+    Key::Insert, Key::PageDown, Key::PageUp, Key::ArrowDown, Key::ArrowLeft, Key::ArrowRight,
+    Key::ArrowUp, Key::Numpad0, Key::Numpad1, Key::Numpad2, Key::Numpad3, Key::Numpad4,
+    Key::Numpad5, Key::Numpad6, Key::Numpad7, Key::Numpad8, Key::Numpad9, Key::NumpadAdd,
+    Key::NumpadDecimal, Key::NumpadDivide, Key::NumpadEnter, Key::NumpadMultiply,
+    Key::NumpadSubtract, Key::Escape, Key::PrintScreen, Key::Pause, Key::F1, Key::F2, Key::F3,
+    Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12,
+];
+
+/// parses `KeyCode` name ("KeyW", "Digit1", "ArrowUp") or short alias
+/// ("W", "1", "Up", "Ctrl", "Esc"), case insensitive
+pub fn parse_key(name: &str) -> Option<Key> {
+    let name = name.trim().to_ascii_lowercase();
+    let alias = match name.as_str() {
+        "ctrl" | "control" => "controlleft",
+        "shift" => "shiftleft",
+        "alt" => "altleft",
+        "super" | "cmd" | "win" => "superleft",
+        "esc" => "escape",
+        "return" => "enter",
+        "del" => "delete",
+        "up" | "down" | "left" | "right" => &format!("arrow{name}"),
+        "`" | "~" => "backquote",
+        n if n.len() == 1 && n.as_bytes()[0].is_ascii_lowercase() => &format!("key{n}"),
+        n if n.len() == 1 && n.as_bytes()[0].is_ascii_digit() => &format!("digit{n}"),
+        n => n,
+    };
+    NAMED_KEYS
+        .into_iter()
+        .find(|k| format!("{k:?}").eq_ignore_ascii_case(alias))
+}
+
+/// `KeyCode` name, parsed back by [`parse_key`]
+pub fn key_name(key: Key) -> String {
+    format!("{key:?}")
+}
+
+const RECORDING_MAGIC: &[u8; 4] = b"SLKI";
+const RECORDING_VERSION: u32 = 1;
+/// dt, mouse, mouse x/y, scroll, mouse press x/y, keys, focus
+const FRAME_SIZE: usize = 4 + 5 + 4 * 3 + 4 * 5 * 2 + 194 + 1;
+
+/// finger on touch screen, position in same -1..1 space as mouse
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Touch {
+    pub id: u64,
+    pub phase: TouchPhase,
+    pub x: f32,
+    pub y: f32,
+}
+
 pub struct Input {
     mouse: [bool; 5],
     mouse_old: [bool; 5],
@@ -14,6 +80,8 @@ pub struct Input {
     key_old: [bool; 194],
     focus: bool,
     focus_old: bool,
+    /// ended/cancelled touches are kept until next `reset`
+    touches: Vec<Touch>,
 }
 
 impl Input {
@@ -30,6 +98,7 @@ impl Input {
             key_old: [false; 194],
             focus: true,
             focus_old: false,
+            touches: Vec::new(),
         }
     }
 
@@ -73,12 +142,27 @@ impl Input {
                 }
             }
             Event::Touch(touch) => {
-                self.mouse_x = touch.location.x as f32;
-                self.mouse_y = touch.location.y as f32;
-                use winit::event::TouchPhase;
-                match touch.phase {
-                    TouchPhase::Started | TouchPhase::Moved => self.mouse[0] = true,
-                    TouchPhase::Ended | TouchPhase::Cancelled => self.mouse[0] = false,
+                let t = Touch {
+                    id: touch.id,
+                    phase: touch.phase,
+                    x: touch.location.x as f32 / width.max(1) as f32 * 2.0 - 1.0,
+                    y: 1.0 - touch.location.y as f32 / height.max(1) as f32 * 2.0,
+                };
+                match self.touches.iter_mut().find(|o| o.id == t.id) {
+                    Some(old) => *old = t,
+                    None => self.touches.push(t),
+                }
+                // first finger also acts as left mouse button
+                if self.touches[0].id == t.id {
+                    (self.mouse_x, self.mouse_y) = (t.x, t.y);
+                    match t.phase {
+                        TouchPhase::Started => {
+                            self.mouse[0] = true;
+                            (self.mouse_press_x[0], self.mouse_press_y[0]) = (t.x, t.y);
+                        }
+                        TouchPhase::Moved => self.mouse[0] = true,
+                        TouchPhase::Ended | TouchPhase::Cancelled => self.mouse[0] = false,
+                    }
                 }
             }
             Event::KeyboardInput {
@@ -105,6 +189,8 @@ impl Input {
         self.mouse_old = self.mouse;
         self.key_old = self.key;
         self.focus_old = self.focus;
+        self.touches
+            .retain(|t| matches!(t.phase, TouchPhase::Started | TouchPhase::Moved));
     }
 
     pub fn mouse_x(&self) -> f32 {
@@ -159,10 +245,36 @@ impl Input {
         self.key[k as usize]
     }
 
+    /// active touches, plus ones that ended this frame
+    pub fn touches(&self) -> &[Touch] {
+        &self.touches
+    }
+
     pub fn focused(&self) -> bool {
         !self.focus_old && self.focus
     }
 
+    fn write(&self, w: &mut Writer) {
+        let f32s = [self.mouse_x, self.mouse_y, self.mouse_scroll];
+        let press = [self.mouse_press_x, self.mouse_press_y];
+        w.write(&self.mouse.map(u8::from));
+        for v in f32s.iter().chain(press.as_flattened()) {
+            w.write32(v.to_bits());
+        }
+        w.write(&self.key.map(u8::from));
+        w.write8(self.focus as u8);
+    }
+
+    fn read(&mut self, r: &mut Reader) {
+        self.mouse = r.read_arr::<5>().map(|b| b != 0);
+        let mut f = || f32::from_bits(r.read32());
+        (self.mouse_x, self.mouse_y, self.mouse_scroll) = (f(), f(), f());
+        self.mouse_press_x = std::array::from_fn(|_| f());
+        self.mouse_press_y = std::array::from_fn(|_| f());
+        self.key = r.read_arr::<194>().map(|b| b != 0);
+        self.focus = r.read8() != 0;
+    }
+
     fn mouse_idx(mouse: Mouse) -> usize {
         match mouse {
             Mouse::Left => 0,
@@ -174,3 +286,71 @@ impl Input {
         }
     }
 }
+
+/// per frame input state and dt, replaying it makes app see exactly what it saw
+/// while recording, see `AppContext::record_input`
+/// input is live, recorded or replayed, see `AppContext::record_input`
+pub(crate) enum InputMode {
+    Live,
+    Recording(InputRecording),
+    /// recording and next frame to replay
+    Replaying(InputRecording, usize),
+}
+
+#[derive(Default)]
+pub struct InputRecording {
+    /// FRAME_SIZE bytes per frame
+    frames: Vec<u8>,
+}
+
+impl InputRecording {
+    pub fn len(&self) -> usize {
+        self.frames.len() / FRAME_SIZE
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub(crate) fn push(&mut self, dt: f32, input: &Input) {
+        let mut w = Writer::new(FRAME_SIZE);
+        w.write32(dt.to_bits());
+        input.write(&mut w);
+        self.frames.extend_from_slice(&w.finish());
+    }
+
+    /// restores `frame` state into `input` and returns its dt
+    pub(crate) fn frame(&self, frame: usize, input: &mut Input) -> f32 {
+        let mut r = Reader::new(&self.frames[frame * FRAME_SIZE..][..FRAME_SIZE]);
+        let dt = f32::from_bits(r.read32());
+        input.read(&mut r);
+        dt
+    }
+
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let mut w = Writer::new(12 + self.frames.len());
+        w.write(RECORDING_MAGIC);
+        w.write32(RECORDING_VERSION);
+        w.write32(self.len() as u32);
+        w.write(&self.frames[..]);
+        std::fs::write(path, w.finish())
+    }
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        let mut r = Reader::new(&bytes);
+        let valid = bytes.len() >= 12
+            && r.read_arr::<4>() == *RECORDING_MAGIC
+            && r.read32() == RECORDING_VERSION
+            && bytes.len() - 12 == r.read32() as usize * FRAME_SIZE;
+        if !valid {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "invalid input recording",
+            ));
+        }
+        Ok(Self {
+            frames: bytes[12..].to_vec(),
+        })
+    }
+}
@@ -1,6 +1,7 @@
 #![feature(
     mapped_lock_guards,
     once_cell_get_mut,
+    portable_simd,
     slice_as_chunks,
     slice_as_array,
     str_from_raw_parts
@@ -8,30 +9,56 @@
 
 pub mod prelude;
 
+mod bench;
+mod config;
+mod console;
+mod crash;
+mod diagnostics;
+mod error;
 mod event;
+mod gesture;
 mod gfx;
 mod input;
+mod scene;
+mod settings;
+mod shortcut;
+mod theme;
 mod util;
 
 use ash::vk;
-use event::{Dispatcher, Event, WindowResize};
+use bench::{Bench, BenchReport};
+use config::{AntiAliasing, EngineConfig, Fullscreen, VideoMode};
+use console::Console;
+use diagnostics::Diagnostics;
+use error::Error;
+use event::{
+    AssetFailed, AssetLoaded, Dispatcher, Event, GpuError, Pan, Pinch, PipelineProgress, Tap,
+    ThemeChange, TweenDone, WindowResize,
+};
+use gesture::{Gesture, Gestures};
 use gfx::{
-    GraphicsPipelineInfo, ImageInfo, ImgLayout, ImgUsage, MSAA, MemProp, RenderCtx, Renderer,
-    queue_idle,
+    Background, Caps, FramePacer, GpuInfo, ImageInfo, ImgLayout, ImgUsage, MemProp, PacingStats,
+    PostChain, RenderCtx, Renderer, caps, gpu_info, lock_ctx, queue_idle, vulkan_config,
 };
 
 use input::*;
+use scene::Scene;
+use settings::Settings;
+use shortcut::{Shortcut, Shortcuts};
 use std::{
     any::TypeId,
     collections::HashMap,
     sync::{Arc, LazyLock, Mutex},
-    time::Instant,
+    time::{Duration, Instant},
 };
+use theme::{Theme, Themes};
+use util::{TaskHandle, Tasks, Tweens};
 use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::WindowEvent,
+    event::{StartCause, WindowEvent},
     event_loop::ActiveEventLoop,
     monitor::MonitorHandle,
+    raw_window_handle::HasDisplayHandle,
     window::Window,
     {event_loop::ControlFlow, window::WindowId},
     {platform::run_on_demand::EventLoopExtRunOnDemand, window::WindowAttributes},
@@ -42,21 +69,34 @@ pub const RES_PATH: &str = "res";
 #[cfg(test)]
 pub const RES_PATH: &str = "../target/test_res";
 
+/// time spent building queued pipelines each update, see `RenderCtx::queue_pipeline`
+const PIPELINE_BUDGET: Duration = Duration::from_millis(4);
+
 pub static INIT_PATHS: LazyLock<()> = LazyLock::new(|| {
     use std::fs;
     fs::create_dir_all(RES_PATH).unwrap_or_default();
     fs::create_dir_all(format!("{RES_PATH}/shaders")).unwrap_or_default();
     fs::create_dir_all(format!("{RES_PATH}/images")).unwrap_or_default();
     fs::create_dir_all(format!("{RES_PATH}/fonts")).unwrap_or_default();
+    fs::create_dir_all(format!("{RES_PATH}/videos")).unwrap_or_default();
     #[cfg(not(debug_assertions))]
     fs::create_dir_all(format!("{RES_PATH}/cache/shaders")).unwrap_or_default();
 });
 
+/// app is stored in it's context and taken out while it's callbacks run,
+/// so they can borrow context mutably alongside app without aliasing
 pub trait App: Sized {
-    fn new(app: *mut AppContext<Self>) -> Self;
-    fn update(&mut self);
+    fn new(ctx: &mut AppContext<Self>) -> Self;
+    fn update(&mut self, ctx: &mut AppContext<Self>);
     fn render(&mut self, gfx: &mut Renderer);
-    fn event(&mut self, _e: WindowEvent) {}
+    fn event(&mut self, _ctx: &mut AppContext<Self>, _e: WindowEvent) {}
+    /// gpu crashed or was reset (driver update, timeout), device and named resources were
+    /// recreated but their contents were lost, so bufs, imgs, textures and fonts must be reuploaded
+    fn on_device_lost(&mut self, _ctx: &mut AppContext<Self>) {}
+    /// window got minimized or fully covered, nothing is rendered and updates are throttled
+    /// to [`EngineConfig::background_fps`] until [`App::on_resume`]
+    fn on_suspend(&mut self, _ctx: &mut AppContext<Self>) {}
+    fn on_resume(&mut self, _ctx: &mut AppContext<Self>) {}
 }
 
 pub struct AppContext<A: App> {
@@ -74,17 +114,49 @@ pub struct AppContext<A: App> {
     pub fps: f32,
     pub frame: u32,
     input: Input,
+    input_mode: InputMode,
     pub mouse_x: f32,
     pub mouse_y: f32,
     pub mouse_scroll: f32,
     pub surface_format: vk::Format,
+    pub scene: Scene,
+    pub settings: Settings,
+    pub tweens: Tweens,
+    tasks: Tasks,
+    console: Console,
+    shortcuts: Shortcuts,
+    gestures: Gestures,
+    background: Background,
+    themes: Themes,
     ctx: Arc<Mutex<RenderCtx>>,
     renderer: Renderer,
+    post_chain: PostChain,
+    /// see [`Self::set_dither`]
+    dither: f32,
+    anti_aliasing: AntiAliasing,
+    live_resize: bool,
+    /// size window-sized render targets are allocated at, see [`Self::target_size`]
+    target_size: (u32, u32),
+    fullscreen: Fullscreen,
+    pacer: FramePacer,
+    /// control flow while window is visible, see [`Engine::with`]
+    control_flow: ControlFlow,
+    background_fps: u32,
+    /// see [`EngineConfig::capture_frame`]
+    capture_frame: Option<u32>,
+    occluded: bool,
+    suspended: bool,
+    bench: Option<Bench>,
     dispatchers: HashMap<TypeId, Box<dyn std::any::Any + Send + Sync>>,
 }
 
 impl<A: App> AppContext<A> {
-    pub fn new(window: Window, monitor: MonitorHandle) -> Arc<Mutex<Self>> {
+    pub fn new(
+        window: Window,
+        monitor: MonitorHandle,
+        config: &EngineConfig,
+        settings: Settings,
+    ) -> Arc<Mutex<Self>> {
         scope_time!("init");
         *INIT_PATHS;
         let PhysicalSize {
@@ -92,31 +164,25 @@ impl<A: App> AppContext<A> {
             height: monitor_height,
         } = monitor.size();
         let PhysicalSize { width, height } = window.inner_size();
-        let refresh_rate =
-            (monitor.refresh_rate_millihertz().unwrap_or(60) as f32 / 1000.0).round() as u32;
+        let refresh_rate = match config.fullscreen {
+            Fullscreen::Exclusive(mode) => mode.refresh_rate(),
+            _ => monitor_refresh_rate(&monitor),
+        };
         log!(
             "Monitor: {} {monitor_width}x{monitor_height} {refresh_rate}hz",
             monitor.name().unwrap_or_default(),
         );
 
         let ctx = Arc::new(Mutex::new(RenderCtx::new(&window)));
+        crash::watch_ctx(&ctx);
         let surf_fmt = ctx.lock().unwrap().surface_format.format;
-        {
-            let mut ctx = ctx.lock().unwrap();
-            ctx.add_shader("fxaa");
-            ctx.add_pipeline(
-                "fxaa",
-                "fxaa",
-                GraphicsPipelineInfo::default()
-                    .blend_attachment_empty()
-                    .dyn_size()
-                    .color_attachment(surf_fmt)
-                    .topology(vk::PrimitiveTopology::TRIANGLE_STRIP),
-                &[],
-            );
-            ctx.add_desc_set("fxaa ds", "fxaa", 0);
-            ctx.write_ds_sampler("fxaa ds", "linear", 1);
-        }
+        let anti_aliasing = config.anti_aliasing.validated();
+        let mut renderer = Renderer::new(ctx.clone(), anti_aliasing.samples());
+        renderer.set_scale_factor(window.scale_factor() as f32);
+        let themes = Themes::new(window.theme());
+        themes.active().apply(&mut renderer);
+        let mut post_chain = PostChain::new(ctx.clone());
+        Self::add_aa_effects(&mut post_chain, anti_aliasing);
         let app = Arc::new(Mutex::new(Self {
             my_app: None,
             window,
@@ -132,19 +198,49 @@ impl<A: App> AppContext<A> {
             fps: 0.0,
             frame: 0,
             input: Input::new(),
+            input_mode: InputMode::Live,
             mouse_x: 0.0,
             mouse_y: 0.0,
             mouse_scroll: 0.0,
             ctx: ctx.clone(),
             surface_format: surf_fmt,
-            renderer: Renderer::new(ctx.clone()),
+            scene: Scene::new(),
+            settings,
+            tweens: Tweens::new(),
+            tasks: Tasks::new(),
+            console: Console::new(),
+            shortcuts: Shortcuts::default(),
+            gestures: Gestures::default(),
+            background: Background::None,
+            themes,
+            renderer,
+            post_chain,
+            dither: 0.0,
+            anti_aliasing,
+            live_resize: config.live_resize,
+            target_size: (0, 0),
+            fullscreen: config.fullscreen,
+            pacer: FramePacer::new(refresh_rate),
+            control_flow: ControlFlow::Poll,
+            background_fps: config.background_fps,
+            capture_frame: config.capture_frame,
+            occluded: false,
+            suspended: false,
+            bench: None,
             dispatchers: Default::default(),
         }));
         {
-            let app_ptr = &*app.lock().unwrap() as *const AppContext<A>;
-            let app_mut = unsafe { app_ptr.cast_mut().as_mut().unwrap() };
-            app_mut.my_app = Some(A::new(app_ptr as *mut _));
-            app_mut.dispatcher().post(&WindowResize::new(width, height));
+            let mut ctx = app.lock().unwrap();
+            if let Some(chord) = &config.capture_key
+                && ctx.ctx().renderdoc_attached()
+            {
+                let render_ctx = ctx.ctx.clone();
+                ctx.shortcuts
+                    .add(chord, move || lock_ctx(&render_ctx).trigger_capture());
+            }
+            let my_app = A::new(&mut ctx);
+            ctx.my_app = Some(my_app);
+            ctx.dispatcher().post(&WindowResize::new(width, height));
         }
         app
     }
@@ -153,199 +249,345 @@ impl<A: App> AppContext<A> {
         scope_time!("update {}", self.frame; self.frame < 4);
         let now = Instant::now().duration_since(self.start_time).as_secs_f32();
         self.dt = now - self.time;
+        self.apply_input_mode();
         self.fps = 1.0 / self.dt;
-        self.time = now;
-        self.my_app().update();
+        self.time += self.dt;
+        for name in self.tweens.update(self.dt) {
+            self.dispatcher().post(&TweenDone::new(name));
+        }
+        if !self.ctx().device_lost() && self.ctx().build_queued_pipelines(PIPELINE_BUDGET) != 0 {
+            let (ready, total) = self.ctx().pipeline_progress();
+            self.dispatcher().post(&PipelineProgress::new(ready, total));
+        }
+        // tasks can spawn tasks while being polled
+        let mut tasks = std::mem::take(&mut self.tasks);
+        tasks.update(self.time);
+        tasks.append(&mut self.tasks);
+        self.tasks = tasks;
+        self.with_app(|app, ctx| app.update(ctx));
+        self.scene.update(self.dt);
+    }
+
+    /// records live input or replaces it (and dt) with replayed frame
+    fn apply_input_mode(&mut self) {
+        match &mut self.input_mode {
+            InputMode::Live => {}
+            InputMode::Recording(recording) => recording.push(self.dt, &self.input),
+            InputMode::Replaying(recording, frame) if *frame < recording.len() => {
+                self.dt = recording.frame(*frame, &mut self.input);
+                *frame += 1;
+                self.mouse_x = self.input.mouse_x();
+                self.mouse_y = self.input.mouse_y();
+                self.mouse_scroll = self.input.mouse_scroll();
+                // keep wall clock time in sync with replayed dts
+                self.start_time = Instant::now() - Duration::from_secs_f32(self.time + self.dt);
+            }
+            InputMode::Replaying(..) => {
+                log!("Input replay finished");
+                self.input_mode = InputMode::Live;
+            }
+        }
     }
 
     fn render(&mut self) {
-        if self.width != 0 && self.height != 0 {
-            scope_time!("render {}", self.frame; self.frame < 4);
+        let device_lost = self.ctx().device_lost();
+        if device_lost {
+            // nothing can be rendered, pace so event loop doesn't spin
+            self.pacer.frame();
+        } else if self.suspended || self.width == 0 || self.height == 0 {
+            // minimized or occluded, event loop is throttled while nothing is visible
+            self.pacer.idle();
+        } else {
+            self.render_frame();
+        }
+        self.renderer.reset();
+        let errors = self.ctx().take_errors();
+        let lost = errors.contains(&Error::DeviceLost);
+        for error in errors {
+            self.dispatcher().post(&GpuError::new(error));
+        }
+        if lost && !device_lost {
+            let ctx = self.ctx.clone();
+            let size = lock_ctx(&ctx).recreate_device();
+            // render targets and resolution ubo are rewritten even if size didn't change
+            (self.width, self.height) = (0, 0);
+            self.resize(size.width, size.height);
+            self.with_app(|app, ctx| app.on_device_lost(ctx));
+        }
+        for (name, error) in self.renderer.take_loaded() {
+            match error {
+                None => self.dispatcher().post(&AssetLoaded::new(name)),
+                Some(error) => self.dispatcher().post(&AssetFailed::new(name, error)),
+            }
+        }
 
-            self.ctx().wait_prev_frame();
+        self.input.reset();
+        self.frame += 1;
+        crash::set_frame(self.frame);
+    }
 
-            self.my_app.as_mut().unwrap().render(&mut self.renderer);
-            self.renderer.flush();
+    /// scene fills window, or is drawn once into each viewport with it's camera
+    fn draw_scene(&mut self) {
+        let viewports: Vec<String> = self.renderer.viewports().map(String::from).collect();
+        if viewports.is_empty() {
+            self.scene.draw(&mut self.renderer);
+        }
+        for viewport in viewports {
+            self.renderer.begin_viewport(&viewport);
+            self.scene.draw(&mut self.renderer);
+            self.renderer.end_viewport();
+        }
+    }
 
-            let optimal_size = self.ctx().begin_frame();
-            self.resize(optimal_size.width, optimal_size.height);
+    fn render_frame(&mut self) {
+        scope_time!("render {}", self.frame; self.frame < 4);
 
-            // make sure rendered_img is ready to be written in fs color output
-            self.ctx().set_img_layout(
-                "rendered image",
-                ImgLayout::COLOR,
-                vk::PipelineStageFlags2::TOP_OF_PIPE,
-                vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
-                vk::AccessFlags2::NONE,
-                vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
-            );
+        self.pacer.frame();
 
-            // Render (write rendered_img color output at fs shader)
-            let (width, height) = (self.width, self.height);
-            self.ctx().begin_render(
-                width,
-                height,
-                "rendered image view",
-                if MSAA > 1 {
-                    "sampled rendered image view"
-                } else {
-                    ""
-                },
-            );
-            self.renderer.render();
-            self.ctx().end_render();
+        self.ctx().wait_prev_frame();
 
-            // make sure rendered_img color output is written to read in fxaa fs shader
-            self.ctx().set_img_layout(
-                "rendered image",
-                ImgLayout::SHADER_READ,
-                vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
-                vk::PipelineStageFlags2::FRAGMENT_SHADER,
-                vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
-                vk::AccessFlags2::SHADER_READ,
-            );
+        self.background.draw(&mut self.renderer);
+        self.draw_scene();
+        self.my_app.as_mut().unwrap().render(&mut self.renderer);
+        self.console.draw(&mut self.renderer);
 
-            // make sure fxaa_img is ready to be written in fs color output
-            self.ctx().set_img_layout(
-                "fxaa image",
-                ImgLayout::COLOR,
-                vk::PipelineStageFlags2::TOP_OF_PIPE,
-                vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
-                vk::AccessFlags2::NONE,
-                vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
-            );
+        // ctx is locked once for rest of frame and passed down, instead of locking per stage
+        let ctx = self.ctx.clone();
+        let resized = self.submit_frame(&mut lock_ctx(&ctx));
+        // resize handlers may lock ctx themselves
+        for e in resized {
+            self.dispatcher().post(&e);
+        }
+    }
 
-            // FXAA
-            self.ctx()
-                .begin_render(width, height, "fxaa image view", "");
-            self.ctx().bind_pipeline("fxaa");
-            self.ctx().bind_ds("fxaa ds");
-            self.ctx().draw(3, 1);
-            self.ctx().end_render();
-
-            // make sure fxaa_img color output is written
-            self.ctx().set_img_layout(
-                "fxaa image",
-                ImgLayout::SRC,
-                vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
-                vk::PipelineStageFlags2::BLIT,
-                vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
-                vk::AccessFlags2::TRANSFER_READ,
-            );
+    /// records and presents drawn frame, returns resize events to post once ctx is unlocked
+    fn submit_frame(&mut self, gpu: &mut RenderCtx) -> Vec<WindowResize> {
+        self.renderer.jitter(gpu, self.post_chain.jitter(self.frame));
+        // post chain may have changed, so batch is only dithered if it's output is presented
+        let dither = if self.post_chain.dithers() { 0.0 } else { self.dither };
+        self.renderer.write_dither(gpu, dither);
+        self.renderer.flush(gpu);
 
-            // make sure swap_img is ready to be blitted to
-            let swap_img = self.ctx().cur_img();
-            self.ctx().set_img_layout(
-                &swap_img,
-                ImgLayout::DST,
-                vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
-                vk::PipelineStageFlags2::BLIT,
-                vk::AccessFlags2::NONE,
-                vk::AccessFlags2::TRANSFER_WRITE,
-            );
+        // swapchain img couldn't be acquired (minimized mid frame or still out of date),
+        // resize picks up 0x0 surface so following frames idle instead of retrying
+        let Some(optimal_size) = gpu.begin_frame() else {
+            return self.resize_locked(gpu, 0, 0).into_iter().collect();
+        };
+        let mut resized: Vec<_> = self
+            .resize_locked(gpu, optimal_size.width, optimal_size.height)
+            .into_iter()
+            .collect();
 
-            // blit fxaa_img into swap_img for presenting
-            self.ctx().blit("fxaa image", &swap_img);
-
-            // make sure swap_img is ready for presenting
-            self.ctx().set_img_layout(
-                &swap_img,
-                ImgLayout::PRESENT,
-                vk::PipelineStageFlags2::BLIT,
-                vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
-                vk::AccessFlags2::TRANSFER_WRITE,
-                vk::AccessFlags2::NONE,
-            );
+        // make sure rendered_img is ready to be written in fs color output
+        gpu.set_img_layout(
+            "rendered image",
+            ImgLayout::COLOR,
+            vk::PipelineStageFlags2::TOP_OF_PIPE,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            vk::AccessFlags2::NONE,
+            vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+        );
+
+        // Render (write rendered_img color output at fs shader)
+        let (width, height) = (self.width, self.height);
+        let multisampled = self.anti_aliasing.samples() > 1;
+        let clear_color = self.renderer.clear_value();
+        gpu.begin_render_clear(
+            width,
+            height,
+            "rendered image view",
+            if multisampled {
+                "sampled rendered image view"
+            } else {
+                ""
+            },
+            clear_color,
+        );
+        gpu.debug_begin("renderer");
+        self.renderer.render(gpu);
+        gpu.debug_end();
+        gpu.end_render();
+
+        // post processing (reads rendered_img in compute shaders)
+        gpu.debug_begin("post chain");
+        let out = self.post_chain.render(gpu, "rendered image");
+        gpu.debug_end();
 
-            let optimal_size = self.ctx.lock().unwrap().end_frame(&self.window);
-            self.resize(optimal_size.width, optimal_size.height);
+        // make sure final post img is written
+        gpu.set_img_layout(
+            &out,
+            ImgLayout::SRC,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT
+                | vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::PipelineStageFlags2::BLIT,
+            vk::AccessFlags2::COLOR_ATTACHMENT_WRITE | vk::AccessFlags2::SHADER_STORAGE_WRITE,
+            vk::AccessFlags2::TRANSFER_READ,
+        );
+
+        // make sure swap_img is ready to be blitted to
+        let swap_img = gpu.cur_img();
+        gpu.set_img_layout(
+            &swap_img,
+            ImgLayout::DST,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            vk::PipelineStageFlags2::BLIT,
+            vk::AccessFlags2::NONE,
+            vk::AccessFlags2::TRANSFER_WRITE,
+        );
+
+        // blit final post img into swap_img for presenting, render targets can be bigger than window
+        gpu.blit_region(
+            &out,
+            &swap_img,
+            (width, height),
+            (width, height),
+            vk::Filter::NEAREST,
+        );
+
+        // make sure swap_img is ready for presenting
+        gpu.set_img_layout(
+            swap_img,
+            ImgLayout::PRESENT,
+            vk::PipelineStageFlags2::BLIT,
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            vk::AccessFlags2::TRANSFER_WRITE,
+            vk::AccessFlags2::NONE,
+        );
+
+        // renderdoc starts capturing after present
+        if self.capture_frame == Some(self.frame + 1) {
+            gpu.trigger_capture();
         }
-        self.renderer.reset();
+        let optimal_size = gpu.end_frame(&self.window);
+        resized.extend(self.resize_locked(gpu, optimal_size.width, optimal_size.height));
+        resized
+    }
 
-        self.input.reset();
-        self.frame += 1;
+    fn resize(&mut self, width: u32, height: u32) {
+        let ctx = self.ctx.clone();
+        let resized = self.resize_locked(&mut lock_ctx(&ctx), width, height);
+        if let Some(e) = resized {
+            self.dispatcher().post(&e);
+        }
     }
 
-    fn resize(&mut self, mut width: u32, mut height: u32) {
-        if width == self.width && height == self.height {
-            return;
+    /// recreates swapchain and render targets, returns resize event to post once ctx is unlocked
+    fn resize_locked(
+        &mut self,
+        ctx: &mut RenderCtx,
+        mut width: u32,
+        mut height: u32,
+    ) -> Option<WindowResize> {
+        if (width == self.width && height == self.height) || ctx.device_lost() {
+            return None;
         }
-        let optimal_size = self.ctx.lock().unwrap().recreate_swapchain();
+        let optimal_size = ctx.recreate_swapchain();
         width = optimal_size.width;
         height = optimal_size.height;
         if width == self.width && height == self.height {
-            return;
+            return None;
         }
         self.width = width;
         self.height = height;
         let e = WindowResize::new(width, height);
-        self.renderer.on_resize(&e);
-        self.dispatcher().post(&e);
-        if width != 0 && height != 0 {
-            let mut ctx = self.ctx.lock().unwrap();
+        let target_size = self.target_size(width, height);
+        self.renderer.on_resize(ctx, &e, target_size);
+        self.post_chain.on_resize(ctx, &e, target_size);
+        if width != 0 && height != 0 && target_size != self.target_size {
+            self.target_size = target_size;
             // resize rendered image
             queue_idle();
             ctx.try_remove_img("rendered image");
             ctx.add_img(
                 "rendered image",
                 &ImageInfo::new()
-                    .width(width)
-                    .height(height)
+                    .width(target_size.0)
+                    .height(target_size.1)
                     .format(self.surface_format)
-                    .usage(ImgUsage::COLOR | ImgUsage::SAMPLED),
+                    .usage(ImgUsage::COLOR | ImgUsage::SAMPLED | ImgUsage::SRC),
                 MemProp::GPU,
             );
             ctx.add_img_view("rendered image view", "rendered image");
+            self.create_msaa_img(ctx);
+        }
+        self.resize_locked(ctx, optimal_size.width, optimal_size.height)
+            .or(Some(e))
+    }
 
-            if MSAA > 1 {
-                ctx.try_remove_img("sampled rendered image");
-                ctx.add_img(
-                    "sampled rendered image",
-                    &ImageInfo::new()
-                        .width(width)
-                        .height(height)
-                        .samples(MSAA)
-                        .format(self.surface_format)
-                        .usage(ImgUsage::COLOR | ImgUsage::TRANSIENT),
-                    MemProp::GPU,
-                );
-                ctx.add_img_view("sampled rendered image view", "sampled rendered image");
-            }
-
-            // rewrite rendered ds image
-            ctx.write_ds_img("fxaa ds", "rendered image view", ImgLayout::SHADER_READ, 0);
-            // resize fxaa image
-            ctx.try_remove_img("fxaa image");
-            ctx.add_img(
-                "fxaa image",
-                &ImageInfo::new()
-                    .width(width)
-                    .height(height)
-                    .format(self.surface_format)
-                    .usage(ImgUsage::COLOR | ImgUsage::SRC),
-                MemProp::GPU,
-            );
-            ctx.add_img_view("fxaa image view", "fxaa image");
+    /// size render targets need for `width`x`height` window, with live resize they're
+    /// allocated at least at monitor size so resizing reuses them, otherwise exact window size
+    fn target_size(&self, width: u32, height: u32) -> (u32, u32) {
+        if width == 0 || height == 0 {
+            return self.target_size;
+        }
+        if !self.live_resize {
+            return (width, height);
+        }
+        let (tw, th) = self.target_size;
+        if width <= tw && height <= th {
+            return (tw, th);
         }
-        self.resize(optimal_size.width, optimal_size.height);
+        (
+            width.max(self.monitor_width).next_multiple_of(64),
+            height.max(self.monitor_height).next_multiple_of(64),
+        )
     }
 
     fn event(&mut self, event_loop: &ActiveEventLoop, event: WindowEvent, window_id: WindowId) {
+        let consumed = window_id == self.window.id() && self.console.event(&event);
         if window_id == self.window.id() {
-            self.input.event(&event, self.width, self.height);
-            self.mouse_x = self.input.mouse_x();
-            self.mouse_y = self.input.mouse_y();
-            self.mouse_scroll = self.input.mouse_scroll();
+            // replayed input replaces live input, typing into console doesn't reach app
+            if !self.replaying() && !consumed {
+                self.shortcuts.event(&event);
+                for gesture in self.gestures.event(&event, self.width, self.height) {
+                    match gesture {
+                        Gesture::Tap { x, y } => self.dispatcher().post(&Tap::new(x, y)),
+                        Gesture::Pinch { scale, x, y } => {
+                            self.dispatcher().post(&Pinch::new(scale, x, y))
+                        }
+                        Gesture::Pan { dx, dy } => self.dispatcher().post(&Pan::new(dx, dy)),
+                    }
+                }
+                self.input.event(&event, self.width, self.height);
+                self.mouse_x = self.input.mouse_x();
+                self.mouse_y = self.input.mouse_y();
+                self.mouse_scroll = self.input.mouse_scroll();
+            }
             match &event {
                 WindowEvent::Resized(size) => {
                     self.resize(size.width, size.height);
                 }
                 WindowEvent::RedrawRequested => {
+                    let frame_start = Instant::now();
                     self.update();
                     self.render();
+                    if let Some(bench) = &mut self.bench {
+                        let ctx = self.ctx.lock().unwrap();
+                        if bench.frame(
+                            frame_start.elapsed(),
+                            ctx.gpu_frame_time(),
+                            ctx.draw_stats(),
+                        ) {
+                            event_loop.exit();
+                        }
+                    }
+                }
+                WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                    self.renderer.set_scale_factor(*scale_factor as f32);
+                    self.update_monitor();
+                }
+                WindowEvent::Moved(_) => self.update_monitor(),
+                WindowEvent::Occluded(occluded) => self.occluded = *occluded,
+                WindowEvent::ThemeChanged(theme) => {
+                    if self.themes.follow_system {
+                        self.set_theme(match theme {
+                            winit::window::Theme::Dark => "dark",
+                            winit::window::Theme::Light => "light",
+                        });
+                    }
                 }
                 WindowEvent::Focused(focused) => {
-                    if !*focused {
+                    if !*focused && !self.replaying() {
                         self.input.reset();
                     }
                 }
@@ -356,12 +598,64 @@ impl<A: App> AppContext<A> {
             }
         }
 
-        self.my_app().event(event);
-        self.window.request_redraw();
+        if !consumed {
+            self.with_app(|app, ctx| app.event(ctx, event));
+        }
+        self.update_suspended();
+        // while suspended redraws are requested at background fps, see `new_events`
+        if !self.suspended {
+            self.window.request_redraw();
+        }
+    }
+
+    /// suspends when window is minimized (0x0 swapchain) or occluded, resumes once visible
+    fn update_suspended(&mut self) {
+        let suspended = self.occluded || self.width == 0 || self.height == 0;
+        if suspended == self.suspended {
+            return;
+        }
+        self.suspended = suspended;
+        if suspended {
+            log!("Suspended");
+            self.with_app(|app, ctx| app.on_suspend(ctx));
+        } else {
+            log!("Resumed");
+            self.with_app(|app, ctx| app.on_resume(ctx));
+            self.window.request_redraw();
+        }
+    }
+
+    /// throttles event loop to background fps while suspended
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if !self.suspended {
+            event_loop.set_control_flow(self.control_flow);
+        } else if self.background_fps == 0 {
+            event_loop.set_control_flow(ControlFlow::Wait);
+        } else if !matches!(event_loop.control_flow(), ControlFlow::WaitUntil(t) if t > Instant::now())
+        {
+            let interval = Duration::from_secs_f64(1.0 / self.background_fps as f64);
+            event_loop.set_control_flow(ControlFlow::WaitUntil(Instant::now() + interval));
+        }
+    }
+
+    /// window was minimized or occluded last frame
+    pub fn suspended(&self) -> bool {
+        self.suspended
     }
 
-    fn my_app(&mut self) -> &mut A {
-        self.my_app.as_mut().unwrap()
+    pub fn set_background_fps(&mut self, background_fps: u32) {
+        self.background_fps = background_fps;
+    }
+
+    /// takes app out while `f` runs, so it and context can both be borrowed mutably
+    fn with_app<R>(&mut self, f: impl FnOnce(&mut A, &mut Self) -> R) -> R {
+        let mut app = self
+            .my_app
+            .take()
+            .expect("app callback can't run while another one is running");
+        let r = f(&mut app, self);
+        self.my_app = Some(app);
+        r
     }
 
     expose!(input.[mouse_press_x, mouse_press_y, mouse_drag_x, mouse_drag_y](m: Mouse) -> f32);
@@ -369,21 +663,329 @@ impl<A: App> AppContext<A> {
     expose!(input.[key_down, key_released, key_pressed](k: Key) -> bool);
     expose!(input.focused() -> bool);
 
+    /// active touches, plus ones that ended this frame, see `Tap`, `Pinch` and `Pan` events
+    pub fn touches(&self) -> &[Touch] {
+        self.input.touches()
+    }
+
+    /// starts recording input and dt of every frame, for reproducing bugs
+    /// and testing app behavior, see `stop_recording` and `replay_input`
+    pub fn record_input(&mut self) {
+        self.input_mode = InputMode::Recording(InputRecording::default());
+    }
+
+    /// none if input wasn't being recorded
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        match std::mem::replace(&mut self.input_mode, InputMode::Live) {
+            InputMode::Recording(recording) => Some(recording),
+            mode => {
+                self.input_mode = mode;
+                None
+            }
+        }
+    }
+
+    /// plays back recorded input and dt frame by frame, live input is ignored until it ends
+    pub fn replay_input(&mut self, recording: InputRecording) {
+        self.input_mode = InputMode::Replaying(recording, 0);
+    }
+
+    pub fn replaying(&self) -> bool {
+        matches!(self.input_mode, InputMode::Replaying(..))
+    }
+
+    /// runs `future` on frame driven executor, polled once per update,
+    /// see `next_frame`, `wait_frames` and `wait_secs`
+    pub fn spawn(&mut self, future: impl Future<Output = ()> + 'static) -> TaskHandle {
+        self.tasks.spawn(future)
+    }
+
+    /// queued pipelines are still being built over frames, app can draw loading screen meanwhile,
+    /// progress is posted as `PipelineProgress` events
+    pub fn loading(&mut self) -> bool {
+        self.ctx().pipelines_pending()
+    }
+
+    /// color window is cleared to before background, transparent black by default
+    pub fn set_clear_color(&mut self, r: u8, g: u8, b: u8, a: u8) {
+        self.renderer.clear_color(r, g, b, a);
+    }
+
+    /// drawn every frame under scene and app render, e.g. `Background::Gradient(top, bottom)`
+    pub fn set_background(&mut self, background: Background) {
+        self.background = background;
+    }
+
+    /// drop-down dev console, register commands with `app.console().register("name", |args| ..)`
+    pub fn console(&mut self) -> &mut Console {
+        &mut self.console
+    }
+
+    /// calls `f` when chord like "Ctrl+S" or "Ctrl+Shift+Z" is pressed, replaces same chord.
+    /// panics if chord can't be parsed
+    pub fn shortcut(&mut self, chord: &str, f: impl FnMut() + 'static) -> &mut Shortcut {
+        self.shortcuts.add(chord, f)
+    }
+
+    pub fn remove_shortcut(&mut self, chord: &str) -> bool {
+        self.shortcuts.remove(chord)
+    }
+
     pub fn gfx(&mut self) -> &mut Renderer {
         &mut self.renderer
     }
 
+    /// one-shot offscreen render of `draw` into rgba8 pixels, see [`Renderer::render_to_image`]
+    pub fn render_to_image(
+        &mut self,
+        width: u32,
+        height: u32,
+        draw: impl FnOnce(&mut Renderer),
+    ) -> Vec<u8> {
+        self.renderer.render_to_image(width, height, draw)
+    }
+
     pub fn ctx(&mut self) -> std::sync::MutexGuard<'_, RenderCtx> {
-        self.ctx.lock().unwrap()
+        lock_ctx(&self.ctx)
     }
 
+    /// evenly spaces frames at monitor refresh interval, avoids micro-stutter on vrr displays
+    pub fn set_frame_pacing(&mut self, enabled: bool) {
+        self.pacer.enabled = enabled;
+        self.pacer.set_refresh_rate(self.refresh_rate);
+    }
+
+    pub fn pacing_stats(&self) -> PacingStats {
+        self.pacer.stats()
+    }
+
+    /// whether present mode lets variable refresh displays vary refresh rate, see [`RenderCtx::vrr_capable`]
+    pub fn vrr_capable(&self) -> bool {
+        self.ctx.lock().unwrap().vrr_capable()
+    }
+
+    pub fn anti_aliasing(&self) -> AntiAliasing {
+        self.anti_aliasing
+    }
+
+    /// switches anti-aliasing at runtime, rebuilding msaa pipeline/imgs and aa post effects,
+    /// msaa sample count is clamped to what gpu supports
+    pub fn set_anti_aliasing(&mut self, anti_aliasing: AntiAliasing) {
+        let anti_aliasing = anti_aliasing.validated();
+        if anti_aliasing == self.anti_aliasing {
+            return;
+        }
+        self.anti_aliasing = anti_aliasing;
+        self.renderer.set_samples(anti_aliasing.samples());
+        Self::add_aa_effects(&mut self.post_chain, anti_aliasing);
+        let ctx = self.ctx.clone();
+        self.create_msaa_img(&mut lock_ctx(&ctx));
+    }
+
+    /// adds taa/fxaa post effects `anti_aliasing` needs and removes ones it doesn't
+    fn add_aa_effects(post_chain: &mut PostChain, anti_aliasing: AntiAliasing) {
+        let taa = anti_aliasing == AntiAliasing::Taa;
+        if taa && !post_chain.has("taa") {
+            post_chain.insert(0, "taa");
+        } else if !taa && post_chain.has("taa") {
+            post_chain.remove("taa");
+        }
+        let fxaa = anti_aliasing != AntiAliasing::None;
+        if fxaa && !post_chain.has("fxaa") {
+            post_chain.add("fxaa");
+        } else if !fxaa && post_chain.has("fxaa") {
+            post_chain.remove("fxaa");
+        }
+    }
+
+    /// (re)creates multisampled render target for current sample count
+    fn create_msaa_img(&self, ctx: &mut RenderCtx) {
+        queue_idle();
+        ctx.try_remove_img("sampled rendered image");
+        let samples = self.anti_aliasing.samples();
+        let (width, height) = self.target_size;
+        if samples > 1 && width != 0 && height != 0 {
+            ctx.add_img(
+                "sampled rendered image",
+                &ImageInfo::new()
+                    .width(width)
+                    .height(height)
+                    .samples(samples)
+                    .format(self.surface_format)
+                    .usage(ImgUsage::COLOR | ImgUsage::TRANSIENT),
+                MemProp::GPU,
+            );
+            ctx.add_img_view("sampled rendered image view", "sampled rendered image");
+        }
+    }
+
+    /// post processing effects applied to rendered image, fxaa by default
+    pub fn post_chain(&mut self) -> &mut PostChain {
+        &mut self.post_chain
+    }
+
+    /// noise dither strength of last stage before present, removes 8 bit gradient banding.
+    /// it's fxaa or tonemap post effect ending post chain, otherwise batch shader.
+    /// 0.0 disables it, 1.0 is +-0.5/255 noise
+    pub fn set_dither(&mut self, strength: f32) {
+        self.dither = strength;
+        self.post_chain.dither(strength);
+    }
+
+    /// video modes of current monitor, largest and fastest first
+    pub fn video_modes(&self) -> Vec<VideoMode> {
+        let mut modes: Vec<VideoMode> = self
+            .monitor
+            .video_modes()
+            .map(|mode| VideoMode::from_winit(&mode))
+            .collect();
+        modes.sort_by_key(|m| {
+            std::cmp::Reverse((m.width * m.height, m.refresh_rate_millihertz, m.bit_depth))
+        });
+        modes.dedup();
+        modes
+    }
+
+    pub fn fullscreen(&self) -> Fullscreen {
+        self.fullscreen
+    }
+
+    /// unsupported exclusive video mode falls back to borderless
+    pub fn set_fullscreen(&mut self, fullscreen: Fullscreen) {
+        let winit_fullscreen = fullscreen.to_winit(&self.monitor);
+        self.fullscreen = match winit_fullscreen {
+            Some(winit::window::Fullscreen::Exclusive(_)) => fullscreen,
+            Some(winit::window::Fullscreen::Borderless(_)) => Fullscreen::Borderless,
+            None => Fullscreen::Windowed,
+        };
+        self.window.set_fullscreen(winit_fullscreen);
+        self.refresh_rate = match self.fullscreen {
+            Fullscreen::Exclusive(mode) => mode.refresh_rate(),
+            _ => monitor_refresh_rate(&self.monitor),
+        };
+        self.pacer.set_refresh_rate(self.refresh_rate);
+        // display mode change can make swapchain out of date without resizing window,
+        // resize event recreates it otherwise
+        self.ctx().invalidate_swapchain();
+        let PhysicalSize { width, height } = self.window.inner_size();
+        if width == self.width && height == self.height {
+            let size = self.ctx().recreate_swapchain();
+            self.resize(size.width, size.height);
+        }
+    }
+
+    /// centers window on monitor it's currently on
     pub fn center_window(&self) {
+        let pos = self.monitor.position();
+        let size = self.window.outer_size();
         self.window.set_outer_position(PhysicalPosition::new(
-            (self.monitor_width as i32 - self.width as i32) / 2,
-            (self.monitor_height as i32 - self.height as i32) / 2,
+            pos.x + (self.monitor_width as i32 - size.width as i32) / 2,
+            pos.y + (self.monitor_height as i32 - size.height as i32) / 2,
         ));
     }
 
+    pub fn monitors(&self) -> Vec<MonitorHandle> {
+        self.window.available_monitors().collect()
+    }
+
+    /// moves window to center of `monitor`, keeps fullscreen mode if possible
+    pub fn move_to_monitor(&mut self, monitor: &MonitorHandle) {
+        if *monitor == self.monitor {
+            self.center_window();
+            return;
+        }
+        self.set_monitor(monitor.clone());
+        if self.fullscreen == Fullscreen::Windowed {
+            self.center_window();
+        } else {
+            // exclusive video modes are per monitor
+            let fullscreen = match self.fullscreen {
+                Fullscreen::Exclusive(_) => Fullscreen::Borderless,
+                fullscreen => fullscreen,
+            };
+            self.set_fullscreen(fullscreen);
+        }
+    }
+
+    /// physical pixels per logical pixel of window's monitor, used by `Dp` unit
+    pub fn scale_factor(&self) -> f32 {
+        self.renderer.scale_factor()
+    }
+
+    /// gpu picked at startup, see [`EngineConfig::gpu`]
+    pub fn gpu(&self) -> &'static GpuInfo {
+        gpu_info()
+    }
+
+    /// optional gpu features in use, missing ones are emulated with slower fallbacks
+    pub fn caps(&self) -> &'static Caps {
+        caps()
+    }
+
+    /// all gpus detected on this machine, selectable with [`EngineConfig::gpu`]
+    pub fn gpus(&self) -> Vec<&'static GpuInfo> {
+        gfx::gpus().collect()
+    }
+
+    /// engine, gpu info and active settings, `Display` it for bug reports
+    pub fn diagnostics(&self) -> Diagnostics {
+        let ctx = self.ctx.lock().unwrap();
+        Diagnostics::new()
+            .setting("size", (self.width, self.height))
+            .setting("monitor", self.monitor.name().unwrap_or_default())
+            .setting("refresh rate", self.refresh_rate)
+            .setting("scale factor", self.scale_factor())
+            .setting("fullscreen", self.fullscreen)
+            .setting("anti aliasing", self.anti_aliasing)
+            .setting("surface format", ctx.surface_format)
+            .setting("present mode", ctx.present_mode())
+            .setting("hdr", vulkan_config().hdr)
+            .setting("linear", ctx.linear())
+            .setting("vsync", vulkan_config().vsync)
+            .setting("theme", self.themes.active_name())
+    }
+
+    pub fn theme(&self) -> &Theme {
+        self.themes.active()
+    }
+
+    pub fn themes(&mut self) -> &mut Themes {
+        &mut self.themes
+    }
+
+    /// switches active theme, applies its colors to renderer and posts `ThemeChange`
+    pub fn set_theme(&mut self, name: &str) {
+        if self.themes.set_active(name) {
+            self.themes.active().apply(&mut self.renderer);
+            self.dispatcher().post(&ThemeChange::new(name.to_string()));
+        }
+    }
+
+    fn update_monitor(&mut self) {
+        if let Some(monitor) = self.window.current_monitor()
+            && monitor != self.monitor
+        {
+            self.set_monitor(monitor);
+        }
+    }
+
+    /// updates monitor info after window changed monitors
+    fn set_monitor(&mut self, monitor: MonitorHandle) {
+        let PhysicalSize { width, height } = monitor.size();
+        self.monitor_width = width;
+        self.monitor_height = height;
+        if !matches!(self.fullscreen, Fullscreen::Exclusive(_)) {
+            self.refresh_rate = monitor_refresh_rate(&monitor);
+            self.pacer.set_refresh_rate(self.refresh_rate);
+        }
+        log!(
+            "Monitor: {} {width}x{height} {}hz",
+            monitor.name().unwrap_or_default(),
+            self.refresh_rate
+        );
+        self.monitor = monitor;
+    }
+
     fn dispatcher<T: Event + 'static>(&mut self) -> &mut Dispatcher<T> {
         let tid = TypeId::of::<T>();
         self.dispatchers
@@ -414,9 +1016,18 @@ impl<A: App> AppContext<A> {
     }
 }
 
+fn monitor_refresh_rate(monitor: &MonitorHandle) -> u32 {
+    (monitor.refresh_rate_millihertz().unwrap_or(60) as f32 / 1000.0).round() as u32
+}
+
 pub struct Engine<A: App> {
     app: Option<Arc<Mutex<AppContext<A>>>>,
     window_attribs: WindowAttributes,
+    config: EngineConfig,
+    settings: Settings,
+    /// frames to benchmark, see [`Engine::bench`]
+    bench_frames: Option<u32>,
+    control_flow: ControlFlow,
 }
 
 struct UnsafeEventLoop(winit::event_loop::EventLoop<()>);
@@ -447,11 +1058,14 @@ static EVENT_LOOP: LazyLock<Mutex<UnsafeEventLoop>> = LazyLock::new(|| {
 static PANIC_HOOK: LazyLock<()> = LazyLock::new(|| {
     std::panic::set_hook(Box::new(|panic_info| {
         let panic = |s: &str| {
+            let backtrace = crate::util::print::backtrace(1);
             println!(
                 "panicked: \x1b[38;2;241;76;76m{}\x1b[0m\n\x1b[2m{}\x1b[0m",
-                s,
-                crate::util::print::backtrace(1)
+                s, backtrace
             );
+            if let Some(path) = crash::dump(s, &backtrace) {
+                println!("crash dump: {path}");
+            }
         };
         if let Some(s) = panic_info.payload().downcast_ref::<&str>() {
             panic(s);
@@ -465,22 +1079,58 @@ static PANIC_HOOK: LazyLock<()> = LazyLock::new(|| {
 
 impl<T: App> Engine<T> {
     pub fn window(title: &str, width: u32, height: u32) {
-        Self::with(
-            WindowAttributes::default()
-                .with_title(title)
-                .with_inner_size(PhysicalSize::new(width, height)),
-            ControlFlow::Poll,
-        );
+        Self::run(EngineConfig::new().title(title).size(width, height));
     }
 
     pub fn default() {
         Self::with(WindowAttributes::default(), ControlFlow::Poll);
     }
 
+    pub fn run(config: EngineConfig) {
+        Self::start(config.window_attribs(), ControlFlow::Poll, config, None);
+    }
+
     pub fn with(window_attribs: WindowAttributes, control_flow: ControlFlow) {
+        Self::start(window_attribs, control_flow, EngineConfig::default(), None);
+    }
+
+    /// runs `frames` frames as fast as possible (no vsync/pacing) after a short warmup,
+    /// prints cpu/gpu frame times and draw counts, use [`BenchReport::json`] for ci tracking
+    pub fn bench(frames: u32) -> BenchReport {
+        let config = EngineConfig::new().title("bench").vsync(false);
+        let engine = Self::start(
+            config.window_attribs(),
+            ControlFlow::Poll,
+            config,
+            Some(frames),
+        );
+        let app = engine.app.expect("bench window was never created");
+        let report = app.lock().unwrap().bench.as_ref().unwrap().report();
+        println!("{report}");
+        report
+    }
+
+    fn start(
+        mut window_attribs: WindowAttributes,
+        control_flow: ControlFlow,
+        mut config: EngineConfig,
+        bench_frames: Option<u32>,
+    ) -> Self {
+        *INIT_PATHS;
+        let settings_path = format!("{RES_PATH}/settings.toml");
+        let settings = Settings::load(&settings_path, &config);
+        // benchmarks ignore user settings so runs stay comparable
+        if bench_frames.is_none() && std::path::Path::new(&settings_path).exists() {
+            settings.apply(&mut config, &mut window_attribs);
+        }
+        gfx::set_vulkan_config(config.vulkan.clone());
         let mut engine = Self {
             app: None,
             window_attribs,
+            config,
+            settings,
+            bench_frames,
+            control_flow,
         };
         EVENT_LOOP.lock().unwrap().set_control_flow(control_flow);
         EVENT_LOOP
@@ -488,6 +1138,7 @@ impl<T: App> Engine<T> {
             .unwrap()
             .run_app_on_demand(&mut engine)
             .unwrap();
+        engine
     }
 }
 
@@ -508,10 +1159,19 @@ impl<T: App> winit::application::ApplicationHandler for Engine<T> {
                     (monitor.size().height as i32 - height) / 2,
                 )));
         }
+        if self.config.fullscreen != Fullscreen::Windowed {
+            self.window_attribs.fullscreen = self.config.fullscreen.to_winit(&monitor);
+        }
+        gfx::set_display(event_loop.display_handle().unwrap().as_raw());
         let window = event_loop
             .create_window(self.window_attribs.clone())
             .unwrap();
-        self.app = Some(AppContext::new(window, monitor));
+        let app = AppContext::new(window, monitor, &self.config, self.settings.clone());
+        if let Some(frames) = self.bench_frames {
+            app.lock().unwrap().bench = Some(Bench::new(frames));
+        }
+        app.lock().unwrap().control_flow = self.control_flow;
+        self.app = Some(app);
     }
 
     fn window_event(
@@ -524,4 +1184,17 @@ impl<T: App> winit::application::ApplicationHandler for Engine<T> {
             app.lock().unwrap().event(event_loop, event, window_id);
         }
     }
+
+    fn new_events(&mut self, _event_loop: &ActiveEventLoop, cause: StartCause) {
+        // background frame while suspended
+        if let (Some(app), StartCause::ResumeTimeReached { .. }) = (&self.app, cause) {
+            app.lock().unwrap().window.request_redraw();
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if let Some(app) = &self.app {
+            app.lock().unwrap().about_to_wait(event_loop);
+        }
+    }
 }
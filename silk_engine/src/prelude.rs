@@ -1,8 +1,17 @@
 pub use crate::{
     App, AppContext, Engine,
+    bench::{BenchReport, Timing},
+    config::{AntiAliasing, EngineConfig, Fullscreen, VideoMode},
+    console::Console,
+    diagnostics::Diagnostics,
+    error::Error,
     event::*,
     gfx::*,
-    input::{Key, Mouse},
+    input::{InputRecording, Key, Mouse, Touch, key_name, parse_key},
+    scene::*,
+    settings::Settings,
+    shortcut::{Chord, Shortcut},
+    theme::{Theme, Themes},
     util::*,
 };
 
@@ -0,0 +1,407 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    gfx::{Renderer, Unit::Pc},
+    util::Vec2,
+};
+
+/// ordered by slot, so components are iterated, updated and drawn in same order each frame
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Entity {
+    idx: u32,
+    generation: u32,
+}
+
+/// position/scale are in pixels, rotation in radians
+#[derive(Clone, Copy)]
+pub struct Transform {
+    pub pos: Vec2,
+    pub scale: Vec2,
+    pub rotation: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            pos: Vec2::new(0.0, 0.0),
+            scale: Vec2::new(1.0, 1.0),
+            rotation: 0.0,
+        }
+    }
+}
+
+impl Transform {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self {
+            pos: Vec2::new(x, y),
+            ..Default::default()
+        }
+    }
+
+    pub fn scale(mut self, x: f32, y: f32) -> Self {
+        self.scale = Vec2::new(x, y);
+        self
+    }
+
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// transforms child (local) transform into parent's space
+    pub fn mul(&self, child: &Transform) -> Transform {
+        let (s, c) = self.rotation.sin_cos();
+        let p = child.pos * self.scale;
+        Transform {
+            pos: self.pos + Vec2::new(p.x * c - p.y * s, p.x * s + p.y * c),
+            scale: self.scale * child.scale,
+            rotation: self.rotation + child.rotation,
+        }
+    }
+}
+
+/// centered rect of size width x height, optionally textured with atlas img
+#[derive(Clone)]
+pub struct Sprite {
+    pub width: f32,
+    pub height: f32,
+    pub color: [u8; 4],
+    pub roundness: f32,
+    pub img: Option<String>,
+}
+
+impl Sprite {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            color: [255, 255, 255, 255],
+            roundness: 0.0,
+            img: None,
+        }
+    }
+
+    pub fn color(mut self, color: [u8; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn roundness(mut self, roundness: f32) -> Self {
+        self.roundness = roundness;
+        self
+    }
+
+    pub fn img(mut self, name: &str) -> Self {
+        self.img = Some(name.to_string());
+        self
+    }
+}
+
+/// text with top-left at entity position, uses renderer's current font if font is None
+#[derive(Clone)]
+pub struct Text {
+    pub text: String,
+    pub size: f32,
+    pub color: [u8; 4],
+    pub font: Option<String>,
+}
+
+impl Text {
+    pub fn new(text: &str, size: f32) -> Self {
+        Self {
+            text: text.to_string(),
+            size,
+            color: [255, 255, 255, 255],
+            font: None,
+        }
+    }
+
+    pub fn color(mut self, color: [u8; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn font(mut self, name: &str) -> Self {
+        self.font = Some(name.to_string());
+        self
+    }
+}
+
+/// entity position is centered on screen, only the active camera is used
+#[derive(Clone, Copy)]
+pub struct Camera {
+    pub zoom: f32,
+    pub active: bool,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            active: true,
+        }
+    }
+}
+
+/// called every update with (scene, self entity, dt)
+#[derive(Clone, Copy)]
+pub struct Script(pub fn(&mut Scene, Entity, f32));
+
+pub trait Component: Sized + 'static {
+    fn storage(scene: &Scene) -> &BTreeMap<Entity, Self>;
+    fn storage_mut(scene: &mut Scene) -> &mut BTreeMap<Entity, Self>;
+}
+
+macro_rules! component {
+    ($ty:ty, $field:ident) => {
+        impl Component for $ty {
+            fn storage(scene: &Scene) -> &BTreeMap<Entity, Self> {
+                &scene.$field
+            }
+
+            fn storage_mut(scene: &mut Scene) -> &mut BTreeMap<Entity, Self> {
+                &mut scene.$field
+            }
+        }
+    };
+}
+
+component!(Sprite, sprites);
+component!(Text, texts);
+component!(Camera, cameras);
+component!(Script, scripts);
+
+#[derive(Default)]
+struct Node {
+    generation: u32,
+    alive: bool,
+    parent: Option<Entity>,
+    children: Vec<Entity>,
+    local: Transform,
+    world: Transform,
+}
+
+#[derive(Default)]
+pub struct Scene {
+    nodes: Vec<Node>,
+    free: Vec<u32>,
+    sprites: BTreeMap<Entity, Sprite>,
+    texts: BTreeMap<Entity, Text>,
+    cameras: BTreeMap<Entity, Camera>,
+    scripts: BTreeMap<Entity, Script>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawn(&mut self, transform: Transform) -> Entity {
+        let idx = self.free.pop().unwrap_or_else(|| {
+            self.nodes.push(Node::default());
+            self.nodes.len() as u32 - 1
+        });
+        let node = &mut self.nodes[idx as usize];
+        node.alive = true;
+        node.local = transform;
+        node.world = transform;
+        Entity {
+            idx,
+            generation: node.generation,
+        }
+    }
+
+    pub fn spawn_child(&mut self, parent: Entity, transform: Transform) -> Entity {
+        let e = self.spawn(transform);
+        self.set_parent(e, Some(parent));
+        e
+    }
+
+    /// despawns entity, it's children and all their components
+    pub fn despawn(&mut self, e: Entity) {
+        assert!(self.alive(e), "entity not found: {e:?}");
+        self.set_parent(e, None);
+        let children = std::mem::take(&mut self.nodes[e.idx as usize].children);
+        for child in children {
+            self.nodes[child.idx as usize].parent = None;
+            self.despawn(child);
+        }
+        self.sprites.remove(&e);
+        self.texts.remove(&e);
+        self.cameras.remove(&e);
+        self.scripts.remove(&e);
+        let node = &mut self.nodes[e.idx as usize];
+        node.alive = false;
+        node.generation = node.generation.wrapping_add(1);
+        node.local = Transform::default();
+        self.free.push(e.idx);
+    }
+
+    pub fn alive(&self, e: Entity) -> bool {
+        self.nodes
+            .get(e.idx as usize)
+            .is_some_and(|n| n.alive && n.generation == e.generation)
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.alive)
+            .map(|(i, n)| Entity {
+                idx: i as u32,
+                generation: n.generation,
+            })
+    }
+
+    pub fn set_parent(&mut self, e: Entity, parent: Option<Entity>) {
+        assert!(self.alive(e), "entity not found: {e:?}");
+        if let Some(old) = self.nodes[e.idx as usize].parent.take() {
+            self.nodes[old.idx as usize].children.retain(|&c| c != e);
+        }
+        if let Some(parent) = parent {
+            assert!(self.alive(parent), "parent entity not found: {parent:?}");
+            let mut p = Some(parent);
+            while let Some(a) = p {
+                assert!(a != e, "entity can't be it's own ancestor");
+                p = self.nodes[a.idx as usize].parent;
+            }
+            self.nodes[parent.idx as usize].children.push(e);
+        }
+        self.nodes[e.idx as usize].parent = parent;
+    }
+
+    pub fn parent(&self, e: Entity) -> Option<Entity> {
+        self.node(e).parent
+    }
+
+    pub fn children(&self, e: Entity) -> &[Entity] {
+        &self.node(e).children
+    }
+
+    /// local transform, relative to parent
+    pub fn transform(&mut self, e: Entity) -> &mut Transform {
+        assert!(self.alive(e), "entity not found: {e:?}");
+        &mut self.nodes[e.idx as usize].local
+    }
+
+    /// world transform as of last update()
+    pub fn world(&self, e: Entity) -> Transform {
+        self.node(e).world
+    }
+
+    pub fn add<C: Component>(&mut self, e: Entity, component: C) {
+        assert!(self.alive(e), "entity not found: {e:?}");
+        C::storage_mut(self).insert(e, component);
+    }
+
+    pub fn remove<C: Component>(&mut self, e: Entity) -> Option<C> {
+        C::storage_mut(self).remove(&e)
+    }
+
+    pub fn get<C: Component>(&self, e: Entity) -> Option<&C> {
+        C::storage(self).get(&e)
+    }
+
+    pub fn get_mut<C: Component>(&mut self, e: Entity) -> Option<&mut C> {
+        C::storage_mut(self).get_mut(&e)
+    }
+
+    pub fn iter<C: Component>(&self) -> impl Iterator<Item = (Entity, &C)> {
+        C::storage(self).iter().map(|(&e, c)| (e, c))
+    }
+
+    /// runs scripts and propagates transforms through hierarchy
+    pub fn update(&mut self, dt: f32) {
+        let scripts: Vec<_> = self.scripts.iter().map(|(&e, &s)| (e, s)).collect();
+        for (e, Script(f)) in scripts {
+            if self.alive(e) {
+                f(self, e, dt);
+            }
+        }
+        let roots: Vec<_> = self
+            .entities()
+            .filter(|&e| self.node(e).parent.is_none())
+            .collect();
+        let mut stack: Vec<_> = roots
+            .into_iter()
+            .map(|e| (e, Transform::new(0.0, 0.0)))
+            .collect();
+        while let Some((e, parent)) = stack.pop() {
+            let node = &mut self.nodes[e.idx as usize];
+            node.world = parent.mul(&node.local);
+            let world = node.world;
+            stack.extend(node.children.iter().map(|&c| (c, world)));
+        }
+    }
+
+    pub fn draw(&self, gfx: &mut Renderer) {
+        let (w, h) = (gfx.width(), gfx.height());
+        if w == 0.0 || h == 0.0 {
+            return;
+        }
+        let view = self
+            .cameras
+            .iter()
+            .find(|(_, c)| c.active)
+            .map(|(&e, c)| {
+                let cam = self.world(e);
+                let scale = Vec2::new(c.zoom, c.zoom);
+                Transform {
+                    pos: Vec2::new(w * 0.5, h * 0.5),
+                    scale,
+                    rotation: -cam.rotation,
+                }
+                .mul(&Transform {
+                    pos: cam.pos * -1.0,
+                    ..Default::default()
+                })
+            })
+            .unwrap_or_default();
+
+        for e in self.entities() {
+            let (sprite, text) = (self.sprites.get(&e), self.texts.get(&e));
+            if sprite.is_none() && text.is_none() {
+                continue;
+            }
+            // one state push/pop per entity, it's text is drawn over it's sprite
+            let mut gfx = gfx.scoped();
+            let t = view.mul(&self.world(e));
+            let (roundness, rotation) = (gfx.roundness, gfx.rotation);
+            if let Some(sprite) = sprite {
+                gfx.color = sprite.color;
+                gfx.roundness = sprite.roundness;
+                gfx.rotation = t.rotation;
+                if let Some(img) = &sprite.img {
+                    gfx.img(img);
+                }
+                gfx.rectc(
+                    Pc(t.pos.x / w),
+                    Pc(t.pos.y / h),
+                    Pc(sprite.width * t.scale.x * 0.5 / w),
+                    Pc(sprite.height * t.scale.y * 0.5 / h),
+                );
+            }
+            let Some(text) = text else {
+                continue;
+            };
+            // glyphs aren't rounded or rotated like sprite
+            (gfx.roundness, gfx.rotation) = (roundness, rotation);
+            gfx.color = text.color;
+            if let Some(font) = &text.font {
+                gfx.font(font);
+            }
+            gfx.text(
+                &text.text,
+                Pc(t.pos.x / w),
+                Pc(t.pos.y / h),
+                Pc(text.size * t.scale.y / h),
+            );
+        }
+    }
+
+    fn node(&self, e: Entity) -> &Node {
+        assert!(self.alive(e), "entity not found: {e:?}");
+        &self.nodes[e.idx as usize]
+    }
+}
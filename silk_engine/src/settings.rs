@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use winit::{dpi::PhysicalSize, window::WindowAttributes};
+
+use crate::{
+    config::{EngineConfig, Fullscreen},
+    input::{Key, key_name, parse_key},
+    util::Toml,
+    warn,
+};
+
+/// user editable settings loaded from `res/settings.toml` at startup,
+/// missing values default to ones from [`EngineConfig`]
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    /// master audio volume in [0, 1]
+    pub volume: f32,
+    /// action name -> key, see [`Settings::key`]
+    pub keys: BTreeMap<String, Key>,
+    path: String,
+}
+
+impl Settings {
+    pub fn load(path: &str, config: &EngineConfig) -> Self {
+        let toml = Toml::load(path);
+        let keys = toml
+            .iter()
+            .filter_map(|(name, _)| name.strip_prefix("keys."))
+            .filter_map(|action| {
+                let name: String = toml.get(&format!("keys.{action}"))?;
+                let key = parse_key(&name);
+                if key.is_none() {
+                    warn!("unknown key in settings: {action} = {name}");
+                }
+                Some((action.to_string(), key?))
+            })
+            .collect();
+        Self {
+            width: toml.get("window.width").unwrap_or(config.width),
+            height: toml.get("window.height").unwrap_or(config.height),
+            fullscreen: toml
+                .get("window.fullscreen")
+                .unwrap_or(config.fullscreen != Fullscreen::Windowed),
+            vsync: toml.get("window.vsync").unwrap_or(config.vulkan.vsync),
+            volume: toml.get("audio.volume").unwrap_or(1.0f32).clamp(0.0, 1.0),
+            keys,
+            path: path.to_string(),
+        }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let mut toml = Toml::new();
+        toml.set("window.width", self.width);
+        toml.set("window.height", self.height);
+        toml.set("window.fullscreen", self.fullscreen);
+        toml.set("window.vsync", self.vsync);
+        toml.set("audio.volume", self.volume);
+        for (action, &key) in &self.keys {
+            toml.set(&format!("keys.{action}"), key_name(key));
+        }
+        toml.save(&self.path)
+    }
+
+    /// key bound to `action`, binds `default` first time so it shows up in saved settings
+    pub fn key(&mut self, action: &str, default: Key) -> Key {
+        *self.keys.entry(action.to_string()).or_insert(default)
+    }
+
+    pub fn bind(&mut self, action: &str, key: Key) {
+        self.keys.insert(action.to_string(), key);
+    }
+
+    /// overrides window size, fullscreen and vsync of `config` before window is created
+    pub(crate) fn apply(&self, config: &mut EngineConfig, window_attribs: &mut WindowAttributes) {
+        config.width = self.width;
+        config.height = self.height;
+        config.vulkan.vsync = self.vsync;
+        config.fullscreen = match (self.fullscreen, config.fullscreen) {
+            (false, _) => Fullscreen::Windowed,
+            (true, Fullscreen::Windowed) => Fullscreen::Borderless,
+            (true, fullscreen) => fullscreen,
+        };
+        window_attribs.inner_size = Some(PhysicalSize::new(self.width, self.height).into());
+    }
+}
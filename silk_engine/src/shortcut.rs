@@ -0,0 +1,118 @@
+use winit::{event::WindowEvent, keyboard::ModifiersState, keyboard::PhysicalKey};
+
+use crate::input::{Key, key_name, parse_key};
+
+/// key with exact set of held modifiers, like "Ctrl+Shift+Z"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chord {
+    pub key: Key,
+    pub mods: ModifiersState,
+}
+
+impl Chord {
+    /// `+` separated modifiers (ctrl, shift, alt, super/cmd) followed by key name, case insensitive
+    pub fn parse(chord: &str) -> Option<Self> {
+        let mut parts: Vec<&str> = chord.split('+').map(str::trim).collect();
+        let key = parse_key(parts.pop()?)?;
+        let mut mods = ModifiersState::empty();
+        for part in parts {
+            mods |= match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => ModifiersState::CONTROL,
+                "shift" => ModifiersState::SHIFT,
+                "alt" => ModifiersState::ALT,
+                "super" | "cmd" | "win" | "meta" => ModifiersState::SUPER,
+                _ => return None,
+            };
+        }
+        Some(Self { key, mods })
+    }
+}
+
+impl std::fmt::Display for Chord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (m, name) in [
+            (ModifiersState::CONTROL, "Ctrl"),
+            (ModifiersState::SHIFT, "Shift"),
+            (ModifiersState::ALT, "Alt"),
+            (ModifiersState::SUPER, "Super"),
+        ] {
+            if self.mods.contains(m) {
+                write!(f, "{name}+")?;
+            }
+        }
+        write!(f, "{}", key_name(self.key))
+    }
+}
+
+pub struct Shortcut {
+    chord: Chord,
+    repeat: bool,
+    callback: Box<dyn FnMut()>,
+}
+
+impl Shortcut {
+    /// also fire on key repeat while held, off by default
+    pub fn repeat(&mut self, repeat: bool) -> &mut Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn chord(&self) -> Chord {
+        self.chord
+    }
+}
+
+/// fires callbacks when chord key is pressed while exactly its modifiers are held.
+/// modifiers come from winit modifier state instead of key flags,
+/// so releasing Ctrl before S doesn't leave a stale Ctrl behind
+#[derive(Default)]
+pub(crate) struct Shortcuts {
+    shortcuts: Vec<Shortcut>,
+    mods: ModifiersState,
+}
+
+impl Shortcuts {
+    pub fn add(&mut self, chord: &str, f: impl FnMut() + 'static) -> &mut Shortcut {
+        let chord = Chord::parse(chord).unwrap_or_else(|| panic!("invalid shortcut: {chord}"));
+        self.shortcuts.retain(|s| s.chord != chord);
+        self.shortcuts.push(Shortcut {
+            chord,
+            repeat: false,
+            callback: Box::new(f),
+        });
+        self.shortcuts.last_mut().unwrap()
+    }
+
+    pub fn remove(&mut self, chord: &str) -> bool {
+        let Some(chord) = Chord::parse(chord) else {
+            return false;
+        };
+        let len = self.shortcuts.len();
+        self.shortcuts.retain(|s| s.chord != chord);
+        len != self.shortcuts.len()
+    }
+
+    pub fn event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::ModifiersChanged(mods) => self.mods = mods.state(),
+            WindowEvent::Focused(false) => self.mods = ModifiersState::empty(),
+            WindowEvent::KeyboardInput { event, .. } if event.state.is_pressed() => {
+                let PhysicalKey::Code(key) = event.physical_key else {
+                    return;
+                };
+                let chord = Chord {
+                    key,
+                    mods: self.mods,
+                };
+                if let Some(s) = self
+                    .shortcuts
+                    .iter_mut()
+                    .find(|s| s.chord == chord && (s.repeat || !event.repeat))
+                {
+                    (s.callback)();
+                }
+            }
+            _ => {}
+        }
+    }
+}
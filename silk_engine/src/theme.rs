@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use crate::gfx::Renderer;
+
+/// named colors, gradients, spacing and font sizes for consistent ui styling
+#[derive(Clone, Debug)]
+pub struct Theme {
+    pub dark: bool,
+    colors: HashMap<String, [u8; 4]>,
+    /// (from, to) colors
+    gradients: HashMap<String, ([u8; 4], [u8; 4])>,
+    /// in pixels
+    spacing: HashMap<String, f32>,
+    /// in pixels
+    font_sizes: HashMap<String, f32>,
+}
+
+const SPACING: [(&str, f32); 5] = [
+    ("xs", 2.0),
+    ("sm", 4.0),
+    ("md", 8.0),
+    ("lg", 16.0),
+    ("xl", 32.0),
+];
+const FONT_SIZES: [(&str, f32); 4] = [
+    ("small", 12.0),
+    ("body", 16.0),
+    ("title", 24.0),
+    ("heading", 32.0),
+];
+
+impl Theme {
+    fn new(dark: bool, colors: &[(&str, [u8; 4])]) -> Self {
+        let to_map = |v: &[(&str, f32)]| v.iter().map(|&(k, v)| (k.to_string(), v)).collect();
+        let colors: HashMap<String, [u8; 4]> =
+            colors.iter().map(|&(k, v)| (k.to_string(), v)).collect();
+        let gradients = [
+            ("accent", "accent", "accent_alt"),
+            ("surface", "surface", "background"),
+        ]
+        .into_iter()
+        .map(|(name, from, to)| (name.to_string(), (colors[from], colors[to])))
+        .collect();
+        Self {
+            dark,
+            colors,
+            gradients,
+            spacing: to_map(&SPACING),
+            font_sizes: to_map(&FONT_SIZES),
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self::new(
+            true,
+            &[
+                ("background", [18, 18, 22, 255]),
+                ("surface", [32, 32, 38, 255]),
+                ("text", [232, 232, 236, 255]),
+                ("text_muted", [150, 150, 160, 255]),
+                ("border", [64, 64, 74, 255]),
+                ("accent", [98, 132, 255, 255]),
+                ("accent_alt", [160, 98, 255, 255]),
+                ("success", [80, 200, 120, 255]),
+                ("warning", [240, 180, 60, 255]),
+                ("error", [240, 80, 80, 255]),
+            ],
+        )
+    }
+
+    pub fn light() -> Self {
+        Self::new(
+            false,
+            &[
+                ("background", [246, 246, 248, 255]),
+                ("surface", [255, 255, 255, 255]),
+                ("text", [24, 24, 28, 255]),
+                ("text_muted", [100, 100, 110, 255]),
+                ("border", [210, 210, 218, 255]),
+                ("accent", [48, 92, 230, 255]),
+                ("accent_alt", [120, 60, 220, 255]),
+                ("success", [30, 150, 80, 255]),
+                ("warning", [200, 130, 20, 255]),
+                ("error", [210, 50, 50, 255]),
+            ],
+        )
+    }
+
+    pub fn color(&self, name: &str) -> [u8; 4] {
+        *self
+            .colors
+            .get(name)
+            .unwrap_or_else(|| panic!("theme color not found: {name}"))
+    }
+
+    pub fn gradient(&self, name: &str) -> ([u8; 4], [u8; 4]) {
+        *self
+            .gradients
+            .get(name)
+            .unwrap_or_else(|| panic!("theme gradient not found: {name}"))
+    }
+
+    pub fn spacing(&self, name: &str) -> f32 {
+        *self
+            .spacing
+            .get(name)
+            .unwrap_or_else(|| panic!("theme spacing not found: {name}"))
+    }
+
+    pub fn font_size(&self, name: &str) -> f32 {
+        *self
+            .font_sizes
+            .get(name)
+            .unwrap_or_else(|| panic!("theme font size not found: {name}"))
+    }
+
+    pub fn set_color(&mut self, name: &str, color: [u8; 4]) {
+        self.colors.insert(name.to_string(), color);
+    }
+
+    pub fn set_gradient(&mut self, name: &str, from: [u8; 4], to: [u8; 4]) {
+        self.gradients.insert(name.to_string(), (from, to));
+    }
+
+    pub fn set_spacing(&mut self, name: &str, px: f32) {
+        self.spacing.insert(name.to_string(), px);
+    }
+
+    pub fn set_font_size(&mut self, name: &str, px: f32) {
+        self.font_sizes.insert(name.to_string(), px);
+    }
+
+    /// sets renderer's default fill/stroke colors to theme text/border colors
+    pub fn apply(&self, gfx: &mut Renderer) {
+        gfx.color = self.color("text");
+        gfx.stroke_color = self.color("border");
+    }
+}
+
+/// theme registry with active theme, has "dark" and "light" by default
+pub struct Themes {
+    themes: HashMap<String, Theme>,
+    active: String,
+    /// switch between "dark" and "light" when os theme changes
+    pub follow_system: bool,
+}
+
+impl Themes {
+    pub(crate) fn new(system: Option<winit::window::Theme>) -> Self {
+        let themes = [
+            ("dark".to_string(), Theme::dark()),
+            ("light".to_string(), Theme::light()),
+        ];
+        let active = match system {
+            Some(winit::window::Theme::Light) => "light",
+            _ => "dark",
+        };
+        Self {
+            themes: themes.into_iter().collect(),
+            active: active.to_string(),
+            follow_system: true,
+        }
+    }
+
+    /// replaces theme with same name
+    pub fn add(&mut self, name: &str, theme: Theme) {
+        self.themes.insert(name.to_string(), theme);
+    }
+
+    pub fn get(&self, name: &str) -> &Theme {
+        self.themes
+            .get(name)
+            .unwrap_or_else(|| panic!("theme not found: {name}"))
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> &mut Theme {
+        self.themes
+            .get_mut(name)
+            .unwrap_or_else(|| panic!("theme not found: {name}"))
+    }
+
+    pub fn active(&self) -> &Theme {
+        self.get(&self.active)
+    }
+
+    pub fn active_name(&self) -> &str {
+        &self.active
+    }
+
+    /// returns true if active theme changed
+    pub(crate) fn set_active(&mut self, name: &str) -> bool {
+        assert!(self.themes.contains_key(name), "theme not found: {name}");
+        if self.active == name {
+            return false;
+        }
+        self.active = name.to_string();
+        true
+    }
+}
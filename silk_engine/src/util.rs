@@ -1,32 +1,63 @@
+pub(crate) mod logger;
 pub(crate) mod print;
 
 mod bmp;
 mod buddy_alloc;
+mod color;
 mod contain_range;
 mod cooldown;
+mod cube;
+mod fixed;
 mod image_loader;
+mod jobs;
+mod jpeg;
+mod mat;
 mod mem;
+mod mmap;
+mod noise;
 mod qoi;
 mod rand;
 mod reader;
+mod spatial;
+mod svg_path;
+mod task;
+mod toml;
 mod tracked;
 mod ttf;
+mod tween;
+mod tweak;
 mod vec;
+mod vfs;
 mod writer;
 
 pub(crate) use bmp::Bmp;
 pub(crate) use buddy_alloc::BuddyAlloc;
 pub(crate) use contain_range::ContainRange;
-pub(crate) use image_loader::{ImageData, ImageFormat, ImageLoader};
+pub(crate) use cube::CubeLut;
+pub(crate) use image_loader::{ImageData, ImageFormat, ImageLoader, ImageSave};
+pub(crate) use jpeg::Jpeg;
 pub(crate) use qoi::Qoi;
 pub(crate) use ttf::Ttf;
 
+pub use color::{Color, ColorMatrix, Hsl, Hsv, Oklab, Palette, linear_to_srgb, srgb_to_linear};
 pub use cooldown::Cooldown;
+pub use fixed::{Fixed, Vec2fp, f16_to_f32, f32_to_f16};
+pub use jobs::{JobHandle, Jobs, jobs};
+pub use logger::{ConsoleSink, FileSink, Level, LogBuffer, Logger, Record, Sink, logger};
+pub use mat::{Mat2, Mat3, Mat4, Quat};
 pub use mem::Mem;
-pub use rand::{Noise, Rand};
+pub use mmap::FileBytes;
+pub use rand::{Noise, Rand, RngStream};
 pub use reader::{Reader, ReaderBe};
+pub use spatial::{Aabb, Quadtree, SpatialHash};
+pub use svg_path::SvgPath;
+pub use task::{TaskHandle, Tasks, WaitFrames, WaitSecs, next_frame, wait_frames, wait_secs};
+pub use toml::{Toml, TomlType, TomlValue};
 pub use tracked::Tracked;
-pub use vec::{Bezier, ExtraFns, Vec2, Vec2u, Vec3, Vectorf, Vectoru};
+pub use tween::{Ease, Tween, Tweenable, Tweens};
+pub use tweak::{Tweaks, tweaks};
+pub use vec::{Bezier, ExtraFns, Vec2, Vec2u, Vec3, Vec3u, Vec4, Vectorf, Vectoru};
+pub use vfs::{Vfs, vfs, vfs_mut};
 pub use writer::Writer;
 
 #[macro_export]
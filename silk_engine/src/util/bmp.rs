@@ -1,6 +1,6 @@
 use crate::{
     RES_PATH,
-    util::{ImageData, ImageFormat, Reader, Writer},
+    util::{ImageData, ImageFormat, ImageSave, Reader, Writer, vfs},
 };
 
 pub struct Bmp;
@@ -28,7 +28,8 @@ const BMP_HEAD_LEN: usize = size_of::<Head>();
 
 impl ImageFormat for Bmp {
     fn load(name: &str) -> ImageData {
-        let data = std::fs::read(format!("{RES_PATH}/images/{name}.bmp"))
+        let data = vfs()
+            .read(&format!("images/{name}.bmp"))
             .unwrap_or_else(|_| panic!("bmp image not found: {name}"));
         let mut reader = Reader::new(&data);
         let magic = reader.read16().to_le_bytes();
@@ -93,7 +94,9 @@ impl ImageFormat for Bmp {
 
         ImageData::new(img, width, height, channels)
     }
+}
 
+impl ImageSave for Bmp {
     fn save(name: &str, img: &[u8], width: u32, height: u32, channels: u8) {
         assert!(!img.is_empty(), "img was empty");
         assert_ne!(width, 0, "width was 0");
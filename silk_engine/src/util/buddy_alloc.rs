@@ -94,6 +94,23 @@ impl BuddyAlloc {
         self.size
     }
 
+    /// sum of free block sizes
+    pub fn free_size(&self) -> usize {
+        self.free_lists
+            .iter()
+            .enumerate()
+            .map(|(i, fl)| fl.len() << i)
+            .sum()
+    }
+
+    /// biggest size that can be allocated without growing
+    pub fn largest_free(&self) -> usize {
+        self.free_lists
+            .iter()
+            .rposition(|fl| !fl.is_empty())
+            .map_or(0, |i| 1 << i)
+    }
+
     /// `O(log N)`, where N is pool size
     /// worst case `O(n)` where n is allocations
     /// checks if buddy is free and merges it
@@ -0,0 +1,526 @@
+use std::collections::HashMap;
+
+use super::{Toml, TomlValue, Tweenable, vfs};
+use crate::warn;
+
+/// 8 bit srgb color with alpha, converts from and into `[u8; 4]`, [`Hsv`], [`Hsl`] and [`Oklab`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const WHITE: Self = Self::rgb(255, 255, 255);
+    pub const BLACK: Self = Self::rgb(0, 0, 0);
+    pub const TRANSPARENT: Self = Self::rgba(0, 0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 255)
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// parses "#rgb", "#rgba", "#rrggbb" or "#rrggbbaa", # is optional
+    pub fn hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim();
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.is_ascii() {
+            return None;
+        }
+        let digit = |i: usize, n: usize| u8::from_str_radix(&hex[i * n..(i + 1) * n], 16).ok();
+        let (n, a) = match hex.len() {
+            3 => (1, None),
+            4 => (1, Some(3)),
+            6 => (2, None),
+            8 => (2, Some(3)),
+            _ => return None,
+        };
+        // single digits are repeated, f -> ff
+        let scale = if n == 1 { 17 } else { 1 };
+        let c = |i| digit(i, n).map(|d| d * scale);
+        Some(Self::rgba(c(0)?, c(1)?, c(2)?, a.map_or(Some(255), c)?))
+    }
+
+    /// "#rrggbbaa"
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+    }
+
+    pub fn with_alpha(mut self, a: u8) -> Self {
+        self.a = a;
+        self
+    }
+
+    /// linear rgba in [0, 1]
+    pub fn to_linear(self) -> [f32; 4] {
+        [
+            srgb_to_linear(self.r as f32 / 255.0),
+            srgb_to_linear(self.g as f32 / 255.0),
+            srgb_to_linear(self.b as f32 / 255.0),
+            self.a as f32 / 255.0,
+        ]
+    }
+
+    /// from linear rgba, clamped to [0, 1]
+    pub fn from_linear([r, g, b, a]: [f32; 4]) -> Self {
+        unorm([linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a])
+    }
+
+    /// perceptually even blend, doesn't go gray between saturated colors like srgb lerp
+    pub fn lerp_oklab(self, to: Self, t: f32) -> Self {
+        let (a, b) = (Oklab::from(self), Oklab::from(to));
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Oklab {
+            l: lerp(a.l, b.l),
+            a: lerp(a.a, b.a),
+            b: lerp(a.b, b.b),
+            alpha: lerp(a.alpha, b.alpha),
+        }
+        .into()
+    }
+}
+
+/// srgb transfer fn, `c` in [0, 1]
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl From<[u8; 4]> for Color {
+    fn from([r, g, b, a]: [u8; 4]) -> Self {
+        Self::rgba(r, g, b, a)
+    }
+}
+
+impl From<[u8; 3]> for Color {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Self::rgb(r, g, b)
+    }
+}
+
+/// 0xRRGGBBAA
+impl From<u32> for Color {
+    fn from(hex: u32) -> Self {
+        hex.to_be_bytes().into()
+    }
+}
+
+impl From<Color> for [u8; 4] {
+    fn from(c: Color) -> Self {
+        [c.r, c.g, c.b, c.a]
+    }
+}
+
+/// blends in oklab, see [`Color::lerp_oklab`]
+impl Tweenable for Color {
+    fn tween(self, to: Self, t: f32) -> Self {
+        self.lerp_oklab(to, t)
+    }
+}
+
+/// hue in degrees [0, 360), saturation, value and alpha in [0, 1]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Hsv {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+    pub a: f32,
+}
+
+impl Hsv {
+    pub fn new(h: f32, s: f32, v: f32) -> Self {
+        Self { h, s, v, a: 1.0 }
+    }
+}
+
+/// hue in degrees [0, 360), saturation, lightness and alpha in [0, 1]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Hsl {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+    pub a: f32,
+}
+
+impl Hsl {
+    pub fn new(h: f32, s: f32, l: f32) -> Self {
+        Self { h, s, l, a: 1.0 }
+    }
+}
+
+/// perceptual color space, lightness in [0, 1], `a` green-red and `b` blue-yellow around 0
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+impl Oklab {
+    pub fn new(l: f32, a: f32, b: f32) -> Self {
+        Self {
+            l,
+            a,
+            b,
+            alpha: 1.0,
+        }
+    }
+}
+
+/// (hue in degrees, max channel, min channel) of rgb in [0, 1]
+fn hue(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let d = max - min;
+    let h = if d == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+    (h, max, min)
+}
+
+/// rgb in [0, 1] from hue, chroma and value
+fn from_hue(h: f32, chroma: f32, v: f32) -> [f32; 3] {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let [r, g, b] = match h as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    let m = v - chroma;
+    [r + m, g + m, b + m]
+}
+
+fn unorm([r, g, b, a]: [f32; 4]) -> Color {
+    let to_u8 = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Color::rgba(to_u8(r), to_u8(g), to_u8(b), to_u8(a))
+}
+
+impl From<Color> for Hsv {
+    fn from(c: Color) -> Self {
+        let [r, g, b, a] = <[u8; 4]>::from(c).map(|c| c as f32 / 255.0);
+        let (h, max, min) = hue(r, g, b);
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+        Self { h, s, v: max, a }
+    }
+}
+
+impl From<Hsv> for Color {
+    fn from(Hsv { h, s, v, a }: Hsv) -> Self {
+        let [r, g, b] = from_hue(h, v * s, v);
+        unorm([r, g, b, a])
+    }
+}
+
+impl From<Color> for Hsl {
+    fn from(c: Color) -> Self {
+        let [r, g, b, a] = <[u8; 4]>::from(c).map(|c| c as f32 / 255.0);
+        let (h, max, min) = hue(r, g, b);
+        let l = (max + min) * 0.5;
+        let s = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * l - 1.0).abs())
+        };
+        Self { h, s, l, a }
+    }
+}
+
+impl From<Hsl> for Color {
+    fn from(Hsl { h, s, l, a }: Hsl) -> Self {
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let [r, g, b] = from_hue(h, chroma, l + chroma * 0.5);
+        unorm([r, g, b, a])
+    }
+}
+
+impl From<Color> for Oklab {
+    fn from(c: Color) -> Self {
+        let [r, g, b, alpha] = c.to_linear();
+        let l = (0.41222147 * r + 0.53633254 * g + 0.051445993 * b).cbrt();
+        let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+        let s = (0.08830246 * r + 0.28171884 * g + 0.6299787 * b).cbrt();
+        Self {
+            l: 0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+            a: 1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+            b: 0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+            alpha,
+        }
+    }
+}
+
+impl From<Oklab> for Color {
+    fn from(Oklab { l, a, b, alpha }: Oklab) -> Self {
+        let l_ = (l + 0.39633778 * a + 0.21580376 * b).powi(3);
+        let m_ = (l - 0.105561346 * a - 0.06385417 * b).powi(3);
+        let s_ = (l - 0.08948418 * a - 1.2914855 * b).powi(3);
+        Color::from_linear([
+            4.0767417 * l_ - 3.3077116 * m_ + 0.23096993 * s_,
+            -1.268438 * l_ + 2.6097574 * m_ - 0.3413194 * s_,
+            -0.0041960863 * l_ - 0.7034186 * m_ + 1.7076147 * s_,
+            alpha,
+        ])
+    }
+}
+
+/// 4x5 matrix mapping rgba in [0, 1], each row is weights of r, g, b, a plus offset,
+/// like svg feColorMatrix
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorMatrix(pub [[f32; 5]; 4]);
+
+impl Default for ColorMatrix {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl ColorMatrix {
+    pub const IDENTITY: Self = Self([
+        [1.0, 0.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0, 0.0],
+    ]);
+
+    /// 0 is grayscale, 1 is unchanged, above 1 oversaturates
+    pub fn saturate(s: f32) -> Self {
+        let (r, g, b) = (0.213 * (1.0 - s), 0.715 * (1.0 - s), 0.072 * (1.0 - s));
+        Self([
+            [r + s, g, b, 0.0, 0.0],
+            [r, g + s, b, 0.0, 0.0],
+            [r, g, b + s, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0, 0.0],
+        ])
+    }
+
+    pub fn grayscale() -> Self {
+        Self::saturate(0.0)
+    }
+
+    pub fn sepia() -> Self {
+        Self([
+            [0.393, 0.769, 0.189, 0.0, 0.0],
+            [0.349, 0.686, 0.168, 0.0, 0.0],
+            [0.272, 0.534, 0.131, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0, 0.0],
+        ])
+    }
+
+    /// inverts rgb, keeps alpha
+    pub fn invert() -> Self {
+        Self([
+            [-1.0, 0.0, 0.0, 0.0, 1.0],
+            [0.0, -1.0, 0.0, 0.0, 1.0],
+            [0.0, 0.0, -1.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0, 1.0, 0.0],
+        ])
+    }
+
+    /// rotates hue by `degrees` while roughly keeping luminance
+    pub fn hue_rotate(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self([
+            [
+                0.213 + cos * 0.787 - sin * 0.213,
+                0.715 - cos * 0.715 - sin * 0.715,
+                0.072 - cos * 0.072 + sin * 0.928,
+                0.0,
+                0.0,
+            ],
+            [
+                0.213 - cos * 0.213 + sin * 0.143,
+                0.715 + cos * 0.285 + sin * 0.140,
+                0.072 - cos * 0.072 - sin * 0.283,
+                0.0,
+                0.0,
+            ],
+            [
+                0.213 - cos * 0.213 - sin * 0.787,
+                0.715 - cos * 0.715 + sin * 0.715,
+                0.072 + cos * 0.928 + sin * 0.072,
+                0.0,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0, 0.0],
+        ])
+    }
+
+    /// applies `self` after `first`
+    pub fn after(self, first: Self) -> Self {
+        let mut m = [[0.0; 5]; 4];
+        for (row, out) in self.0.iter().zip(&mut m) {
+            for (j, o) in out.iter_mut().enumerate() {
+                *o = (0..4).map(|k| row[k] * first.0[k][j]).sum::<f32>();
+            }
+            out[4] += row[4];
+        }
+        Self(m)
+    }
+
+    pub fn apply(self, color: Color) -> Color {
+        let c = [color.r, color.g, color.b, color.a].map(|c| c as f32 / 255.0);
+        unorm(
+            self.0
+                .map(|row| row[4] + row.iter().zip(c).map(|(w, c)| w * c).sum::<f32>()),
+        )
+    }
+
+    /// column major 4x4 matrix followed by offset, layout of mat4x4f and vec4f in wgsl
+    pub fn to_cols(self) -> [f32; 20] {
+        let mut cols = [0.0; 20];
+        for (i, row) in self.0.iter().enumerate() {
+            for (j, &w) in row.iter().enumerate() {
+                cols[j * 4 + i] = w;
+            }
+        }
+        cols
+    }
+}
+
+/// named colors, loaded from toml file of `name = "#rrggbb"` lines
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Palette {
+    colors: HashMap<String, Color>,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// colors that aren't hex strings are skipped with a warning
+    pub fn parse(text: &str) -> Self {
+        let mut palette = Self::new();
+        for (name, value) in Toml::parse(text).iter() {
+            let color = match value {
+                TomlValue::Str(hex) => Color::hex(hex),
+                _ => None,
+            };
+            match color {
+                Some(color) => palette.set(name, color),
+                None => warn!("invalid palette color {name}: {value}"),
+            }
+        }
+        palette
+    }
+
+    /// loads palette file from vfs, empty if it doesn't exist
+    pub fn load(path: &str) -> Self {
+        vfs()
+            .read_to_string(path)
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, name: &str) -> Color {
+        *self
+            .colors
+            .get(name)
+            .unwrap_or_else(|| panic!("palette color not found: {name}"))
+    }
+
+    pub fn try_get(&self, name: &str) -> Option<Color> {
+        self.colors.get(name).copied()
+    }
+
+    pub fn set(&mut self, name: &str, color: Color) {
+        self.colors.insert(name.to_string(), color);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Color)> {
+        self.colors.iter().map(|(k, &v)| (k.as_str(), v))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn color_test() {
+        assert_eq!(Color::hex("#ff8800"), Some(Color::rgb(255, 136, 0)));
+        assert_eq!(Color::hex("f80c"), Some(Color::rgba(255, 136, 0, 204)));
+        assert_eq!(Color::hex("#12345678").unwrap().to_hex(), "#12345678");
+        assert_eq!(Color::hex("#ff88"), Color::hex("#ffff8888"));
+        assert_eq!(Color::hex("#ff880"), None);
+        assert_eq!(Color::hex("#gg8800"), None);
+
+        let colors = [
+            Color::rgb(255, 136, 0),
+            Color::rgb(12, 200, 90),
+            Color::rgba(40, 60, 250, 128),
+            Color::WHITE,
+            Color::BLACK,
+        ];
+        for c in colors {
+            assert_eq!(Color::from(Hsv::from(c)), c);
+            assert_eq!(Color::from(Hsl::from(c)), c);
+            assert_eq!(Color::from(Oklab::from(c)), c);
+        }
+        assert_eq!(
+            Color::from(Hsv::new(120.0, 1.0, 1.0)),
+            Color::rgb(0, 255, 0)
+        );
+        assert_eq!(
+            Color::from(Hsl::new(240.0, 1.0, 0.5)),
+            Color::rgb(0, 0, 255)
+        );
+        let white = Oklab::from(Color::WHITE);
+        assert!((white.l - 1.0).abs() < 1e-3 && white.a.abs() < 1e-3 && white.b.abs() < 1e-3);
+
+        let (a, b) = (Color::rgb(255, 0, 0), Color::rgb(0, 0, 255));
+        assert_eq!(a.lerp_oklab(b, 0.0), a);
+        assert_eq!(a.lerp_oklab(b, 1.0), b);
+        // oklab midpoint stays brighter than srgb one
+        let mid = Oklab::from(a.lerp_oklab(b, 0.5)).l;
+        assert!(mid > Oklab::from(Color::rgb(128, 0, 128)).l);
+
+        let c = Color::rgba(255, 136, 0, 200);
+        assert_eq!(ColorMatrix::IDENTITY.apply(c), c);
+        assert_eq!(ColorMatrix::saturate(1.0).apply(c), c);
+        assert_eq!(
+            ColorMatrix::invert().apply(c),
+            Color::rgba(0, 119, 255, 200)
+        );
+        let gray = ColorMatrix::grayscale().apply(c);
+        assert!(gray.r == gray.g && gray.g == gray.b && gray.a == 200);
+        assert_eq!(ColorMatrix::hue_rotate(0.0).apply(c), c);
+        assert_eq!(
+            ColorMatrix::invert().after(ColorMatrix::invert()).apply(c),
+            c
+        );
+        let cols = ColorMatrix::invert().to_cols();
+        assert_eq!((cols[0], cols[16], cols[19]), (-1.0, 1.0, 0.0));
+
+        let palette = Palette::parse("bg = \"#101014\"\naccent = \"#6284ff\"\nbad = 3\n");
+        assert_eq!(palette.get("bg"), Color::rgb(16, 16, 20));
+        assert_eq!(palette.try_get("accent"), Some(Color::rgb(98, 132, 255)));
+        assert_eq!(palette.try_get("bad"), None);
+    }
+}
@@ -0,0 +1,113 @@
+use super::vfs;
+
+/// 3d color lookup table in adobe/resolve `.cube` format
+pub(crate) struct CubeLut {
+    pub size: u32,
+    /// rgb entries, red changes fastest then green then blue
+    pub data: Vec<[f32; 3]>,
+}
+
+impl CubeLut {
+    /// loads `res/luts/{name}.cube`
+    pub fn load(name: &str) -> Self {
+        crate::scope_time!("CUBE load");
+        let text = vfs()
+            .read_to_string(&format!("luts/{name}.cube"))
+            .unwrap_or_else(|_| panic!("cube lut not found: {name}"));
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut size = 0;
+        let mut min = [0.0; 3];
+        let mut max = [1.0; 3];
+        let mut data = Vec::new();
+        let floats = |args: std::str::SplitWhitespace| -> [f32; 3] {
+            let v = args
+                .map(|a| {
+                    a.parse()
+                        .unwrap_or_else(|_| panic!("invalid cube lut value: {a}"))
+                })
+                .collect::<Vec<f32>>();
+            assert_eq!(v.len(), 3, "cube lut line should have 3 values");
+            [v[0], v[1], v[2]]
+        };
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut args = line.split_whitespace();
+            match args.next().unwrap() {
+                "TITLE" => {}
+                "LUT_1D_SIZE" => panic!("1d cube luts are not supported"),
+                "LUT_3D_SIZE" => {
+                    size = args
+                        .next()
+                        .and_then(|s| s.parse().ok())
+                        .expect("invalid cube lut size");
+                    data.reserve((size * size * size) as usize);
+                }
+                "DOMAIN_MIN" => min = floats(args),
+                "DOMAIN_MAX" => max = floats(args),
+                _ => {
+                    let rgb = floats(line.split_whitespace());
+                    data.push(std::array::from_fn(|i| {
+                        (rgb[i] - min[i]) / (max[i] - min[i])
+                    }));
+                }
+            }
+        }
+        assert!(size >= 2, "cube lut size missing or less than 2");
+        assert_eq!(
+            data.len(),
+            (size * size * size) as usize,
+            "cube lut should have size^3 entries"
+        );
+        Self { size, data }
+    }
+
+    /// identity lut, maps every color to itself
+    pub fn identity(size: u32) -> Self {
+        let s = (size - 1) as f32;
+        let data = (0..size * size * size)
+            .map(|i| {
+                let (r, g, b) = (i % size, i / size % size, i / (size * size));
+                [r as f32 / s, g as f32 / s, b as f32 / s]
+            })
+            .collect();
+        Self { size, data }
+    }
+
+    /// rgba8 strip of blue slices side by side, `size * size` wide and `size` tall
+    pub fn strip(&self) -> Vec<u8> {
+        let n = self.size as usize;
+        let mut img = vec![255; n * n * n * 4];
+        for (i, rgb) in self.data.iter().enumerate() {
+            let (r, g, b) = (i % n, i / n % n, i / (n * n));
+            let idx = (g * n * n + b * n + r) * 4;
+            for c in 0..3 {
+                img[idx + c] = (rgb[c].clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        }
+        img
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let lut = CubeLut::parse(
+            "# comment\nTITLE \"test\"\nLUT_3D_SIZE 2\nDOMAIN_MIN 0 0 0\nDOMAIN_MAX 2 2 2\n\
+             0 0 0\n2 0 0\n0 2 0\n2 2 0\n0 0 2\n2 0 2\n0 2 2\n2 2 2\n",
+        );
+        assert_eq!(lut.size, 2);
+        assert_eq!(lut.data, CubeLut::identity(2).data);
+        let strip = lut.strip();
+        // r=1, g=0, b=1 is at x = b * size + r = 3, y = g = 0
+        assert_eq!(&strip[3 * 4..4 * 4], &[255, 0, 255, 255]);
+    }
+}
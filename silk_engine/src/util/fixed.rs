@@ -0,0 +1,392 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use super::Vec2;
+
+/// Q16.16 fixed point number, arithmetic is integer only (wrapping on overflow),
+/// so results are bit exact across platforms, useful for lockstep simulation
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(pub i32);
+
+impl Fixed {
+    pub const FRAC_BITS: u32 = 16;
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(1 << Self::FRAC_BITS);
+    pub const HALF: Self = Self(1 << (Self::FRAC_BITS - 1));
+    pub const PI: Self = Self(205887);
+    pub const FRAC_PI_2: Self = Self(102944);
+    pub const TAU: Self = Self(411775);
+    pub const MIN: Self = Self(i32::MIN);
+    pub const MAX: Self = Self(i32::MAX);
+    /// smallest positive value
+    pub const EPSILON: Self = Self(1);
+
+    pub const fn from_int(v: i32) -> Self {
+        Self(v << Self::FRAC_BITS)
+    }
+
+    /// rounds to nearest representable value
+    pub fn from_f32(v: f32) -> Self {
+        Self((v * Self::ONE.0 as f32).round() as i32)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / Self::ONE.0 as f32
+    }
+
+    /// rounds towards negative infinity
+    pub const fn to_int(self) -> i32 {
+        self.0 >> Self::FRAC_BITS
+    }
+
+    pub const fn floor(self) -> Self {
+        Self(self.0 & !(Self::ONE.0 - 1))
+    }
+
+    pub const fn fract(self) -> Self {
+        Self(self.0 & (Self::ONE.0 - 1))
+    }
+
+    pub const fn abs(self) -> Self {
+        Self(self.0.wrapping_abs())
+    }
+
+    pub fn min(self, rhs: Self) -> Self {
+        Ord::min(self, rhs)
+    }
+
+    pub fn max(self, rhs: Self) -> Self {
+        Ord::max(self, rhs)
+    }
+
+    pub fn clamp(self, min: Self, max: Self) -> Self {
+        Ord::clamp(self, min, max)
+    }
+
+    pub fn lerp(self, other: Self, k: Self) -> Self {
+        self + (other - self) * k
+    }
+
+    /// 0 for negative values
+    pub fn sqrt(self) -> Self {
+        if self.0 <= 0 {
+            return Self::ZERO;
+        }
+        Self(((self.0 as u64) << Self::FRAC_BITS).isqrt() as i32)
+    }
+
+    /// `x` wrapped into [-pi, pi)
+    fn wrap_angle(x: Self) -> Self {
+        let tau = Self::TAU.0 as i64;
+        Self(((x.0 as i64 + Self::PI.0 as i64).rem_euclid(tau) - Self::PI.0 as i64) as i32)
+    }
+
+    pub fn sin(self) -> Self {
+        let mut x = Self::wrap_angle(self);
+        // fold into [-pi/2, pi/2] where series converges fast
+        if x > Self::FRAC_PI_2 {
+            x = Self::PI - x;
+        } else if x < -Self::FRAC_PI_2 {
+            x = -Self::PI - x;
+        }
+        // x * (1 - x²/6 * (1 - x²/20 * (1 - x²/42 * (1 - x²/72))))
+        let x2 = x * x;
+        let mut t = Self::ONE;
+        for k in [72, 42, 20, 6] {
+            t = Self::ONE - Self((x2 * t).0 / k);
+        }
+        x * t
+    }
+
+    pub fn cos(self) -> Self {
+        (self + Self::FRAC_PI_2).sin()
+    }
+
+    /// angle of (x, y) in [-pi, pi], abs error under 1e-4
+    pub fn atan2(y: Self, x: Self) -> Self {
+        if x == Self::ZERO && y == Self::ZERO {
+            return Self::ZERO;
+        }
+        let (ax, ay) = (x.abs(), y.abs());
+        let z = ax.min(ay) / ax.max(ay);
+        let z2 = z * z;
+        // abramowitz & stegun 4.4.49
+        let mut a = Self(1365);
+        for c in [-5579, 11806, -21647, 65527] {
+            a = Self(c) + a * z2;
+        }
+        a *= z;
+        if ay > ax {
+            a = Self::FRAC_PI_2 - a;
+        }
+        if x.0 < 0 {
+            a = Self::PI - a;
+        }
+        if y.0 < 0 { -a } else { a }
+    }
+}
+
+impl std::fmt::Debug for Fixed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_f32())
+    }
+}
+
+impl std::fmt::Display for Fixed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_f32())
+    }
+}
+
+impl Add for Fixed {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self(((self.0 as i64 * rhs.0 as i64) >> Self::FRAC_BITS) as i32)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        Self((((self.0 as i64) << Self::FRAC_BITS) / rhs.0 as i64) as i32)
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self(self.0.wrapping_neg())
+    }
+}
+
+macro_rules! impl_fixed_assign {
+    ($ty: ident, $rhs: ty, $($trait: ident, $method: ident, $op: tt),+) => {
+        $(
+            impl $trait<$rhs> for $ty {
+                fn $method(&mut self, rhs: $rhs) {
+                    *self = *self $op rhs;
+                }
+            }
+        )+
+    };
+}
+
+impl_fixed_assign!(Fixed, Fixed, AddAssign, add_assign, +, SubAssign, sub_assign, -, MulAssign, mul_assign, *, DivAssign, div_assign, /);
+
+impl From<i32> for Fixed {
+    fn from(value: i32) -> Self {
+        Self::from_int(value)
+    }
+}
+
+impl From<Fixed> for f32 {
+    fn from(value: Fixed) -> Self {
+        value.to_f32()
+    }
+}
+
+/// fixed point 2d vector for deterministic simulation, convert to `Vec2` for rendering
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct Vec2fp {
+    pub x: Fixed,
+    pub y: Fixed,
+}
+
+impl Vec2fp {
+    pub const ZERO: Self = Self::new(Fixed::ZERO, Fixed::ZERO);
+
+    pub const fn new(x: Fixed, y: Fixed) -> Self {
+        Self { x, y }
+    }
+
+    pub const fn from_int(x: i32, y: i32) -> Self {
+        Self::new(Fixed::from_int(x), Fixed::from_int(y))
+    }
+
+    /// unit vector at angle `a`
+    pub fn angle(a: Fixed) -> Self {
+        Self::new(a.cos(), a.sin())
+    }
+
+    pub fn dot(self, rhs: Self) -> Fixed {
+        self.x * rhs.x + self.y * rhs.y
+    }
+
+    pub fn cross(self, rhs: Self) -> Fixed {
+        self.x * rhs.y - self.y * rhs.x
+    }
+
+    pub fn len2(self) -> Fixed {
+        self.dot(self)
+    }
+
+    pub fn len(self) -> Fixed {
+        self.len2().sqrt()
+    }
+
+    /// zero vector stays zero
+    pub fn norm(self) -> Self {
+        let len = self.len();
+        if len == Fixed::ZERO {
+            return self;
+        }
+        self / len
+    }
+
+    pub fn lerp(self, other: Self, k: Fixed) -> Self {
+        self + (other - self) * k
+    }
+
+    pub fn to_vec2(self) -> Vec2 {
+        Vec2::new(self.x.to_f32(), self.y.to_f32())
+    }
+}
+
+impl Add for Vec2fp {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Vec2fp {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<Fixed> for Vec2fp {
+    type Output = Self;
+    fn mul(self, rhs: Fixed) -> Self {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Div<Fixed> for Vec2fp {
+    type Output = Self;
+    fn div(self, rhs: Fixed) -> Self {
+        Self::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl Neg for Vec2fp {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl_fixed_assign!(Vec2fp, Vec2fp, AddAssign, add_assign, +, SubAssign, sub_assign, -);
+impl_fixed_assign!(Vec2fp, Fixed, MulAssign, mul_assign, *, DivAssign, div_assign, /);
+
+impl From<Vec2> for Vec2fp {
+    fn from(value: Vec2) -> Self {
+        Self::new(Fixed::from_f32(value.x), Fixed::from_f32(value.y))
+    }
+}
+
+impl From<Vec2fp> for Vec2 {
+    fn from(value: Vec2fp) -> Self {
+        value.to_vec2()
+    }
+}
+
+/// bits of nearest ieee half float, rounds ties to even, overflows to infinity
+pub fn f32_to_f16(v: f32) -> u16 {
+    let x = v.to_bits();
+    let sign = ((x >> 16) & 0x8000) as u16;
+    let exp = ((x >> 23) & 0xff) as i32;
+    let man = x & 0x7f_ffff;
+    if exp == 0xff {
+        // inf, nan keeps a mantissa bit
+        return sign | 0x7c00 | if man != 0 { 0x200 } else { 0 };
+    }
+    let exp = exp - 127 + 15;
+    if exp >= 0x1f {
+        return sign | 0x7c00;
+    }
+    // rounds away `shift` low bits of `man`
+    let round = |man: u32, shift: u32| {
+        let (half, rem, mid) = (man >> shift, man & ((1 << shift) - 1), 1 << (shift - 1));
+        half + (rem > mid || (rem == mid && half & 1 != 0)) as u32
+    };
+    if exp <= 0 {
+        if exp < -10 {
+            return sign;
+        }
+        // subnormal, implicit bit becomes explicit
+        return sign | round(man | 0x80_0000, (14 - exp) as u32) as u16;
+    }
+    // mantissa rounding up carries into exponent
+    sign | round(((exp as u32) << 23) | man, 13) as u16
+}
+
+pub fn f16_to_f32(h: u16) -> f32 {
+    let sign = ((h & 0x8000) as u32) << 16;
+    let exp = ((h >> 10) & 0x1f) as u32;
+    let man = (h & 0x3ff) as u32;
+    match exp {
+        0 => {
+            let v = man as f32 / (1 << 24) as f32;
+            if sign != 0 { -v } else { v }
+        }
+        0x1f => f32::from_bits(sign | 0x7f80_0000 | (man << 13)),
+        _ => f32::from_bits(sign | ((exp + 127 - 15) << 23) | (man << 13)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fixed_test() {
+        let close = |a: Fixed, b: f32| (a.to_f32() - b).abs() < 2e-4;
+        assert_eq!(Fixed::from_int(3) * Fixed::HALF, Fixed::from_f32(1.5));
+        assert_eq!(
+            Fixed::from_int(-7) / Fixed::from_int(2),
+            Fixed::from_f32(-3.5)
+        );
+        assert!(close(Fixed::from_int(2).sqrt(), 2f32.sqrt()));
+        for i in -64..64 {
+            let a = i as f32 * 0.2;
+            let fa = Fixed::from_f32(a);
+            assert!(close(fa.sin(), a.sin()), "sin({a}) = {}", fa.sin());
+            assert!(close(fa.cos(), a.cos()), "cos({a}) = {}", fa.cos());
+            let (y, x) = (a.sin() * 3.0, a.cos() * 3.0);
+            let at = Fixed::atan2(Fixed::from_f32(y), Fixed::from_f32(x));
+            let diff = (at.to_f32() - y.atan2(x)).abs();
+            // atan2 at pi and -pi are same angle
+            assert!(diff < 2e-4 || (diff - std::f32::consts::TAU).abs() < 2e-4);
+        }
+    }
+
+    #[test]
+    fn f16_test() {
+        for v in [0.0, -0.0, 1.0, -2.5, 0.1, 65504.0, 6e-8, 1234.567] {
+            let back = f16_to_f32(f32_to_f16(v));
+            assert!((back - v).abs() <= v.abs() / 1024.0 + 6e-8, "{v} -> {back}");
+        }
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(65520.0), 0x7c00);
+        assert_eq!(f32_to_f16(f32::NEG_INFINITY), 0xfc00);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+        // 1 + 2^-11 is tie between 1 and next half, rounds to even
+        assert_eq!(f32_to_f16(1.0 + 1.0 / 2048.0), 0x3c00);
+        assert_eq!(f32_to_f16(1.0 + 3.0 / 2048.0), 0x3c02);
+    }
+}
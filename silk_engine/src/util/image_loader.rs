@@ -1,4 +1,4 @@
-use crate::util::{Bmp, Qoi};
+use crate::util::{Bmp, Jpeg, Qoi};
 
 pub struct ImageData {
     pub img: Vec<u8>,
@@ -20,6 +20,10 @@ impl ImageData {
 
 pub trait ImageFormat {
     fn load(name: &str) -> ImageData;
+}
+
+/// formats that can also be encoded, jpeg is decode only
+pub trait ImageSave: ImageFormat {
     fn save(name: &str, img: &[u8], width: u32, height: u32, channels: u8);
 }
 
@@ -67,6 +71,7 @@ impl ImageLoader {
                 img_data
             }
             "bmp" => Bmp::load(name),
+            "jpg" | "jpeg" => Jpeg::load(name),
             _ => panic!("unsupported image file extension: {file_ext}"),
         }
     }
@@ -0,0 +1,234 @@
+use std::{
+    collections::VecDeque,
+    ops::Range,
+    panic::AssertUnwindSafe,
+    sync::{
+        Arc, Condvar, LazyLock, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    thread::Result as ThreadResult,
+};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+static JOBS: LazyLock<Jobs> = LazyLock::new(|| {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get().saturating_sub(1));
+    Jobs::new(threads.max(1))
+});
+
+/// global job pool, one worker per core (minus main thread)
+pub fn jobs() -> &'static Jobs {
+    &JOBS
+}
+
+struct Shared {
+    /// one queue per worker, owner pops front, thieves pop back
+    queues: Vec<Mutex<VecDeque<Job>>>,
+    /// queued jobs that no thread started yet
+    queued: AtomicUsize,
+    next: AtomicUsize,
+    sleep: Mutex<()>,
+    wake: Condvar,
+}
+
+impl Shared {
+    fn push(&self, job: Job) {
+        let i = self.next.fetch_add(1, Ordering::Relaxed) % self.queues.len();
+        self.queues[i].lock().unwrap().push_back(job);
+        self.queued.fetch_add(1, Ordering::Release);
+        // lock so sleeping worker can't miss this between checking `queued` and waiting
+        let _sleep = self.sleep.lock().unwrap();
+        self.wake.notify_one();
+    }
+
+    /// own queue first, then steals from others
+    fn pop(&self, home: usize) -> Option<Job> {
+        let n = self.queues.len();
+        for k in 0..n {
+            let mut queue = self.queues[(home + k) % n].lock().unwrap();
+            let job = if k == 0 {
+                queue.pop_front()
+            } else {
+                queue.pop_back()
+            };
+            if job.is_some() {
+                self.queued.fetch_sub(1, Ordering::Relaxed);
+                return job;
+            }
+        }
+        None
+    }
+
+    fn work(&self, home: usize) {
+        loop {
+            if let Some(job) = self.pop(home) {
+                job();
+                continue;
+            }
+            let sleep = self.sleep.lock().unwrap();
+            if self.queued.load(Ordering::Acquire) == 0 {
+                drop(self.wake.wait(sleep).unwrap());
+            }
+        }
+    }
+
+    /// runs queued jobs on calling thread until `done`, so waiting never deadlocks
+    fn help_until(&self, done: impl Fn() -> bool) {
+        while !done() {
+            match self.pop(0) {
+                Some(job) => job(),
+                None => std::thread::yield_now(),
+            }
+        }
+    }
+}
+
+/// result of [`Jobs::spawn`], dropping it detaches the job
+pub struct JobHandle<T> {
+    result: Arc<Mutex<Option<ThreadResult<T>>>>,
+}
+
+impl<T> JobHandle<T> {
+    pub fn done(&self) -> bool {
+        self.result.lock().unwrap().is_some()
+    }
+
+    /// blocks until job finished, running other jobs meanwhile.
+    /// panics if job panicked
+    pub fn wait(self) -> T {
+        JOBS.shared.help_until(|| self.done());
+        match self.result.lock().unwrap().take().unwrap() {
+            Ok(value) => value,
+            Err(e) => std::panic::resume_unwind(e),
+        }
+    }
+}
+
+/// work stealing thread pool, use global one through [`jobs`]
+pub struct Jobs {
+    shared: Arc<Shared>,
+}
+
+impl Jobs {
+    fn new(threads: usize) -> Self {
+        let shared = Arc::new(Shared {
+            queues: (0..threads).map(|_| Default::default()).collect(),
+            queued: AtomicUsize::new(0),
+            next: AtomicUsize::new(0),
+            sleep: Mutex::new(()),
+            wake: Condvar::new(),
+        });
+        for i in 0..threads {
+            let shared = shared.clone();
+            std::thread::Builder::new()
+                .name(format!("silk job {i}"))
+                .spawn(move || shared.work(i))
+                .unwrap_or_else(|e| panic!("failed to spawn job thread: {e}"));
+        }
+        Self { shared }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.shared.queues.len()
+    }
+
+    pub fn spawn<T: Send + 'static>(&self, f: impl FnOnce() -> T + Send + 'static) -> JobHandle<T> {
+        let result = Arc::new(Mutex::new(None));
+        let job_result = result.clone();
+        self.shared.push(Box::new(move || {
+            let res = std::panic::catch_unwind(AssertUnwindSafe(f));
+            *job_result.lock().unwrap() = Some(res);
+        }));
+        JobHandle { result }
+    }
+
+    /// calls `f` for every index in `range`, `chunk` indices per job.
+    /// blocks until all are done, calling thread works too
+    pub fn parallel_for(&self, range: Range<usize>, chunk: usize, f: impl Fn(usize) + Sync) {
+        let chunk = chunk.max(1);
+        let remaining = AtomicUsize::new(range.len().div_ceil(chunk));
+        let panicked = AtomicBool::new(false);
+        let (f, remaining_ref, panicked_ref) = (&f, &remaining, &panicked);
+        for start in range.clone().step_by(chunk) {
+            let end = (start + chunk).min(range.end);
+            let job: Box<dyn FnOnce() + Send + '_> = Box::new(move || {
+                let res = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    (start..end).for_each(f);
+                }));
+                if res.is_err() {
+                    panicked_ref.store(true, Ordering::Relaxed);
+                }
+                remaining_ref.fetch_sub(1, Ordering::Release);
+            });
+            // SAFETY: jobs only borrow locals of this fn, which waits below until every job ran
+            let job: Job = unsafe { std::mem::transmute(job) };
+            self.shared.push(job);
+        }
+        self.shared
+            .help_until(|| remaining.load(Ordering::Acquire) == 0);
+        assert!(
+            !panicked.load(Ordering::Relaxed),
+            "parallel_for job panicked"
+        );
+    }
+
+    /// like `parallel_for`, but collects results in index order
+    pub fn parallel_map<T: Send>(
+        &self,
+        range: Range<usize>,
+        chunk: usize,
+        f: impl Fn(usize) -> T + Sync,
+    ) -> Vec<T> {
+        let chunk = chunk.max(1);
+        let start = range.start;
+        let chunks = Mutex::new(Vec::new());
+        let chunk_count = range.len().div_ceil(chunk);
+        self.parallel_for(0..chunk_count, 1, |c| {
+            let lo = start + c * chunk;
+            let out: Vec<T> = (lo..(lo + chunk).min(range.end)).map(&f).collect();
+            chunks.lock().unwrap().push((c, out));
+        });
+        let mut chunks = chunks.into_inner().unwrap();
+        chunks.sort_unstable_by_key(|(c, _)| *c);
+        chunks.into_iter().flat_map(|(_, out)| out).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn jobs_test() {
+        let sum = AtomicUsize::new(0);
+        jobs().parallel_for(0..1000, 7, |i| {
+            sum.fetch_add(i, Ordering::Relaxed);
+        });
+        assert_eq!(sum.load(Ordering::Relaxed), 999 * 1000 / 2);
+
+        let squares = jobs().parallel_map(3..503, 16, |i| i * i);
+        assert!(
+            squares
+                .iter()
+                .enumerate()
+                .all(|(i, &s)| s == (i + 3) * (i + 3))
+        );
+
+        // nested work doesn't deadlock, waiting threads run queued jobs
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                jobs().spawn(move || {
+                    jobs()
+                        .parallel_map(0..64, 4, |j| i * j)
+                        .iter()
+                        .sum::<usize>()
+                })
+            })
+            .collect();
+        let total: usize = handles.into_iter().map(JobHandle::wait).sum();
+        assert_eq!(total, (0..8).sum::<usize>() * (0..64).sum::<usize>());
+
+        let failed = jobs().spawn(|| panic!("job failed"));
+        assert!(std::panic::catch_unwind(AssertUnwindSafe(|| failed.wait())).is_err());
+    }
+}
@@ -0,0 +1,487 @@
+use std::sync::LazyLock;
+
+use crate::util::ImageFormat;
+
+use super::{ImageData, ReaderBe, vfs};
+
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+// markers
+const SOF0: u8 = 0xC0; // baseline
+const SOF1: u8 = 0xC1; // extended sequential, huffman
+const SOF2: u8 = 0xC2; // progressive
+const DHT: u8 = 0xC4;
+const SOI: u8 = 0xD8;
+const EOI: u8 = 0xD9;
+const SOS: u8 = 0xDA;
+const DQT: u8 = 0xDB;
+const DRI: u8 = 0xDD;
+
+/// canonical huffman table
+#[derive(Clone)]
+struct Huffman {
+    max_code: [i32; 17],
+    val_off: [i32; 17],
+    vals: Vec<u8>,
+}
+
+impl Default for Huffman {
+    fn default() -> Self {
+        Self {
+            max_code: [-1; 17],
+            val_off: [0; 17],
+            vals: Vec::new(),
+        }
+    }
+}
+
+impl Huffman {
+    fn new(counts: &[u8; 16], vals: &[u8]) -> Self {
+        let mut max_code = [-1; 17];
+        let mut val_off = [0; 17];
+        let mut code = 0i32;
+        let mut k = 0i32;
+        for len in 1..=16 {
+            let cnt = counts[len - 1] as i32;
+            if cnt > 0 {
+                val_off[len] = k - code;
+                code += cnt;
+                k += cnt;
+                max_code[len] = code - 1;
+            }
+            code <<= 1;
+        }
+        Self {
+            max_code,
+            val_off,
+            vals: vals.to_vec(),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quant: usize,
+    dc: usize,
+    ac: usize,
+    pred: i32,
+}
+
+/// reads entropy coded bits, skipping byte stuffing
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bits: u32,
+    cnt: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self {
+            data,
+            pos,
+            bits: 0,
+            cnt: 0,
+        }
+    }
+
+    fn fill(&mut self) {
+        while self.cnt <= 24 {
+            let mut byte = 0;
+            if self.pos < self.data.len() {
+                byte = self.data[self.pos];
+                if byte == 0xFF {
+                    let next = self.data.get(self.pos + 1).copied().unwrap_or(0);
+                    if next == 0x00 {
+                        self.pos += 2;
+                    } else {
+                        // marker, feed zeros until restart() or end of scan
+                        byte = 0;
+                    }
+                } else {
+                    self.pos += 1;
+                }
+            }
+            self.bits |= (byte as u32) << (24 - self.cnt);
+            self.cnt += 8;
+        }
+    }
+
+    fn bit(&mut self) -> u32 {
+        self.bits(1)
+    }
+
+    fn bits(&mut self, n: u32) -> u32 {
+        if n == 0 {
+            return 0;
+        }
+        self.fill();
+        let v = self.bits >> (32 - n);
+        self.bits <<= n;
+        self.cnt -= n;
+        v
+    }
+
+    fn decode(&mut self, h: &Huffman) -> u8 {
+        let mut code = 0i32;
+        for len in 1..=16 {
+            code = (code << 1) | self.bit() as i32;
+            if code <= h.max_code[len] {
+                return h.vals[(h.val_off[len] + code) as usize];
+            }
+        }
+        panic!("invalid jpeg huffman code")
+    }
+
+    /// reads n bit signed value
+    fn receive_extend(&mut self, n: u32) -> i32 {
+        let v = self.bits(n) as i32;
+        if n != 0 && v < (1 << (n - 1)) {
+            v - (1 << n) + 1
+        } else {
+            v
+        }
+    }
+
+    /// skips to after restart marker
+    fn restart(&mut self) {
+        self.bits = 0;
+        self.cnt = 0;
+        while self.pos + 1 < self.data.len()
+            && !(self.data[self.pos] == 0xFF && (0xD0..=0xD7).contains(&self.data[self.pos + 1]))
+        {
+            self.pos += 1;
+        }
+        self.pos += 2;
+    }
+}
+
+/// c[u][x] = C(u) * cos((2x + 1) * u * pi / 16) / 2
+static IDCT_COS: LazyLock<[[f32; 8]; 8]> = LazyLock::new(|| {
+    let mut c = [[0.0f32; 8]; 8];
+    for (u, row) in c.iter_mut().enumerate() {
+        let cu = if u == 0 {
+            std::f32::consts::FRAC_1_SQRT_2
+        } else {
+            1.0
+        };
+        for (x, v) in row.iter_mut().enumerate() {
+            *v = cu * ((2 * x + 1) as f32 * u as f32 * std::f32::consts::PI / 16.0).cos() * 0.5;
+        }
+    }
+    c
+});
+
+fn idct(block: &mut [f32; 64]) {
+    let c = &*IDCT_COS;
+    let mut tmp = [0.0f32; 64];
+    for y in 0..8 {
+        for x in 0..8 {
+            tmp[y * 8 + x] = (0..8).map(|u| c[u][x] * block[y * 8 + u]).sum();
+        }
+    }
+    for x in 0..8 {
+        for y in 0..8 {
+            block[y * 8 + x] = (0..8).map(|v| c[v][y] * tmp[v * 8 + x]).sum();
+        }
+    }
+}
+
+pub struct Jpeg;
+
+impl Jpeg {
+    /// decodes baseline jpeg into rgba8
+    pub fn decode(jpeg: &[u8]) -> ImageData {
+        let mut reader = ReaderBe::new(jpeg);
+        assert_eq!(
+            reader.read16(),
+            0xFF00 | SOI as u16,
+            "invalid jpeg magic number"
+        );
+        let mut quants = [[0u16; 64]; 4];
+        let mut dcs = vec![Huffman::default(); 4];
+        let mut acs = vec![Huffman::default(); 4];
+        let mut comps: Vec<Component> = Vec::new();
+        let (mut width, mut height) = (0, 0);
+        let mut restart_interval = 0;
+        let mut planes: Vec<Vec<u8>> = Vec::new();
+        let (mut hmax, mut vmax, mut mcux, mut mcuy) = (1, 1, 0, 0);
+        loop {
+            let mut marker = reader.read8();
+            if marker != 0xFF {
+                continue;
+            }
+            while marker == 0xFF {
+                marker = reader.read8();
+            }
+            if marker == EOI {
+                break;
+            }
+            let len = reader.read16() as usize - 2;
+            let seg_end = reader.idx() + len;
+            match marker {
+                DQT => {
+                    while reader.idx() < seg_end {
+                        let pq_tq = reader.read8();
+                        let quant = &mut quants[(pq_tq & 15) as usize];
+                        for q in quant.iter_mut() {
+                            *q = if pq_tq >> 4 == 0 {
+                                reader.read8() as u16
+                            } else {
+                                reader.read16()
+                            };
+                        }
+                    }
+                }
+                DHT => {
+                    while reader.idx() < seg_end {
+                        let tc_th = reader.read8();
+                        let counts = reader.read_arr::<16>();
+                        let total = counts.iter().map(|&c| c as usize).sum();
+                        let h = Huffman::new(&counts, reader.read(total));
+                        if tc_th >> 4 == 0 {
+                            dcs[(tc_th & 15) as usize] = h;
+                        } else {
+                            acs[(tc_th & 15) as usize] = h;
+                        }
+                    }
+                }
+                SOF0 | SOF1 => {
+                    assert_eq!(reader.read8(), 8, "only 8 bit jpeg is supported");
+                    height = reader.read16() as usize;
+                    width = reader.read16() as usize;
+                    let num_comps = reader.read8();
+                    assert!(
+                        num_comps == 1 || num_comps == 3,
+                        "unsupported jpeg component count: {num_comps}"
+                    );
+                    for _ in 0..num_comps {
+                        let id = reader.read8();
+                        let hv = reader.read8();
+                        let quant = reader.read8() as usize;
+                        comps.push(Component {
+                            id,
+                            h: (hv >> 4) as usize,
+                            v: (hv & 15) as usize,
+                            quant,
+                            ..Default::default()
+                        });
+                    }
+                    hmax = comps.iter().map(|c| c.h).max().unwrap();
+                    vmax = comps.iter().map(|c| c.v).max().unwrap();
+                    mcux = width.div_ceil(8 * hmax);
+                    mcuy = height.div_ceil(8 * vmax);
+                    planes = comps
+                        .iter()
+                        .map(|c| vec![0; mcux * c.h * 8 * mcuy * c.v * 8])
+                        .collect();
+                }
+                SOF2 => panic!("progressive jpeg is not supported"),
+                DRI => restart_interval = reader.read16() as usize,
+                SOS => {
+                    let num_scan_comps = reader.read8() as usize;
+                    let mut scan_comps = Vec::with_capacity(num_scan_comps);
+                    for _ in 0..num_scan_comps {
+                        let id = reader.read8();
+                        let tables = reader.read8();
+                        let ci = comps
+                            .iter()
+                            .position(|c| c.id == id)
+                            .unwrap_or_else(|| panic!("jpeg component not found: {id}"));
+                        comps[ci].dc = (tables >> 4) as usize;
+                        comps[ci].ac = (tables & 15) as usize;
+                        comps[ci].pred = 0;
+                        scan_comps.push(ci);
+                    }
+                    reader.goto(seg_end);
+                    let mut bits = BitReader::new(jpeg, seg_end);
+                    // non-interleaved scans go over component's own blocks, not mcus
+                    let (units_x, units_y) = if num_scan_comps == 1 {
+                        let c = &comps[scan_comps[0]];
+                        (
+                            (width * c.h).div_ceil(hmax).div_ceil(8),
+                            (height * c.v).div_ceil(vmax).div_ceil(8),
+                        )
+                    } else {
+                        (mcux, mcuy)
+                    };
+                    let mut block = [0.0f32; 64];
+                    for unit in 0..units_x * units_y {
+                        if restart_interval != 0 && unit != 0 && unit % restart_interval == 0 {
+                            bits.restart();
+                            for &ci in scan_comps.iter() {
+                                comps[ci].pred = 0;
+                            }
+                        }
+                        let (ux, uy) = (unit % units_x, unit / units_x);
+                        for &ci in scan_comps.iter() {
+                            let c = &mut comps[ci];
+                            let (bw, bh) = if num_scan_comps == 1 {
+                                (1, 1)
+                            } else {
+                                (c.h, c.v)
+                            };
+                            let stride = mcux * c.h * 8;
+                            for by in 0..bh {
+                                for bx in 0..bw {
+                                    block.fill(0.0);
+                                    let q = &quants[c.quant];
+                                    let s = bits.decode(&dcs[c.dc]) as u32;
+                                    c.pred += bits.receive_extend(s);
+                                    block[0] = (c.pred * q[0] as i32) as f32;
+                                    let mut k = 1;
+                                    while k < 64 {
+                                        let rs = bits.decode(&acs[c.ac]);
+                                        let (r, s) = ((rs >> 4) as usize, (rs & 15) as u32);
+                                        if s == 0 {
+                                            if r != 15 {
+                                                break;
+                                            }
+                                            k += 16;
+                                            continue;
+                                        }
+                                        k += r;
+                                        if k > 63 {
+                                            break;
+                                        }
+                                        block[ZIGZAG[k]] =
+                                            (bits.receive_extend(s) * q[k] as i32) as f32;
+                                        k += 1;
+                                    }
+                                    idct(&mut block);
+                                    let x0 = (ux * bw + bx) * 8;
+                                    let y0 = (uy * bh + by) * 8;
+                                    let plane = &mut planes[ci];
+                                    for y in 0..8 {
+                                        let row = (y0 + y) * stride + x0;
+                                        if row + 8 > plane.len() {
+                                            break;
+                                        }
+                                        for x in 0..8 {
+                                            plane[row + x] =
+                                                (block[y * 8 + x] + 128.0).clamp(0.0, 255.0) as u8;
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    // continue after entropy coded data
+                    let mut pos = bits.pos.min(jpeg.len());
+                    while pos + 1 < jpeg.len()
+                        && !(jpeg[pos] == 0xFF
+                            && jpeg[pos + 1] != 0
+                            && !(0xD0..=0xD7).contains(&jpeg[pos + 1]))
+                    {
+                        pos += 1;
+                    }
+                    reader.goto(pos);
+                    continue;
+                }
+                _ => {}
+            }
+            reader.goto(seg_end);
+        }
+
+        let mut img = vec![255u8; width * height * 4];
+        let sample = |ci: usize, x: usize, y: usize| {
+            let c = &comps[ci];
+            let stride = mcux * c.h * 8;
+            planes[ci][(y * c.v / vmax) * stride + x * c.h / hmax] as f32
+        };
+        for y in 0..height {
+            for x in 0..width {
+                let i = (y * width + x) * 4;
+                if comps.len() == 1 {
+                    let l = sample(0, x, y) as u8;
+                    img[i..i + 3].copy_from_slice(&[l, l, l]);
+                } else {
+                    let (l, cb, cr) = (
+                        sample(0, x, y),
+                        sample(1, x, y) - 128.0,
+                        sample(2, x, y) - 128.0,
+                    );
+                    img[i] = (l + 1.402 * cr).clamp(0.0, 255.0) as u8;
+                    img[i + 1] = (l - 0.344136 * cb - 0.714136 * cr).clamp(0.0, 255.0) as u8;
+                    img[i + 2] = (l + 1.772 * cb).clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+        ImageData::new(img, width as u32, height as u32, 4)
+    }
+}
+
+impl ImageFormat for Jpeg {
+    fn load(name: &str) -> ImageData {
+        crate::scope_time!("JPEG load");
+        let jpeg = vfs()
+            .read(&format!("images/{name}.jpg"))
+            .unwrap_or_else(|_| panic!("jpeg image not found: {name}"));
+        Self::decode(&jpeg)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 16x16 baseline jpeg with red, green, blue and white 8x8 quadrants,
+    /// only dc coefficients are coded, so decoded colors are exact up to rounding
+    fn quadrants() -> Vec<u8> {
+        let mut jpeg = vec![0xFF, SOI, 0xFF, DQT, 0x00, 0x43, 0x00];
+        jpeg.extend([1; 64]); // dc isn't quantized
+        #[rustfmt::skip]
+        jpeg.extend([
+            0xFF, SOF0, 0x00, 0x11, 0x08, 0x00, 0x10, 0x00, 0x10, // 8 bit 16x16
+            0x03, 0x01, 0x11, 0x00, 0x02, 0x11, 0x00, 0x03, 0x11, 0x00, // ycbcr 4:4:4
+            0xFF, DHT, 0x00, 0x20, 0x00, // dc, categories 0..=12 coded in 4 bits
+            0x00, 0x00, 0x00, 0x0D, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C,
+            0xFF, DHT, 0x00, 0x14, 0x10, // ac, only eob coded in 1 bit
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00,
+            0xFF, SOS, 0x00, 0x0C, 0x03, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x00, 0x3F, 0x00,
+            0x93, 0x0A, 0x54, 0xEA, 0xFF, 0x00, 0x15, 0x25, // 0xFF is byte stuffed
+            0xA5, 0x66, 0xB1, 0x5A, 0xA0, 0xED, 0x7A, 0x81,
+            0x55, 0x82, 0xF8, 0x7A, 0x80, 0x34, 0x53, 0x3F,
+            0xFF, EOI,
+        ]);
+        jpeg
+    }
+
+    #[test]
+    fn jpeg_test() {
+        let img = Jpeg::decode(&quadrants());
+        assert_eq!((img.width, img.height, img.channels), (16, 16, 4));
+        let quadrants = [
+            (0, 0, [255, 0, 0]),
+            (8, 0, [0, 255, 0]),
+            (0, 8, [0, 0, 255]),
+            (8, 8, [255, 255, 255]),
+        ];
+        for (qx, qy, rgb) in quadrants {
+            for y in qy..qy + 8 {
+                for x in qx..qx + 8 {
+                    let i = (y * 16 + x) * 4;
+                    let px = &img.img[i..i + 4];
+                    for c in 0..3 {
+                        assert!(
+                            px[c].abs_diff(rgb[c]) <= 2,
+                            "pixel ({x}, {y}) is {px:?}, expected {rgb:?}"
+                        );
+                    }
+                    assert_eq!(px[3], 255);
+                }
+            }
+        }
+    }
+}
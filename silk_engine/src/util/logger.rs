@@ -0,0 +1,303 @@
+use std::{
+    collections::VecDeque,
+    fmt::Arguments,
+    fs::File,
+    io::Write,
+    sync::{Arc, LazyLock, Mutex, MutexGuard},
+    time::Instant,
+};
+
+use super::print::{self, INIT_LOG_FOLDER, log_path};
+
+/// last records, written into crash dumps
+static RECENT: LazyLock<LogBuffer> = LazyLock::new(|| LogBuffer::new(100));
+
+static LOGGER: LazyLock<Mutex<Logger>> = LazyLock::new(|| {
+    let mut logger = Logger::new();
+    logger.set_level(if cfg!(debug_assertions) {
+        Level::Debug
+    } else {
+        Level::Info
+    });
+    logger.add_sink(Level::Info, ConsoleSink);
+    logger.add_sink(Level::Trace, RECENT.clone());
+    #[cfg(any(debug_assertions, test))]
+    logger.add_sink(
+        Level::Trace,
+        FileSink::new(&format!("{}/debug.log", log_path()), 1 << 20, 2),
+    );
+    Mutex::new(logger)
+});
+
+/// global logger used by `log!`, `info!`, `warn!` and `err!`
+pub fn logger() -> MutexGuard<'static, Logger> {
+    LOGGER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// last 100 records of global logger, oldest first, empty if they are being written
+pub(crate) fn recent_records() -> Vec<Record> {
+    RECENT
+        .records
+        .try_lock()
+        .map(|r| r.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// called by log macros, formats only if record passes filters
+pub fn log(level: Level, target: &str, args: Arguments) {
+    let mut logger = logger();
+    if logger.enabled(level, target) {
+        logger.log(level, target, args);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    /// filter level that silences everything
+    Off,
+}
+
+#[derive(Clone, Debug)]
+pub struct Record {
+    pub level: Level,
+    /// module path, or explicit target like "vulkan"
+    pub target: String,
+    /// seconds since logger was created
+    pub time: f32,
+    pub msg: String,
+}
+
+pub trait Sink: Send {
+    fn write(&mut self, record: &Record);
+}
+
+/// colored stdout, errors go to stderr
+pub struct ConsoleSink;
+
+impl Sink for ConsoleSink {
+    fn write(&mut self, r: &Record) {
+        let target = r.target.rsplit("::").next().unwrap_or_default();
+        let head = print::dim(&format!("[{:.3} {target}]", r.time));
+        match r.level {
+            Level::Error => eprintln!("{head} {}", print::err(&r.msg)),
+            Level::Warn => println!("{head} {}", print::warn(&r.msg)),
+            Level::Info => println!("{head} {}", print::info(&r.msg)),
+            _ => println!("{head} {}", print::trace(&r.msg)),
+        }
+    }
+}
+
+/// appends to `path`, once it reaches `max_size` bytes it's moved to `path.1`
+/// (older ones shift to `path.2`...) and at most `max_files` old files are kept
+pub struct FileSink {
+    path: String,
+    max_size: u64,
+    max_files: u32,
+    file: Option<File>,
+    size: u64,
+}
+
+impl FileSink {
+    pub fn new(path: &str, max_size: u64, max_files: u32) -> Self {
+        *INIT_LOG_FOLDER;
+        let mut sink = Self {
+            path: path.to_string(),
+            max_size,
+            max_files,
+            file: None,
+            size: 0,
+        };
+        sink.open();
+        sink
+    }
+
+    fn open(&mut self) {
+        self.file = File::options()
+            .append(true)
+            .create(true)
+            .open(&self.path)
+            .ok();
+        self.size = self
+            .file
+            .as_ref()
+            .and_then(|f| f.metadata().ok())
+            .map_or(0, |m| m.len());
+    }
+
+    fn rotate(&mut self) {
+        self.file = None;
+        let old = |i: u32| format!("{}.{i}", self.path);
+        std::fs::remove_file(old(self.max_files)).unwrap_or_default();
+        for i in (1..self.max_files).rev() {
+            std::fs::rename(old(i), old(i + 1)).unwrap_or_default();
+        }
+        if self.max_files > 0 {
+            std::fs::rename(&self.path, old(1)).unwrap_or_default();
+        } else {
+            std::fs::remove_file(&self.path).unwrap_or_default();
+        }
+        self.open();
+    }
+}
+
+impl Sink for FileSink {
+    fn write(&mut self, r: &Record) {
+        if self.size >= self.max_size {
+            self.rotate();
+        }
+        let line = format!("{:.3} {:?} {}: {}\n", r.time, r.level, r.target, r.msg);
+        if let Some(file) = &mut self.file
+            && file.write_all(line.as_bytes()).is_ok()
+        {
+            self.size += line.len() as u64;
+        }
+    }
+}
+
+/// keeps last `capacity` records in memory, for showing logs in app (e.g. dev console).
+/// clones share same buffer, so one clone can be added as sink and other read from
+#[derive(Clone)]
+pub struct LogBuffer {
+    records: Arc<Mutex<VecDeque<Record>>>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// oldest first
+    pub fn records(&self) -> Vec<Record> {
+        self.records.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.records.lock().unwrap().clear();
+    }
+}
+
+impl Sink for LogBuffer {
+    fn write(&mut self, r: &Record) {
+        let mut records = self.records.lock().unwrap();
+        if records.len() >= self.capacity {
+            records.pop_front();
+        }
+        records.push_back(r.clone());
+    }
+}
+
+/// filters records by level and target, then writes them to every sink that accepts their level
+pub struct Logger {
+    level: Level,
+    /// (target prefix, level), longest matching prefix overrides global level
+    filters: Vec<(String, Level)>,
+    sinks: Vec<(Level, Box<dyn Sink>)>,
+    start: Instant,
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Logger {
+    /// no sinks, logs everything
+    pub fn new() -> Self {
+        Self {
+            level: Level::Trace,
+            filters: Vec::new(),
+            sinks: Vec::new(),
+            start: Instant::now(),
+        }
+    }
+
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    pub fn set_level(&mut self, level: Level) {
+        self.level = level;
+    }
+
+    /// targets starting with `target` use `level` instead of global one,
+    /// e.g. `filter("vulkan", Level::Error)` silences vulkan warnings
+    pub fn filter(&mut self, target: &str, level: Level) {
+        self.remove_filter(target);
+        self.filters.push((target.to_string(), level));
+        self.filters
+            .sort_by_key(|(t, _)| std::cmp::Reverse(t.len()));
+    }
+
+    pub fn remove_filter(&mut self, target: &str) {
+        self.filters.retain(|(t, _)| t != target);
+    }
+
+    /// `sink` receives records at `level` and above
+    pub fn add_sink(&mut self, level: Level, sink: impl Sink + 'static) {
+        self.sinks.push((level, Box::new(sink)));
+    }
+
+    pub fn clear_sinks(&mut self) {
+        self.sinks.clear();
+    }
+
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        let min = self
+            .filters
+            .iter()
+            .find(|(t, _)| target.starts_with(t.as_str()))
+            .map_or(self.level, |&(_, l)| l);
+        level != Level::Off && level >= min
+    }
+
+    pub fn log(&mut self, level: Level, target: &str, args: Arguments) {
+        if !self.enabled(level, target) {
+            return;
+        }
+        let record = Record {
+            level,
+            target: target.to_string(),
+            time: self.start.elapsed().as_secs_f32(),
+            msg: args.to_string(),
+        };
+        for (min, sink) in &mut self.sinks {
+            if level >= *min {
+                sink.write(&record);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn logger_test() {
+        let mut logger = Logger::new();
+        let buf = LogBuffer::new(3);
+        logger.add_sink(Level::Info, buf.clone());
+        logger.set_level(Level::Debug);
+        logger.filter("vulkan", Level::Error);
+        logger.filter("vulkan::sync", Level::Trace);
+        logger.log(Level::Trace, "app", format_args!("dropped by level"));
+        logger.log(Level::Debug, "app", format_args!("dropped by sink level"));
+        logger.log(Level::Warn, "vulkan", format_args!("dropped by filter"));
+        logger.log(Level::Info, "vulkan::sync", format_args!("a {}", 1));
+        logger.log(Level::Error, "vulkan", format_args!("b"));
+        logger.log(Level::Warn, "app", format_args!("c"));
+        let msgs: Vec<_> = buf.records().into_iter().map(|r| r.msg).collect();
+        assert_eq!(msgs, ["a 1", "b", "c"]);
+        assert!(!logger.enabled(Level::Off, "app"));
+    }
+}
@@ -0,0 +1,483 @@
+use std::{
+    ops::{Mul, MulAssign},
+    simd::f32x4,
+};
+
+use super::vec::{Vec2, Vec3, Vec4, Vectorf};
+
+const fn identity<const N: usize>() -> [[f32; N]; N] {
+    let mut m = [[0.0; N]; N];
+    let mut i = 0;
+    while i < N {
+        m[i][i] = 1.0;
+        i += 1;
+    }
+    m
+}
+
+/// gauss-jordan elimination with partial pivoting, returns (inverse, determinant)
+fn invert<const N: usize>(m: [[f32; N]; N]) -> ([[f32; N]; N], f32) {
+    let mut a = m.map(|c| c.map(|v| v as f64));
+    let mut inv = identity::<N>().map(|c| c.map(|v| v as f64));
+    let mut det = 1.0;
+    // columns are rows of transpose, inverse of transpose is transposed back at the end
+    for col in 0..N {
+        let pivot = (col..N)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .unwrap();
+        if a[pivot][col] == 0.0 {
+            return ([[f32::NAN; N]; N], 0.0);
+        }
+        if pivot != col {
+            a.swap(pivot, col);
+            inv.swap(pivot, col);
+            det = -det;
+        }
+        let p = a[col][col];
+        det *= p;
+        for k in 0..N {
+            a[col][k] /= p;
+            inv[col][k] /= p;
+        }
+        for row in 0..N {
+            if row != col {
+                let f = a[row][col];
+                for k in 0..N {
+                    a[row][k] -= f * a[col][k];
+                    inv[row][k] -= f * inv[col][k];
+                }
+            }
+        }
+    }
+    (inv.map(|c| c.map(|v| v as f32)), det as f32)
+}
+
+macro_rules! impl_mat {
+    ($ty: ident, $n: literal) => {
+        impl $ty {
+            pub const IDENTITY: Self = Self {
+                cols: identity::<$n>(),
+            };
+
+            pub const fn from_cols(cols: [[f32; $n]; $n]) -> Self {
+                Self { cols }
+            }
+
+            pub fn transpose(self) -> Self {
+                let mut cols = [[0.0; $n]; $n];
+                for c in 0..$n {
+                    for r in 0..$n {
+                        cols[r][c] = self.cols[c][r];
+                    }
+                }
+                Self { cols }
+            }
+
+            pub fn det(self) -> f32 {
+                invert(self.cols).1
+            }
+
+            /// None if matrix isn't invertible
+            pub fn inverse(self) -> Option<Self> {
+                let (cols, det) = invert(self.cols);
+                (det != 0.0).then_some(Self { cols })
+            }
+        }
+
+        impl Default for $ty {
+            fn default() -> Self {
+                Self::IDENTITY
+            }
+        }
+
+        impl MulAssign for $ty {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+    };
+}
+
+/// scalar products, `Mat4` has simd versions instead
+macro_rules! impl_mat_mul {
+    ($ty: ident, $n: literal) => {
+        impl Mul for $ty {
+            type Output = Self;
+            fn mul(self, rhs: Self) -> Self {
+                let mut cols = [[0.0; $n]; $n];
+                for c in 0..$n {
+                    for r in 0..$n {
+                        cols[c][r] = (0..$n).map(|k| self.cols[k][r] * rhs.cols[c][k]).sum();
+                    }
+                }
+                Self { cols }
+            }
+        }
+
+        impl Mul<f32> for $ty {
+            type Output = Self;
+            fn mul(self, rhs: f32) -> Self {
+                Self {
+                    cols: self.cols.map(|c| c.map(|v| v * rhs)),
+                }
+            }
+        }
+    };
+}
+
+/// column major 2x2 matrix
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat2 {
+    pub cols: [[f32; 2]; 2],
+}
+
+impl_mat!(Mat2, 2);
+impl_mat_mul!(Mat2, 2);
+
+impl Mat2 {
+    /// counter clockwise in y up space (clockwise on screen, since screen y is down)
+    pub fn rotation(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+        Self::from_cols([[c, s], [-s, c]])
+    }
+
+    pub fn scale(scale: Vec2) -> Self {
+        Self::from_cols([[scale.x, 0.0], [0.0, scale.y]])
+    }
+}
+
+impl Mul<Vec2> for Mat2 {
+    type Output = Vec2;
+    fn mul(self, v: Vec2) -> Vec2 {
+        let [c0, c1] = self.cols;
+        Vec2::new(c0[0] * v.x + c1[0] * v.y, c0[1] * v.x + c1[1] * v.y)
+    }
+}
+
+/// column major 3x3 matrix, also used as 2d affine transform
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat3 {
+    pub cols: [[f32; 3]; 3],
+}
+
+impl_mat!(Mat3, 3);
+impl_mat_mul!(Mat3, 3);
+
+impl Mat3 {
+    pub fn translation(offset: Vec2) -> Self {
+        Self::from_cols([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [offset.x, offset.y, 1.0]])
+    }
+
+    /// 2d rotation, see [`Mat2::rotation`]
+    pub fn rotation(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+        Self::from_cols([[c, s, 0.0], [-s, c, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    pub fn scale(scale: Vec2) -> Self {
+        Self::from_cols([[scale.x, 0.0, 0.0], [0.0, scale.y, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    pub fn from_quat(q: Quat) -> Self {
+        let Quat { x, y, z, w } = q;
+        let (x2, y2, z2) = (x + x, y + y, z + z);
+        let (xx, yy, zz) = (x * x2, y * y2, z * z2);
+        let (xy, xz, yz) = (x * y2, x * z2, y * z2);
+        let (wx, wy, wz) = (w * x2, w * y2, w * z2);
+        Self::from_cols([
+            [1.0 - yy - zz, xy + wz, xz - wy],
+            [xy - wz, 1.0 - xx - zz, yz + wx],
+            [xz + wy, yz - wx, 1.0 - xx - yy],
+        ])
+    }
+
+    /// applies 2d affine transform to point (translated)
+    pub fn transform_point2(self, p: Vec2) -> Vec2 {
+        let v = self * Vec3::new(p.x, p.y, 1.0);
+        Vec2::new(v.x, v.y)
+    }
+
+    /// applies 2d affine transform to direction (not translated)
+    pub fn transform_vec2(self, v: Vec2) -> Vec2 {
+        let v = self * Vec3::new(v.x, v.y, 0.0);
+        Vec2::new(v.x, v.y)
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+    fn mul(self, v: Vec3) -> Vec3 {
+        let [c0, c1, c2] = self.cols;
+        Vec3::new(
+            c0[0] * v.x + c1[0] * v.y + c2[0] * v.z,
+            c0[1] * v.x + c1[1] * v.y + c2[1] * v.z,
+            c0[2] * v.x + c1[2] * v.y + c2[2] * v.z,
+        )
+    }
+}
+
+/// column major 4x4 matrix, layout matches wgsl mat4x4f.
+/// projections are right handed (camera looks down -z), map depth to 0-1
+/// and flip y, since vulkan ndc y points down
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat4 {
+    pub cols: [[f32; 4]; 4],
+}
+
+impl_mat!(Mat4, 4);
+
+impl Mat4 {
+    pub fn translation(offset: Vec3) -> Self {
+        let mut m = Self::IDENTITY;
+        m.cols[3] = [offset.x, offset.y, offset.z, 1.0];
+        m
+    }
+
+    pub fn scale(scale: Vec3) -> Self {
+        Self::from_cols([
+            [scale.x, 0.0, 0.0, 0.0],
+            [0.0, scale.y, 0.0, 0.0],
+            [0.0, 0.0, scale.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn from_quat(q: Quat) -> Self {
+        Mat3::from_quat(q).into()
+    }
+
+    /// translation * rotation * scale
+    pub fn from_trs(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self::translation(translation) * Self::from_quat(rotation) * Self::scale(scale)
+    }
+
+    /// `fov_y` in radians
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / (fov_y * 0.5).tan();
+        let r = 1.0 / (near - far);
+        Self::from_cols([
+            [f / aspect, 0.0, 0.0, 0.0],
+            [0.0, -f, 0.0, 0.0],
+            [0.0, 0.0, far * r, -1.0],
+            [0.0, 0.0, near * far * r, 0.0],
+        ])
+    }
+
+    pub fn ortho(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let (w, h, d) = (right - left, top - bottom, far - near);
+        Self::from_cols([
+            [2.0 / w, 0.0, 0.0, 0.0],
+            [0.0, -2.0 / h, 0.0, 0.0],
+            [0.0, 0.0, -1.0 / d, 0.0],
+            [-(right + left) / w, (top + bottom) / h, -near / d, 1.0],
+        ])
+    }
+
+    /// view matrix of camera at `eye` looking at `target`
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let f = (target - eye).norm();
+        let s = f.cross(up).norm();
+        let u = s.cross(f);
+        Self::from_cols([
+            [s.x, u.x, -f.x, 0.0],
+            [s.y, u.y, -f.y, 0.0],
+            [s.z, u.z, -f.z, 0.0],
+            [-s.dot(eye), -u.dot(eye), f.dot(eye), 1.0],
+        ])
+    }
+
+    /// transforms point with perspective divide
+    pub fn transform_point3(self, p: Vec3) -> Vec3 {
+        let [x, y, z, w] = self * [p.x, p.y, p.z, 1.0];
+        Vec3::new(x, y, z) / w
+    }
+
+    /// transforms direction, ignoring translation
+    pub fn transform_vec3(self, v: Vec3) -> Vec3 {
+        let [x, y, z, _] = self * [v.x, v.y, v.z, 0.0];
+        Vec3::new(x, y, z)
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_cols(rhs.cols.map(|c| (self * Vec4::from(c)).into()))
+    }
+}
+
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+    #[inline]
+    fn mul(self, v: Vec4) -> Vec4 {
+        let [c0, c1, c2, c3] = self.cols.map(f32x4::from_array);
+        let v = v.simd();
+        Vec4::from_simd(
+            c0 * f32x4::splat(v[0])
+                + c1 * f32x4::splat(v[1])
+                + c2 * f32x4::splat(v[2])
+                + c3 * f32x4::splat(v[3]),
+        )
+    }
+}
+
+impl Mul<[f32; 4]> for Mat4 {
+    type Output = [f32; 4];
+    fn mul(self, v: [f32; 4]) -> [f32; 4] {
+        (self * Vec4::from(v)).into()
+    }
+}
+
+impl Mul<f32> for Mat4 {
+    type Output = Self;
+    fn mul(self, rhs: f32) -> Self {
+        let rhs = f32x4::splat(rhs);
+        Self::from_cols(self.cols.map(|c| (f32x4::from_array(c) * rhs).to_array()))
+    }
+}
+
+impl From<Mat3> for Mat4 {
+    fn from(m: Mat3) -> Self {
+        let [c0, c1, c2] = m.cols;
+        Self::from_cols([
+            [c0[0], c0[1], c0[2], 0.0],
+            [c1[0], c1[1], c1[2], 0.0],
+            [c2[0], c2[1], c2[2], 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+}
+
+/// rotation quaternion, w is real part
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quat {
+    pub const IDENTITY: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    /// rotation by `angle` radians around normalized `axis`
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let (s, c) = (angle * 0.5).sin_cos();
+        Self::new(axis.x * s, axis.y * s, axis.z * s, c)
+    }
+
+    pub fn dot(self, rhs: Self) -> f32 {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z + self.w * rhs.w
+    }
+
+    pub fn len(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn norm(self) -> Self {
+        let l = 1.0 / self.len();
+        Self::new(self.x * l, self.y * l, self.z * l, self.w * l)
+    }
+
+    pub fn conjugate(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn inverse(self) -> Self {
+        let c = self.conjugate();
+        let l2 = 1.0 / self.dot(self);
+        Self::new(c.x * l2, c.y * l2, c.z * l2, c.w * l2)
+    }
+
+    /// spherical interpolation along shortest path
+    pub fn slerp(self, mut rhs: Self, t: f32) -> Self {
+        let mut cos = self.dot(rhs);
+        if cos < 0.0 {
+            rhs = Self::new(-rhs.x, -rhs.y, -rhs.z, -rhs.w);
+            cos = -cos;
+        }
+        let (a, b) = if cos > 0.9995 {
+            // nearly parallel, lerp avoids division by ~0
+            (1.0 - t, t)
+        } else {
+            let theta = cos.acos();
+            let sin = theta.sin();
+            (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+        };
+        Self::new(
+            self.x * a + rhs.x * b,
+            self.y * a + rhs.y * b,
+            self.z * a + rhs.z * b,
+            self.w * a + rhs.w * b,
+        )
+        .norm()
+    }
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Quat {
+    type Output = Self;
+    fn mul(self, r: Self) -> Self {
+        Self::new(
+            self.w * r.x + self.x * r.w + self.y * r.z - self.z * r.y,
+            self.w * r.y - self.x * r.z + self.y * r.w + self.z * r.x,
+            self.w * r.z + self.x * r.y - self.y * r.x + self.z * r.w,
+            self.w * r.w - self.x * r.x - self.y * r.y - self.z * r.z,
+        )
+    }
+}
+
+impl Mul<Vec3> for Quat {
+    type Output = Vec3;
+    fn mul(self, v: Vec3) -> Vec3 {
+        let q = Vec3::new(self.x, self.y, self.z);
+        let t = q.cross(v) * 2.0;
+        v + t * self.w + q.cross(t)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mat_test() {
+        let close = |a: Mat4, b: Mat4| {
+            a.cols
+                .iter()
+                .flatten()
+                .zip(b.cols.iter().flatten())
+                .all(|(a, b)| (a - b).abs() < 1e-4)
+        };
+        let q = Quat::from_axis_angle(Vec3::new(0.0, 0.6, 0.8), 1.2);
+        let m = Mat4::from_trs(Vec3::new(1.0, -2.0, 3.0), q, Vec3::new(2.0, 2.0, 0.5));
+        assert!(close(m * m.inverse().unwrap(), Mat4::IDENTITY));
+        assert!((m.det() - 2.0).abs() < 1e-4);
+        assert!(Mat4::scale(Vec3::ZERO).inverse().is_none());
+
+        let v = Vec3::new(0.3, -1.0, 2.0);
+        let (a, b) = (q * v, Mat3::from_quat(q) * v);
+        assert!((a - b).len() < 1e-5);
+        assert!((q.inverse() * a - v).len() < 1e-5);
+
+        let p = Mat4::perspective(1.0, 1.5, 0.1, 100.0);
+        assert!((p.transform_point3(Vec3::new(0.0, 0.0, -0.1)).z).abs() < 1e-5);
+        assert!((p.transform_point3(Vec3::new(0.0, 0.0, -100.0)).z - 1.0).abs() < 1e-5);
+        let view = Mat4::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+        assert!((view.transform_point3(Vec3::ZERO) - Vec3::new(0.0, 0.0, -5.0)).len() < 1e-5);
+    }
+}
@@ -0,0 +1,126 @@
+use std::{fs::File, ops::Deref, path::Path};
+
+/// smaller files are just read, mapping them costs more than copying
+const MMAP_MIN_SIZE: u64 = 256 * 1024;
+
+#[cfg(all(unix, target_pointer_width = "64"))]
+mod sys {
+    use std::ffi::c_void;
+
+    pub const PROT_READ: i32 = 1;
+    pub const MAP_PRIVATE: i32 = 2;
+    pub const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+    unsafe extern "C" {
+        pub fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: i32,
+            flags: i32,
+            fd: i32,
+            off: i64,
+        ) -> *mut c_void;
+        pub fn munmap(addr: *mut c_void, len: usize) -> i32;
+    }
+}
+
+/// read-only file contents, large files are memory mapped so decoding them
+/// doesn't need file bytes and decoded data in ram at the same time,
+/// falls back to reading whole file if platform has no mmap or mapping fails.
+/// mapped file shouldn't be modified while this is alive
+pub enum FileBytes {
+    Mapped { ptr: *const u8, len: usize },
+    Read(Vec<u8>),
+}
+
+unsafe impl Send for FileBytes {}
+unsafe impl Sync for FileBytes {}
+
+impl FileBytes {
+    pub fn read(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len >= MMAP_MIN_SIZE
+            && let Some(mapped) = Self::map(&file, len as usize)
+        {
+            return Ok(mapped);
+        }
+        let mut bytes = Vec::with_capacity(len as usize);
+        std::io::Read::read_to_end(&mut &file, &mut bytes)?;
+        Ok(Self::Read(bytes))
+    }
+
+    #[cfg(all(unix, target_pointer_width = "64"))]
+    fn map(file: &File, len: usize) -> Option<Self> {
+        use std::os::fd::AsRawFd;
+        let ptr = unsafe {
+            sys::mmap(
+                std::ptr::null_mut(),
+                len,
+                sys::PROT_READ,
+                sys::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        (ptr != sys::MAP_FAILED).then_some(Self::Mapped {
+            ptr: ptr as *const u8,
+            len,
+        })
+    }
+
+    #[cfg(not(all(unix, target_pointer_width = "64")))]
+    fn map(_file: &File, _len: usize) -> Option<Self> {
+        None
+    }
+
+    pub fn is_mapped(&self) -> bool {
+        matches!(self, Self::Mapped { .. })
+    }
+}
+
+impl Deref for FileBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Mapped { ptr, len } => unsafe { std::slice::from_raw_parts(*ptr, *len) },
+            Self::Read(bytes) => bytes,
+        }
+    }
+}
+
+impl Drop for FileBytes {
+    fn drop(&mut self) {
+        #[cfg(all(unix, target_pointer_width = "64"))]
+        if let Self::Mapped { ptr, len } = *self {
+            unsafe { sys::munmap(ptr as *mut _, len) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{INIT_PATHS, RES_PATH};
+
+    #[test]
+    fn mmap_test() {
+        *INIT_PATHS;
+        let small = format!("{RES_PATH}/mmap_small.bin");
+        let large = format!("{RES_PATH}/mmap_large.bin");
+        let data: Vec<u8> = (0..MMAP_MIN_SIZE * 2).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&small, &data[..100]).unwrap();
+        std::fs::write(&large, &data).unwrap();
+        let small_bytes = FileBytes::read(&small).unwrap();
+        let large_bytes = FileBytes::read(&large).unwrap();
+        assert!(!small_bytes.is_mapped());
+        #[cfg(all(unix, target_pointer_width = "64"))]
+        assert!(large_bytes.is_mapped());
+        assert_eq!(&small_bytes[..], &data[..100]);
+        assert_eq!(&large_bytes[..], &data[..]);
+        drop(large_bytes);
+        std::fs::remove_file(small).unwrap();
+        std::fs::remove_file(large).unwrap();
+    }
+}
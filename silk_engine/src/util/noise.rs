@@ -0,0 +1,222 @@
+//! gradient noise (perlin/simplex), exposed through [`Noise`](super::Noise).
+//! lattice points are hashed with [`Rand`], results are roughly in [-1, 1]
+
+use super::{
+    Rand,
+    vec::{Vec2, Vec3},
+};
+
+const F2: f32 = 0.366_025_4; // (sqrt(3) - 1) / 2
+const G2: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+const F3: f32 = 1.0 / 3.0;
+const G3: f32 = 1.0 / 6.0;
+
+fn hash1(i: i32) -> u32 {
+    (i as u32).rand()
+}
+
+fn hash2(i: i32, j: i32) -> u32 {
+    (hash1(i) ^ j as u32).rand()
+}
+
+fn hash3(i: i32, j: i32, k: i32) -> u32 {
+    (hash2(i, j) ^ k as u32).rand()
+}
+
+/// quintic, has zero 1st and 2nd derivatives at 0 and 1
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn grad1(h: u32, x: f32) -> f32 {
+    let g = 1.0 + (h & 7) as f32;
+    if h & 8 == 0 { g * x } else { -g * x }
+}
+
+fn grad2(h: u32, x: f32, y: f32) -> f32 {
+    match h & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+/// 12 cube edge directions, as in improved perlin noise
+fn grad3(h: u32, x: f32, y: f32, z: f32) -> f32 {
+    let h = h & 15;
+    let u = if h < 8 { x } else { y };
+    let v = match h {
+        0..4 => y,
+        12 | 14 => x,
+        _ => z,
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+pub(super) fn perlin1(x: f32) -> f32 {
+    let i = x.floor();
+    let f = x - i;
+    let i = i as i32;
+    // grad1 slopes go up to 8, scaled back down to [-1, 1]
+    lerp(grad1(hash1(i), f), grad1(hash1(i + 1), f - 1.0), fade(f)) * 0.25
+}
+
+pub(super) fn perlin2(p: Vec2) -> f32 {
+    let (ix, iy) = (p.x.floor(), p.y.floor());
+    let (fx, fy) = (p.x - ix, p.y - iy);
+    let (ix, iy) = (ix as i32, iy as i32);
+    let (u, v) = (fade(fx), fade(fy));
+    let a = lerp(
+        grad2(hash2(ix, iy), fx, fy),
+        grad2(hash2(ix + 1, iy), fx - 1.0, fy),
+        u,
+    );
+    let b = lerp(
+        grad2(hash2(ix, iy + 1), fx, fy - 1.0),
+        grad2(hash2(ix + 1, iy + 1), fx - 1.0, fy - 1.0),
+        u,
+    );
+    lerp(a, b, v)
+}
+
+pub(super) fn perlin3(p: Vec3) -> f32 {
+    let (ix, iy, iz) = (p.x.floor(), p.y.floor(), p.z.floor());
+    let (fx, fy, fz) = (p.x - ix, p.y - iy, p.z - iz);
+    let (ix, iy, iz) = (ix as i32, iy as i32, iz as i32);
+    let (u, v, w) = (fade(fx), fade(fy), fade(fz));
+    let corner = |dx: i32, dy: i32, dz: i32| {
+        let h = hash3(ix + dx, iy + dy, iz + dz);
+        grad3(h, fx - dx as f32, fy - dy as f32, fz - dz as f32)
+    };
+    let z0 = lerp(
+        lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+        lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+        v,
+    );
+    let z1 = lerp(
+        lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+        lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+        v,
+    );
+    lerp(z0, z1, w)
+}
+
+pub(super) fn simplex1(x: f32) -> f32 {
+    let i = x.floor();
+    let x0 = x - i;
+    let x1 = x0 - 1.0;
+    let i = i as i32;
+    let k = |x: f32| (1.0 - x * x).powi(4);
+    0.395 * (k(x0) * grad1(hash1(i), x0) + k(x1) * grad1(hash1(i + 1), x1))
+}
+
+pub(super) fn simplex2(p: Vec2) -> f32 {
+    // skew to find simplex cell, then unskew back to get offsets from its corners
+    let s = (p.x + p.y) * F2;
+    let (i, j) = ((p.x + s).floor(), (p.y + s).floor());
+    let t = (i + j) * G2;
+    let (x0, y0) = (p.x - (i - t), p.y - (j - t));
+    let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+    let (i, j) = (i as i32, j as i32);
+    let corner = |x: f32, y: f32, h: u32| {
+        let t = 0.5 - x * x - y * y;
+        if t < 0.0 {
+            0.0
+        } else {
+            t.powi(4) * grad2(h, x, y)
+        }
+    };
+    let n0 = corner(x0, y0, hash2(i, j));
+    let n1 = corner(
+        x0 - i1 as f32 + G2,
+        y0 - j1 as f32 + G2,
+        hash2(i + i1, j + j1),
+    );
+    let n2 = corner(
+        x0 - 1.0 + 2.0 * G2,
+        y0 - 1.0 + 2.0 * G2,
+        hash2(i + 1, j + 1),
+    );
+    70.0 * (n0 + n1 + n2)
+}
+
+pub(super) fn simplex3(p: Vec3) -> f32 {
+    let s = (p.x + p.y + p.z) * F3;
+    let (i, j, k) = ((p.x + s).floor(), (p.y + s).floor(), (p.z + s).floor());
+    let t = (i + j + k) * G3;
+    let (x0, y0, z0) = (p.x - (i - t), p.y - (j - t), p.z - (k - t));
+    // which of the 6 tetrahedra of the skewed cube the point is in
+    let (o1, o2) = if x0 >= y0 {
+        if y0 >= z0 {
+            ((1, 0, 0), (1, 1, 0))
+        } else if x0 >= z0 {
+            ((1, 0, 0), (1, 0, 1))
+        } else {
+            ((0, 0, 1), (1, 0, 1))
+        }
+    } else if y0 < z0 {
+        ((0, 0, 1), (0, 1, 1))
+    } else if x0 < z0 {
+        ((0, 1, 0), (0, 1, 1))
+    } else {
+        ((0, 1, 0), (1, 1, 0))
+    };
+    let (i, j, k) = (i as i32, j as i32, k as i32);
+    let corner = |(di, dj, dk): (i32, i32, i32), n: f32| {
+        let (x, y, z) = (
+            x0 - di as f32 + n * G3,
+            y0 - dj as f32 + n * G3,
+            z0 - dk as f32 + n * G3,
+        );
+        let t = 0.6 - x * x - y * y - z * z;
+        if t < 0.0 {
+            0.0
+        } else {
+            t.powi(4) * grad3(hash3(i + di, j + dj, k + dk), x, y, z)
+        }
+    };
+    32.0 * (corner((0, 0, 0), 0.0) + corner(o1, 1.0) + corner(o2, 2.0) + corner((1, 1, 1), 3.0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::Vectorf;
+
+    #[test]
+    fn noise_test() {
+        for i in 0..4096 {
+            let x = i as f32 * 0.173 - 300.0;
+            let (p2, p3) = (Vec2::new(x, x * 0.7 + 1.3), Vec3::new(x, -x * 0.3, x * 1.1));
+            for n in [
+                perlin1(x),
+                perlin2(p2),
+                perlin3(p3),
+                simplex1(x),
+                simplex2(p2),
+                simplex3(p3),
+            ] {
+                assert!((-1.01..=1.01).contains(&n), "noise out of range: {n}");
+            }
+            // lattice points are zero crossings for gradient noise
+            assert_eq!(perlin2(p2.floor()), 0.0);
+            // continuous, small steps give small changes
+            let d = 1e-3;
+            assert!((perlin3(p3) - perlin3(p3 + d)).abs() < 0.05);
+            assert!((simplex2(p2) - simplex2(p2 + d)).abs() < 0.05);
+        }
+        assert_eq!(
+            simplex3(Vec3::new(1.5, 2.5, 3.5)),
+            simplex3(Vec3::new(1.5, 2.5, 3.5))
+        );
+    }
+}
@@ -44,30 +44,45 @@ macro_rules! fatal {
 
 #[macro_export]
 macro_rules! err {
+    (target: $target:expr, $($args:tt)*) => {
+        $crate::util::logger::log(
+            $crate::util::logger::Level::Error,
+            $target,
+            format_args!("{}\n{}", format_args!($($args)*), $crate::util::print::backtrace(1)),
+        )
+    };
     ($($args:tt)*) => {
-        eprintln!("\x1b[38;2;241;76;76m{}\x1b[0m\n\x1b[2m{}\x1b[0m", format_args!($($args)*),
-            $crate::util::print::backtrace(1))
+        $crate::err!(target: module_path!(), $($args)*)
     };
 }
 
 #[macro_export]
 macro_rules! warn {
+    (target: $target:expr, $($args:tt)*) => {
+        $crate::util::logger::log($crate::util::logger::Level::Warn, $target, format_args!($($args)*))
+    };
     ($($args:tt)*) => {
-        println!("\x1b[38;2;240;230;80m{}\x1b[0m", format_args!($($args)*))
+        $crate::warn!(target: module_path!(), $($args)*)
     };
 }
 
 #[macro_export]
 macro_rules! info {
+    (target: $target:expr, $($args:tt)*) => {
+        $crate::util::logger::log($crate::util::logger::Level::Info, $target, format_args!($($args)*))
+    };
     ($($args:tt)*) => {
-        println!("\x1b[38;2;41;184;219m{}\x1b[0m", format_args!($($args)*))
+        $crate::info!(target: module_path!(), $($args)*)
     };
 }
 
 #[macro_export]
 macro_rules! trace {
+    (target: $target:expr, $($args:tt)*) => {
+        $crate::util::logger::log($crate::util::logger::Level::Trace, $target, format_args!($($args)*))
+    };
     ($($args:tt)*) => {
-        println!("{}", print::trace(&format!($($args),*)))
+        $crate::trace!(target: module_path!(), $($args)*)
     };
 }
 
@@ -151,11 +166,15 @@ macro_rules! log_file {
     };
 }
 
+/// debug level, goes to `logs/debug.log` by default
 #[macro_export]
 macro_rules! log {
+    (target: $target:expr, $($args:tt)*) => {
+        $crate::util::logger::log($crate::util::logger::Level::Debug, $target, format_args!($($args)*))
+    };
     ($($args:tt)*) => {
-        $crate::log_file!([$crate::util::print::log_path() + "/debug.log"].concat(), $($args)*);
-    }
+        $crate::log!(target: module_path!(), $($args)*)
+    };
 }
 
 #[macro_export]
@@ -1,6 +1,9 @@
-use crate::{RES_PATH, util::ImageFormat};
+use crate::{
+    RES_PATH,
+    util::{ImageFormat, ImageSave},
+};
 
-use super::ImageData;
+use super::{ImageData, vfs};
 
 const MAX_PIXELS: u32 = 400_000_000;
 const SRGB: u8 = 0;
@@ -29,8 +32,9 @@ impl Qoi {
 impl ImageFormat for Qoi {
     fn load(name: &str) -> ImageData {
         crate::scope_time!("QOI load");
-        let path = format!("{RES_PATH}/images/{name}.qoi");
-        let qoi = std::fs::read(path).unwrap_or_else(|_| panic!("qoi image not found: {name}"));
+        let qoi = vfs()
+            .read(&format!("images/{name}.qoi"))
+            .unwrap_or_else(|_| panic!("qoi image not found: {name}"));
         assert_eq!(
             &qoi[0..4],
             b"qoif",
@@ -145,7 +149,9 @@ impl ImageFormat for Qoi {
         );
         ImageData::new(img, width, height, channels as u8)
     }
+}
 
+impl ImageSave for Qoi {
     fn save(name: &str, img: &[u8], width: u32, height: u32, channels: u8) {
         crate::scope_time!("QOI save");
         let pixels = width * height;
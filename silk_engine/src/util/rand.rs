@@ -1,6 +1,6 @@
 use std::ops::Add;
 
-use super::ExtraFns;
+use super::{ExtraFns, noise};
 
 pub trait Rand: Sized {
     fn rand(self) -> Self;
@@ -214,16 +214,122 @@ impl Rand for f64 {
     }
 }
 
+/// named seedable random sequence, counter based so it only advances when drawn from,
+/// streams with same seed but different names are independent
+#[derive(Clone, Debug)]
+pub struct RngStream {
+    seed: u64,
+    key: u64,
+    counter: u64,
+}
+
+impl RngStream {
+    pub fn new(name: &str, seed: u64) -> Self {
+        // fnv-1a, so stream key is stable across runs and platforms
+        let name_hash = name.bytes().fold(0xCBF29CE484222325u64, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x100000001B3)
+        });
+        Self {
+            seed,
+            key: (seed ^ name_hash).rand(),
+            counter: 0,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// numbers drawn so far, can be saved and restored with `set_position`
+    pub fn position(&self) -> u64 {
+        self.counter
+    }
+
+    pub fn set_position(&mut self, position: u64) {
+        self.counter = position;
+    }
+
+    /// restarts sequence from the beginning
+    pub fn reset(&mut self) {
+        self.counter = 0;
+    }
+
+    /// independent child stream, derived from this stream's seed and name
+    pub fn fork(&self, name: &str) -> Self {
+        let mut child = Self::new(name, self.seed);
+        child.key = (child.key ^ self.key).rand();
+        child
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let x = (self.key ^ self.counter.wrapping_mul(0x9E3779B97F4A7C15)).rand();
+        self.counter += 1;
+        x
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// in [min, max)
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// in [min, max)
+    pub fn range_u32(&mut self, min: u32, max: u32) -> u32 {
+        assert!(min < max, "empty rng range: {min}..{max}");
+        min + ((self.next_u32() as u64 * (max - min) as u64) >> 32) as u32
+    }
+
+    /// true with probability `p`
+    pub fn chance(&mut self, p: f32) -> bool {
+        self.next_f32() < p
+    }
+
+    /// normally distributed with mean 0 and stdev 1
+    pub fn randn(&mut self) -> f32 {
+        let a = 1.0 - self.next_f32();
+        let b = self.next_f32();
+        (-2.0 * a.ln()).sqrt() * (2.0 * std::f32::consts::PI * b).cos()
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range_u32(0, items.len() as u32) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.range_u32(0, i as u32 + 1) as usize);
+        }
+    }
+}
+
 pub trait Noise: Sized + ExtraFns + Copy + From<f32> + std::ops::MulAssign {
     fn hash(self) -> f32;
+    /// value noise in [0, 1]
     fn noise(self) -> f32;
+    /// gradient noise in [-1, 1]
+    fn perlin(self) -> f32;
+    /// gradient noise on simplex grid in [-1, 1], less directional artifacts than perlin
+    fn simplex(self) -> f32;
     fn fbm(self, oct: u32) -> f32 {
+        self.fbm_with(oct, Self::noise)
+    }
+    /// sums `oct` octaves of `noise`, each with double frequency and half amplitude,
+    /// e.g. `p.fbm_with(5, Noise::simplex)`
+    fn fbm_with(self, oct: u32, noise: impl Fn(Self) -> f32) -> f32 {
         let mut s = 0.0;
         let mut m = 0.0;
         let mut a = 0.5;
         let mut p = self;
         for _ in 0..oct {
-            s += a * p.noise();
+            s += a * noise(p);
             m += a;
             a *= 0.5;
             p *= Self::from(2.0);
@@ -245,4 +351,38 @@ impl Noise for f32 {
         let fr = self.abs().fract();
         fl.rand().lerp((fl + 1.0).rand(), fr.smooth())
     }
+
+    fn perlin(self) -> f32 {
+        noise::perlin1(self)
+    }
+
+    fn simplex(self) -> f32 {
+        noise::simplex1(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rng_stream_test() {
+        let mut a = RngStream::new("particles", 7);
+        let mut b = RngStream::new("particles", 7);
+        let mut other = RngStream::new("ai", 7);
+        let first: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+        // drawing from other streams doesn't change sequence
+        for _ in 0..5 {
+            other.next_u64();
+        }
+        let second: Vec<u64> = (0..8).map(|_| b.next_u64()).collect();
+        assert_eq!(first, second);
+        assert_ne!(first[0], RngStream::new("ai", 7).next_u64());
+        a.set_position(3);
+        assert_eq!(a.next_u64(), first[3]);
+        for _ in 0..1000 {
+            let x = a.range(-1.0, 1.0);
+            assert!((-1.0..1.0).contains(&x));
+        }
+    }
 }
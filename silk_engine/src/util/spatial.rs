@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+
+use super::{Vec2, Vec2u, Vec3u};
+
+/// spreads 32 bits so there is 1 zero bit between each
+fn part1by1(x: u32) -> u64 {
+    let mut x = x as u64;
+    x = (x | (x << 16)) & 0x0000FFFF0000FFFF;
+    x = (x | (x << 8)) & 0x00FF00FF00FF00FF;
+    x = (x | (x << 4)) & 0x0F0F0F0F0F0F0F0F;
+    x = (x | (x << 2)) & 0x3333333333333333;
+    (x | (x << 1)) & 0x5555555555555555
+}
+
+fn compact1by1(x: u64) -> u32 {
+    let mut x = x & 0x5555555555555555;
+    x = (x | (x >> 1)) & 0x3333333333333333;
+    x = (x | (x >> 2)) & 0x0F0F0F0F0F0F0F0F;
+    x = (x | (x >> 4)) & 0x00FF00FF00FF00FF;
+    x = (x | (x >> 8)) & 0x0000FFFF0000FFFF;
+    ((x | (x >> 16)) & 0xFFFFFFFF) as u32
+}
+
+/// spreads low 21 bits so there are 2 zero bits between each
+fn part1by2(x: u32) -> u64 {
+    let mut x = x as u64 & 0x1FFFFF;
+    x = (x | (x << 32)) & 0x001F00000000FFFF;
+    x = (x | (x << 16)) & 0x001F0000FF0000FF;
+    x = (x | (x << 8)) & 0x100F00F00F00F00F;
+    x = (x | (x << 4)) & 0x10C30C30C30C30C3;
+    (x | (x << 2)) & 0x1249249249249249
+}
+
+fn compact1by2(x: u64) -> u32 {
+    let mut x = x & 0x1249249249249249;
+    x = (x | (x >> 2)) & 0x10C30C30C30C30C3;
+    x = (x | (x >> 4)) & 0x100F00F00F00F00F;
+    x = (x | (x >> 8)) & 0x001F0000FF0000FF;
+    x = (x | (x >> 16)) & 0x001F00000000FFFF;
+    ((x | (x >> 32)) & 0x1FFFFF) as u32
+}
+
+impl Vec2u {
+    /// z-order index, nearby points have nearby indices
+    pub fn morton(self) -> u64 {
+        part1by1(self.x) | (part1by1(self.y) << 1)
+    }
+
+    pub fn from_morton(m: u64) -> Self {
+        Self::new(compact1by1(m), compact1by1(m >> 1))
+    }
+}
+
+impl Vec3u {
+    /// z-order index, only low 21 bits of each component are used
+    pub fn morton(self) -> u64 {
+        part1by2(self.x) | (part1by2(self.y) << 1) | (part1by2(self.z) << 2)
+    }
+
+    pub fn from_morton(m: u64) -> Self {
+        Self::new(compact1by2(m), compact1by2(m >> 1), compact1by2(m >> 2))
+    }
+}
+
+/// axis aligned bounding box
+#[derive(Clone, Copy)]
+pub struct Aabb {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Aabb {
+    pub const fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+
+    pub const fn xywh(x: f32, y: f32, w: f32, h: f32) -> Self {
+        Self::new(Vec2::new(x, y), Vec2::new(x + w, y + h))
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    pub fn contains(&self, other: &Self) -> bool {
+        self.min.x <= other.min.x
+            && self.min.y <= other.min.y
+            && other.max.x <= self.max.x
+            && other.max.y <= self.max.y
+    }
+
+    pub fn contains_point(&self, p: Vec2) -> bool {
+        self.min.x <= p.x && p.x <= self.max.x && self.min.y <= p.y && p.y <= self.max.y
+    }
+}
+
+/// uniform grid of buckets, good for many similarly sized objects
+pub struct SpatialHash<T> {
+    cell_size: f32,
+    items: Vec<(T, Aabb)>,
+    cells: HashMap<(i32, i32), Vec<u32>>,
+}
+
+impl<T> SpatialHash<T> {
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "spatial hash cell size must be positive");
+        Self {
+            cell_size,
+            items: Vec::new(),
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell_range(&self, aabb: &Aabb) -> ([i32; 2], [i32; 2]) {
+        let cell = |v: f32| (v / self.cell_size).floor() as i32;
+        (
+            [cell(aabb.min.x), cell(aabb.min.y)],
+            [cell(aabb.max.x), cell(aabb.max.y)],
+        )
+    }
+
+    pub fn insert(&mut self, item: T, aabb: Aabb) {
+        let idx = self.items.len() as u32;
+        let (min, max) = self.cell_range(&aabb);
+        for y in min[1]..=max[1] {
+            for x in min[0]..=max[0] {
+                self.cells.entry((x, y)).or_default().push(idx);
+            }
+        }
+        self.items.push((item, aabb));
+    }
+
+    /// items overlapping `aabb`, in insertion order
+    pub fn query(&self, aabb: Aabb) -> Vec<&T> {
+        let (min, max) = self.cell_range(&aabb);
+        let mut idxs = Vec::new();
+        for y in min[1]..=max[1] {
+            for x in min[0]..=max[0] {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    idxs.extend_from_slice(cell);
+                }
+            }
+        }
+        idxs.sort_unstable();
+        idxs.dedup();
+        idxs.into_iter()
+            .map(|i| &self.items[i as usize])
+            .filter(|(_, item_aabb)| item_aabb.overlaps(&aabb))
+            .map(|(item, _)| item)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// keeps allocated buckets, so rebuilding every frame doesn't reallocate
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.cells.values_mut().for_each(Vec::clear);
+    }
+}
+
+struct QuadNode {
+    aabb: Aabb,
+    /// items that don't fit in single child
+    items: Vec<u32>,
+    children: Option<Box<[QuadNode; 4]>>,
+}
+
+impl QuadNode {
+    fn new(aabb: Aabb) -> Self {
+        Self {
+            aabb,
+            items: Vec::new(),
+            children: None,
+        }
+    }
+}
+
+/// adaptive tree for objects of varying size and uneven distribution
+pub struct Quadtree<T> {
+    root: QuadNode,
+    items: Vec<(T, Aabb)>,
+    /// node splits when it has more items than this
+    max_items: usize,
+    max_depth: u32,
+}
+
+impl<T> Quadtree<T> {
+    pub fn new(bounds: Aabb) -> Self {
+        Self {
+            root: QuadNode::new(bounds),
+            items: Vec::new(),
+            max_items: 8,
+            max_depth: 8,
+        }
+    }
+
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.max_items = max_items;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// items outside bounds are stored in root
+    pub fn insert(&mut self, item: T, aabb: Aabb) {
+        let idx = self.items.len() as u32;
+        self.items.push((item, aabb));
+        Self::insert_node(
+            &mut self.root,
+            &self.items,
+            idx,
+            0,
+            self.max_items,
+            self.max_depth,
+        );
+    }
+
+    fn child_for(node: &QuadNode, aabb: &Aabb) -> Option<usize> {
+        node.children
+            .as_ref()?
+            .iter()
+            .position(|c| c.aabb.contains(aabb))
+    }
+
+    fn insert_node(
+        node: &mut QuadNode,
+        items: &[(T, Aabb)],
+        idx: u32,
+        depth: u32,
+        max_items: usize,
+        max_depth: u32,
+    ) {
+        if let Some(i) = Self::child_for(node, &items[idx as usize].1) {
+            let child = &mut node.children.as_mut().unwrap()[i];
+            Self::insert_node(child, items, idx, depth + 1, max_items, max_depth);
+            return;
+        }
+        node.items.push(idx);
+        if node.children.is_none() && node.items.len() > max_items && depth < max_depth {
+            let (min, c, max) = (node.aabb.min, node.aabb.center(), node.aabb.max);
+            node.children = Some(Box::new([
+                QuadNode::new(Aabb::new(min, c)),
+                QuadNode::new(Aabb::new(Vec2::new(c.x, min.y), Vec2::new(max.x, c.y))),
+                QuadNode::new(Aabb::new(Vec2::new(min.x, c.y), Vec2::new(c.x, max.y))),
+                QuadNode::new(Aabb::new(c, max)),
+            ]));
+            // push down items that fit in a child
+            for idx in std::mem::take(&mut node.items) {
+                Self::insert_node(node, items, idx, depth, max_items, max_depth);
+            }
+        }
+    }
+
+    pub fn query(&self, aabb: Aabb) -> Vec<&T> {
+        let mut result = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            for &idx in &node.items {
+                let (item, item_aabb) = &self.items[idx as usize];
+                if item_aabb.overlaps(&aabb) {
+                    result.push(item);
+                }
+            }
+            if let Some(children) = &node.children {
+                stack.extend(children.iter().filter(|c| c.aabb.overlaps(&aabb)));
+            }
+        }
+        result
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.root = QuadNode::new(self.root.aabb);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spatial_test() {
+        let p = Vec2u::new(0xDEAD, 0xBEEF);
+        let m = p.morton();
+        assert_eq!(Vec2u::from_morton(m).x, p.x);
+        assert_eq!(Vec2u::from_morton(m).y, p.y);
+        assert_eq!(Vec2u::new(1, 0).morton(), 1);
+        assert_eq!(Vec2u::new(0, 1).morton(), 2);
+        let p3 = Vec3u::new(1234, 5678, 91011);
+        let m3 = Vec3u::from_morton(p3.morton());
+        assert_eq!((m3.x, m3.y, m3.z), (p3.x, p3.y, p3.z));
+
+        let mut hash = SpatialHash::new(10.0);
+        let mut tree = Quadtree::new(Aabb::xywh(0.0, 0.0, 100.0, 100.0)).max_items(2);
+        for i in 0..50 {
+            let aabb = Aabb::xywh((i % 10) as f32 * 10.0, (i / 10) as f32 * 10.0, 5.0, 5.0);
+            hash.insert(i, aabb);
+            tree.insert(i, aabb);
+        }
+        let region = Aabb::xywh(12.0, 12.0, 20.0, 10.0);
+        let mut from_hash: Vec<i32> = hash.query(region).into_iter().copied().collect();
+        let mut from_tree: Vec<i32> = tree.query(region).into_iter().copied().collect();
+        from_hash.sort();
+        from_tree.sort();
+        assert_eq!(from_hash, vec![11, 12, 13, 21, 22, 23]);
+        assert_eq!(from_tree, from_hash);
+    }
+}
@@ -0,0 +1,455 @@
+/// segment of contour, continuing from previous point
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Segment {
+    Line([f32; 2]),
+    /// control, end
+    Quad([f32; 2], [f32; 2]),
+    /// control 1, control 2, end
+    Cubic([f32; 2], [f32; 2], [f32; 2]),
+}
+
+/// max segments a curve is flattened into
+const MAX_CURVE_SEGMENTS: usize = 64;
+/// sample rows per px in [`SvgPath::rasterize`], horizontal coverage is exact
+const RASTER_SAMPLES: usize = 4;
+
+/// subset of svg path data: M, L, H, V, C, Q, Z and their relative versions,
+/// every contour is closed and filled with nonzero rule, see [`crate::gfx::Renderer::path`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SvgPath {
+    /// (start, segments)
+    contours: Vec<([f32; 2], Vec<Segment>)>,
+    /// x, y, w, h of path space mapped onto drawn rect, bounds if none
+    view_box: Option<[f32; 4]>,
+}
+
+/// command letters and numbers of path data
+struct Tokens<'a> {
+    s: &'a [u8],
+    i: usize,
+}
+
+impl Tokens<'_> {
+    fn skip_separators(&mut self) {
+        while self
+            .s
+            .get(self.i)
+            .is_some_and(|c| c.is_ascii_whitespace() || *c == b',')
+        {
+            self.i += 1;
+        }
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let c = *self.s.get(self.i)?;
+        if c.is_ascii_alphabetic() {
+            self.i += 1;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    /// next token is number, implicitly repeating last command
+    fn has_number(&mut self) -> bool {
+        self.skip_separators();
+        self.s
+            .get(self.i)
+            .is_some_and(|c| c.is_ascii_digit() || b"+-.".contains(c))
+    }
+
+    /// numbers can be glued together, "1-2.5.5" is 1, -2.5, 0.5
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let start = self.i;
+        let digits = |t: &mut Self| {
+            while t.s.get(t.i).is_some_and(u8::is_ascii_digit) {
+                t.i += 1;
+            }
+        };
+        if self.s.get(self.i).is_some_and(|c| b"+-".contains(c)) {
+            self.i += 1;
+        }
+        digits(self);
+        if self.s.get(self.i) == Some(&b'.') {
+            self.i += 1;
+            digits(self);
+        }
+        if self.s.get(self.i).is_some_and(|c| b"eE".contains(c)) {
+            self.i += 1;
+            if self.s.get(self.i).is_some_and(|c| b"+-".contains(c)) {
+                self.i += 1;
+            }
+            digits(self);
+        }
+        std::str::from_utf8(&self.s[start..self.i])
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    fn point(&mut self, rel: bool, cur: [f32; 2]) -> Option<[f32; 2]> {
+        let (x, y) = (self.number()?, self.number()?);
+        Some(if rel {
+            [cur[0] + x, cur[1] + y]
+        } else {
+            [x, y]
+        })
+    }
+}
+
+impl SvgPath {
+    /// parses path data (`d` attribute), None if it's invalid or uses unsupported commands
+    pub fn parse(d: &str) -> Option<Self> {
+        let mut tokens = Tokens {
+            s: d.as_bytes(),
+            i: 0,
+        };
+        let mut contours: Vec<([f32; 2], Vec<Segment>)> = Vec::new();
+        let mut cur = [0.0; 2];
+        let mut cmd = None;
+        loop {
+            cmd = match tokens.command() {
+                Some(c) => Some(c),
+                // numbers after command repeat it, after moveto they're linetos
+                None if tokens.has_number() => match cmd? {
+                    b'M' => Some(b'L'),
+                    b'm' => Some(b'l'),
+                    b'Z' | b'z' => return None,
+                    c => Some(c),
+                },
+                None if tokens.i == tokens.s.len() => break,
+                None => return None,
+            };
+            let c = cmd?;
+            let rel = c.is_ascii_lowercase();
+            if !c.eq_ignore_ascii_case(&b'M') && contours.is_empty() {
+                return None;
+            }
+            let segment = match c.to_ascii_uppercase() {
+                b'M' => {
+                    cur = tokens.point(rel, cur)?;
+                    contours.push((cur, Vec::new()));
+                    continue;
+                }
+                b'Z' => {
+                    // next contour starts where this one did
+                    cur = contours.last()?.0;
+                    continue;
+                }
+                b'L' => Segment::Line(tokens.point(rel, cur)?),
+                b'H' => {
+                    let x = tokens.number()?;
+                    Segment::Line([if rel { cur[0] + x } else { x }, cur[1]])
+                }
+                b'V' => {
+                    let y = tokens.number()?;
+                    Segment::Line([cur[0], if rel { cur[1] + y } else { y }])
+                }
+                b'Q' => Segment::Quad(tokens.point(rel, cur)?, tokens.point(rel, cur)?),
+                b'C' => Segment::Cubic(
+                    tokens.point(rel, cur)?,
+                    tokens.point(rel, cur)?,
+                    tokens.point(rel, cur)?,
+                ),
+                _ => return None,
+            };
+            cur = match segment {
+                Segment::Line(p) | Segment::Quad(_, p) | Segment::Cubic(_, _, p) => p,
+            };
+            contours.last_mut()?.1.push(segment);
+        }
+        Some(Self {
+            contours,
+            view_box: None,
+        })
+    }
+
+    /// path space rect that's mapped onto drawn rect, like svg viewBox
+    pub fn view_box(mut self, x: f32, y: f32, w: f32, h: f32) -> Self {
+        self.view_box = Some([x, y, w, h]);
+        self
+    }
+
+    /// starts new contour at x, y
+    pub fn move_to(mut self, x: f32, y: f32) -> Self {
+        self.contours.push(([x, y], Vec::new()));
+        self
+    }
+
+    pub fn line_to(self, x: f32, y: f32) -> Self {
+        self.segment(Segment::Line([x, y]))
+    }
+
+    pub fn quad_to(self, cx: f32, cy: f32, x: f32, y: f32) -> Self {
+        self.segment(Segment::Quad([cx, cy], [x, y]))
+    }
+
+    pub fn cubic_to(self, c0x: f32, c0y: f32, c1x: f32, c1y: f32, x: f32, y: f32) -> Self {
+        self.segment(Segment::Cubic([c0x, c0y], [c1x, c1y], [x, y]))
+    }
+
+    fn segment(mut self, segment: Segment) -> Self {
+        self.contours
+            .last_mut()
+            .expect("path segment added before move_to()")
+            .1
+            .push(segment);
+        self
+    }
+
+    /// x, y, w, h of all points, including curve controls
+    pub fn bounds(&self) -> [f32; 4] {
+        let mut min = [f32::MAX; 2];
+        let mut max = [f32::MIN; 2];
+        let mut add = |p: [f32; 2]| {
+            min = [min[0].min(p[0]), min[1].min(p[1])];
+            max = [max[0].max(p[0]), max[1].max(p[1])];
+        };
+        for (start, segments) in &self.contours {
+            add(*start);
+            for &segment in segments {
+                match segment {
+                    Segment::Line(p) => add(p),
+                    Segment::Quad(c, p) => [c, p].into_iter().for_each(&mut add),
+                    Segment::Cubic(c0, c1, p) => [c0, c1, p].into_iter().for_each(&mut add),
+                }
+            }
+        }
+        if min[0] > max[0] {
+            return [0.0; 4];
+        }
+        [min[0], min[1], max[0] - min[0], max[1] - min[1]]
+    }
+
+    /// view box if set, bounds otherwise
+    pub fn rect(&self) -> [f32; 4] {
+        self.view_box.unwrap_or_else(|| self.bounds())
+    }
+
+    /// contours as polygons, curves are split until they're within ~`tolerance` of straight
+    pub fn flatten(&self, tolerance: f32) -> Vec<Vec<[f32; 2]>> {
+        let dist =
+            |a: [f32; 2], b: [f32; 2]| ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt();
+        let segments = |len: f32| {
+            ((len / tolerance.max(1e-6)).sqrt().ceil() as usize).clamp(1, MAX_CURVE_SEGMENTS)
+        };
+        let lerp =
+            |a: [f32; 2], b: [f32; 2], t: f32| [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t];
+        self.contours
+            .iter()
+            .map(|&(start, ref segs)| {
+                let mut poly = vec![start];
+                let mut cur = start;
+                for &segment in segs {
+                    match segment {
+                        Segment::Line(p) => poly.push(p),
+                        Segment::Quad(c, p) => {
+                            let n = segments(dist(cur, c) + dist(c, p));
+                            poly.extend((1..=n).map(|i| {
+                                let t = i as f32 / n as f32;
+                                lerp(lerp(cur, c, t), lerp(c, p, t), t)
+                            }));
+                        }
+                        Segment::Cubic(c0, c1, p) => {
+                            let n = segments(dist(cur, c0) + dist(c0, c1) + dist(c1, p));
+                            poly.extend((1..=n).map(|i| {
+                                let t = i as f32 / n as f32;
+                                let (a, b, c) = (lerp(cur, c0, t), lerp(c0, c1, t), lerp(c1, p, t));
+                                lerp(lerp(a, b, t), lerp(b, c, t), t)
+                            }));
+                        }
+                    }
+                    cur = *poly.last().unwrap();
+                }
+                poly
+            })
+            .collect()
+    }
+
+    /// triangle list filling path with nonzero rule, split into horizontal trapezoids
+    /// between point heights, so holes and overlapping contours work,
+    /// self intersections within a band aren't split
+    pub fn fill(&self, tolerance: f32) -> Vec<[f32; 2]> {
+        let polys = self.flatten(tolerance);
+        // (top, bottom, winding), top has smaller y
+        let mut edges = Vec::new();
+        let mut ys = Vec::new();
+        for poly in &polys {
+            for (i, &a) in poly.iter().enumerate() {
+                let b = poly[(i + 1) % poly.len()];
+                ys.push(a[1]);
+                if a[1] < b[1] {
+                    edges.push((a, b, 1));
+                } else if a[1] > b[1] {
+                    edges.push((b, a, -1));
+                }
+            }
+        }
+        ys.sort_by(f32::total_cmp);
+        ys.dedup();
+        let x_at = |(a, b, _): ([f32; 2], [f32; 2], i32), y: f32| {
+            a[0] + (b[0] - a[0]) * (y - a[1]) / (b[1] - a[1])
+        };
+        let mut tris = Vec::new();
+        let mut crossing = Vec::new();
+        for band in ys.windows(2) {
+            let (y0, y1) = (band[0], band[1]);
+            let mid = (y0 + y1) * 0.5;
+            crossing.clear();
+            crossing.extend(
+                edges
+                    .iter()
+                    .filter(|e| e.0[1] <= y0 && e.1[1] >= y1)
+                    .map(|&e| (x_at(e, mid), x_at(e, y0), x_at(e, y1), e.2)),
+            );
+            crossing.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut winding = 0;
+            let mut left = (0.0, 0.0);
+            for &(_, top, bottom, w) in &crossing {
+                let prev = winding;
+                winding += w;
+                if prev == 0 && winding != 0 {
+                    left = (top, bottom);
+                } else if prev != 0 && winding == 0 {
+                    tris.extend([[left.0, y0], [top, y0], [bottom, y1]]);
+                    tris.extend([[left.0, y0], [bottom, y1], [left.1, y1]]);
+                }
+            }
+        }
+        tris
+    }
+
+    /// coverage mask of path filled with nonzero rule, with `rect()` mapped onto width x height,
+    /// first row is at smallest y
+    pub fn rasterize(&self, width: u32, height: u32) -> Vec<u8> {
+        let (w, h) = (width as usize, height as usize);
+        let [rx, ry, rw, rh] = self.rect();
+        if rw <= 0.0 || rh <= 0.0 {
+            return vec![0; w * h];
+        }
+        let (sx, sy) = (width as f32 / rw, height as f32 / rh);
+        // (top, bottom, winding) in px
+        let mut edges = Vec::new();
+        for poly in self.flatten(0.25 / sx.max(sy)) {
+            for (i, &a) in poly.iter().enumerate() {
+                let b = poly[(i + 1) % poly.len()];
+                let a = [(a[0] - rx) * sx, (a[1] - ry) * sy];
+                let b = [(b[0] - rx) * sx, (b[1] - ry) * sy];
+                if a[1] < b[1] {
+                    edges.push((a, b, 1));
+                } else if a[1] > b[1] {
+                    edges.push((b, a, -1));
+                }
+            }
+        }
+        let mut coverage = vec![0.0f32; w * h];
+        let mut crossings = Vec::new();
+        let weight = 1.0 / RASTER_SAMPLES as f32;
+        for row in 0..h * RASTER_SAMPLES {
+            let y = (row as f32 + 0.5) * weight;
+            crossings.clear();
+            crossings.extend(
+                edges
+                    .iter()
+                    .filter(|(a, b, _)| a[1] <= y && y < b[1])
+                    .map(|&(a, b, wn)| (a[0] + (b[0] - a[0]) * (y - a[1]) / (b[1] - a[1]), wn)),
+            );
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let line = &mut coverage[row / RASTER_SAMPLES * w..][..w];
+            let (mut winding, mut start) = (0, 0.0);
+            for &(x, wn) in &crossings {
+                let prev = winding;
+                winding += wn;
+                if prev == 0 && winding != 0 {
+                    start = x;
+                } else if prev != 0 && winding == 0 {
+                    let (x0, x1) = (start.clamp(0.0, w as f32), x.clamp(0.0, w as f32));
+                    for (px, c) in line
+                        .iter_mut()
+                        .enumerate()
+                        .take(x1.ceil() as usize)
+                        .skip(x0 as usize)
+                    {
+                        let px = px as f32;
+                        *c += (x1.min(px + 1.0) - x0.max(px)) * weight;
+                    }
+                }
+            }
+        }
+        coverage
+            .into_iter()
+            .map(|c| (c.min(1.0) * 255.0).round() as u8)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn area(tris: &[[f32; 2]]) -> f32 {
+        tris.chunks_exact(3)
+            .map(|t| {
+                let (a, b, c) = (t[0], t[1], t[2]);
+                ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])).abs() * 0.5
+            })
+            .sum()
+    }
+
+    #[test]
+    fn svg_path_test() {
+        let abs = SvgPath::parse("M0 0 L10 0 L10 10 L0 10 Z").unwrap();
+        let rel = SvgPath::parse("m0,0 h10 v10 h-10 z").unwrap();
+        assert_eq!(abs.flatten(0.1), rel.flatten(0.1));
+        // implicit lineto after moveto and glued numbers
+        let glued = SvgPath::parse("M0-0 10 0 10 10 0 10z").unwrap();
+        assert_eq!(glued.flatten(0.1), abs.flatten(0.1));
+        assert_eq!(abs.bounds(), [0.0, 0.0, 10.0, 10.0]);
+        assert_eq!(
+            SvgPath::parse("M.5.5").unwrap().bounds(),
+            [0.5, 0.5, 0.0, 0.0]
+        );
+        assert_eq!(SvgPath::parse("L10 0"), None);
+        assert_eq!(SvgPath::parse("M0 0 A1 1 0 0 0 1 1"), None);
+        assert_eq!(SvgPath::parse("M0 0 L10"), None);
+
+        let quad = SvgPath::parse("M0 0 Q5 10 10 0").unwrap();
+        let poly = &quad.flatten(0.01)[0];
+        assert!(poly.len() > 3);
+        assert_eq!(*poly.last().unwrap(), [10.0, 0.0]);
+        // peak of quadratic is half way to control
+        let peak = poly.iter().map(|p| p[1]).fold(0.0, f32::max);
+        assert!((peak - 5.0).abs() < 0.1);
+        let cubic = SvgPath::parse("M0 0 C0 10 10 10 10 0").unwrap();
+        assert_eq!(*cubic.flatten(0.01)[0].last().unwrap(), [10.0, 0.0]);
+
+        let square = SvgPath::parse("M1 1 H3 V3 H1 Z")
+            .unwrap()
+            .view_box(0.0, 0.0, 4.0, 4.0);
+        let mask = square.rasterize(4, 4);
+        for (i, &c) in mask.iter().enumerate() {
+            let inside = (1..3).contains(&(i % 4)) && (1..3).contains(&(i / 4));
+            assert_eq!(c, if inside { 255 } else { 0 });
+        }
+        let half = SvgPath::default()
+            .move_to(0.0, 0.0)
+            .line_to(1.5, 0.0)
+            .line_to(1.5, 1.0)
+            .line_to(0.0, 1.0)
+            .view_box(0.0, 0.0, 2.0, 1.0);
+        assert_eq!(half.rasterize(2, 1), [255, 128]);
+
+        let square = SvgPath::parse("M0 0 H10 V10 H0 Z").unwrap();
+        assert!((area(&square.fill(0.1)) - 100.0).abs() < 1e-3);
+        // hole winds opposite way
+        let framed = SvgPath::parse("M0 0 H10 V10 H0 Z M2 2 V8 H8 V2 Z").unwrap();
+        assert!((area(&framed.fill(0.1)) - 64.0).abs() < 1e-3);
+        // same winding overlaps are filled once
+        let overlap = SvgPath::parse("M0 0 H10 V10 H0 Z M5 0 H15 V10 H5 Z").unwrap();
+        assert!((area(&overlap.fill(0.1)) - 150.0).abs() < 1e-3);
+        let triangle = SvgPath::parse("M0 0 L10 10 L0 10 Z").unwrap();
+        assert!((area(&triangle.fill(0.1)) - 50.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+thread_local! {
+    /// time of frame currently being polled, read by `wait_secs`
+    static TIME: Cell<f32> = const { Cell::new(0.0) };
+}
+
+/// yields until next frame
+pub fn next_frame() -> WaitFrames {
+    wait_frames(1)
+}
+
+pub fn wait_frames(frames: u32) -> WaitFrames {
+    WaitFrames { frames }
+}
+
+/// yields until `secs` of app time passed, counted from first poll
+pub fn wait_secs(secs: f32) -> WaitSecs {
+    WaitSecs { secs, until: None }
+}
+
+pub struct WaitFrames {
+    frames: u32,
+}
+
+impl Future for WaitFrames {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        if self.frames == 0 {
+            return Poll::Ready(());
+        }
+        self.frames -= 1;
+        Poll::Pending
+    }
+}
+
+pub struct WaitSecs {
+    secs: f32,
+    until: Option<f32>,
+}
+
+impl Future for WaitSecs {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        let now = TIME.get();
+        let until = now + self.secs;
+        let until = *self.until.get_or_insert(until);
+        if now >= until {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// shared state of spawned task, see [`Tasks::spawn`]
+#[derive(Clone, Default)]
+pub struct TaskHandle {
+    done: Rc<Cell<bool>>,
+    cancelled: Rc<Cell<bool>>,
+}
+
+impl TaskHandle {
+    pub fn done(&self) -> bool {
+        self.done.get()
+    }
+
+    /// drops task before its next poll
+    pub fn cancel(&self) {
+        self.cancelled.set(true);
+    }
+}
+
+struct Task {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    handle: TaskHandle,
+}
+
+/// frame driven executor, every task is polled once per frame by `AppContext::update`,
+/// so awaiting `next_frame()` resumes on the next update
+#[derive(Default)]
+pub struct Tasks {
+    tasks: Vec<Task>,
+}
+
+impl Tasks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawn(&mut self, future: impl Future<Output = ()> + 'static) -> TaskHandle {
+        let handle = TaskHandle::default();
+        self.tasks.push(Task {
+            future: Box::pin(future),
+            handle: handle.clone(),
+        });
+        handle
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// moves tasks from `other`, used for tasks spawned while polling
+    pub fn append(&mut self, other: &mut Self) {
+        self.tasks.append(&mut other.tasks);
+    }
+
+    /// polls every task once, `time` is what `wait_secs` measures against
+    pub fn update(&mut self, time: f32) {
+        TIME.set(time);
+        let mut cx = Context::from_waker(Waker::noop());
+        self.tasks.retain_mut(|task| {
+            if task.handle.cancelled.get() {
+                return false;
+            }
+            let done = task.future.as_mut().poll(&mut cx).is_ready();
+            task.handle.done.set(done);
+            !done
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn task_test() {
+        let log = Rc::new(Cell::new(0));
+        let mut tasks = Tasks::new();
+        let l = log.clone();
+        let handle = tasks.spawn(async move {
+            l.set(1);
+            next_frame().await;
+            l.set(2);
+            wait_secs(1.0).await;
+            l.set(3);
+        });
+        let cancelled = tasks.spawn(async {
+            wait_frames(100).await;
+            unreachable!();
+        });
+        tasks.update(0.0);
+        assert_eq!(log.get(), 1);
+        tasks.update(0.1);
+        assert_eq!(log.get(), 2);
+        cancelled.cancel();
+        tasks.update(0.6);
+        assert_eq!(log.get(), 2);
+        tasks.update(1.1);
+        assert_eq!(log.get(), 3);
+        assert!(handle.done() && tasks.is_empty());
+    }
+}
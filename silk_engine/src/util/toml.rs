@@ -0,0 +1,299 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::warn;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TomlValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl TomlValue {
+    /// parses value like it would appear after `key = `
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if let Some(s) = text.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            return Some(Self::Str(unescape(s)));
+        }
+        match text {
+            "true" => return Some(Self::Bool(true)),
+            "false" => return Some(Self::Bool(false)),
+            _ => {}
+        }
+        let num = text.replace('_', "");
+        if let Ok(i) = num.parse() {
+            Some(Self::Int(i))
+        } else {
+            num.parse().ok().map(Self::Float)
+        }
+    }
+}
+
+impl Display for TomlValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Int(i) => write!(f, "{i}"),
+            Self::Float(x) => write!(f, "{x:?}"),
+            Self::Str(s) => write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+        }
+    }
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+    out
+}
+
+/// rust type stored as [`TomlValue`]
+pub trait TomlType: Into<TomlValue> + Sized {
+    fn from_toml(value: &TomlValue) -> Option<Self>;
+}
+
+macro_rules! impl_toml_type {
+    ($variant: ident, $($ty: ty),*) => {
+        $(
+            impl From<$ty> for TomlValue {
+                fn from(v: $ty) -> Self {
+                    Self::$variant(v as _)
+                }
+            }
+
+            impl TomlType for $ty {
+                fn from_toml(value: &TomlValue) -> Option<Self> {
+                    match *value {
+                        TomlValue::Int(i) => i.try_into().ok(),
+                        TomlValue::Float(x) if x.fract() == 0.0 => Some(x as _),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_toml_type!(Int, i32, i64, u32, u64, usize);
+
+impl From<f32> for TomlValue {
+    /// goes through string so 0.1f32 is saved as 0.1
+    fn from(v: f32) -> Self {
+        Self::Float(v.to_string().parse().unwrap_or(v as f64))
+    }
+}
+
+impl From<f64> for TomlValue {
+    fn from(v: f64) -> Self {
+        Self::Float(v)
+    }
+}
+
+impl TomlType for f32 {
+    fn from_toml(value: &TomlValue) -> Option<Self> {
+        f64::from_toml(value).map(|x| x as f32)
+    }
+}
+
+impl TomlType for f64 {
+    fn from_toml(value: &TomlValue) -> Option<Self> {
+        match *value {
+            TomlValue::Int(i) => Some(i as f64),
+            TomlValue::Float(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for TomlValue {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+impl TomlType for bool {
+    fn from_toml(value: &TomlValue) -> Option<Self> {
+        match *value {
+            TomlValue::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for TomlValue {
+    fn from(v: String) -> Self {
+        Self::Str(v)
+    }
+}
+
+impl From<&str> for TomlValue {
+    fn from(v: &str) -> Self {
+        Self::Str(v.to_string())
+    }
+}
+
+impl TomlType for String {
+    fn from_toml(value: &TomlValue) -> Option<Self> {
+        match value {
+            TomlValue::Str(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// minimal toml subset: `[section]` tables with bool, int, float and string values.
+/// keys are flattened with dots, so `[window] width = 800` is `window.width`.
+/// comments aren't kept when saving
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Toml {
+    values: BTreeMap<String, TomlValue>,
+}
+
+impl Toml {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// invalid lines are skipped with a warning
+    pub fn parse(text: &str) -> Self {
+        let mut toml = Self::new();
+        let mut section = String::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+            let parsed = line.split_once('=').and_then(|(key, value)| {
+                let key = key.trim().trim_matches('"');
+                (!key.is_empty()).then_some((key, TomlValue::parse(value)?))
+            });
+            match parsed {
+                Some((key, value)) if section.is_empty() => toml.set(key, value),
+                Some((key, value)) => toml.set(&format!("{section}.{key}"), value),
+                None => warn!("invalid toml line {}: {line}", i + 1),
+            }
+        }
+        toml
+    }
+
+    /// empty if file doesn't exist
+    pub fn load(path: &str) -> Self {
+        std::fs::read_to_string(path)
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    pub fn get<T: TomlType>(&self, key: &str) -> Option<T> {
+        T::from_toml(self.values.get(key)?)
+    }
+
+    pub fn value(&self, key: &str) -> Option<&TomlValue> {
+        self.values.get(key)
+    }
+
+    pub fn set(&mut self, key: &str, value: impl Into<TomlValue>) {
+        self.values.insert(key.to_string(), value.into());
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<TomlValue> {
+        self.values.remove(key)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    /// sorted by key
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &TomlValue)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v))
+    }
+}
+
+impl Display for Toml {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut entries: Vec<_> = self
+            .iter()
+            .map(|(k, v)| (k.rsplit_once('.').unwrap_or(("", k)), v))
+            .collect();
+        // top level keys have to come before any section
+        entries.sort_by_key(|&((section, key), _)| (!section.is_empty(), section, key));
+        let mut section = "";
+        for (i, ((sec, key), value)) in entries.into_iter().enumerate() {
+            if sec != section {
+                section = sec;
+                let gap = if i == 0 { "" } else { "\n" };
+                writeln!(f, "{gap}[{section}]")?;
+            }
+            writeln!(f, "{key} = {value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// removes `# comment` outside of strings
+fn strip_comment(line: &str) -> &str {
+    let mut in_str = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '\\' if in_str => {
+                escaped = !escaped;
+                continue;
+            }
+            '"' if !escaped => in_str = !in_str,
+            '#' if !in_str => return &line[..i],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn toml_test() {
+        let text = r#"
+            title = "a \"b\" # c" # comment
+            [window]
+            width = 1_280
+            vsync = true
+            scale = 1.5
+            bad line
+        "#;
+        let toml = Toml::parse(text);
+        assert_eq!(toml.get::<String>("title").unwrap(), "a \"b\" # c");
+        assert_eq!(toml.get::<u32>("window.width"), Some(1280));
+        assert_eq!(toml.get::<f32>("window.width"), Some(1280.0));
+        assert_eq!(toml.get::<bool>("window.vsync"), Some(true));
+        assert_eq!(toml.get::<f32>("window.scale"), Some(1.5));
+        assert_eq!(toml.get::<bool>("window.scale"), None);
+        assert_eq!(Toml::parse(&toml.to_string()), toml);
+        let mut toml = Toml::new();
+        toml.set("bloom.intensity", 0.1f32);
+        assert_eq!(toml.to_string(), "[bloom]\nintensity = 0.1\n");
+    }
+}
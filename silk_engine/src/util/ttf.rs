@@ -1,4 +1,6 @@
-use crate::{RES_PATH, util::ReaderBe};
+use std::collections::HashMap;
+
+use crate::util::{ReaderBe, vfs};
 
 #[derive(Default, Debug, Clone)]
 pub(crate) struct GlyphMetrics {
@@ -35,6 +37,8 @@ struct TableOffs {
     cmap: u32,
     hhea: u32,
     hmtx: u32,
+    colr: u32,
+    cpal: u32,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -64,13 +68,16 @@ pub(crate) struct Ttf {
     pub(crate) head: Head,
     pub(crate) glyphs: Vec<GlyphData>,
     pub(crate) idx2uni: Vec<char>,
+    /// color glyph idx to it's layers (glyph idx, rgba) from bottom to top, from COLR v0 table
+    pub(crate) color_layers: HashMap<u16, Vec<(u16, [u8; 4])>>,
 }
 
 // TTF parsing: https://youtu.be/SO83KQuuZvg
 impl Ttf {
     pub(crate) fn new(name: &str) -> Self {
-        let path = format!("{RES_PATH}/fonts/{name}.ttf");
-        let bytes = std::fs::read(path).unwrap();
+        let bytes = vfs()
+            .read(&format!("fonts/{name}.ttf"))
+            .unwrap_or_else(|_| panic!("font not found: {name}"));
 
         let mut reader = ReaderBe::new(&bytes);
         let table_offs = Self::read_table_offs(&mut reader);
@@ -84,13 +91,67 @@ impl Ttf {
         );
         let idx2uni = Self::read_idx2uni_mappings(&mut reader, table_offs.cmap);
         let glyphs = Self::read_glyphs(&mut reader, &glyph_offs, &table_offs);
+        let color_layers = Self::read_color_layers(&mut reader, &table_offs);
         Self {
             head,
             glyphs,
             idx2uni,
+            color_layers,
         }
     }
 
+    /// layers use first CPAL palette, foreground color entry (0xFFFF) is white
+    fn read_color_layers(
+        reader: &mut ReaderBe,
+        table_offs: &TableOffs,
+    ) -> HashMap<u16, Vec<(u16, [u8; 4])>> {
+        if table_offs.colr == 0 || table_offs.cpal == 0 {
+            return HashMap::new();
+        }
+        reader.goto(table_offs.cpal as usize);
+        reader.skip(2); // version
+        let num_entries = reader.read16();
+        reader.skip(4); // num palettes, num color records
+        let records_off = reader.read32();
+        let first_record = reader.read16();
+        reader.goto((table_offs.cpal + records_off) as usize + first_record as usize * 4);
+        let palette = (0..num_entries)
+            .map(|_| {
+                let [b, g, r, a] = reader.read_arr();
+                [r, g, b, a]
+            })
+            .collect::<Vec<_>>();
+
+        reader.goto(table_offs.colr as usize);
+        reader.skip(2); // version, only v0 records are read
+        let num_bases = reader.read16();
+        let bases_off = reader.read32();
+        let layers_off = reader.read32();
+        let bases = (0..num_bases)
+            .map(|i| {
+                reader.goto((table_offs.colr + bases_off) as usize + i as usize * 6);
+                (reader.read16(), reader.read16(), reader.read16())
+            })
+            .collect::<Vec<_>>();
+        bases
+            .into_iter()
+            .map(|(glyph_idx, first_layer, num_layers)| {
+                reader.goto((table_offs.colr + layers_off) as usize + first_layer as usize * 4);
+                let layers = (0..num_layers)
+                    .map(|_| {
+                        let layer_idx = reader.read16();
+                        let color = palette
+                            .get(reader.read16() as usize)
+                            .copied()
+                            .unwrap_or([255; 4]);
+                        (layer_idx, color)
+                    })
+                    .collect();
+                (glyph_idx, layers)
+            })
+            .collect()
+    }
+
     fn read_table_offs(reader: &mut ReaderBe) -> TableOffs {
         reader.skip(4); // scalar_type
         let num_tables = reader.read16();
@@ -111,6 +172,8 @@ impl Ttf {
                 b"cmap" => table_offs.cmap = off,
                 b"hhea" => table_offs.hhea = off,
                 b"hmtx" => table_offs.hmtx = off,
+                b"COLR" => table_offs.colr = off,
+                b"CPAL" => table_offs.cpal = off,
                 _ => {}
             }
         }
@@ -521,3 +584,38 @@ impl Ttf {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ttf_test() {
+        #[rustfmt::skip]
+        let cpal: [u8; 22] = [
+            0, 0, 0, 2, 0, 1, 0, 2, // version, entries, palettes, color records
+            0, 0, 0, 14, 0, 0, // color records off, palette 0 first record
+            0x10, 0x20, 0x30, 255, 0, 0, 255, 128, // bgra records
+        ];
+        #[rustfmt::skip]
+        let colr: [u8; 38] = [
+            0, 0, 0, 2, // version, base glyphs
+            0, 0, 0, 14, 0, 0, 0, 26, 0, 3, // bases off, layers off, layers
+            0, 5, 0, 1, 0, 2, // glyph 5 has layers 1..3
+            0, 6, 0, 0, 0, 1, // glyph 6 has layer 0
+            0, 9, 0, 0, 0, 7, 0, 1, 0, 8, 255, 255, // (glyph, palette entry) layers
+        ];
+        // 0 offset means font has no such table, so tables start after padding
+        let bytes = [&[0; 4][..], &cpal, &colr].concat();
+        let table_offs = TableOffs {
+            cpal: 4,
+            colr: 4 + cpal.len() as u32,
+            ..Default::default()
+        };
+        let layers = Ttf::read_color_layers(&mut ReaderBe::new(&bytes), &table_offs);
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[&6], [(9, [0x30, 0x20, 0x10, 255])]);
+        // foreground color entry is white
+        assert_eq!(layers[&5], [(7, [255, 0, 0, 128]), (8, [255; 4])]);
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::{LazyLock, Mutex, MutexGuard};
+
+use super::{Toml, TomlType, TomlValue};
+use crate::{RES_PATH, warn};
+
+static TWEAKS: LazyLock<Mutex<Tweaks>> =
+    LazyLock::new(|| Mutex::new(Tweaks::load(&format!("{RES_PATH}/config.toml"))));
+
+/// global tweak registry used by `tweak!`, loaded from `res/config.toml`
+pub fn tweaks() -> MutexGuard<'static, Tweaks> {
+    TWEAKS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// value that can be changed at runtime from dev console (`set bloom.intensity 2`),
+/// registered with `default` on first use, or with value saved in `res/config.toml`
+#[macro_export]
+macro_rules! tweak {
+    ($name:expr, $default:expr) => {
+        $crate::prelude::tweaks().get_or($name, $default)
+    };
+}
+
+/// named runtime variables, see `tweak!`
+pub struct Tweaks {
+    path: String,
+    /// values from file, used instead of defaults when tweak is registered
+    saved: Toml,
+    values: Toml,
+}
+
+impl Tweaks {
+    /// no file means no saved values
+    pub fn load(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            saved: Toml::load(path),
+            values: Toml::new(),
+        }
+    }
+
+    /// current value, registers tweak if it doesn't exist yet
+    pub fn get_or<T: TomlType + Clone>(&mut self, name: &str, default: T) -> T {
+        if let Some(value) = self.values.get(name) {
+            return value;
+        }
+        let value = self.saved.get(name).unwrap_or(default);
+        self.values.set(name, value.clone());
+        value
+    }
+
+    pub fn value(&self, name: &str) -> Option<&TomlValue> {
+        self.values.value(name)
+    }
+
+    /// parses `text` as type of registered tweak, warns and returns false if invalid
+    pub fn set(&mut self, name: &str, text: &str) -> bool {
+        let Some(old) = self.values.value(name) else {
+            warn!("tweak not found: {name}");
+            return false;
+        };
+        let new = match old {
+            TomlValue::Str(_) => TomlValue::parse(text).filter(|v| matches!(v, TomlValue::Str(_))),
+            TomlValue::Float(_) => TomlValue::parse(text).map(|v| match v {
+                TomlValue::Int(i) => TomlValue::Float(i as f64),
+                v => v,
+            }),
+            _ => TomlValue::parse(text),
+        }
+        .or_else(|| matches!(old, TomlValue::Str(_)).then(|| text.into()));
+        match new {
+            Some(new) if std::mem::discriminant(&new) == std::mem::discriminant(old) => {
+                self.values.set(name, new);
+                true
+            }
+            _ => {
+                warn!("invalid value for tweak {name} = {old}: {text}");
+                false
+            }
+        }
+    }
+
+    /// sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &TomlValue)> {
+        self.values.iter()
+    }
+
+    /// writes current values to config file, keeping saved values of tweaks that weren't used
+    pub fn save(&mut self) -> std::io::Result<()> {
+        for (name, value) in self.values.iter() {
+            self.saved.set(name, value.clone());
+        }
+        self.saved.save(&self.path)
+    }
+}
@@ -0,0 +1,379 @@
+use std::{any::Any, collections::HashMap, f32::consts::PI};
+
+use super::vec::{Bezier, ExtraFns, Vec2, Vec3, Vec4};
+
+/// easing curve, maps progress in [0, 1] to eased progress (can overshoot for back/elastic)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Ease {
+    #[default]
+    Linear,
+    InQuad,
+    OutQuad,
+    InOutQuad,
+    InCubic,
+    OutCubic,
+    InOutCubic,
+    InSine,
+    OutSine,
+    InOutSine,
+    InExpo,
+    OutExpo,
+    InOutExpo,
+    InBack,
+    OutBack,
+    InOutBack,
+    OutElastic,
+    OutBounce,
+    /// quadratic bezier from 0 to 1 with given control point
+    Bezier(f32),
+}
+
+impl Ease {
+    pub fn apply(self, t: f32) -> f32 {
+        const C1: f32 = 1.70158;
+        const C2: f32 = C1 * 1.525;
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::InQuad => t * t,
+            Self::OutQuad => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::InOutQuad if t < 0.5 => 2.0 * t * t,
+            Self::InOutQuad => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0,
+            Self::InCubic => t * t * t,
+            Self::OutCubic => 1.0 - (1.0 - t).powi(3),
+            Self::InOutCubic if t < 0.5 => 4.0 * t * t * t,
+            Self::InOutCubic => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            Self::InSine => 1.0 - (t * PI / 2.0).cos(),
+            Self::OutSine => (t * PI / 2.0).sin(),
+            Self::InOutSine => -((PI * t).cos() - 1.0) / 2.0,
+            Self::InExpo if t == 0.0 => 0.0,
+            Self::InExpo => 2f32.powf(10.0 * t - 10.0),
+            Self::OutExpo if t == 1.0 => 1.0,
+            Self::OutExpo => 1.0 - 2f32.powf(-10.0 * t),
+            Self::InOutExpo if t == 0.0 || t == 1.0 => t,
+            Self::InOutExpo if t < 0.5 => 2f32.powf(20.0 * t - 10.0) / 2.0,
+            Self::InOutExpo => (2.0 - 2f32.powf(-20.0 * t + 10.0)) / 2.0,
+            Self::InBack => (C1 + 1.0) * t * t * t - C1 * t * t,
+            Self::OutBack => 1.0 + (C1 + 1.0) * (t - 1.0).powi(3) + C1 * (t - 1.0).powi(2),
+            Self::InOutBack if t < 0.5 => (2.0 * t).powi(2) * ((C2 + 1.0) * 2.0 * t - C2) / 2.0,
+            Self::InOutBack => {
+                ((2.0 * t - 2.0).powi(2) * ((C2 + 1.0) * (t * 2.0 - 2.0) + C2) + 2.0) / 2.0
+            }
+            Self::OutElastic if t == 0.0 || t == 1.0 => t,
+            Self::OutElastic => {
+                2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+            }
+            Self::OutBounce => {
+                const N: f32 = 7.5625;
+                const D: f32 = 2.75;
+                if t < 1.0 / D {
+                    N * t * t
+                } else if t < 2.0 / D {
+                    let t = t - 1.5 / D;
+                    N * t * t + 0.75
+                } else if t < 2.5 / D {
+                    let t = t - 2.25 / D;
+                    N * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D;
+                    N * t * t + 0.984375
+                }
+            }
+            Self::Bezier(c) => 0f32.bezier(c, 1.0, t),
+        }
+    }
+}
+
+/// value that can be animated by [`Tween`]
+pub trait Tweenable: Copy + 'static {
+    fn tween(self, to: Self, t: f32) -> Self;
+}
+
+macro_rules! impl_tweenable {
+    ($($ty: ty),*) => {
+        $(impl Tweenable for $ty {
+            fn tween(self, to: Self, t: f32) -> Self {
+                self.lerp(to, t)
+            }
+        })*
+    };
+}
+
+impl_tweenable!(f32, Vec2, Vec3, Vec4);
+
+/// rgba color
+impl Tweenable for [u8; 4] {
+    fn tween(self, to: Self, t: f32) -> Self {
+        std::array::from_fn(|i| {
+            (self[i] as f32)
+                .lerp(to[i] as f32, t)
+                .round()
+                .clamp(0.0, 255.0) as u8
+        })
+    }
+}
+
+/// animates value from `from` to `to` over `duration` seconds
+pub struct Tween<T: Tweenable> {
+    from: T,
+    to: T,
+    duration: f32,
+    delay: f32,
+    ease: Ease,
+    /// remaining extra plays, negative repeats forever
+    repeats: i32,
+    yoyo: bool,
+    elapsed: f32,
+    paused: bool,
+    on_done: Option<Box<dyn FnMut()>>,
+}
+
+impl<T: Tweenable> Tween<T> {
+    pub fn new(from: T, to: T, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            duration: duration.max(0.0),
+            delay: 0.0,
+            ease: Ease::Linear,
+            repeats: 0,
+            yoyo: false,
+            elapsed: 0.0,
+            paused: false,
+            on_done: None,
+        }
+    }
+
+    pub fn ease(mut self, ease: Ease) -> Self {
+        self.ease = ease;
+        self
+    }
+
+    /// seconds to wait before starting
+    pub fn delay(mut self, secs: f32) -> Self {
+        self.delay = secs;
+        self
+    }
+
+    /// plays `times` more times after first play
+    pub fn repeat(mut self, times: u32) -> Self {
+        self.repeats = times as i32;
+        self
+    }
+
+    pub fn forever(mut self) -> Self {
+        self.repeats = -1;
+        self
+    }
+
+    /// every other repeat plays backwards
+    pub fn yoyo(mut self) -> Self {
+        self.yoyo = true;
+        self
+    }
+
+    /// called once when tween finishes, never called for `forever` tweens
+    pub fn on_done(mut self, f: impl FnMut() + 'static) -> Self {
+        self.on_done = Some(Box::new(f));
+        self
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// restarts from the beginning, including delay
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    fn plays(&self) -> Option<f32> {
+        (self.repeats >= 0).then(|| (self.repeats + 1) as f32)
+    }
+
+    pub fn done(&self) -> bool {
+        self.plays()
+            .is_some_and(|plays| self.elapsed >= self.delay + self.duration * plays)
+    }
+
+    /// eased progress in current play, in [0, 1] unless ease overshoots
+    pub fn progress(&self) -> f32 {
+        let t = (self.elapsed - self.delay).max(0.0);
+        if self.done() {
+            let last_reversed = self.yoyo && self.repeats % 2 == 1;
+            return if last_reversed { 0.0 } else { 1.0 };
+        }
+        if self.duration == 0.0 {
+            return 1.0;
+        }
+        let play = (t / self.duration).floor();
+        let mut p = t / self.duration - play;
+        if self.yoyo && play as u32 % 2 == 1 {
+            p = 1.0 - p;
+        }
+        self.ease.apply(p)
+    }
+
+    pub fn value(&self) -> T {
+        self.from.tween(self.to, self.progress())
+    }
+
+    /// advances tween, true if it finished during this update
+    pub fn update(&mut self, dt: f32) -> bool {
+        if self.paused || self.done() {
+            return false;
+        }
+        self.elapsed += dt;
+        let done = self.done();
+        if done && let Some(on_done) = &mut self.on_done {
+            on_done();
+        }
+        done
+    }
+}
+
+trait AnyTween {
+    fn update(&mut self, dt: f32) -> bool;
+    fn done(&self) -> bool;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Tweenable> AnyTween for Tween<T> {
+    fn update(&mut self, dt: f32) -> bool {
+        Tween::update(self, dt)
+    }
+
+    fn done(&self) -> bool {
+        Tween::done(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// named tweens updated every frame by `AppContext`, each posts `TweenDone` when finished.
+/// finished tweens are kept so their final value can still be read
+#[derive(Default)]
+pub struct Tweens {
+    tweens: HashMap<String, Box<dyn AnyTween>>,
+}
+
+impl Tweens {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// replaces tween with same name, so ui transitions can be retargeted mid animation
+    pub fn add<T: Tweenable>(&mut self, name: &str, tween: Tween<T>) -> &mut Tween<T> {
+        self.tweens.insert(name.to_string(), Box::new(tween));
+        self.tween(name)
+    }
+
+    /// tweens from current value of `name` (or `from` if there is none) to `to`
+    pub fn to<T: Tweenable>(&mut self, name: &str, from: T, to: T, duration: f32) -> &mut Tween<T> {
+        let from = self.try_get(name).unwrap_or(from);
+        self.add(name, Tween::new(from, to, duration))
+    }
+
+    pub fn tween<T: Tweenable>(&mut self, name: &str) -> &mut Tween<T> {
+        self.tweens
+            .get_mut(name)
+            .unwrap_or_else(|| panic!("tween not found: {name}"))
+            .as_any_mut()
+            .downcast_mut()
+            .unwrap_or_else(|| panic!("tween has different type: {name}"))
+    }
+
+    pub fn get<T: Tweenable>(&self, name: &str) -> T {
+        self.try_get(name)
+            .unwrap_or_else(|| panic!("tween not found: {name}"))
+    }
+
+    /// None if there is no tween `name` of type `T`
+    pub fn try_get<T: Tweenable>(&self, name: &str) -> Option<T> {
+        self.tweens
+            .get(name)?
+            .as_any()
+            .downcast_ref::<Tween<T>>()
+            .map(Tween::value)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.tweens.contains_key(name)
+    }
+
+    /// exists and hasn't finished
+    pub fn active(&self, name: &str) -> bool {
+        self.tweens.get(name).is_some_and(|t| !t.done())
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.tweens.remove(name).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.tweens.clear();
+    }
+
+    /// advances all tweens, returns names of ones that finished
+    pub fn update(&mut self, dt: f32) -> Vec<String> {
+        self.tweens
+            .iter_mut()
+            .filter_map(|(name, t)| t.update(dt).then(|| name.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{cell::Cell, rc::Rc};
+
+    #[test]
+    fn tween_test() {
+        for ease in [
+            Ease::Linear,
+            Ease::InOutCubic,
+            Ease::OutBounce,
+            Ease::OutElastic,
+        ] {
+            assert!(ease.apply(0.0).abs() < 1e-5 && (ease.apply(1.0) - 1.0).abs() < 1e-5);
+        }
+        let done = Rc::new(Cell::new(0));
+        let mut tweens = Tweens::new();
+        let d = done.clone();
+        tweens.add(
+            "fade",
+            Tween::new(0.0, 10.0, 1.0)
+                .delay(0.5)
+                .on_done(move || d.set(d.get() + 1)),
+        );
+        tweens.add(
+            "pos",
+            Tween::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0), 1.0)
+                .repeat(1)
+                .yoyo(),
+        );
+        assert!(tweens.update(0.5).is_empty());
+        assert_eq!(tweens.get::<f32>("fade"), 0.0);
+        tweens.update(0.5);
+        assert!((tweens.get::<f32>("fade") - 5.0).abs() < 1e-4);
+        assert_eq!(tweens.update(0.5), ["fade"]);
+        assert_eq!(tweens.get::<f32>("fade"), 10.0);
+        assert!((tweens.get::<Vec2>("pos").x - 0.5).abs() < 1e-4);
+        tweens.update(1.0);
+        assert!(!tweens.active("pos") && tweens.get::<Vec2>("pos").x == 0.0);
+        assert_eq!(done.get(), 1);
+        assert!(tweens.try_get::<Vec2>("fade").is_none());
+    }
+}
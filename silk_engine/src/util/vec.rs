@@ -1,7 +1,13 @@
 #![allow(unused)]
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::{
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    simd::{StdFloat, f32x4, num::SimdFloat},
+};
 
-use super::rand::{Noise, Rand};
+use super::{
+    noise,
+    rand::{Noise, Rand},
+};
 
 pub trait ExtraFns:
     Sized + Rand + Copy + Add<Self, Output = Self> + Mul<Self, Output = Self> + Sub<Self, Output = Self>
@@ -363,6 +369,14 @@ impl Noise for Vec2 {
         res * res
     }
 
+    fn perlin(self) -> f32 {
+        noise::perlin2(self)
+    }
+
+    fn simplex(self) -> f32 {
+        noise::simplex2(self)
+    }
+
     fn voronoise(self, smooth: f32) -> f32 {
         let hash3 = |p: &Self| {
             let q = Vec3::new(
@@ -485,6 +499,44 @@ impl Vec3 {
         let bc = b.cos();
         Self::new(a.cos() * bc, a.sin() * bc, b.sin())
     }
+
+    pub fn cross(self, rhs: Self) -> Self {
+        Self::new(
+            self.y * rhs.z - self.z * rhs.y,
+            self.z * rhs.x - self.x * rhs.z,
+            self.x * rhs.y - self.y * rhs.x,
+        )
+    }
+}
+
+impl Noise for Vec3 {
+    fn hash(self) -> f32 {
+        let ux = (self.x * 141421356.0).to_bits();
+        let uy = (self.y * 2718281828.0).to_bits();
+        let uz = (self.z * 1618033988.0).to_bits();
+        (ux ^ uy.rotate_left(11) ^ uz.rotate_left(22)).rand() as f32 / u32::MAX as f32
+    }
+
+    fn noise(self) -> f32 {
+        let ip = self.floor();
+        let u = self.fract().smooth();
+        let h = |x: f32, y: f32, z: f32| (ip + Vec3::new(x, y, z)).hash();
+        let z0 = h(0.0, 0.0, 0.0)
+            .lerp(h(1.0, 0.0, 0.0), u.x)
+            .lerp(h(0.0, 1.0, 0.0).lerp(h(1.0, 1.0, 0.0), u.x), u.y);
+        let z1 = h(0.0, 0.0, 1.0)
+            .lerp(h(1.0, 0.0, 1.0), u.x)
+            .lerp(h(0.0, 1.0, 1.0).lerp(h(1.0, 1.0, 1.0), u.x), u.y);
+        z0.lerp(z1, u.z)
+    }
+
+    fn perlin(self) -> f32 {
+        noise::perlin3(self)
+    }
+
+    fn simplex(self) -> f32 {
+        noise::simplex3(self)
+    }
 }
 
 impl Vectorf for Vec3 {
@@ -535,6 +587,208 @@ impl From<Vec3u> for Vec3 {
     }
 }
 
+macro_rules! impl_simd_op {
+    ($trait: ident, $method: ident, $op: tt, $assign_trait: ident, $assign_method: ident) => {
+        impl $trait<Vec4> for Vec4 {
+            type Output = Self;
+            #[inline(always)]
+            fn $method(self, rhs: Self) -> Self {
+                Self::from_simd(self.simd() $op rhs.simd())
+            }
+        }
+
+        impl $trait<&Vec4> for Vec4 {
+            type Output = Self;
+            #[inline(always)]
+            fn $method(self, rhs: &Self) -> Self {
+                Self::from_simd(self.simd() $op rhs.simd())
+            }
+        }
+
+        impl $trait<f32> for Vec4 {
+            type Output = Self;
+            #[inline(always)]
+            fn $method(self, rhs: f32) -> Self {
+                Self::from_simd(self.simd() $op f32x4::splat(rhs))
+            }
+        }
+
+        impl $trait<Vec4> for f32 {
+            type Output = Vec4;
+            #[inline(always)]
+            fn $method(self, rhs: Vec4) -> Vec4 {
+                Vec4::from_simd(f32x4::splat(self) $op rhs.simd())
+            }
+        }
+
+        impl $assign_trait<Vec4> for Vec4 {
+            #[inline(always)]
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = *self $op rhs;
+            }
+        }
+
+        impl $assign_trait<f32> for Vec4 {
+            #[inline(always)]
+            fn $assign_method(&mut self, rhs: f32) {
+                *self = *self $op rhs;
+            }
+        }
+    };
+}
+
+/// simd backed (sse/neon) 4 component vector
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vec4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl_simd_op!(Add, add, +, AddAssign, add_assign);
+impl_simd_op!(Sub, sub, -, SubAssign, sub_assign);
+impl_simd_op!(Mul, mul, *, MulAssign, mul_assign);
+impl_simd_op!(Div, div, /, DivAssign, div_assign);
+impl_extra!(Vec4, x, y, z, w);
+
+impl Vec4 {
+    pub const ZERO: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+    pub const ONE: Self = Self::new(1.0, 1.0, 1.0, 1.0);
+    pub const X: Self = Self::new(1.0, 0.0, 0.0, 0.0);
+    pub const Y: Self = Self::new(0.0, 1.0, 0.0, 0.0);
+    pub const Z: Self = Self::new(0.0, 0.0, 1.0, 0.0);
+    pub const W: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    #[inline(always)]
+    pub fn simd(self) -> f32x4 {
+        // same size and alignment, so this is a single register load
+        unsafe { std::mem::transmute(self) }
+    }
+
+    #[inline(always)]
+    pub fn from_simd(v: f32x4) -> Self {
+        unsafe { std::mem::transmute(v) }
+    }
+
+    pub fn xyz(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+
+    /// fused a * b + self
+    #[inline(always)]
+    pub fn mul_add(self, a: Self, b: Self) -> Self {
+        Self::from_simd(a.simd().mul_add(b.simd(), self.simd()))
+    }
+}
+
+impl Vectorf for Vec4 {
+    impl_vec_fn!(exp, x, y, z, w);
+    impl_vec_fn!(sin, x, y, z, w);
+    impl_vec_fn!(cos, x, y, z, w);
+    impl_vec_fn!(cbrt, x, y, z, w);
+
+    fn splat(v: f32) -> Self {
+        Self::from_simd(f32x4::splat(v))
+    }
+
+    fn dot(self, rhs: Self) -> f32 {
+        (self.simd() * rhs.simd()).reduce_sum()
+    }
+
+    fn len(self) -> f32 {
+        self.len2().sqrt()
+    }
+
+    fn norm(self) -> Self {
+        self / self.len()
+    }
+
+    fn sign(self) -> Self {
+        Self::from_simd(self.simd().signum())
+    }
+
+    fn abs(self) -> Self {
+        Self::from_simd(self.simd().abs())
+    }
+
+    fn floor(self) -> Self {
+        Self::from_simd(self.simd().floor())
+    }
+
+    fn round(self) -> Self {
+        Self::from_simd(self.simd().round())
+    }
+
+    fn ceil(self) -> Self {
+        Self::from_simd(self.simd().ceil())
+    }
+
+    fn fract(self) -> Self {
+        Self::from_simd(self.simd().fract())
+    }
+
+    fn sqrt(self) -> Self {
+        Self::from_simd(self.simd().sqrt())
+    }
+
+    fn pow(mut self, p: Self) -> Self {
+        self.x = self.x.powf(p.x);
+        self.y = self.y.powf(p.y);
+        self.z = self.z.powf(p.z);
+        self.w = self.w.powf(p.w);
+        self
+    }
+
+    fn angle_between(self, rhs: Self) -> f32 {
+        self.norm().dot(rhs.norm())
+    }
+
+    fn min(self, rhs: Self) -> Self {
+        Self::from_simd(self.simd().simd_min(rhs.simd()))
+    }
+
+    fn min_elem(self) -> f32 {
+        self.simd().reduce_min()
+    }
+
+    fn max(self, rhs: Self) -> Self {
+        Self::from_simd(self.simd().simd_max(rhs.simd()))
+    }
+
+    fn max_elem(self) -> f32 {
+        self.simd().reduce_max()
+    }
+
+    fn clamp(self, min: Self, max: Self) -> Self {
+        // simd_clamp asserts min <= max per lane, which is slower
+        Self::from_simd(self.simd().simd_max(min.simd()).simd_min(max.simd()))
+    }
+}
+
+impl From<f32> for Vec4 {
+    fn from(value: f32) -> Self {
+        Self::splat(value)
+    }
+}
+
+impl From<[f32; 4]> for Vec4 {
+    fn from([x, y, z, w]: [f32; 4]) -> Self {
+        Self::new(x, y, z, w)
+    }
+}
+
+impl From<Vec4> for [f32; 4] {
+    fn from(v: Vec4) -> Self {
+        [v.x, v.y, v.z, v.w]
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct Vec2u {
     pub x: u32,
@@ -0,0 +1,212 @@
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{LazyLock, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use super::{FileBytes, Reader, Writer};
+use crate::RES_PATH;
+
+const PACK_MAGIC: &[u8; 4] = b"SLKP";
+const PACK_VERSION: u32 = 1;
+
+enum Mount {
+    Dir(PathBuf),
+    /// pack file bytes and (path, byte range) of files in it
+    Pack {
+        bytes: FileBytes,
+        files: HashMap<String, Range<usize>>,
+    },
+    Memory(HashMap<String, Vec<u8>>),
+}
+
+/// resolves asset paths relative to res dir (like "fonts/roboto.ttf") through
+/// mounted dirs, pack files and in-memory files, later mounts shadow earlier ones.
+/// `RES_PATH` dir is mounted by default
+pub struct Vfs {
+    mounts: Vec<Mount>,
+}
+
+static VFS: LazyLock<RwLock<Vfs>> = LazyLock::new(|| {
+    RwLock::new(Vfs {
+        mounts: vec![Mount::Dir(PathBuf::from(RES_PATH))],
+    })
+});
+
+pub fn vfs() -> RwLockReadGuard<'static, Vfs> {
+    VFS.read().unwrap()
+}
+
+pub fn vfs_mut() -> RwLockWriteGuard<'static, Vfs> {
+    VFS.write().unwrap()
+}
+
+impl Vfs {
+    pub fn mount_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.mounts.push(Mount::Dir(dir.into()));
+    }
+
+    /// mounts pack made with [`Vfs::pack`]
+    pub fn mount_pack(&mut self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let bytes = FileBytes::read(path)?;
+        let invalid = || Error::new(ErrorKind::InvalidData, "invalid asset pack");
+        if bytes.len() < 12 || &bytes[..4] != PACK_MAGIC {
+            return Err(invalid());
+        }
+        let mut reader = Reader::new(&bytes);
+        reader.skip(4);
+        if reader.read32() != PACK_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "unsupported asset pack version",
+            ));
+        }
+        let file_cnt = reader.read32() as usize;
+        let mut files = HashMap::with_capacity(file_cnt);
+        for _ in 0..file_cnt {
+            if bytes.len() - reader.idx() < 4 {
+                return Err(invalid());
+            }
+            let path_len = reader.read32() as usize;
+            if bytes.len() - reader.idx() < path_len + 16 {
+                return Err(invalid());
+            }
+            let path = String::from_utf8(reader.read(path_len).to_vec()).map_err(|_| invalid())?;
+            let off = reader.read64() as usize;
+            let len = reader.read64() as usize;
+            if off.checked_add(len).is_none_or(|end| end > bytes.len()) {
+                return Err(invalid());
+            }
+            files.insert(path, off..off + len);
+        }
+        self.mounts.push(Mount::Pack { bytes, files });
+        Ok(())
+    }
+
+    /// adds file that only exists in memory, useful for tests
+    pub fn mount_file(&mut self, path: &str, bytes: Vec<u8>) {
+        if let Some(Mount::Memory(files)) = self.mounts.last_mut() {
+            files.insert(path.to_string(), bytes);
+        } else {
+            self.mounts
+                .push(Mount::Memory(HashMap::from([(path.to_string(), bytes)])));
+        }
+    }
+
+    /// removes all mounts including default res dir
+    pub fn unmount_all(&mut self) {
+        self.mounts.clear();
+    }
+
+    pub fn read(&self, path: &str) -> std::io::Result<FileBytes> {
+        for mount in self.mounts.iter().rev() {
+            match mount {
+                Mount::Dir(dir) => match FileBytes::read(dir.join(path)) {
+                    Err(e) if e.kind() == ErrorKind::NotFound => {}
+                    read => return read,
+                },
+                Mount::Pack { bytes, files } => {
+                    if let Some(range) = files.get(path) {
+                        return Ok(FileBytes::Read(bytes[range.clone()].to_vec()));
+                    }
+                }
+                Mount::Memory(files) => {
+                    if let Some(bytes) = files.get(path) {
+                        return Ok(FileBytes::Read(bytes.clone()));
+                    }
+                }
+            }
+        }
+        Err(Error::new(
+            ErrorKind::NotFound,
+            format!("not in vfs: {path}"),
+        ))
+    }
+
+    pub fn read_to_string(&self, path: &str) -> std::io::Result<String> {
+        String::from_utf8(self.read(path)?.to_vec())
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.mounts.iter().any(|mount| match mount {
+            Mount::Dir(dir) => dir.join(path).is_file(),
+            Mount::Pack { files, .. } => files.contains_key(path),
+            Mount::Memory(files) => files.contains_key(path),
+        })
+    }
+
+    /// packs every file in `dir` into single asset pack at `out`, for shipping,
+    /// layout: magic, version, file count, (path len, path, offset, size) per file, data
+    pub fn pack(dir: impl AsRef<Path>, out: impl AsRef<Path>) -> std::io::Result<()> {
+        fn walk(
+            dir: &Path,
+            root: &Path,
+            files: &mut Vec<(String, Vec<u8>)>,
+        ) -> std::io::Result<()> {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    walk(&path, root, files)?;
+                } else {
+                    let rel = path.strip_prefix(root).unwrap().to_string_lossy();
+                    files.push((rel.replace('\\', "/"), std::fs::read(&path)?));
+                }
+            }
+            Ok(())
+        }
+        let dir = dir.as_ref();
+        let mut files = Vec::new();
+        walk(dir, dir, &mut files)?;
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        let header_size = 12 + files.iter().map(|(p, _)| 4 + p.len() + 16).sum::<usize>();
+        let data_size = files.iter().map(|(_, d)| d.len()).sum::<usize>();
+        let mut writer = Writer::new(header_size + data_size);
+        writer.write(PACK_MAGIC);
+        writer.write32(PACK_VERSION);
+        writer.write32(files.len() as u32);
+        let mut off = header_size;
+        for (path, data) in &files {
+            writer.write32(path.len() as u32);
+            writer.write(path.as_bytes());
+            writer.write64(off as u64);
+            writer.write64(data.len() as u64);
+            off += data.len();
+        }
+        for (_, data) in &files {
+            writer.write(&data[..]);
+        }
+        std::fs::write(out, writer.finish())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::INIT_PATHS;
+
+    #[test]
+    fn vfs_test() {
+        *INIT_PATHS;
+        let dir = format!("{RES_PATH}/vfs_test");
+        std::fs::create_dir_all(format!("{dir}/sub")).unwrap();
+        std::fs::write(format!("{dir}/a.txt"), b"packed a").unwrap();
+        std::fs::write(format!("{dir}/sub/b.bin"), [1, 2, 3]).unwrap();
+        let pack = format!("{RES_PATH}/vfs_test.pack");
+        Vfs::pack(&dir, &pack).unwrap();
+
+        let mut vfs = Vfs { mounts: vec![] };
+        vfs.mount_pack(&pack).unwrap();
+        assert_eq!(vfs.read_to_string("a.txt").unwrap(), "packed a");
+        assert_eq!(&vfs.read("sub/b.bin").unwrap()[..], &[1, 2, 3]);
+        vfs.mount_file("a.txt", b"memory a".to_vec());
+        assert_eq!(vfs.read_to_string("a.txt").unwrap(), "memory a");
+        assert!(!vfs.exists("missing"));
+        assert!(vfs.read("missing").is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+        std::fs::remove_file(pack).unwrap();
+    }
+}
@@ -1,16 +1,13 @@
 use silk_engine::*;
 
-pub struct MyApp<'a> {
-    app: &'a mut AppContext<Self>,
-}
+pub struct MyApp {}
 
-impl App for MyApp<'_> {
-    fn new(app: *mut AppContext<Self>) -> Self {
-        let app = unsafe { &mut *app };
-        Self { app }
+impl App for MyApp {
+    fn new(ctx: &mut AppContext<Self>) -> Self {
+        Self {}
     }
 
-    fn update(&mut self) {}
+    fn update(&mut self, ctx: &mut AppContext<Self>) {}
 
     fn render(&mut self, gfx: &mut Renderer) {}
 }